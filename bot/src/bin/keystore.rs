@@ -0,0 +1,123 @@
+/*!
+Keystore - convert plaintext keypair files into encrypted keystores
+
+The password comes from `--password-file`, the `SNIFFER_KEYSTORE_PASSWORD` env var or an
+interactive prompt (entered twice).
+*/
+
+use std::fs;
+
+use anyhow::{anyhow, Result};
+use sniffer_bot_light::keystore::{EncryptedKeystore, KdfParams, KeystoreConfig, PasswordProvider};
+use sniffer_bot_light::wallet::WalletManager;
+
+/// Parsed command line
+struct CliConfig {
+    command: String,
+    paths: Vec<String>,
+    password_file: Option<String>,
+    delete_plaintext: bool,
+}
+
+fn parse_args() -> CliConfig {
+    let args: Vec<String> = std::env::args().collect();
+    let mut config = CliConfig {
+        command: String::new(),
+        paths: Vec::new(),
+        password_file: None,
+        delete_plaintext: false,
+    };
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--password-file" => {
+                if i + 1 < args.len() {
+                    config.password_file = Some(args[i + 1].clone());
+                    i += 1;
+                }
+            }
+            "--delete-plaintext" => config.delete_plaintext = true,
+            "--help" => {
+                print_help();
+                std::process::exit(0);
+            }
+            arg if config.command.is_empty() => config.command = arg.to_string(),
+            arg => config.paths.push(arg.to_string()),
+        }
+        i += 1;
+    }
+
+    config
+}
+
+fn print_help() {
+    println!("Usage:");
+    println!("  keystore encrypt <keypair.json> <keystore.json> [--delete-plaintext] [--password-file <path>]");
+    println!("  keystore inspect <keystore.json>");
+    println!();
+    println!("encrypt   Encrypt a plaintext keypair (JSON array or base58) into a keystore.");
+    println!("          --delete-plaintext overwrites and removes the source after verifying");
+    println!("          the keystore decrypts to the same pubkey.");
+    println!("inspect   Print a keystore's pubkey and KDF parameters without decrypting it.");
+}
+
+fn encrypt(cli: &CliConfig) -> Result<()> {
+    let [source, target] = cli.paths.as_slice() else {
+        return Err(anyhow!("encrypt needs <keypair.json> <keystore.json>"));
+    };
+    if fs::metadata(target).is_ok() {
+        return Err(anyhow!("{} already exists", target));
+    }
+
+    let wallet = WalletManager::from_file(source)?;
+    let passwords = PasswordProvider::new(KeystoreConfig {
+        password_file: cli.password_file.clone(),
+        ..KeystoreConfig::default()
+    });
+    let password = passwords.new_password()?;
+    wallet.save_encrypted(target, &password, KdfParams::default())?;
+
+    // Only remove the plaintext once the keystore provably unlocks to the same key
+    let keystore = EncryptedKeystore::parse(&fs::read_to_string(target)?)
+        .ok_or_else(|| anyhow!("{} is not a keystore", target))?;
+    let unlocked = WalletManager::from_keypair(keystore.decrypt(&password)?);
+    if unlocked.pubkey() != wallet.pubkey() {
+        return Err(anyhow!("verification failed: keystore unlocks to {}", unlocked.pubkey()));
+    }
+    println!("Encrypted {} -> {} ({})", source, target, wallet.pubkey());
+
+    if cli.delete_plaintext {
+        // Best effort: journaling filesystems and SSDs may keep older copies
+        let len = fs::metadata(source)?.len() as usize;
+        fs::write(source, vec![0u8; len])?;
+        fs::remove_file(source)?;
+        println!("Removed plaintext {}", source);
+    }
+    Ok(())
+}
+
+fn inspect(cli: &CliConfig) -> Result<()> {
+    let [path] = cli.paths.as_slice() else {
+        return Err(anyhow!("inspect needs <keystore.json>"));
+    };
+    let keystore = EncryptedKeystore::parse(&fs::read_to_string(path)?)
+        .ok_or_else(|| anyhow!("{} is not a keystore", path))?;
+    println!("pubkey:  {}", keystore.pubkey);
+    println!("version: {}", keystore.version);
+    println!("kdf:     {} {:?}", keystore.kdf, keystore.kdf_params);
+    println!("cipher:  {}", keystore.cipher);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let cli = parse_args();
+    match cli.command.as_str() {
+        "encrypt" => encrypt(&cli),
+        "inspect" => inspect(&cli),
+        _ => {
+            print_help();
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Slot-aware blockhash service.
+//!
+//! Polls `getEpochInfo` on roughly a slot cadence and refreshes the blockhash every
+//! `refresh_every_slots` slots, remembering each hash's `lastValidBlockHeight`. The observed
+//! block height lets the confirmation tracker tell precisely when a transaction expired.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash};
+use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::{debug, warn};
+
+use crate::metrics::metrics;
+
+/// Recent hashes kept for `lastValidBlockHeight` lookups (~150 blocks of validity)
+const HISTORY_LEN: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockhashConfig {
+    /// How often the slot/block height is polled
+    #[serde(default = "default_slot_poll_ms")]
+    pub slot_poll_ms: u64,
+    /// Fetch a new blockhash once the slot advanced this far
+    #[serde(default = "default_refresh_every_slots")]
+    pub refresh_every_slots: u64,
+    /// A hash not refreshed within this window is not served from cache
+    #[serde(default = "default_max_age_ms")]
+    pub max_age_ms: u64,
+    /// getSignatureStatuses interval while waiting for confirmation
+    #[serde(default = "default_confirm_poll_ms")]
+    pub confirm_poll_ms: u64,
+}
+
+fn default_slot_poll_ms() -> u64 {
+    400
+}
+fn default_refresh_every_slots() -> u64 {
+    4
+}
+fn default_max_age_ms() -> u64 {
+    5_000
+}
+fn default_confirm_poll_ms() -> u64 {
+    500
+}
+
+impl Default for BlockhashConfig {
+    fn default() -> Self {
+        Self {
+            slot_poll_ms: default_slot_poll_ms(),
+            refresh_every_slots: default_refresh_every_slots(),
+            max_age_ms: default_max_age_ms(),
+            confirm_poll_ms: default_confirm_poll_ms(),
+        }
+    }
+}
+
+impl BlockhashConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.slot_poll_ms == 0 || self.confirm_poll_ms == 0 {
+            return Err("blockhash poll intervals must be greater than 0".to_string());
+        }
+        if self.refresh_every_slots == 0 {
+            return Err("blockhash.refresh_every_slots must be greater than 0".to_string());
+        }
+        if self.max_age_ms <= self.slot_poll_ms {
+            return Err("blockhash.max_age_ms must exceed slot_poll_ms".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A blockhash with the last block height at which transactions using it are valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockhashInfo {
+    pub hash: Hash,
+    pub last_valid_block_height: u64,
+    /// Slot at which the hash was fetched
+    pub slot: u64,
+}
+
+#[derive(Debug)]
+struct Current {
+    info: BlockhashInfo,
+    fetched_at: Instant,
+}
+
+pub struct BlockhashService {
+    rpc: Arc<RpcClient>,
+    config: BlockhashConfig,
+    current: RwLock<Option<Current>>,
+    history: RwLock<VecDeque<BlockhashInfo>>,
+    slot: AtomicU64,
+    block_height: AtomicU64,
+    polled_at: RwLock<Option<Instant>>,
+}
+
+impl std::fmt::Debug for BlockhashService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockhashService")
+            .field("rpc", &self.rpc.url())
+            .field("slot", &self.slot.load(Ordering::Relaxed))
+            .field("block_height", &self.block_height.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl BlockhashService {
+    pub fn new(rpc: Arc<RpcClient>, config: BlockhashConfig) -> Self {
+        Self {
+            rpc,
+            config,
+            current: RwLock::new(None),
+            history: RwLock::new(VecDeque::with_capacity(HISTORY_LEN)),
+            slot: AtomicU64::new(0),
+            block_height: AtomicU64::new(0),
+            polled_at: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &BlockhashConfig {
+        &self.config
+    }
+
+    /// Cached blockhash if it is still fresh.
+    pub async fn cached(&self) -> Option<BlockhashInfo> {
+        let current = self.current.read().await;
+        current
+            .as_ref()
+            .filter(|c| c.fetched_at.elapsed() < Duration::from_millis(self.config.max_age_ms))
+            .map(|c| c.info)
+    }
+
+    /// Fresh blockhash, fetching one now if the background refresh fell behind.
+    pub async fn latest(&self) -> Result<BlockhashInfo> {
+        if let Some(info) = self.cached().await {
+            return Ok(info);
+        }
+        metrics().increment_counter("blockhash_cache_misses");
+        self.refresh().await
+    }
+
+    /// `lastValidBlockHeight` of a hash this service handed out.
+    pub async fn last_valid_block_height(&self, hash: &Hash) -> Option<u64> {
+        self.history
+            .read()
+            .await
+            .iter()
+            .find(|info| info.hash == *hash)
+            .map(|info| info.last_valid_block_height)
+    }
+
+    /// Last observed block height (0 before the first poll).
+    pub fn block_height(&self) -> u64 {
+        self.block_height.load(Ordering::Relaxed)
+    }
+
+    /// Block height, polling the cluster itself when the background poll fell behind.
+    pub async fn current_block_height(&self) -> Result<u64> {
+        let stale_after = Duration::from_millis(self.config.slot_poll_ms * 2);
+        let fresh = matches!(*self.polled_at.read().await, Some(at) if at.elapsed() < stale_after);
+        if !fresh {
+            self.poll_slot().await?;
+        }
+        Ok(self.block_height())
+    }
+
+    /// Whether transactions built on a hash valid until `last_valid_block_height` expired.
+    pub fn is_expired(&self, last_valid_block_height: u64) -> bool {
+        self.block_height() > last_valid_block_height
+    }
+
+    /// Fetch a new blockhash and record it.
+    pub async fn refresh(&self) -> Result<BlockhashInfo> {
+        let (hash, last_valid_block_height) = self
+            .rpc
+            .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+            .await
+            .map_err(|e| anyhow!("getLatestBlockhash: {}", e))?;
+        let info = BlockhashInfo {
+            hash,
+            last_valid_block_height,
+            slot: self.slot.load(Ordering::Relaxed),
+        };
+
+        *self.current.write().await = Some(Current {
+            info,
+            fetched_at: Instant::now(),
+        });
+        let mut history = self.history.write().await;
+        if history.front().map(|h| h.hash) != Some(hash) {
+            history.push_front(info);
+            history.truncate(HISTORY_LEN);
+        }
+        metrics().increment_counter("blockhash_refreshes");
+        debug!(%hash, last_valid_block_height, "Blockhash refreshed");
+        Ok(info)
+    }
+
+    /// Update the slot and block height; returns the slot.
+    async fn poll_slot(&self) -> Result<u64> {
+        let epoch = self
+            .rpc
+            .get_epoch_info_with_commitment(CommitmentConfig::confirmed())
+            .await
+            .map_err(|e| anyhow!("getEpochInfo: {}", e))?;
+        self.slot.store(epoch.absolute_slot, Ordering::Relaxed);
+        self.block_height.store(epoch.block_height, Ordering::Relaxed);
+        *self.polled_at.write().await = Some(Instant::now());
+        metrics().set_gauge("block_height", epoch.block_height);
+        Ok(epoch.absolute_slot)
+    }
+
+    /// One step of the background loop: poll the slot and refresh when due.
+    pub async fn tick(&self) -> Result<()> {
+        let slot = self.poll_slot().await?;
+        let due = match self.current.read().await.as_ref() {
+            Some(c) => {
+                slot.saturating_sub(c.info.slot) >= self.config.refresh_every_slots
+                    || c.fetched_at.elapsed() >= Duration::from_millis(self.config.max_age_ms / 2)
+            }
+            None => true,
+        };
+        if due {
+            self.refresh().await?;
+        }
+        Ok(())
+    }
+
+    /// Keep the blockhash fresh until the handle is aborted.
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(self.config.slot_poll_ms));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.tick().await {
+                    metrics().increment_counter("blockhash_refresh_failures");
+                    warn!("Blockhash service poll failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_validation() {
+        assert!(BlockhashConfig::default().validate().is_ok());
+        let bad = BlockhashConfig { refresh_every_slots: 0, ..BlockhashConfig::default() };
+        assert!(bad.validate().is_err());
+        let bad = BlockhashConfig { max_age_ms: 100, ..BlockhashConfig::default() };
+        assert!(bad.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn expiry_follows_block_height() {
+        let service = BlockhashService::new(
+            Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())),
+            BlockhashConfig::default(),
+        );
+        assert!(service.cached().await.is_none());
+        service.block_height.store(1_000, Ordering::Relaxed);
+        assert!(!service.is_expired(1_000));
+        assert!(service.is_expired(999));
+    }
+}
@@ -11,25 +11,34 @@ use std::{sync::{Arc, atomic::{AtomicBool, AtomicU32, Ordering}}, time::{Duratio
 
 use anyhow::{anyhow, Context, Result};
 use solana_sdk::{
+    hash::Hash,
     pubkey::Pubkey,
     signature::Signature,
     transaction::VersionedTransaction,
 };
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, timeout};
 use tracing::{debug, error, info, warn};
 use crate::config::Config;
+use crate::confirmation::{ConfirmationOutcome, ConfirmationTracker};
 
 use crate::endpoints::endpoint_server;
+use crate::fee_estimator::priority_fee_of;
+use crate::fee_ladder::{record_rung_landed, record_rung_sent};
+use crate::jito::JitoClient;
 use crate::metrics::{metrics, Timer};
 use crate::nonce_manager::NonceManager;
 
-use crate::rpc_manager::RpcBroadcaster;
+use crate::rebroadcast::Rebroadcaster;
+use crate::rpc_manager::{BroadcastError, RpcBroadcaster};
 use crate::security::validator;
 use crate::structured_logging::{PipelineContext, StructuredLogger};
+use crate::time_utils::now_ms;
 use crate::observability::CorrelationId;
 use crate::tx_builder::{TransactionBuilder, TransactionConfig};
 use crate::types::{AppState, CandidateReceiver, Mode, PremintCandidate};
+use crate::wallet_pool::WalletPool;
 
 /// Exponential backoff state for failure handling
 #[derive(Debug)]
@@ -91,7 +100,15 @@ pub struct BuyEngine {
     pub candidate_rx: CandidateReceiver,
     pub app_state: Arc<Mutex<AppState>>,
     pub config: Config,
-    pub tx_builder: Option<TransactionBuilder>,
+    pub tx_builder: Option<Arc<TransactionBuilder>>,
+    /// Source of tip accounts for fee ladder rungs that carry a Jito tip
+    jito_client: Option<Arc<JitoClient>>,
+    /// Confirms broadcast buys against their blockhash expiry before entering PassiveToken
+    confirmations: Option<Arc<ConfirmationTracker>>,
+    /// Resends the landed buy until it confirms or expires (needs `confirmations`)
+    rebroadcaster: Option<Arc<Rebroadcaster>>,
+    /// Wallets buys are spread over; exits are signed by the wallet holding the position
+    wallet_pool: Option<Arc<WalletPool>>,
     backoff_state: BackoffState,
     pending_buy: Arc<AtomicBool>,
 }
@@ -111,12 +128,152 @@ impl BuyEngine {
             candidate_rx,
             app_state,
             config,
-            tx_builder,
+            tx_builder: tx_builder.map(Arc::new),
+            jito_client: None,
+            confirmations: None,
+            rebroadcaster: None,
+            wallet_pool: None,
             backoff_state: BackoffState::new(),
             pending_buy: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Enable in-transaction Jito tips for fee ladder rungs.
+    pub fn with_jito_client(mut self, client: Arc<JitoClient>) -> Self {
+        self.jito_client = Some(client);
+        self
+    }
+
+    /// Require buys to confirm (or expire) before the engine switches to PassiveToken.
+    pub fn with_confirmation_tracker(mut self, tracker: Arc<ConfirmationTracker>) -> Self {
+        self.confirmations = Some(tracker);
+        self
+    }
+
+    /// Keep resending broadcast buys while waiting for their confirmation.
+    pub fn with_rebroadcaster(mut self, rebroadcaster: Arc<Rebroadcaster>) -> Self {
+        self.rebroadcaster = Some(rebroadcaster);
+        self
+    }
+
+    /// Spread buys over a wallet pool (the builder must share the same pool).
+    pub fn with_wallet_pool(mut self, pool: Arc<WalletPool>) -> Self {
+        self.wallet_pool = Some(pool);
+        self
+    }
+
+    /// Sell-only engine for exits requested outside the buy loop (e.g. the GUI). It shares
+    /// this engine's builder, wallets and pending-buy guard and consumes no candidates.
+    pub fn seller(&self) -> BuyEngine {
+        let (_tx, candidate_rx) = tokio::sync::mpsc::channel(1);
+        BuyEngine {
+            rpc: self.rpc.clone(),
+            nonce_manager: self.nonce_manager.clone(),
+            candidate_rx,
+            app_state: self.app_state.clone(),
+            config: self.config.clone(),
+            tx_builder: self.tx_builder.clone(),
+            jito_client: self.jito_client.clone(),
+            confirmations: self.confirmations.clone(),
+            rebroadcaster: self.rebroadcaster.clone(),
+            wallet_pool: self.wallet_pool.clone(),
+            backoff_state: BackoffState::new(),
+            pending_buy: self.pending_buy.clone(),
+        }
+    }
+
+    /// Wait for a broadcast buy to confirm, rebroadcasting `tx` meanwhile when enabled; failed
+    /// or expired buys become errors.
+    async fn confirm_buy(
+        &self,
+        signature: Signature,
+        tx: Option<&VersionedTransaction>,
+        blockhash: &solana_sdk::hash::Hash,
+    ) -> Result<Signature> {
+        let Some(tracker) = &self.confirmations else {
+            return Ok(signature);
+        };
+        let Some(last_valid_block_height) = tracker.last_valid_block_height_of(blockhash).await else {
+            warn!(%signature, "Buy blockhash unknown to the blockhash service; skipping confirmation");
+            return Ok(signature);
+        };
+        let outcome = match (&self.rebroadcaster, tx) {
+            (Some(rebroadcaster), Some(tx)) => rebroadcaster
+                .run(tx, last_valid_block_height)
+                .await?
+                .ok_or_else(|| anyhow!("buy {} rebroadcast cancelled before confirmation", signature))?,
+            _ => tracker.wait(&signature, last_valid_block_height).await?,
+        };
+        match outcome {
+            ConfirmationOutcome::Confirmed { .. } => Ok(signature),
+            ConfirmationOutcome::Failed(err) => Err(anyhow!("buy {} failed on-chain: {}", signature, err)),
+            ConfirmationOutcome::Expired { block_height, .. } => Err(anyhow!(
+                "buy {} expired at block height {} (valid through {})",
+                signature,
+                block_height,
+                last_valid_block_height
+            )),
+        }
+    }
+
+    /// Rebuilds with a fresh blockhash allowed per buy or sell (`retry_policy`).
+    fn max_blockhash_refreshes(&self) -> u32 {
+        self.config.retry_policy.clone().unwrap_or_default().max_blockhash_refreshes
+    }
+
+    /// Drop the blockhash endpoints rejected so the rebuild picks a fresh one.
+    async fn invalidate_blockhash(&self, hash: Hash) {
+        if let Some(builder) = &self.tx_builder {
+            builder.invalidate_blockhash(hash).await;
+        }
+        metrics().increment_counter("blockhash_not_found_rebuilds");
+    }
+
+    /// Builder config carrying the app-level settings (e.g. the Jupiter sell fallback).
+    fn tx_config(&self) -> TransactionConfig {
+        TransactionConfig {
+            jupiter_api_url: self.config.jupiter_api_url.clone(),
+            ..TransactionConfig::default()
+        }
+    }
+
+    /// Transaction config for the `step`-th fee ladder rung (the plain config without a ladder).
+    fn buy_config_for_rung(&self, step: usize) -> (TransactionConfig, Option<usize>) {
+        let base = self.tx_config();
+        match &self.config.fee_ladder {
+            Some(ladder) => {
+                let tip_account = self.jito_client.as_ref().map(|c| c.next_tip_account());
+                (ladder.apply(&base, step, tip_account), Some(ladder.rung_index(step)))
+            }
+            None => (base, None),
+        }
+    }
+
+    /// Close empty token accounts in the background when `account_sweep` is configured: once
+    /// at startup, then every `interval_secs`. Each sweep waits until no buy is in flight.
+    pub fn spawn_account_sweep(&self) -> Option<JoinHandle<()>> {
+        let sweep = self.config.account_sweep.clone()?;
+        let builder = self.tx_builder.clone()?;
+        let config = self.tx_config();
+        let pending_buy = self.pending_buy.clone();
+        Some(tokio::spawn(async move {
+            loop {
+                while pending_buy.load(Ordering::Relaxed) {
+                    sleep(Duration::from_millis(100)).await;
+                }
+                let closed = builder.sweep_all_wallets(&config, &sweep).await;
+                if !closed.is_empty() {
+                    let lamports: u64 = closed.iter().map(|account| account.lamports).sum();
+                    info!(accounts = closed.len(), lamports, "Token account sweep reclaimed rent");
+                }
+                if sweep.interval_secs == 0 {
+                    break;
+                }
+                sleep(Duration::from_secs(sweep.interval_secs)).await;
+            }
+        }))
+    }
+
     pub async fn run(&mut self) {
         info!("BuyEngine started");
         loop {
@@ -275,7 +432,7 @@ impl BuyEngine {
             }
         };
 
-        let _candidate = candidate_opt.ok_or_else(|| anyhow!("no active token in AppState"))?;
+        let candidate = candidate_opt.ok_or_else(|| anyhow!("no active token in AppState"))?;
         
         // Validate the new holdings calculation
         let new_holdings = match validator().validate_holdings_percent((current_pct * (1.0 - pct)).max(0.0)) {
@@ -289,9 +446,31 @@ impl BuyEngine {
         ctx.logger.log_sell_operation(&mint.to_string(), pct, new_holdings);
         info!(mint=%mint, sell_percent=pct, correlation_id=ctx.correlation_id, "Composing SELL transaction");
 
-        let sell_tx = self.create_sell_transaction(&mint, pct).await?;
+        let wallet = match &self.wallet_pool {
+            Some(pool) => pool.holder_of(&mint).await,
+            None => None,
+        };
+        // Exit through the route the position was bought on
+        let mut sell_tx = self.create_sell_transaction(&mint, &candidate.program, pct, wallet).await?;
+
+        let correlation_id = CorrelationId::from_string(ctx.correlation_id.to_string());
+        let mut refreshes = 0;
+        let res = loop {
+            match self.rpc.send_on_many_rpc(vec![sell_tx.clone()], Some(correlation_id.clone())).await {
+                Err(e) if refreshes < self.max_blockhash_refreshes() => match rejected_blockhash(&e) {
+                    Some(blockhash) => {
+                        refreshes += 1;
+                        warn!(mint=%mint, %blockhash, refreshes, correlation_id=ctx.correlation_id, "SELL blockhash not found; rebuilding");
+                        self.invalidate_blockhash(blockhash).await;
+                        sell_tx = self.create_sell_transaction(&mint, &candidate.program, pct, wallet).await?;
+                    }
+                    None => break Err(e),
+                },
+                res => break res,
+            }
+        };
 
-        match self.rpc.send_on_many_rpc(vec![sell_tx], None).await {
+        match res {
             Ok(sig) => {
                 // Check for duplicate signatures
                 let sig_str = sig.to_string();
@@ -301,6 +480,11 @@ impl BuyEngine {
                 }
                 
                 info!(mint=%mint, sig=%sig, correlation_id=ctx.correlation_id, "SELL broadcasted");
+                if let Some(pool) = &self.wallet_pool {
+                    if let Some(wallet) = pool.record_exit(&mint, pct).await {
+                        journal_trade(&ctx, pool, "sell", &mint, wallet, &sig, pct).await;
+                    }
+                }
                 let mut st = self.app_state.lock().await;
                 st.holdings_percent = new_holdings;
                 if st.holdings_percent <= f64::EPSILON {
@@ -337,53 +521,45 @@ impl BuyEngine {
     async fn try_buy(&self, candidate: PremintCandidate, ctx: PipelineContext) -> Result<Signature> {
         let mut acquired_indices: Vec<usize> = Vec::new();
 
-        let mut txs: Vec<VersionedTransaction> = Vec::new();
+        // Detect→signed latency is reported per build path so templates can be compared. It is
+        // measured from the sniffer's detection, so time queued in the channel counts too.
+        let detected_at_ms = match candidate.detected_at_ms {
+            0 => now_ms(),
+            ms => ms,
+        };
+        let templated = match &self.tx_builder {
+            Some(builder) => builder.has_template_for(&candidate.program).await,
+            None => false,
+        };
 
-        // Get recent blockhash once for all transactions
-        let recent_blockhash = self.get_recent_blockhash().await;
+        // Every transaction of one buy is paid by the same wallet, so one of them holds the
+        // position
+        let wallet = match &self.wallet_pool {
+            Some(pool) => Some(pool.select(&candidate.program).await),
+            None => None,
+        };
 
         for _ in 0..self.config.nonce_count {
             match self.nonce_manager.acquire_nonce().await {
-
                 Ok((_nonce_pubkey, idx)) => {
                     ctx.logger.log_nonce_operation("acquire", Some(idx), true);
                     acquired_indices.push(idx);
-
-                    let tx = self.create_buy_transaction(&candidate, recent_blockhash).await?;
-                    txs.push(tx);
                 }
                 Err(e) => {
-
                     ctx.logger.log_nonce_operation("acquire_failed", None, false);
                     warn!(error=%e, correlation_id=ctx.correlation_id, "Failed to acquire nonce; proceeding with fewer");
-
                     break;
                 }
             }
         }
 
-        if txs.is_empty() {
-
-
-            for idx in acquired_indices.drain(..) {
-
-                ctx.logger.log_nonce_operation("release", Some(idx), true);
-                self.nonce_manager.release_nonce(idx);
-
-            }
-
-
+        if acquired_indices.is_empty() {
             return Err(anyhow!("no transactions prepared (no nonces acquired)"));
         }
 
-
-        ctx.logger.log_buy_attempt(&candidate.mint.to_string(), txs.len());
-        
         let res = self
-            .rpc
-            .send_on_many_rpc(txs, Some(CorrelationId::new()))
-            .await
-            .context("broadcast BUY failed");
+            .buy_on_leases(&candidate, &ctx, acquired_indices.len(), wallet, detected_at_ms, templated)
+            .await;
 
         for idx in acquired_indices {
             ctx.logger.log_nonce_operation("release", Some(idx), true);
@@ -391,18 +567,158 @@ impl BuyEngine {
         }
 
         res
+    }
+
+    /// Send `copies` buys at once: identical copies, which share a signature, or fee ladder
+    /// rungs, which share a durable nonce. Either way at most one of them executes.
+    async fn buy_on_leases(
+        &self,
+        candidate: &PremintCandidate,
+        ctx: &PipelineContext,
+        copies: usize,
+        wallet: Option<usize>,
+        detected_at_ms: u64,
+        templated: bool,
+    ) -> Result<Signature> {
+        let correlation_id = CorrelationId::from_string(ctx.correlation_id.to_string());
+        let (tx_configs, rungs): (Vec<TransactionConfig>, Vec<Option<usize>>) =
+            self.buy_configs(copies, wallet).await?.into_iter().unzip();
+        let mut txs = self.create_buy_transactions(candidate, &tx_configs).await?;
+        record_detect_to_signed(
+            Duration::from_millis(now_ms().saturating_sub(detected_at_ms)),
+            templated,
+        );
+
+        ctx.logger.log_buy_attempt(&candidate.mint.to_string(), txs.len());
+
+        // Remember the bids so landing rates can be correlated with fees
+        let priority_fees: Vec<u64> = txs.iter().map(|tx| priority_fee_of(tx).unwrap_or(0)).collect();
+        for rung in rungs.iter().flatten() {
+            record_rung_sent(*rung);
+        }
+
+        // Rungs are never rebuilt: their nonce does not expire, so a rejected nonce value
+        // means the nonce moved, possibly because one of them already executed
+        let ladder = self.config.fee_ladder.is_some();
+        let mut refreshes = 0;
+        let sent = loop {
+            let blockhash = *txs[0].message.recent_blockhash();
+            match self.rpc.send_on_many_rpc(txs.clone(), Some(correlation_id.clone())).await {
+                Err(e) if !ladder && refreshes < self.max_blockhash_refreshes() => match rejected_blockhash(&e) {
+                    Some(blockhash) => {
+                        refreshes += 1;
+                        warn!(mint=%candidate.mint, %blockhash, refreshes, correlation_id=ctx.correlation_id, "BUY blockhash not found; rebuilding");
+                        self.invalidate_blockhash(blockhash).await;
+                        match self.create_buy_transactions(candidate, &tx_configs).await {
+                            Ok(rebuilt) => txs = rebuilt,
+                            Err(e) => break Err(e),
+                        }
+                    }
+                    None => break Err(e),
+                },
+                res => break res.map(|sig| (sig, blockhash)),
+            }
+        };
+        let res = match sent.context("broadcast BUY failed") {
+            Ok((sig, _)) if ladder => self.confirm_rungs(&txs, sig).await,
+            Ok((sig, blockhash)) => self.confirm_buy(sig, landed_tx(&txs, &sig), &blockhash).await,
+            Err(e) => Err(e),
+        };
+
+        match &res {
+            Ok(sig) => {
+                // Identical copies share a signature (no unique match) and a config, so the first
+                // one stands in for them
+                let landed = landed_index(&txs, sig);
+                let rung = landed.and_then(|i| rungs[i]);
+                let landed = landed.unwrap_or(0);
+                if let Some(rung) = rung {
+                    record_rung_landed(rung);
+                }
+                metrics().set_gauge("buy_landed_priority_fee_micro_lamports", priority_fees[landed]);
+                ctx.logger.info("buy_priority_fee", serde_json::json!({
+                    "mint": candidate.mint.to_string(),
+                    "signature": sig.to_string(),
+                    "priority_fee_micro_lamports": priority_fees[landed],
+                    "fee_ladder_rung": rung,
+                    "landed": true
+                }));
+                if let (Some(pool), Some(wallet)) = (&self.wallet_pool, wallet) {
+                    pool.record_buy(wallet, candidate.mint, tx_configs[landed].buy_amount_lamports).await;
+                    journal_trade(ctx, pool, "buy", &candidate.mint, wallet, sig, 1.0).await;
+                }
+            }
+            Err(_) => {
+                ctx.logger.info("buy_priority_fee", serde_json::json!({
+                    "mint": candidate.mint.to_string(),
+                    "priority_fee_micro_lamports": priority_fees,
+                    "fee_ladder_rung": rungs,
+                    "landed": false
+                }));
+            }
+        }
+        res
+    }
 
+    /// Configs (and fee ladder rungs) of the `copies` transactions of one buy, all paid by
+    /// `wallet`. With a ladder, copy `i` is rung `i` and every rung advances the ladder's
+    /// durable nonce, read once here so they all carry the same value.
+    async fn buy_configs(
+        &self,
+        copies: usize,
+        wallet: Option<usize>,
+    ) -> Result<Vec<(TransactionConfig, Option<usize>)>> {
+        let durable_nonce = match (&self.config.fee_ladder, &self.tx_builder) {
+            (Some(ladder), Some(builder)) => {
+                let account = ladder.nonce_account().map_err(|e| anyhow!(e))?;
+                let nonce = builder
+                    .durable_nonce(&account)
+                    .await
+                    .map_err(|e| anyhow!("fee ladder nonce read failed: {}", e))?;
+                Some(nonce)
+            }
+            _ => None,
+        };
+        Ok((0..copies)
+            .map(|copy| {
+                let (mut config, rung) = self.buy_config_for_rung(copy);
+                config.signer_keypair_index = wallet;
+                config.durable_nonce = durable_nonce;
+                (config, rung)
+            })
+            .collect())
+    }
+
+    /// Wait for whichever fee ladder rung lands. Nonce transactions do not expire by block
+    /// height, so the wait is bounded like a buy on a blockhash fetched now; rungs still
+    /// pending then stay valid until the nonce advances.
+    async fn confirm_rungs(&self, txs: &[VersionedTransaction], sent: Signature) -> Result<Signature> {
+        let Some(tracker) = &self.confirmations else {
+            return Ok(sent);
+        };
+        let signatures: Vec<Signature> = txs.iter().filter_map(|tx| tx.signatures.first().copied()).collect();
+        let last_valid_block_height = tracker.current_last_valid_block_height().await?;
+        let (index, outcome) = tracker.wait_any(&signatures, last_valid_block_height).await?;
+        let signature = signatures.get(index).copied().unwrap_or(sent);
+        match outcome {
+            ConfirmationOutcome::Confirmed { .. } => Ok(signature),
+            ConfirmationOutcome::Failed(err) => Err(anyhow!("buy {} failed on-chain: {}", signature, err)),
+            ConfirmationOutcome::Expired { block_height, .. } => Err(anyhow!(
+                "no rung of buy {} confirmed by block height {}; pending rungs stay valid until the nonce advances",
+                sent,
+                block_height
+            )),
+        }
     }
 
     async fn create_buy_transaction(
         &self,
         candidate: &PremintCandidate,
-        _recent_blockhash: Option<solana_sdk::hash::Hash>,
+        config: &TransactionConfig,
     ) -> Result<VersionedTransaction> {
         match &self.tx_builder {
             Some(builder) => {
-                let config = TransactionConfig::default();
-                builder.build_buy_transaction(candidate, &config, false).await
+                builder.build_buy_transaction(candidate, config, true).await
                     .map_err(|e| anyhow!("Transaction build failed: {}", e))
             }
             None => {
@@ -419,15 +735,32 @@ impl BuyEngine {
         }
     }
 
+    async fn create_buy_transactions(
+        &self,
+        candidate: &PremintCandidate,
+        configs: &[TransactionConfig],
+    ) -> Result<Vec<VersionedTransaction>> {
+        let mut txs = Vec::with_capacity(configs.len());
+        for config in configs {
+            txs.push(self.create_buy_transaction(candidate, config).await?);
+        }
+        Ok(txs)
+    }
+
     async fn create_sell_transaction(
         &self,
         mint: &Pubkey,
+        program: &str,
         sell_percent: f64,
+        wallet: Option<usize>,
     ) -> Result<VersionedTransaction> {
         match &self.tx_builder {
             Some(builder) => {
-                let config = TransactionConfig::default();
-                builder.build_sell_transaction(mint, "pump.fun", sell_percent, &config, false).await
+                let config = TransactionConfig {
+                    signer_keypair_index: wallet,
+                    ..self.tx_config()
+                };
+                builder.build_sell_transaction(mint, program, sell_percent, &config, false).await
                     .map_err(|e| anyhow!("Transaction build failed: {}", e))
             }
             None => {
@@ -463,9 +796,66 @@ impl BuyEngine {
     async fn get_execution_price_mock(&self, _candidate: &PremintCandidate) -> f64 {
         0.000001 // Mock price for testing
     }
+}
+
+/// The blockhash a broadcast failed on when the retry policy asks for a rebuild.
+fn rejected_blockhash(error: &anyhow::Error) -> Option<Hash> {
+    match error.downcast_ref::<BroadcastError>() {
+        Some(BroadcastError::BlockhashNotFound(hash)) => Some(*hash),
+        _ => None,
+    }
+}
+
+/// Journal which pool wallet traded `mint`; `fraction` is the share of the position sold.
+async fn journal_trade(
+    ctx: &PipelineContext,
+    pool: &WalletPool,
+    side: &str,
+    mint: &Pubkey,
+    wallet: usize,
+    signature: &Signature,
+    fraction: f64,
+) {
+    ctx.logger.info("trade_journal", serde_json::json!({
+        "side": side,
+        "mint": mint.to_string(),
+        "signature": signature.to_string(),
+        "wallet_index": wallet,
+        "wallet": pool.wallet(wallet).map(|w| w.pubkey().to_string()),
+        "fraction": fraction,
+        "wallet_exposure_lamports": pool.exposure(wallet).await
+    }));
+}
+
+/// Record how long the first buy transaction took from detection to signature.
+fn record_detect_to_signed(elapsed: Duration, templated: bool) {
+    metrics().record_histogram("buy_detect_to_signed", elapsed);
+    let path = if templated {
+        "buy_detect_to_signed_templated"
+    } else {
+        "buy_detect_to_signed_full"
+    };
+    metrics().record_histogram(path, elapsed);
+}
+
+/// The sent transaction that `landed`. Identical copies share a signature; unsigned copies
+/// also share the default one, so differing matches are ambiguous.
+fn landed_tx<'a>(txs: &'a [VersionedTransaction], landed: &Signature) -> Option<&'a VersionedTransaction> {
+    let mut matches = txs.iter().filter(|tx| tx.signatures.first() == Some(landed));
+    let first = matches.next()?;
+    matches.all(|tx| tx == first).then_some(first)
+}
 
-    async fn get_recent_blockhash(&self) -> Option<solana_sdk::hash::Hash> {
-        None // Simplified implementation
+/// Index of the sent transaction that `landed`, if no other transaction carries its signature.
+fn landed_index(txs: &[VersionedTransaction], landed: &Signature) -> Option<usize> {
+    let mut matches = txs
+        .iter()
+        .enumerate()
+        .filter(|(_, tx)| tx.signatures.first() == Some(landed))
+        .map(|(i, _)| i);
+    match (matches.next(), matches.next()) {
+        (Some(i), None) => Some(i),
+        _ => None,
     }
 }
 
@@ -517,7 +907,7 @@ mod tests {
             creator: Pubkey::new_unique(),
             program: "pump.fun".to_string(),
             slot: 0,
-            timestamp: 0, instruction_summary: None, is_jito_bundle: None,
+            timestamp: 0, instruction_summary: None, is_jito_bundle: None, detected_at_ms: 0, token_program: None,
         };
         tx.send(candidate).await.unwrap();
         drop(tx);
@@ -624,7 +1014,7 @@ mod tests {
             creator: Pubkey::new_unique(),
             program: "pump.fun".to_string(),
             slot: 0,
-            timestamp: 0, instruction_summary: None, is_jito_bundle: None,
+            timestamp: 0, instruction_summary: None, is_jito_bundle: None, detected_at_ms: 0, token_program: None,
         };
 
         // First buy should succeed
@@ -640,6 +1030,60 @@ mod tests {
         assert!(result2.unwrap_err().to_string().contains("already in progress"));
     }
 
+    #[tokio::test]
+    async fn blockhash_not_found_rebuilds_and_resends() {
+        #[derive(Debug, Default)]
+        struct StaleOnceBroadcaster {
+            calls: AtomicU32,
+        }
+        impl RpcBroadcaster for StaleOnceBroadcaster {
+            fn send_on_many_rpc<'a>(
+                &'a self,
+                txs: Vec<VersionedTransaction>,
+                _correlation_id: Option<CorrelationId>,
+            ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
+                Box::pin(async move {
+                    if self.calls.fetch_add(1, Ordering::Relaxed) == 0 {
+                        let hash = *txs[0].message.recent_blockhash();
+                        return Err(BroadcastError::BlockhashNotFound(hash).into());
+                    }
+                    Ok(Signature::from([9u8; 64]))
+                })
+            }
+        }
+
+        let (_tx, rx) = mpsc::channel(8);
+        let app_state = Arc::new(Mutex::new(AppState {
+            mode: Mode::Sniffing,
+            active_token: None,
+            last_buy_price: None,
+            holdings_percent: 0.0, quantum_suggestions: Vec::new(),
+        }));
+        let rpc = Arc::new(StaleOnceBroadcaster::default());
+        let engine = BuyEngine::new(
+            rpc.clone(),
+            Arc::new(NonceManager::new(2)),
+            rx,
+            app_state,
+            Config {
+                nonce_count: 1,
+                ..Config::default()
+            },
+            None,
+        );
+        let candidate = PremintCandidate {
+            mint: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            program: "pump.fun".to_string(),
+            slot: 0,
+            timestamp: 0, instruction_summary: None, is_jito_bundle: None, detected_at_ms: 0, token_program: None,
+        };
+
+        let sig = engine.try_buy_with_guards(candidate, CorrelationId::new()).await.unwrap();
+        assert_eq!(sig, Signature::from([9u8; 64]));
+        assert_eq!(rpc.calls.load(Ordering::Relaxed), 2);
+    }
+
     #[tokio::test]
     async fn test_sell_buy_race_protection() {
         let (_tx, rx): (mpsc::Sender<PremintCandidate>, mpsc::Receiver<PremintCandidate>) =
@@ -652,7 +1096,7 @@ mod tests {
                 creator: Pubkey::new_unique(),
                 program: "pump.fun".to_string(),
                 slot: 0,
-                timestamp: 0, instruction_summary: None, is_jito_bundle: None,
+                timestamp: 0, instruction_summary: None, is_jito_bundle: None, detected_at_ms: 0, token_program: None,
             }),
             last_buy_price: Some(1.0),
             holdings_percent: 1.0, quantum_suggestions: Vec::new(),
@@ -676,6 +1120,30 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("buy operation in progress"));
     }
 
+    #[tokio::test]
+    async fn seller_shares_the_pending_buy_guard() {
+        let (_tx, rx) = mpsc::channel(1);
+        let app_state = Arc::new(Mutex::new(AppState {
+            mode: Mode::PassiveToken(Pubkey::new_unique()),
+            active_token: None,
+            last_buy_price: Some(1.0),
+            holdings_percent: 1.0, quantum_suggestions: Vec::new(),
+        }));
+        let engine = BuyEngine::new(
+            Arc::new(AlwaysOkBroadcaster),
+            Arc::new(NonceManager::new(1)),
+            rx,
+            app_state,
+            Config::default(),
+            None,
+        );
+
+        let seller = engine.seller();
+        engine.pending_buy.store(true, Ordering::Relaxed);
+        let err = seller.sell(1.0).await.unwrap_err();
+        assert!(err.to_string().contains("buy operation in progress"), "{err}");
+    }
+
     #[tokio::test]
     async fn test_nonce_lease_raii_behavior() {
         let (_tx, rx): (mpsc::Sender<PremintCandidate>, mpsc::Receiver<PremintCandidate>) =
@@ -710,7 +1178,7 @@ mod tests {
             creator: Pubkey::new_unique(),
             program: "pump.fun".to_string(),
             slot: 0,
-            timestamp: 0, instruction_summary: None, is_jito_bundle: None,
+            timestamp: 0, instruction_summary: None, is_jito_bundle: None, detected_at_ms: 0, token_program: None,
         };
 
         // Perform buy operation - should acquire and release nonces automatically
@@ -724,4 +1192,117 @@ mod tests {
         // All permits should be available again after RAII cleanup
         assert_eq!(nonce_manager.available_permits(), 2);
     }
+
+    #[test]
+    fn landed_tx_requires_unambiguous_signature() {
+        let tx = |sig: Signature| {
+            let mut tx = BuyEngine::create_placeholder_tx(&Pubkey::new_unique(), "buy");
+            tx.signatures = vec![sig];
+            tx
+        };
+        let a = tx(Signature::from([1u8; 64]));
+        let b = tx(Signature::from([2u8; 64]));
+        let txs = [a.clone(), b.clone()];
+        assert_eq!(landed_tx(&txs, &b.signatures[0]), Some(&b));
+        assert_eq!(landed_tx(&txs, &Signature::default()), None);
+        // Identical copies are the same transaction
+        assert_eq!(landed_tx(&[a.clone(), a.clone()], &a.signatures[0]), Some(&a));
+        // Unsigned copies all carry the default signature
+        let unsigned = [tx(Signature::default()), tx(Signature::default())];
+        assert_eq!(landed_tx(&unsigned, &Signature::default()), None);
+    }
+
+    #[tokio::test]
+    async fn rungs_follow_the_fee_ladder() {
+        use crate::fee_ladder::{FeeLadderConfig, FeeRung};
+
+        let (_tx, rx) = mpsc::channel(1);
+        let app_state = Arc::new(Mutex::new(AppState {
+            mode: Mode::Sniffing,
+            active_token: None,
+            last_buy_price: None,
+            holdings_percent: 0.0, quantum_suggestions: Vec::new(),
+        }));
+        let ladder = FeeLadderConfig {
+            rungs: vec![
+                FeeRung::default(),
+                FeeRung { priority_fee_multiplier: 3.0, jito_tip_lamports: Some(10_000), slippage_bps: None },
+            ],
+            nonce_account: Pubkey::new_unique().to_string(),
+        };
+        let engine = BuyEngine::new(
+            Arc::new(AlwaysOkBroadcaster),
+            Arc::new(NonceManager::new(3)),
+            rx,
+            app_state,
+            Config { fee_ladder: Some(ladder), ..Config::default() },
+            None,
+        )
+        .with_jito_client(Arc::new(JitoClient::new(Default::default()).unwrap()));
+
+        let (config, rung) = engine.buy_config_for_rung(1);
+        assert_eq!(rung, Some(1));
+        assert_eq!(config.priority_fee_multiplier, 3.0);
+        assert_eq!(config.jito_tip.map(|(_, lamports)| lamports), Some(10_000));
+        assert_eq!(engine.buy_config_for_rung(2).1, Some(0));
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingBroadcaster {
+        batches: std::sync::Mutex<Vec<usize>>,
+    }
+    impl RpcBroadcaster for RecordingBroadcaster {
+        fn send_on_many_rpc<'a>(
+            &'a self,
+            txs: Vec<VersionedTransaction>,
+            _correlation_id: Option<CorrelationId>,
+        ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
+            self.batches.lock().unwrap().push(txs.len());
+            Box::pin(async { Ok(Signature::from([8u8; 64])) })
+        }
+    }
+
+    #[tokio::test]
+    async fn ladder_sends_every_rung_at_once() {
+        use crate::fee_ladder::{FeeLadderConfig, FeeRung};
+
+        let candidate = PremintCandidate {
+            mint: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            program: "pump.fun".to_string(),
+            slot: 0,
+            timestamp: 0, instruction_summary: None, is_jito_bundle: None, detected_at_ms: 0, token_program: None,
+        };
+        let engine = |fee_ladder: Option<FeeLadderConfig>, rpc: Arc<RecordingBroadcaster>| {
+            let (_tx, rx) = mpsc::channel(1);
+            let app_state = Arc::new(Mutex::new(AppState {
+                mode: Mode::Sniffing,
+                active_token: None,
+                last_buy_price: None,
+                holdings_percent: 0.0, quantum_suggestions: Vec::new(),
+            }));
+            BuyEngine::new(
+                rpc,
+                Arc::new(NonceManager::new(3)),
+                rx,
+                app_state,
+                Config { nonce_count: 3, fee_ladder, ..Config::default() },
+                None,
+            )
+        };
+
+        // Without a ladder every lease sends an identical copy in one broadcast
+        let rpc = Arc::new(RecordingBroadcaster::default());
+        engine(None, rpc.clone()).try_buy_with_guards(candidate.clone(), CorrelationId::new()).await.unwrap();
+        assert_eq!(*rpc.batches.lock().unwrap(), vec![3]);
+
+        // A ladder spends the same leases on distinct rungs, still in one broadcast
+        let ladder = FeeLadderConfig {
+            rungs: vec![FeeRung::default(), FeeRung { priority_fee_multiplier: 3.0, ..FeeRung::default() }],
+            nonce_account: Pubkey::new_unique().to_string(),
+        };
+        let rpc = Arc::new(RecordingBroadcaster::default());
+        engine(Some(ladder), rpc.clone()).try_buy_with_guards(candidate, CorrelationId::new()).await.unwrap();
+        assert_eq!(*rpc.batches.lock().unwrap(), vec![3]);
+    }
 }
\ No newline at end of file
@@ -186,6 +186,8 @@ mod tests {
             timestamp: ts,
             instruction_summary: None,
             is_jito_bundle: None,
+            detected_at_ms: 0,
+            token_program: None,
         }
     }
 
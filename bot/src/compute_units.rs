@@ -0,0 +1,306 @@
+//! Compute unit limit sizing via simulation.
+//!
+//! Simulates the unsigned transaction once per instruction "shape" (program, discriminator,
+//! account count), reads `unitsConsumed` and pads it. Later transactions of the same shape
+//! reuse the cached figure, so the hot path only simulates when the shape changes.
+
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0::Message as MessageV0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::metrics::metrics;
+
+/// Maximum compute units a single transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Simulation-based compute unit sizing policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComputeUnitConfig {
+    /// Extra headroom on top of simulated usage, in percent
+    #[serde(default = "default_padding_percent")]
+    pub padding_percent: u32,
+    /// Lower bound for the padded limit
+    #[serde(default = "default_min_units")]
+    pub min_units: u32,
+    /// Upper bound for the padded limit
+    #[serde(default = "default_max_units")]
+    pub max_units: u32,
+}
+
+fn default_padding_percent() -> u32 {
+    20
+}
+fn default_min_units() -> u32 {
+    20_000
+}
+fn default_max_units() -> u32 {
+    MAX_COMPUTE_UNIT_LIMIT
+}
+
+impl Default for ComputeUnitConfig {
+    fn default() -> Self {
+        Self {
+            padding_percent: default_padding_percent(),
+            min_units: default_min_units(),
+            max_units: default_max_units(),
+        }
+    }
+}
+
+impl ComputeUnitConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_units > MAX_COMPUTE_UNIT_LIMIT {
+            return Err(format!(
+                "compute_units.max_units must be <= {}",
+                MAX_COMPUTE_UNIT_LIMIT
+            ));
+        }
+        if self.min_units > self.max_units {
+            return Err("compute_units.min_units cannot exceed max_units".to_string());
+        }
+        Ok(())
+    }
+
+    /// Apply padding and bounds to a simulated `unitsConsumed`.
+    pub fn padded_limit(&self, units_consumed: u64) -> u32 {
+        let padded = units_consumed.saturating_mul(100 + self.padding_percent as u64) / 100;
+        (padded.min(u32::MAX as u64) as u32).clamp(self.min_units, self.max_units)
+    }
+}
+
+/// Cache key describing the parts of an instruction that drive compute usage.
+/// Mint- and amount-dependent bytes are deliberately excluded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstructionShape {
+    pub dex: String,
+    pub program_id: Pubkey,
+    pub discriminator: Vec<u8>,
+    pub account_count: usize,
+    pub instruction_count: usize,
+}
+
+impl InstructionShape {
+    pub fn of(dex: &str, ix: &Instruction) -> Self {
+        Self {
+            dex: dex.to_string(),
+            program_id: ix.program_id,
+            discriminator: ix.data.iter().take(8).copied().collect(),
+            account_count: ix.accounts.len(),
+            instruction_count: 1,
+        }
+    }
+
+    /// Shape of a multi-instruction swap (ATA setup, wrap, swap, unwrap): keyed on the
+    /// instruction touching the most accounts, which is the DEX call itself.
+    pub fn of_sequence(dex: &str, instructions: &[Instruction]) -> Option<Self> {
+        let main = instructions.iter().max_by_key(|ix| ix.accounts.len())?;
+        Some(Self {
+            instruction_count: instructions.len(),
+            ..Self::of(dex, main)
+        })
+    }
+}
+
+/// Simulation-backed compute unit estimator with a per-shape cache.
+pub struct ComputeUnitEstimator {
+    rpc: Arc<RpcClient>,
+    config: ComputeUnitConfig,
+    cache: RwLock<HashMap<InstructionShape, u32>>,
+}
+
+impl std::fmt::Debug for ComputeUnitEstimator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComputeUnitEstimator")
+            .field("rpc", &self.rpc.url())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl ComputeUnitEstimator {
+    pub fn new(rpc: Arc<RpcClient>, config: ComputeUnitConfig) -> Self {
+        Self {
+            rpc,
+            config,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ComputeUnitConfig {
+        &self.config
+    }
+
+    /// Cached padded limit for a shape, if it has been simulated before.
+    pub async fn cached(&self, shape: &InstructionShape) -> Option<u32> {
+        self.cache.read().await.get(shape).copied()
+    }
+
+    /// Record a simulated `unitsConsumed` for a shape; returns the padded limit stored.
+    pub async fn record(&self, shape: InstructionShape, units_consumed: u64) -> u32 {
+        let limit = self.config.padded_limit(units_consumed);
+        self.cache.write().await.insert(shape, limit);
+        limit
+    }
+
+    /// Compute unit limit for a transaction carrying `program_ixs` (everything except the
+    /// compute budget instructions). Simulates only on a cache miss; falls back to
+    /// `fallback` if simulation fails.
+    pub async fn limit_for(
+        &self,
+        dex: &str,
+        program_ixs: &[Instruction],
+        payer: &Pubkey,
+        recent_blockhash: Hash,
+        fallback: u32,
+    ) -> u32 {
+        let Some(shape) = InstructionShape::of_sequence(dex, program_ixs) else {
+            return fallback;
+        };
+        if let Some(limit) = self.cached(&shape).await {
+            metrics().increment_counter("cu_simulation_cache_hits");
+            return limit;
+        }
+
+        metrics().increment_counter("cu_simulation_cache_misses");
+        match self.simulate(program_ixs, payer, recent_blockhash).await {
+            Some(units) => {
+                let limit = self.record(shape, units).await;
+                debug!(dex, units_consumed = units, limit, "Compute units sized via simulation");
+                limit
+            }
+            None => fallback,
+        }
+    }
+
+    async fn simulate(
+        &self,
+        program_ixs: &[Instruction],
+        payer: &Pubkey,
+        recent_blockhash: Hash,
+    ) -> Option<u64> {
+        // Simulate with the maximum limit so the measurement itself cannot run out of CUs
+        #[allow(deprecated)]
+        let budget_ix = solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(
+            MAX_COMPUTE_UNIT_LIMIT,
+        );
+        let mut instructions = Vec::with_capacity(program_ixs.len() + 1);
+        instructions.push(budget_ix);
+        instructions.extend_from_slice(program_ixs);
+        let message = MessageV0::try_compile(payer, &instructions, &[], recent_blockhash).ok()?;
+        let message = VersionedMessage::V0(message);
+        let required = message.header().num_required_signatures as usize;
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default(); required],
+            message,
+        };
+
+        let sim_cfg = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::processed()),
+            ..Default::default()
+        };
+
+        match self.rpc.simulate_transaction_with_config(&tx, sim_cfg).await {
+            Ok(resp) => {
+                if let Some(err) = resp.value.err {
+                    metrics().increment_counter("cu_simulation_errors");
+                    warn!("Compute unit simulation returned error: {:?}", err);
+                    return None;
+                }
+                resp.value.units_consumed
+            }
+            Err(e) => {
+                metrics().increment_counter("cu_simulation_errors");
+                warn!("Compute unit simulation failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    #[test]
+    fn padded_limit_respects_bounds() {
+        let cfg = ComputeUnitConfig {
+            padding_percent: 25,
+            min_units: 10_000,
+            max_units: 300_000,
+        };
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.padded_limit(40_000), 50_000);
+        assert_eq!(cfg.padded_limit(1_000), 10_000);
+        assert_eq!(cfg.padded_limit(1_000_000), 300_000);
+    }
+
+    #[test]
+    fn shape_ignores_mint_and_amount() {
+        let program = Pubkey::new_unique();
+        let mut data_a = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data_a.extend_from_slice(&100u64.to_le_bytes());
+        let mut data_b = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data_b.extend_from_slice(&999u64.to_le_bytes());
+
+        let a = Instruction::new_with_bytes(
+            program,
+            &data_a,
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        let b = Instruction::new_with_bytes(
+            program,
+            &data_b,
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        assert_eq!(InstructionShape::of("pumpfun", &a), InstructionShape::of("pumpfun", &b));
+        assert_ne!(InstructionShape::of("pumpfun", &a), InstructionShape::of("orca", &b));
+
+        // The widest instruction keys a sequence; setup instructions only change the count
+        let setup = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+        let seq = InstructionShape::of_sequence("raydium", &[setup.clone(), a.clone()]).unwrap();
+        assert_eq!(seq.program_id, program);
+        assert_eq!(seq.instruction_count, 2);
+        assert_ne!(seq, InstructionShape::of("raydium", &a));
+        assert!(InstructionShape::of_sequence("raydium", &[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn cached_shape_skips_simulation() {
+        // Unroutable endpoint: any simulation attempt would fail and return the fallback
+        let estimator = ComputeUnitEstimator::new(
+            Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())),
+            ComputeUnitConfig::default(),
+        );
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[9], vec![]);
+        let shape = InstructionShape::of_sequence("pumpfun", std::slice::from_ref(&ix)).unwrap();
+        let recorded = estimator.record(shape, 50_000).await;
+        assert_eq!(recorded, 60_000);
+
+        let payer = Pubkey::new_unique();
+        let limit = estimator
+            .limit_for("pumpfun", std::slice::from_ref(&ix), &payer, Hash::default(), 200_000)
+            .await;
+        assert_eq!(limit, 60_000);
+
+        let other = Instruction::new_with_bytes(Pubkey::new_unique(), &[9], vec![]);
+        let limit = estimator
+            .limit_for("pumpfun", std::slice::from_ref(&other), &payer, Hash::default(), 200_000)
+            .await;
+        assert_eq!(limit, 200_000);
+    }
+}
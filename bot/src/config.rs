@@ -1,6 +1,25 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 
+use crate::blockhash::BlockhashConfig;
+use crate::compute_units::ComputeUnitConfig;
+use crate::dex::mint::MintPolicy;
+use crate::fee_estimator::PriorityFeeConfig;
+use crate::fee_ladder::FeeLadderConfig;
+use crate::health_check::HealthCheckConfig;
+use crate::jito::JitoConfig;
+use crate::keystore::KeystoreConfig;
+use crate::rate_limit::RateLimitConfig;
+use crate::rebroadcast::RebroadcastConfig;
+use crate::rpc_pool::RpcPoolConfig;
+use crate::rpc_manager::{EndpointRankingConfig, RetryPolicyConfig};
+use crate::signer::RemoteSignerConfig;
+use crate::token_accounts::AccountSweepConfig;
+use crate::tx_guard::TxGuardConfig;
+use crate::tpu::TpuConfig;
+use crate::tx_template::TemplateConfig;
+use crate::wallet_pool::WalletPoolConfig;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SnifferMode {
@@ -9,15 +28,17 @@ pub enum SnifferMode {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum BroadcastMode {
     /// Strict 1:1 pairing (original behavior)
     Pairwise,
     /// Replicate best transaction to all endpoints
     Replicate,
     /// Round-robin transactions across endpoints
+    #[serde(alias = "roundrobin")]
     RoundRobin,
     /// Full fanout - send all transactions to all endpoints
+    #[serde(alias = "fullfanout")]
     FullFanout,
 }
 
@@ -91,6 +112,86 @@ pub struct Config {
     pub http_sig_depth: usize,
     #[serde(default = "default_http_max_parallel_tx_fetch")]
     pub http_max_parallel_tx_fetch: usize,
+
+    // Dynamic priority fees (unset = static `priority_fee_lamports`)
+    #[serde(default)]
+    pub priority_fee: Option<PriorityFeeConfig>,
+
+    // Simulation-sized compute unit limit (unset = static `compute_unit_limit`)
+    #[serde(default)]
+    pub compute_units: Option<ComputeUnitConfig>,
+
+    // Jito bundle submission (unset = plain RPC broadcast)
+    #[serde(default)]
+    pub jito: Option<JitoConfig>,
+
+    // Per-transaction fee/tip/slippage ladder on one durable nonce (unset = identical copies)
+    #[serde(default)]
+    pub fee_ladder: Option<FeeLadderConfig>,
+
+    // Pre-built buy templates refreshed in the background (unset = full build per buy)
+    #[serde(default)]
+    pub tx_templates: Option<TemplateConfig>,
+
+    // Slot-aware blockhash service + confirmation tracking (unset = 15s blockhash cache)
+    #[serde(default)]
+    pub blockhash: Option<BlockhashConfig>,
+
+    // Multi-wallet signer pool with per-trade selection (unset = single `keypair_path` wallet)
+    #[serde(default)]
+    pub wallet_pool: Option<WalletPoolConfig>,
+
+    // Password source for encrypted keystores (unset = env var, then interactive prompt)
+    #[serde(default)]
+    pub keystore: Option<KeystoreConfig>,
+
+    // External signing daemon holding the primary key (unset = sign in-process)
+    #[serde(default)]
+    pub remote_signer: Option<RemoteSignerConfig>,
+
+    // Pre-sign instruction guard overrides (unset = built-in program allowlist, no global cap)
+    #[serde(default)]
+    pub tx_guard: Option<TxGuardConfig>,
+
+    // Token-2022 buy policy (unset = refuse transfer hooks and permanent delegates)
+    #[serde(default)]
+    pub mint_policy: Option<MintPolicy>,
+
+    // Close empty token accounts to reclaim rent (unset = only in-transaction closes on full exits)
+    #[serde(default)]
+    pub account_sweep: Option<AccountSweepConfig>,
+
+    // RPC endpoint scoring and circuit breakers (unset = defaults)
+    #[serde(default)]
+    pub endpoint_ranking: Option<EndpointRankingConfig>,
+
+    // What broadcasts do per RPC error class (unset = defaults)
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicyConfig>,
+
+    // Per-endpoint request quotas shared by every RPC client (unset = unlimited)
+    #[serde(default)]
+    pub rate_limits: Option<RateLimitConfig>,
+
+    // Endpoint roles and per-role timeouts of the shared RPC clients (unset = every endpoint, every role)
+    #[serde(default)]
+    pub rpc_pool: Option<RpcPoolConfig>,
+
+    // Background getHealth/getSlot probes demoting lagging endpoints (unset = disabled)
+    #[serde(default)]
+    pub health_check: Option<HealthCheckConfig>,
+
+    // Resend confirmed-pending buys until they land or expire (unset = send once; needs [blockhash])
+    #[serde(default)]
+    pub rebroadcast: Option<RebroadcastConfig>,
+
+    // Direct QUIC sends to upcoming leaders, alone or racing RPC (unset = RPC/Jito only)
+    #[serde(default)]
+    pub tpu: Option<TpuConfig>,
+
+    // Jupiter-compatible quote/swap API for sells without a native route (unset = no fallback)
+    #[serde(default)]
+    pub jupiter_api_url: Option<String>,
 }
 
 impl Default for Config {
@@ -117,6 +218,26 @@ impl Default for Config {
             http_poll_interval_ms: default_http_poll_interval_ms(),
             http_sig_depth: default_http_sig_depth(),
             http_max_parallel_tx_fetch: default_http_max_parallel_tx_fetch(),
+            priority_fee: None,
+            compute_units: None,
+            jito: None,
+            fee_ladder: None,
+            tx_templates: None,
+            blockhash: None,
+            wallet_pool: None,
+            keystore: None,
+            remote_signer: None,
+            tx_guard: None,
+            mint_policy: None,
+            account_sweep: None,
+            endpoint_ranking: None,
+            retry_policy: None,
+            rate_limits: None,
+            rpc_pool: None,
+            health_check: None,
+            rebroadcast: None,
+            tpu: None,
+            jupiter_api_url: None,
         }
     }
 }
@@ -239,6 +360,73 @@ impl Config {
         if self.rpc_endpoints.is_empty() {
             return Err("At least one RPC endpoint must be configured".to_string());
         }
+
+        if let Some(priority_fee) = &self.priority_fee {
+            priority_fee.validate()?;
+        }
+
+        if let Some(compute_units) = &self.compute_units {
+            compute_units.validate()?;
+        }
+
+        if let Some(jito) = &self.jito {
+            jito.validate()?;
+        }
+
+        if let Some(fee_ladder) = &self.fee_ladder {
+            fee_ladder.validate()?;
+        }
+
+        if let Some(tx_templates) = &self.tx_templates {
+            tx_templates.validate()?;
+        }
+
+        if let Some(blockhash) = &self.blockhash {
+            blockhash.validate()?;
+        }
+
+        if let Some(wallet_pool) = &self.wallet_pool {
+            wallet_pool.validate()?;
+        }
+
+        if let Some(keystore) = &self.keystore {
+            keystore.validate()?;
+        }
+
+        if let Some(remote_signer) = &self.remote_signer {
+            remote_signer.validate()?;
+        }
+
+        if let Some(tx_guard) = &self.tx_guard {
+            tx_guard.validate()?;
+        }
+        if let Some(mint_policy) = &self.mint_policy {
+            mint_policy.validate()?;
+        }
+        if let Some(account_sweep) = &self.account_sweep {
+            account_sweep.validate()?;
+        }
+        if let Some(endpoint_ranking) = &self.endpoint_ranking {
+            endpoint_ranking.validate()?;
+        }
+        if let Some(retry_policy) = &self.retry_policy {
+            retry_policy.validate()?;
+        }
+        if let Some(rate_limits) = &self.rate_limits {
+            rate_limits.validate()?;
+        }
+        if let Some(rpc_pool) = &self.rpc_pool {
+            rpc_pool.validate()?;
+        }
+        if let Some(health_check) = &self.health_check {
+            health_check.validate()?;
+        }
+        if let Some(rebroadcast) = &self.rebroadcast {
+            rebroadcast.validate()?;
+        }
+        if let Some(tpu) = &self.tpu {
+            tpu.validate()?;
+        }
         
         Ok(())
     }
@@ -0,0 +1,179 @@
+//! Transaction confirmation tracking.
+//!
+//! Polls `getSignatureStatuses` until the transaction is confirmed or failed, or until the
+//! block height observed by the `BlockhashService` passes the transaction's
+//! `lastValidBlockHeight`, at which point it can no longer land.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::TransactionConfirmationStatus;
+use tracing::{debug, info, warn};
+
+use crate::blockhash::BlockhashService;
+use crate::metrics::metrics;
+
+/// Terminal state of a sent transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmationOutcome {
+    Confirmed { slot: u64 },
+    Failed(String),
+    /// The blockhash expired before the transaction landed
+    Expired { last_valid_block_height: u64, block_height: u64 },
+}
+
+pub struct ConfirmationTracker {
+    rpc: Arc<RpcClient>,
+    blockhashes: Arc<BlockhashService>,
+    poll_interval: Duration,
+}
+
+impl std::fmt::Debug for ConfirmationTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfirmationTracker")
+            .field("rpc", &self.rpc.url())
+            .field("poll_interval", &self.poll_interval)
+            .finish()
+    }
+}
+
+impl ConfirmationTracker {
+    pub fn new(rpc: Arc<RpcClient>, blockhashes: Arc<BlockhashService>) -> Self {
+        let poll_interval = Duration::from_millis(blockhashes.config().confirm_poll_ms);
+        Self {
+            rpc,
+            blockhashes,
+            poll_interval,
+        }
+    }
+
+    /// `lastValidBlockHeight` for a blockhash the blockhash service issued.
+    pub async fn last_valid_block_height_of(&self, blockhash: &Hash) -> Option<u64> {
+        self.blockhashes.last_valid_block_height(blockhash).await
+    }
+
+    /// `lastValidBlockHeight` of the service's current blockhash: the wait a transaction sent
+    /// now would get if it were not on a durable nonce.
+    pub async fn current_last_valid_block_height(&self) -> Result<u64> {
+        Ok(self.blockhashes.latest().await?.last_valid_block_height)
+    }
+
+    /// Wait for `signature` to reach confirmed commitment or for its blockhash to expire.
+    ///
+    /// RPC errors while polling (rate limits, timeouts) are logged and retried: only a
+    /// confirmed, failed or expired transaction ends the wait.
+    pub async fn wait(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+    ) -> Result<ConfirmationOutcome> {
+        self.wait_any(std::slice::from_ref(signature), last_valid_block_height)
+            .await
+            .map(|(_, outcome)| outcome)
+    }
+
+    /// Like [`wait`](Self::wait) for transactions of which at most one can land (e.g. fee
+    /// ladder rungs on one durable nonce): ends with the first one confirmed or failed and
+    /// returns its index (0 on expiry).
+    pub async fn wait_any(
+        &self,
+        signatures: &[Signature],
+        last_valid_block_height: u64,
+    ) -> Result<(usize, ConfirmationOutcome)> {
+        loop {
+            match self.poll(signatures, last_valid_block_height).await {
+                Ok(Some((index, outcome))) => return Ok((index, record(outcome))),
+                Ok(None) => {}
+                Err(e) => {
+                    metrics().increment_counter("confirm_poll_errors");
+                    warn!(signature = ?signatures.first(), "Confirmation poll failed, retrying: {:#}", e);
+                }
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Confirm a transaction built on a blockhash from the service.
+    pub async fn wait_for_transaction(
+        &self,
+        tx: &VersionedTransaction,
+        signature: &Signature,
+    ) -> Result<ConfirmationOutcome> {
+        let blockhash = tx.message.recent_blockhash();
+        let last_valid_block_height = self
+            .last_valid_block_height_of(blockhash)
+            .await
+            .ok_or_else(|| anyhow!("blockhash {} was not issued by the blockhash service", blockhash))?;
+        self.wait(signature, last_valid_block_height).await
+    }
+
+    /// One poll: the terminal outcome and whose it is, or `None` while a transaction may
+    /// still land.
+    async fn poll(
+        &self,
+        signatures: &[Signature],
+        last_valid_block_height: u64,
+    ) -> Result<Option<(usize, ConfirmationOutcome)>> {
+        // Read the height before the status so a status seen after expiry still counts
+        let block_height = self.blockhashes.current_block_height().await?;
+        if let Some(resolved) = self.status(signatures).await? {
+            return Ok(Some(resolved));
+        }
+        Ok((block_height > last_valid_block_height).then_some((
+            0,
+            ConfirmationOutcome::Expired {
+                last_valid_block_height,
+                block_height,
+            },
+        )))
+    }
+
+    async fn status(&self, signatures: &[Signature]) -> Result<Option<(usize, ConfirmationOutcome)>> {
+        let statuses = self
+            .rpc
+            .get_signature_statuses(signatures)
+            .await
+            .map_err(|e| anyhow!("getSignatureStatuses: {}", e))?;
+        for (index, status) in statuses.value.into_iter().enumerate() {
+            let Some(status) = status else {
+                continue;
+            };
+            if let Some(err) = status.err {
+                return Ok(Some((index, ConfirmationOutcome::Failed(err.to_string()))));
+            }
+            let confirmed = match status.confirmation_status {
+                Some(TransactionConfirmationStatus::Processed) => false,
+                Some(_) => true,
+                // Old nodes: `confirmations: None` means rooted
+                None => status.satisfies_commitment(CommitmentConfig::confirmed()),
+            };
+            if confirmed {
+                return Ok(Some((index, ConfirmationOutcome::Confirmed { slot: status.slot })));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn record(outcome: ConfirmationOutcome) -> ConfirmationOutcome {
+    match &outcome {
+        ConfirmationOutcome::Confirmed { slot } => {
+            metrics().increment_counter("tx_confirmed");
+            debug!(slot, "Transaction confirmed");
+        }
+        ConfirmationOutcome::Failed(err) => {
+            metrics().increment_counter("tx_failed");
+            warn!("Transaction failed on-chain: {}", err);
+        }
+        ConfirmationOutcome::Expired { last_valid_block_height, block_height } => {
+            metrics().increment_counter("tx_expired");
+            info!(last_valid_block_height, block_height, "Transaction blockhash expired before landing");
+        }
+    }
+    outcome
+}
@@ -0,0 +1,131 @@
+//! In-crate DEX integrations (account decoding, quoting and instruction encoding)
+//! that do not depend on external SDK crates.
+
+pub mod jupiter;
+pub mod mint;
+pub mod pumpfun;
+pub mod pumpswap;
+pub mod raydium;
+
+use anyhow::{anyhow, Result};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+
+/// Wrapped SOL mint
+pub const WSOL_MINT: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
+
+/// Direction of a swap relative to SOL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapSide {
+    /// SOL -> token
+    Buy,
+    /// token -> SOL
+    Sell,
+}
+
+/// Minimum acceptable output after applying slippage (bps, 100 = 1%).
+pub fn min_out_with_slippage(expected_out: u64, slippage_bps: u64) -> u64 {
+    ((expected_out as u128) * (10_000u128 - slippage_bps.min(10_000) as u128) / 10_000u128) as u64
+}
+
+/// Constant-product exact-in quote with the input fee taken first (rounded up).
+pub fn constant_product_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> u64 {
+    if amount_in == 0 || reserve_in == 0 || reserve_out == 0 || fee_denominator == 0 {
+        return 0;
+    }
+    let fee = (amount_in as u128 * fee_numerator as u128).div_ceil(fee_denominator as u128);
+    let in_after_fee = (amount_in as u128).saturating_sub(fee);
+    let out = reserve_out as u128 * in_after_fee / (reserve_in as u128 + in_after_fee);
+    out as u64
+}
+
+/// Create (idempotently) the payer's WSOL account, fund it and sync its balance.
+pub fn wrap_sol_instructions(owner: &Pubkey, lamports: u64) -> Result<Vec<Instruction>> {
+    let token_program = spl_token::id();
+    let wsol_ata = get_associated_token_address_with_program_id(owner, &WSOL_MINT, &token_program);
+    #[allow(deprecated)]
+    let fund = solana_sdk::system_instruction::transfer(owner, &wsol_ata, lamports);
+    let sync = spl_token::instruction::sync_native(&token_program, &wsol_ata)
+        .map_err(|e| anyhow!("sync_native: {}", e))?;
+    Ok(vec![
+        create_associated_token_account_idempotent(owner, owner, &WSOL_MINT, &token_program),
+        fund,
+        sync,
+    ])
+}
+
+/// Close the payer's WSOL account, returning every lamport (wrapped or rent) to the owner.
+pub fn unwrap_sol_instruction(owner: &Pubkey) -> Result<Instruction> {
+    let token_program = spl_token::id();
+    let wsol_ata = get_associated_token_address_with_program_id(owner, &WSOL_MINT, &token_program);
+    spl_token::instruction::close_account(&token_program, &wsol_ata, owner, owner, &[])
+        .map_err(|e| anyhow!("close_account: {}", e))
+}
+
+/// Close an empty token account of either token program, returning its rent to `owner`.
+pub fn close_token_account_instruction(token_program: &Pubkey, account: &Pubkey, owner: &Pubkey) -> Instruction {
+    // spl_token refuses other program ids; CloseAccount is encoded identically in Token-2022
+    let mut ix = spl_token::instruction::close_account(&spl_token::id(), account, owner, owner, &[])
+        .expect("spl_token program id");
+    ix.program_id = *token_program;
+    ix
+}
+
+/// Read a little-endian u64 at `offset`.
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    data.get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("account data too short for u64 at {}", offset))
+}
+
+/// Read a pubkey at `offset`.
+pub(crate) fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    data.get(offset..offset + 32)
+        .map(|b| Pubkey::new_from_array(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("account data too short for pubkey at {}", offset))
+}
+
+/// Amount held by an SPL token account (same offset for Token and Token-2022).
+pub(crate) fn token_account_amount(data: &[u8]) -> Result<u64> {
+    read_u64(data, 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_product_quote_matches_hand_calculation() {
+        // 1 SOL into a 100 SOL / 1_000_000 token pool with a 0.25% fee
+        let out = constant_product_out(1_000_000_000, 100_000_000_000, 1_000_000, 25, 10_000);
+        // in_after_fee = 997_500_000 -> 1_000_000 * 997.5e6 / 100.9975e9
+        assert_eq!(out, 9_876);
+        assert_eq!(constant_product_out(0, 1, 1, 25, 10_000), 0);
+    }
+
+    #[test]
+    fn slippage_bounds() {
+        assert_eq!(min_out_with_slippage(10_000, 100), 9_900);
+        assert_eq!(min_out_with_slippage(10_000, 20_000), 0);
+    }
+
+    #[test]
+    fn wrap_and_unwrap_target_wsol_ata() {
+        let owner = Pubkey::new_unique();
+        let ata = get_associated_token_address_with_program_id(&owner, &WSOL_MINT, &spl_token::id());
+        let wrap = wrap_sol_instructions(&owner, 5).unwrap();
+        assert_eq!(wrap.len(), 3);
+        assert!(wrap[2].accounts.iter().any(|m| m.pubkey == ata));
+        let close = unwrap_sol_instruction(&owner).unwrap();
+        assert_eq!(close.accounts[0].pubkey, ata);
+    }
+}
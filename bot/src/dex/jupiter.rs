@@ -0,0 +1,289 @@
+//! Jupiter-compatible aggregator route: quote, swap-instructions and address lookup tables.
+//!
+//! Only the routing instructions are taken from the API. Compute budget instructions are
+//! dropped so the caller can apply its own, and the lookup tables the route references are
+//! fetched so the caller can compile them into its own `VersionedTransaction`.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::AddressLookupTableAccount,
+    pubkey::Pubkey,
+};
+
+/// Size of the lookup table metadata header preceding the address list
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+/// `ProgramState::LookupTable` discriminator
+const LOOKUP_TABLE_DISCRIMINATOR: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiAccountMeta {
+    pubkey: String,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiInstruction {
+    program_id: String,
+    accounts: Vec<ApiAccountMeta>,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapInstructionsResponse {
+    #[serde(default)]
+    setup_instructions: Vec<ApiInstruction>,
+    swap_instruction: ApiInstruction,
+    cleanup_instruction: Option<ApiInstruction>,
+    #[serde(default)]
+    other_instructions: Vec<ApiInstruction>,
+    #[serde(default)]
+    address_lookup_table_addresses: Vec<String>,
+}
+
+impl ApiInstruction {
+    /// Convert to an `Instruction`, refusing any signer other than `user`.
+    fn into_instruction(self, user: &Pubkey) -> Result<Instruction> {
+        let program_id = Pubkey::from_str(&self.program_id)
+            .map_err(|e| anyhow!("invalid program id {}: {}", self.program_id, e))?;
+        let data = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &self.data)
+            .map_err(|e| anyhow!("invalid instruction data: {}", e))?;
+        let accounts = self
+            .accounts
+            .into_iter()
+            .map(|meta| {
+                let pubkey = Pubkey::from_str(&meta.pubkey)
+                    .map_err(|e| anyhow!("invalid account {}: {}", meta.pubkey, e))?;
+                if meta.is_signer && pubkey != *user {
+                    return Err(anyhow!("unexpected signer account: {}", pubkey));
+                }
+                Ok(AccountMeta {
+                    pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Instruction::new_with_bytes(program_id, &data, accounts))
+    }
+}
+
+/// A quoted route ready to be compiled into a transaction.
+#[derive(Debug, Clone)]
+pub struct JupiterRoute {
+    /// Setup, swap, cleanup and other instructions, in execution order
+    pub instructions: Vec<Instruction>,
+    /// Program id of the swap instruction itself
+    pub swap_program: Pubkey,
+    pub lookup_table_addresses: Vec<Pubkey>,
+    pub out_amount: u64,
+    /// Minimum output after slippage (`otherAmountThreshold`)
+    pub min_out_amount: u64,
+}
+
+/// Client for a Jupiter v6 compatible quote/swap API.
+#[derive(Debug, Clone)]
+pub struct JupiterClient {
+    http: Client,
+    base_url: String,
+}
+
+impl JupiterClient {
+    pub fn new(http: Client, base_url: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Fetch an exact-in quote. The raw quote is returned because the swap endpoint
+    /// expects it back verbatim.
+    pub async fn quote(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+        slippage_bps: u64,
+    ) -> Result<Value> {
+        let resp = self
+            .http
+            .get(format!("{}/quote", self.base_url))
+            .query(&[
+                ("inputMint", input_mint.to_string()),
+                ("outputMint", output_mint.to_string()),
+                ("amount", amount.to_string()),
+                ("slippageBps", slippage_bps.to_string()),
+            ])
+            .send()
+            .await
+            .context("quote request")?;
+        let status = resp.status();
+        let body: Value = resp.json().await.context("quote response")?;
+        if !status.is_success() {
+            return Err(anyhow!("quote failed with HTTP {}: {}", status, body));
+        }
+        Ok(body)
+    }
+
+    /// Quote and request the swap instructions for `user`.
+    pub async fn route(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+        slippage_bps: u64,
+        user: &Pubkey,
+    ) -> Result<JupiterRoute> {
+        let quote = self.quote(input_mint, output_mint, amount, slippage_bps).await?;
+        let out_amount = amount_field(&quote, "outAmount")?;
+        let min_out_amount = amount_field(&quote, "otherAmountThreshold")?;
+
+        let resp = self
+            .http
+            .post(format!("{}/swap-instructions", self.base_url))
+            .json(&serde_json::json!({
+                "quoteResponse": quote,
+                "userPublicKey": user.to_string(),
+                "wrapAndUnwrapSol": true,
+            }))
+            .send()
+            .await
+            .context("swap-instructions request")?;
+        let status = resp.status();
+        let body: Value = resp.json().await.context("swap-instructions response")?;
+        if !status.is_success() {
+            return Err(anyhow!("swap-instructions failed with HTTP {}: {}", status, body));
+        }
+        if let Some(err) = body.get("error") {
+            return Err(anyhow!("swap-instructions error: {}", err));
+        }
+        let parsed: SwapInstructionsResponse =
+            serde_json::from_value(body).context("unexpected swap-instructions shape")?;
+
+        let swap = parsed.swap_instruction.into_instruction(user)?;
+        let swap_program = swap.program_id;
+        let mut instructions = Vec::new();
+        for ix in parsed.setup_instructions {
+            instructions.push(ix.into_instruction(user)?);
+        }
+        instructions.push(swap);
+        if let Some(ix) = parsed.cleanup_instruction {
+            instructions.push(ix.into_instruction(user)?);
+        }
+        for ix in parsed.other_instructions {
+            instructions.push(ix.into_instruction(user)?);
+        }
+
+        let lookup_table_addresses = parsed
+            .address_lookup_table_addresses
+            .iter()
+            .map(|a| Pubkey::from_str(a).map_err(|e| anyhow!("invalid lookup table {}: {}", a, e)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(JupiterRoute {
+            instructions,
+            swap_program,
+            lookup_table_addresses,
+            out_amount,
+            min_out_amount,
+        })
+    }
+}
+
+fn amount_field(quote: &Value, field: &str) -> Result<u64> {
+    quote
+        .get(field)
+        .and_then(|v| v.as_str().and_then(|s| s.parse().ok()).or_else(|| v.as_u64()))
+        .ok_or_else(|| anyhow!("quote missing {}", field))
+}
+
+/// Decode an on-chain address lookup table account.
+pub fn decode_lookup_table(key: Pubkey, data: &[u8]) -> Result<AddressLookupTableAccount> {
+    let discriminator = data
+        .get(..4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("lookup table {} too short", key))?;
+    if discriminator != LOOKUP_TABLE_DISCRIMINATOR || data.len() < LOOKUP_TABLE_META_SIZE {
+        return Err(anyhow!("account {} is not an initialized lookup table", key));
+    }
+    let addresses = data[LOOKUP_TABLE_META_SIZE..]
+        .chunks_exact(32)
+        .map(|c| Pubkey::new_from_array(c.try_into().unwrap()))
+        .collect();
+    Ok(AddressLookupTableAccount { key, addresses })
+}
+
+/// Fetch and decode the lookup tables a route references.
+pub async fn fetch_lookup_tables(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+) -> Result<Vec<AddressLookupTableAccount>> {
+    if addresses.is_empty() {
+        return Ok(Vec::new());
+    }
+    let accounts = rpc.get_multiple_accounts(addresses).await?;
+    accounts
+        .into_iter()
+        .zip(addresses)
+        .map(|(acc, key)| {
+            let acc = acc.ok_or_else(|| anyhow!("lookup table {} not found", key))?;
+            decode_lookup_table(*key, &acc.data)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_lookup_table_addresses() {
+        let entries = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = vec![0u8; LOOKUP_TABLE_META_SIZE];
+        data[..4].copy_from_slice(&LOOKUP_TABLE_DISCRIMINATOR.to_le_bytes());
+        for e in &entries {
+            data.extend_from_slice(e.as_ref());
+        }
+        let key = Pubkey::new_unique();
+        let table = decode_lookup_table(key, &data).unwrap();
+        assert_eq!(table.key, key);
+        assert_eq!(table.addresses, entries);
+        assert!(decode_lookup_table(key, &[0u8; LOOKUP_TABLE_META_SIZE]).is_err());
+    }
+
+    #[test]
+    fn api_instruction_rejects_foreign_signers() {
+        let user = Pubkey::new_unique();
+        let ix = ApiInstruction {
+            program_id: Pubkey::new_unique().to_string(),
+            accounts: vec![ApiAccountMeta {
+                pubkey: Pubkey::new_unique().to_string(),
+                is_signer: true,
+                is_writable: true,
+            }],
+            data: "AQID".to_string(),
+        };
+        assert!(ix.into_instruction(&user).is_err());
+
+        let ix = ApiInstruction {
+            program_id: Pubkey::new_unique().to_string(),
+            accounts: vec![ApiAccountMeta {
+                pubkey: user.to_string(),
+                is_signer: true,
+                is_writable: true,
+            }],
+            data: "AQID".to_string(),
+        };
+        assert_eq!(ix.into_instruction(&user).unwrap().data, vec![1, 2, 3]);
+    }
+}
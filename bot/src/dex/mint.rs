@@ -0,0 +1,337 @@
+//! SPL Token / Token-2022 mint decoding.
+//!
+//! The owning program of a mint decides how its token accounts are derived (ATA seeds include
+//! the token program), so every path that touches token accounts resolves it from the mint
+//! account first. Token-2022 extensions that change what a buy or sell means are decoded:
+//! transfer fees (the recipient gets less than was sent), transfer hooks (an arbitrary program
+//! runs on every transfer), permanent delegates (someone else can move or burn our balance)
+//! and non-transferable mints (the position could never be sold).
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use super::{read_pubkey, read_u64};
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// `Mint` size without extensions
+const MINT_LEN: usize = 82;
+const MINT_DECIMALS: usize = 44;
+/// Extended Token-2022 accounts are padded to the token account size, followed by the
+/// account type byte and the TLV extension area
+const ACCOUNT_TYPE_OFFSET: usize = 165;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const TLV_START: usize = ACCOUNT_TYPE_OFFSET + 1;
+
+const EXT_TRANSFER_FEE_CONFIG: u16 = 1;
+const EXT_NON_TRANSFERABLE: u16 = 9;
+const EXT_PERMANENT_DELEGATE: u16 = 12;
+const EXT_TRANSFER_HOOK: u16 = 14;
+
+/// One transfer fee schedule of a `TransferFeeConfig` extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFee {
+    /// First epoch the schedule applies to
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    fn decode(data: &[u8], offset: usize) -> Result<Self> {
+        Ok(Self {
+            epoch: read_u64(data, offset)?,
+            maximum_fee: read_u64(data, offset + 8)?,
+            basis_points: read_u16(data, offset + 16)?,
+        })
+    }
+
+    /// Fee withheld from a transfer of `amount` (rounded up, capped at `maximum_fee`).
+    pub fn fee(&self, amount: u64) -> u64 {
+        if self.basis_points == 0 || amount == 0 {
+            return 0;
+        }
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(10_000);
+        (fee as u64).min(self.maximum_fee)
+    }
+}
+
+/// Current and scheduled transfer fees; a new schedule takes effect two epochs after it is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFeeConfig {
+    pub older: TransferFee,
+    pub newer: TransferFee,
+}
+
+impl TransferFeeConfig {
+    pub fn for_epoch(&self, epoch: u64) -> &TransferFee {
+        if epoch >= self.newer.epoch {
+            &self.newer
+        } else {
+            &self.older
+        }
+    }
+
+    /// The larger fee of both schedules. Quotes use this instead of looking up the epoch:
+    /// the two only differ while a fee change is pending.
+    pub fn worst_case_fee(&self, amount: u64) -> u64 {
+        self.older.fee(amount).max(self.newer.fee(amount))
+    }
+
+    pub fn max_basis_points(&self) -> u16 {
+        self.older.basis_points.max(self.newer.basis_points)
+    }
+}
+
+/// What the builder needs to know about a mint before touching its token accounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintInfo {
+    /// SPL Token or Token-2022
+    pub token_program: Pubkey,
+    pub decimals: u8,
+    pub transfer_fee: Option<TransferFeeConfig>,
+    pub transfer_hook_program: Option<Pubkey>,
+    pub permanent_delegate: Option<Pubkey>,
+    pub non_transferable: bool,
+}
+
+impl MintInfo {
+    /// Legacy SPL Token mint with no extensions.
+    pub fn legacy(decimals: u8) -> Self {
+        Self {
+            token_program: spl_token::id(),
+            decimals,
+            transfer_fee: None,
+            transfer_hook_program: None,
+            permanent_delegate: None,
+            non_transferable: false,
+        }
+    }
+
+    /// Decode a mint account owned by `owner`.
+    pub fn decode(owner: &Pubkey, data: &[u8]) -> Result<Self> {
+        if *owner != spl_token::id() && *owner != TOKEN_2022_PROGRAM_ID {
+            return Err(anyhow!("account owned by {} is not a token mint", owner));
+        }
+        if data.len() < MINT_LEN {
+            return Err(anyhow!("mint data too short ({} bytes)", data.len()));
+        }
+        let mut info = Self {
+            token_program: *owner,
+            ..Self::legacy(data[MINT_DECIMALS])
+        };
+        if *owner == spl_token::id() || data.len() <= ACCOUNT_TYPE_OFFSET {
+            return Ok(info);
+        }
+        if data[ACCOUNT_TYPE_OFFSET] != ACCOUNT_TYPE_MINT {
+            return Err(anyhow!("Token-2022 account is not a mint"));
+        }
+
+        let mut offset = TLV_START;
+        while offset + 4 <= data.len() {
+            let ext_type = read_u16(data, offset)?;
+            let len = read_u16(data, offset + 2)? as usize;
+            let value = data
+                .get(offset + 4..offset + 4 + len)
+                .ok_or_else(|| anyhow!("truncated extension {}", ext_type))?;
+            match ext_type {
+                0 => break,
+                EXT_TRANSFER_FEE_CONFIG => {
+                    // authorities (2 x 32) and withheld amount (8) precede the schedules
+                    info.transfer_fee = Some(TransferFeeConfig {
+                        older: TransferFee::decode(value, 72)?,
+                        newer: TransferFee::decode(value, 90)?,
+                    });
+                }
+                EXT_NON_TRANSFERABLE => info.non_transferable = true,
+                EXT_PERMANENT_DELEGATE => info.permanent_delegate = non_zero(read_pubkey(value, 0)?),
+                EXT_TRANSFER_HOOK => info.transfer_hook_program = non_zero(read_pubkey(value, 32)?),
+                _ => {}
+            }
+            offset += 4 + len;
+        }
+        Ok(info)
+    }
+
+    pub fn is_token_2022(&self) -> bool {
+        self.token_program == TOKEN_2022_PROGRAM_ID
+    }
+
+    /// Worst-case transfer fee on moving `amount` (0 without a fee extension).
+    pub fn transfer_fee(&self, amount: u64) -> u64 {
+        self.transfer_fee.map_or(0, |fee| fee.worst_case_fee(amount))
+    }
+
+    /// What the recipient of a transfer of `amount` ends up with.
+    pub fn net_of_fee(&self, amount: u64) -> u64 {
+        amount.saturating_sub(self.transfer_fee(amount))
+    }
+}
+
+/// Which Token-2022 mints the bot is willing to buy. Sells are never blocked: an existing
+/// position must always be exitable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MintPolicy {
+    /// Buy mints whose transfers run a hook program. Native swap builders do not pass the
+    /// hook's extra accounts, so only provider-built routes can trade them.
+    #[serde(default)]
+    pub allow_transfer_hook: bool,
+    /// Buy mints with a permanent delegate (an authority that can move or burn any balance)
+    #[serde(default)]
+    pub allow_permanent_delegate: bool,
+    /// Highest transfer fee accepted, in bps (unset = any)
+    #[serde(default)]
+    pub max_transfer_fee_bps: Option<u16>,
+}
+
+impl MintPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_transfer_fee_bps.is_some_and(|bps| bps > 10_000) {
+            return Err("mint_policy.max_transfer_fee_bps must be <= 10000".to_string());
+        }
+        Ok(())
+    }
+
+    /// Reason to refuse buying `mint`, if any.
+    pub fn check(&self, mint: &MintInfo) -> Result<(), String> {
+        if mint.non_transferable {
+            return Err("non-transferable mint cannot be sold".to_string());
+        }
+        if let (Some(program), false) = (mint.transfer_hook_program, self.allow_transfer_hook) {
+            return Err(format!("transfer hook program {}", program));
+        }
+        if let (Some(delegate), false) = (mint.permanent_delegate, self.allow_permanent_delegate) {
+            return Err(format!("permanent delegate {}", delegate));
+        }
+        if let (Some(fee), Some(max_bps)) = (mint.transfer_fee, self.max_transfer_fee_bps) {
+            if fee.max_basis_points() > max_bps {
+                return Err(format!("transfer fee {} bps exceeds {} bps", fee.max_basis_points(), max_bps));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("data too short for u16 at {}", offset))
+}
+
+fn non_zero(key: Pubkey) -> Option<Pubkey> {
+    (key != Pubkey::default()).then_some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Token-2022 mint data with the given `(type, value)` extensions.
+    fn token_2022_mint(decimals: u8, extensions: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = vec![0u8; ACCOUNT_TYPE_OFFSET];
+        data[MINT_DECIMALS] = decimals;
+        data[45] = 1; // is_initialized
+        data.push(ACCOUNT_TYPE_MINT);
+        for (ext_type, value) in extensions {
+            data.extend_from_slice(&ext_type.to_le_bytes());
+            data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    fn transfer_fee_ext(older_bps: u16, newer_bps: u16, maximum_fee: u64) -> (u16, Vec<u8>) {
+        let mut value = vec![0u8; 72];
+        for (epoch, bps) in [(0u64, older_bps), (500u64, newer_bps)] {
+            value.extend_from_slice(&epoch.to_le_bytes());
+            value.extend_from_slice(&maximum_fee.to_le_bytes());
+            value.extend_from_slice(&bps.to_le_bytes());
+        }
+        (EXT_TRANSFER_FEE_CONFIG, value)
+    }
+
+    #[test]
+    fn legacy_and_plain_token_2022_mints() {
+        let mut legacy = vec![0u8; MINT_LEN];
+        legacy[MINT_DECIMALS] = 6;
+        assert_eq!(MintInfo::decode(&spl_token::id(), &legacy).unwrap(), MintInfo::legacy(6));
+
+        let plain = MintInfo::decode(&TOKEN_2022_PROGRAM_ID, &legacy).unwrap();
+        assert!(plain.is_token_2022());
+        assert_eq!(plain.transfer_fee(1_000), 0);
+
+        assert!(MintInfo::decode(&Pubkey::new_unique(), &legacy).is_err());
+        assert!(MintInfo::decode(&spl_token::id(), &legacy[..40]).is_err());
+    }
+
+    #[test]
+    fn decodes_extensions() {
+        let delegate = Pubkey::new_unique();
+        let hook = Pubkey::new_unique();
+        let mut hook_value = vec![0u8; 32];
+        hook_value.extend_from_slice(hook.as_ref());
+        let data = token_2022_mint(
+            9,
+            &[
+                (18, vec![7u8; 64]), // metadata pointer, ignored
+                transfer_fee_ext(100, 250, 1_000_000),
+                (EXT_PERMANENT_DELEGATE, delegate.to_bytes().to_vec()),
+                (EXT_TRANSFER_HOOK, hook_value),
+            ],
+        );
+        let info = MintInfo::decode(&TOKEN_2022_PROGRAM_ID, &data).unwrap();
+        assert_eq!(info.decimals, 9);
+        assert_eq!(info.permanent_delegate, Some(delegate));
+        assert_eq!(info.transfer_hook_program, Some(hook));
+        assert!(!info.non_transferable);
+
+        let fee = info.transfer_fee.unwrap();
+        assert_eq!(fee.for_epoch(10).basis_points, 100);
+        assert_eq!(fee.for_epoch(500).basis_points, 250);
+        // 2.5% of 10_001 rounds up
+        assert_eq!(info.transfer_fee(10_001), 251);
+        assert_eq!(info.net_of_fee(10_001), 9_750);
+        // capped by maximum_fee
+        assert_eq!(info.transfer_fee(1_000_000_000), 1_000_000);
+
+        // A zero hook program means the extension is present but unset
+        let unset = token_2022_mint(9, &[(EXT_TRANSFER_HOOK, vec![0u8; 64])]);
+        assert_eq!(MintInfo::decode(&TOKEN_2022_PROGRAM_ID, &unset).unwrap().transfer_hook_program, None);
+    }
+
+    #[test]
+    fn policy_rejections() {
+        let strict = MintPolicy::default();
+        assert!(strict.check(&MintInfo::legacy(6)).is_ok());
+
+        let hooked = MintInfo {
+            transfer_hook_program: Some(Pubkey::new_unique()),
+            ..MintInfo::legacy(6)
+        };
+        assert!(strict.check(&hooked).unwrap_err().contains("transfer hook"));
+        let permissive = MintPolicy {
+            allow_transfer_hook: true,
+            allow_permanent_delegate: true,
+            max_transfer_fee_bps: Some(200),
+        };
+        assert!(permissive.check(&hooked).is_ok());
+
+        let delegated = MintInfo {
+            permanent_delegate: Some(Pubkey::new_unique()),
+            ..MintInfo::legacy(6)
+        };
+        assert!(strict.check(&delegated).unwrap_err().contains("permanent delegate"));
+        assert!(permissive.check(&delegated).is_ok());
+
+        let data = token_2022_mint(6, &[transfer_fee_ext(100, 250, u64::MAX)]);
+        let fee_mint = MintInfo::decode(&TOKEN_2022_PROGRAM_ID, &data).unwrap();
+        assert!(strict.check(&fee_mint).is_ok());
+        assert!(permissive.check(&fee_mint).unwrap_err().contains("250 bps"));
+
+        let soulbound = MintInfo { non_transferable: true, ..MintInfo::legacy(0) };
+        assert!(permissive.check(&soulbound).is_err());
+
+        assert!(MintPolicy { max_transfer_fee_bps: Some(10_001), ..MintPolicy::default() }.validate().is_err());
+    }
+}
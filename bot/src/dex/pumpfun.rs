@@ -0,0 +1,349 @@
+//! pump.fun bonding curve state and migration detection.
+//!
+//! A curve that fills sets `complete` and is then migrated into a PumpSwap pool by the
+//! `migrate` instruction. From that point on, bonding curve sells fail and open positions
+//! have to be sold through PumpSwap instead.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+use super::{constant_product_out, read_pubkey, read_u64};
+use crate::metrics::metrics;
+use crate::types::ProgramLogEvent;
+
+pub const PUMP_FUN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+
+/// Log line emitted by the pump.fun `migrate` instruction
+pub const MIGRATE_LOG: &str = "Instruction: Migrate";
+
+/// Fee program owning the pump.fun `fee_config` account
+pub const PUMP_FEE_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ");
+
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+
+/// Reserves of a freshly created curve
+pub const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
+pub const INITIAL_VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000;
+/// Protocol plus creator fee taken from the SOL paid in
+pub const BUY_FEE_BPS: u64 = 125;
+
+const GLOBAL_FEE_RECIPIENT: usize = 41;
+
+const CURVE_COMPLETE: usize = 48;
+const CURVE_CREATOR: usize = 49;
+const CURVE_MIN_LEN: usize = 49;
+
+/// How long a "not migrated" answer is trusted before the curve is re-read.
+const NOT_MIGRATED_TTL: Duration = Duration::from_secs(5);
+
+/// Decoded pump.fun `BondingCurve` account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BondingCurveState {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    /// Present on curves created after the creator-fee upgrade
+    pub creator: Option<Pubkey>,
+}
+
+impl BondingCurveState {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < CURVE_MIN_LEN {
+            return Err(anyhow!("bonding curve account too short: {} bytes", data.len()));
+        }
+        Ok(Self {
+            virtual_token_reserves: read_u64(data, 8)?,
+            virtual_sol_reserves: read_u64(data, 16)?,
+            real_token_reserves: read_u64(data, 24)?,
+            real_sol_reserves: read_u64(data, 32)?,
+            token_total_supply: read_u64(data, 40)?,
+            complete: data[CURVE_COMPLETE] != 0,
+            creator: read_pubkey(data, CURVE_CREATOR).ok(),
+        })
+    }
+
+    /// Virtual reserves of a curve nobody has bought from yet.
+    pub fn initial() -> Self {
+        Self {
+            virtual_token_reserves: INITIAL_VIRTUAL_TOKEN_RESERVES,
+            virtual_sol_reserves: INITIAL_VIRTUAL_SOL_RESERVES,
+            real_token_reserves: 0,
+            real_sol_reserves: 0,
+            token_total_supply: 0,
+            complete: false,
+            creator: None,
+        }
+    }
+
+    /// Tokens received for `sol_in` lamports (fee taken first).
+    pub fn buy_quote(&self, sol_in: u64) -> u64 {
+        constant_product_out(
+            sol_in,
+            self.virtual_sol_reserves,
+            self.virtual_token_reserves,
+            BUY_FEE_BPS,
+            10_000,
+        )
+    }
+}
+
+pub fn bonding_curve_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &PUMP_FUN_PROGRAM_ID).0
+}
+
+pub fn global_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"global"], &PUMP_FUN_PROGRAM_ID).0
+}
+
+/// Fee recipient configured in the pump.fun `Global` account.
+pub fn decode_fee_recipient(global_data: &[u8]) -> Result<Pubkey> {
+    read_pubkey(global_data, GLOBAL_FEE_RECIPIENT)
+}
+
+/// Accounts of a pump.fun buy that do not depend on the mint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuyFixedAccounts {
+    pub global: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub event_authority: Pubkey,
+    pub global_volume_accumulator: Pubkey,
+    pub user_volume_accumulator: Pubkey,
+    pub fee_config: Pubkey,
+}
+
+impl BuyFixedAccounts {
+    pub fn derive(user: &Pubkey, fee_recipient: Pubkey) -> Self {
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &PUMP_FUN_PROGRAM_ID).0;
+        Self {
+            global: global_address(),
+            fee_recipient,
+            event_authority: pda(&[b"__event_authority"]),
+            global_volume_accumulator: pda(&[b"global_volume_accumulator"]),
+            user_volume_accumulator: pda(&[b"user_volume_accumulator", user.as_ref()]),
+            fee_config: Pubkey::find_program_address(
+                &[b"fee_config", PUMP_FUN_PROGRAM_ID.as_ref()],
+                &PUMP_FEE_PROGRAM_ID,
+            )
+            .0,
+        }
+    }
+}
+
+/// pump.fun `buy`: receive exactly `token_amount`, paying at most `max_sol_cost`.
+pub fn buy_instruction(
+    fixed: &BuyFixedAccounts,
+    user: &Pubkey,
+    mint: &Pubkey,
+    creator: &Pubkey,
+    token_program: &Pubkey,
+    token_amount: u64,
+    max_sol_cost: u64,
+) -> Instruction {
+    let bonding_curve = bonding_curve_address(mint);
+    let creator_vault =
+        Pubkey::find_program_address(&[b"creator-vault", creator.as_ref()], &PUMP_FUN_PROGRAM_ID).0;
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&BUY_DISCRIMINATOR);
+    data.extend_from_slice(&token_amount.to_le_bytes());
+    data.extend_from_slice(&max_sol_cost.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        PUMP_FUN_PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(fixed.global, false),
+            AccountMeta::new(fixed.fee_recipient, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(bonding_curve, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(&bonding_curve, mint, token_program),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(user, mint, token_program),
+                false,
+            ),
+            AccountMeta::new(*user, true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new(creator_vault, false),
+            AccountMeta::new_readonly(fixed.event_authority, false),
+            AccountMeta::new_readonly(PUMP_FUN_PROGRAM_ID, false),
+            AccountMeta::new(fixed.global_volume_accumulator, false),
+            AccountMeta::new(fixed.user_volume_accumulator, false),
+            AccountMeta::new_readonly(fixed.fee_config, false),
+            AccountMeta::new_readonly(PUMP_FEE_PROGRAM_ID, false),
+        ],
+    )
+}
+
+/// True if the logs belong to a pump.fun migration transaction.
+pub fn is_migration_log(logs: &[String]) -> bool {
+    logs.iter().any(|line| line.contains(MIGRATE_LOG))
+}
+
+/// Tracks which mints have left the bonding curve.
+///
+/// The curve's `complete` flag is authoritative; migration transactions seen by the sniffer
+/// drop cached "still on the curve" answers so the next lookup re-reads the curve.
+pub struct MigrationTracker {
+    rpc: Arc<RpcClient>,
+    migrated: RwLock<HashSet<Pubkey>>,
+    not_migrated: RwLock<HashMap<Pubkey, Instant>>,
+}
+
+impl std::fmt::Debug for MigrationTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MigrationTracker")
+            .field("rpc", &self.rpc.url())
+            .finish()
+    }
+}
+
+impl MigrationTracker {
+    pub fn new(rpc: Arc<RpcClient>) -> Self {
+        Self {
+            rpc,
+            migrated: RwLock::new(HashSet::new()),
+            not_migrated: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record a mint as migrated (e.g. from an external signal).
+    pub async fn mark_migrated(&self, mint: Pubkey) {
+        self.not_migrated.write().await.remove(&mint);
+        if self.migrated.write().await.insert(mint) {
+            metrics().increment_counter("pumpfun_migrations_detected");
+            info!(mint = %mint, "pump.fun curve migrated; sells route to PumpSwap");
+        }
+    }
+
+    /// Feed a sniffer log event; returns true if it was a migration transaction.
+    pub async fn observe(&self, event: &ProgramLogEvent) -> bool {
+        if !is_migration_log(&event.logs) {
+            return false;
+        }
+        debug!(signature = %event.signature, slot = event.slot, "pump.fun migration transaction seen");
+        self.not_migrated.write().await.clear();
+        true
+    }
+
+    /// Whether `mint`'s curve has completed. RPC failures are treated as "not migrated"
+    /// so the caller keeps its default route.
+    pub async fn is_migrated(&self, mint: &Pubkey) -> bool {
+        if self.migrated.read().await.contains(mint) {
+            return true;
+        }
+        if let Some(checked) = self.not_migrated.read().await.get(mint) {
+            if checked.elapsed() < NOT_MIGRATED_TTL {
+                return false;
+            }
+        }
+
+        let curve = match self.rpc.get_account_data(&bonding_curve_address(mint)).await {
+            Ok(data) => BondingCurveState::decode(&data),
+            Err(e) => Err(anyhow!(e)),
+        };
+        match curve {
+            Ok(curve) if curve.complete => {
+                self.mark_migrated(*mint).await;
+                true
+            }
+            Ok(_) => {
+                self.not_migrated.write().await.insert(*mint, Instant::now());
+                false
+            }
+            Err(e) => {
+                debug!(mint = %mint, "Bonding curve lookup failed: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_complete_flag_and_creator() {
+        let creator = Pubkey::new_unique();
+        let mut data = vec![0u8; 81];
+        data[8..16].copy_from_slice(&1_073_000_000_000_000u64.to_le_bytes());
+        data[16..24].copy_from_slice(&30_000_000_000u64.to_le_bytes());
+        data[CURVE_COMPLETE] = 1;
+        data[CURVE_CREATOR..CURVE_CREATOR + 32].copy_from_slice(creator.as_ref());
+        let curve = BondingCurveState::decode(&data).unwrap();
+        assert!(curve.complete);
+        assert_eq!(curve.virtual_sol_reserves, 30_000_000_000);
+        assert_eq!(curve.creator, Some(creator));
+
+        // Pre-upgrade curves have no creator field
+        let curve = BondingCurveState::decode(&data[..49]).unwrap();
+        assert_eq!(curve.creator, None);
+        assert!(BondingCurveState::decode(&data[..20]).is_err());
+    }
+
+    #[test]
+    fn buy_instruction_patches_mint_accounts() {
+        let user = Pubkey::new_unique();
+        let fixed = BuyFixedAccounts::derive(&user, Pubkey::new_unique());
+        let (mint, creator) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = buy_instruction(&fixed, &user, &mint, &creator, &spl_token::id(), 1_000, 2_000);
+
+        assert_eq!(ix.accounts.len(), 16);
+        assert_eq!(ix.accounts[1].pubkey, fixed.fee_recipient);
+        assert_eq!(ix.accounts[2].pubkey, mint);
+        assert_eq!(ix.accounts[3].pubkey, bonding_curve_address(&mint));
+        assert!(ix.accounts[6].is_signer && ix.accounts[6].pubkey == user);
+        assert_eq!(&ix.data[..8], &BUY_DISCRIMINATOR);
+        assert_eq!(read_u64(&ix.data, 8).unwrap(), 1_000);
+        assert_eq!(read_u64(&ix.data, 16).unwrap(), 2_000);
+
+        let mut global = vec![0u8; 73];
+        global[GLOBAL_FEE_RECIPIENT..].copy_from_slice(fixed.fee_recipient.as_ref());
+        assert_eq!(decode_fee_recipient(&global).unwrap(), fixed.fee_recipient);
+    }
+
+    #[tokio::test]
+    async fn migration_log_and_marking() {
+        let tracker = MigrationTracker::new(Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())));
+        let mint = Pubkey::new_unique();
+        let event = ProgramLogEvent {
+            slot: 1,
+            signature: "sig".to_string(),
+            program: PUMP_FUN_PROGRAM_ID.to_string(),
+            logs: vec![
+                format!("Program {} invoke [1]", PUMP_FUN_PROGRAM_ID),
+                "Program log: Instruction: Migrate".to_string(),
+            ],
+            ts_ms: 0,
+        };
+        assert!(tracker.observe(&event).await);
+        assert!(!is_migration_log(&["Program log: Instruction: Buy".to_string()]));
+
+        // Unreachable RPC: unknown mints keep the bonding curve route
+        assert!(!tracker.is_migrated(&mint).await);
+        tracker.mark_migrated(mint).await;
+        assert!(tracker.is_migrated(&mint).await);
+    }
+}
@@ -0,0 +1,450 @@
+//! PumpSwap (pump.fun AMM) support: pool discovery for graduated pump.fun mints,
+//! state decoding, fee-aware quoting and buy/sell instruction encoding.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use tracing::debug;
+
+use super::{
+    min_out_with_slippage, mint::MintInfo, pumpfun::PUMP_FUN_PROGRAM_ID, read_pubkey, read_u64,
+    token_account_amount, unwrap_sol_instruction, wrap_sol_instructions, SwapSide, WSOL_MINT,
+};
+
+pub const PUMPSWAP_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// Pool offsets (after the 8-byte anchor discriminator)
+const POOL_INDEX: usize = 9;
+const POOL_CREATOR: usize = 11;
+const POOL_BASE_MINT: usize = 43;
+const POOL_QUOTE_MINT: usize = 75;
+const POOL_BASE_ACCOUNT: usize = 139;
+const POOL_QUOTE_ACCOUNT: usize = 171;
+const POOL_COIN_CREATOR: usize = 211;
+const POOL_MIN_LEN: usize = 211;
+
+// GlobalConfig offsets
+const CONFIG_LP_FEE_BPS: usize = 40;
+const CONFIG_PROTOCOL_FEE_BPS: usize = 48;
+const CONFIG_FEE_RECIPIENTS: usize = 57;
+const CONFIG_FEE_RECIPIENT_COUNT: usize = 8;
+const CONFIG_COIN_CREATOR_FEE_BPS: usize = 313;
+
+/// Decoded PumpSwap `Pool` account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PumpSwapPool {
+    pub address: Pubkey,
+    pub index: u16,
+    pub creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    /// Default pubkey on pools created before creator fees
+    pub coin_creator: Pubkey,
+}
+
+impl PumpSwapPool {
+    pub fn decode(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < POOL_MIN_LEN {
+            return Err(anyhow!("PumpSwap pool {} too short: {} bytes", address, data.len()));
+        }
+        Ok(Self {
+            address,
+            index: u16::from_le_bytes([data[POOL_INDEX], data[POOL_INDEX + 1]]),
+            creator: read_pubkey(data, POOL_CREATOR)?,
+            base_mint: read_pubkey(data, POOL_BASE_MINT)?,
+            quote_mint: read_pubkey(data, POOL_QUOTE_MINT)?,
+            pool_base_token_account: read_pubkey(data, POOL_BASE_ACCOUNT)?,
+            pool_quote_token_account: read_pubkey(data, POOL_QUOTE_ACCOUNT)?,
+            coin_creator: read_pubkey(data, POOL_COIN_CREATOR).unwrap_or_default(),
+        })
+    }
+
+    /// Pool created by the pump.fun `migrate` instruction for `mint`.
+    pub fn canonical_address(mint: &Pubkey) -> Pubkey {
+        let creator =
+            Pubkey::find_program_address(&[b"pool-authority", mint.as_ref()], &PUMP_FUN_PROGRAM_ID).0;
+        Pubkey::find_program_address(
+            &[b"pool", &0u16.to_le_bytes(), creator.as_ref(), mint.as_ref(), WSOL_MINT.as_ref()],
+            &PUMPSWAP_PROGRAM_ID,
+        )
+        .0
+    }
+}
+
+/// Decoded PumpSwap `GlobalConfig` (fee schedule and protocol fee recipients).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalConfig {
+    pub lp_fee_bps: u64,
+    pub protocol_fee_bps: u64,
+    pub coin_creator_fee_bps: u64,
+    pub protocol_fee_recipients: Vec<Pubkey>,
+}
+
+impl GlobalConfig {
+    pub fn address() -> Pubkey {
+        Pubkey::find_program_address(&[b"global_config"], &PUMPSWAP_PROGRAM_ID).0
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let protocol_fee_recipients = (0..CONFIG_FEE_RECIPIENT_COUNT)
+            .map(|i| read_pubkey(data, CONFIG_FEE_RECIPIENTS + i * 32))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|k| *k != Pubkey::default())
+            .collect::<Vec<_>>();
+        if protocol_fee_recipients.is_empty() {
+            return Err(anyhow!("PumpSwap global config has no protocol fee recipients"));
+        }
+        Ok(Self {
+            lp_fee_bps: read_u64(data, CONFIG_LP_FEE_BPS)?,
+            protocol_fee_bps: read_u64(data, CONFIG_PROTOCOL_FEE_BPS)?,
+            coin_creator_fee_bps: read_u64(data, CONFIG_COIN_CREATOR_FEE_BPS).unwrap_or(0),
+            protocol_fee_recipients,
+        })
+    }
+}
+
+/// A PumpSwap pool with the live data needed to quote and swap.
+#[derive(Debug, Clone)]
+pub struct PumpSwapMarket {
+    pub pool: PumpSwapPool,
+    pub config: GlobalConfig,
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    /// Base mint's token program and Token-2022 transfer fee
+    pub base_mint_info: MintInfo,
+}
+
+impl PumpSwapMarket {
+    fn total_fee_bps(&self) -> u64 {
+        let creator_fee = if self.pool.coin_creator == Pubkey::default() {
+            0
+        } else {
+            self.config.coin_creator_fee_bps
+        };
+        self.config.lp_fee_bps + self.config.protocol_fee_bps + creator_fee
+    }
+
+    /// SOL received for selling `base_in` tokens (fees are taken from the output).
+    pub fn quote_sell(&self, base_in: u64) -> u64 {
+        if base_in == 0 || self.base_reserve == 0 || self.quote_reserve == 0 {
+            return 0;
+        }
+        let gross = self.quote_reserve as u128 * base_in as u128
+            / (self.base_reserve as u128 + base_in as u128);
+        let fees = (gross * self.total_fee_bps() as u128).div_ceil(BPS_DENOMINATOR as u128);
+        gross.saturating_sub(fees) as u64
+    }
+
+    /// Tokens bought with at most `quote_in` lamports (fees are added on top of the input).
+    pub fn quote_buy(&self, quote_in: u64) -> u64 {
+        if quote_in == 0 || self.base_reserve == 0 || self.quote_reserve == 0 {
+            return 0;
+        }
+        let net = quote_in as u128 * BPS_DENOMINATOR as u128
+            / (BPS_DENOMINATOR + self.total_fee_bps()) as u128;
+        (self.base_reserve as u128 * net / (self.quote_reserve as u128 + net)) as u64
+    }
+
+    /// Encode a PumpSwap `sell` (exact base in) or `buy` (exact base out, capped quote in).
+    pub fn swap_instruction(&self, owner: &Pubkey, side: SwapSide, base_amount: u64, quote_limit: u64) -> Instruction {
+        let quote_program = spl_token::id();
+        let user_base =
+            get_associated_token_address_with_program_id(owner, &self.pool.base_mint, &self.base_mint_info.token_program);
+        let user_quote = get_associated_token_address_with_program_id(owner, &self.pool.quote_mint, &quote_program);
+        let fee_recipient = self.config.protocol_fee_recipients[0];
+        let fee_recipient_ata =
+            get_associated_token_address_with_program_id(&fee_recipient, &self.pool.quote_mint, &quote_program);
+        let event_authority = Pubkey::find_program_address(&[b"__event_authority"], &PUMPSWAP_PROGRAM_ID).0;
+        let creator_vault_authority = Pubkey::find_program_address(
+            &[b"creator_vault", self.pool.coin_creator.as_ref()],
+            &PUMPSWAP_PROGRAM_ID,
+        )
+        .0;
+        let creator_vault_ata =
+            get_associated_token_address_with_program_id(&creator_vault_authority, &self.pool.quote_mint, &quote_program);
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.pool.address, false),
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(GlobalConfig::address(), false),
+            AccountMeta::new_readonly(self.pool.base_mint, false),
+            AccountMeta::new_readonly(self.pool.quote_mint, false),
+            AccountMeta::new(user_base, false),
+            AccountMeta::new(user_quote, false),
+            AccountMeta::new(self.pool.pool_base_token_account, false),
+            AccountMeta::new(self.pool.pool_quote_token_account, false),
+            AccountMeta::new_readonly(fee_recipient, false),
+            AccountMeta::new(fee_recipient_ata, false),
+            AccountMeta::new_readonly(self.base_mint_info.token_program, false),
+            AccountMeta::new_readonly(quote_program, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(event_authority, false),
+            AccountMeta::new_readonly(PUMPSWAP_PROGRAM_ID, false),
+            AccountMeta::new(creator_vault_ata, false),
+            AccountMeta::new_readonly(creator_vault_authority, false),
+        ];
+
+        let discriminator = match side {
+            SwapSide::Buy => {
+                // Volume accumulators are required by buys only
+                let global_volume =
+                    Pubkey::find_program_address(&[b"global_volume_accumulator"], &PUMPSWAP_PROGRAM_ID).0;
+                let user_volume = Pubkey::find_program_address(
+                    &[b"user_volume_accumulator", owner.as_ref()],
+                    &PUMPSWAP_PROGRAM_ID,
+                )
+                .0;
+                accounts.push(AccountMeta::new_readonly(global_volume, false));
+                accounts.push(AccountMeta::new(user_volume, false));
+                BUY_DISCRIMINATOR
+            }
+            SwapSide::Sell => SELL_DISCRIMINATOR,
+        };
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&discriminator);
+        data.extend_from_slice(&base_amount.to_le_bytes());
+        data.extend_from_slice(&quote_limit.to_le_bytes());
+        Instruction::new_with_bytes(PUMPSWAP_PROGRAM_ID, &data, accounts)
+    }
+
+    /// Full instruction sequence for a SOL<->token swap. `amount_in` is lamports for buys
+    /// and tokens for sells. Returns instructions and the expected output, net of any
+    /// Token-2022 transfer fee on the base mint.
+    pub fn swap_instructions(
+        &self,
+        owner: &Pubkey,
+        side: SwapSide,
+        amount_in: u64,
+        slippage_bps: u64,
+    ) -> Result<(Vec<Instruction>, u64)> {
+        let mut instructions = Vec::with_capacity(6);
+        let expected_out = match side {
+            SwapSide::Buy => {
+                let expected_tokens = self.quote_buy(amount_in);
+                if expected_tokens == 0 {
+                    return Err(anyhow!("PumpSwap pool {} quoted zero output", self.pool.address));
+                }
+                // Exact-out buy: shave the token amount for slippage, spend at most amount_in
+                let base_out = min_out_with_slippage(expected_tokens, slippage_bps);
+                instructions.extend(wrap_sol_instructions(owner, amount_in)?);
+                instructions.push(create_associated_token_account_idempotent(
+                    owner, owner, &self.pool.base_mint, &self.base_mint_info.token_program,
+                ));
+                instructions.push(self.swap_instruction(owner, side, base_out, amount_in));
+                self.base_mint_info.net_of_fee(expected_tokens)
+            }
+            SwapSide::Sell => {
+                // The pool only receives what is left after the transfer fee
+                let expected_sol = self.quote_sell(self.base_mint_info.net_of_fee(amount_in));
+                if expected_sol == 0 {
+                    return Err(anyhow!("PumpSwap pool {} quoted zero output", self.pool.address));
+                }
+                instructions.push(create_associated_token_account_idempotent(
+                    owner, owner, &WSOL_MINT, &spl_token::id(),
+                ));
+                instructions.push(self.swap_instruction(
+                    owner,
+                    side,
+                    amount_in,
+                    min_out_with_slippage(expected_sol, slippage_bps),
+                ));
+                expected_sol
+            }
+        };
+        instructions.push(unwrap_sol_instruction(owner)?);
+        Ok((instructions, expected_out))
+    }
+}
+
+/// RPC-backed PumpSwap pool discovery.
+pub struct PumpSwapClient {
+    rpc: Arc<RpcClient>,
+}
+
+impl PumpSwapClient {
+    pub fn new(rpc: Arc<RpcClient>) -> Self {
+        Self { rpc }
+    }
+
+    /// Locate the SOL pool for `mint`: the canonical migration pool if it exists,
+    /// otherwise the deepest pool found by scanning for the mint.
+    pub async fn find_market(&self, mint: &Pubkey) -> Result<PumpSwapMarket> {
+        let canonical = PumpSwapPool::canonical_address(mint);
+        let pools = match self.rpc.get_account_data(&canonical).await {
+            Ok(data) => vec![PumpSwapPool::decode(canonical, &data)?],
+            Err(e) => {
+                debug!(mint = %mint, "Canonical PumpSwap pool unavailable ({}); scanning", e);
+                self.scan_pools(mint).await?
+            }
+        };
+
+        let config_data = self.rpc.get_account_data(&GlobalConfig::address()).await?;
+        let config = GlobalConfig::decode(&config_data)?;
+        let mint_account = self.rpc.get_account(mint).await.context("mint account")?;
+        let base_mint_info = MintInfo::decode(&mint_account.owner, &mint_account.data)?;
+
+        let mut best: Option<PumpSwapMarket> = None;
+        for pool in pools {
+            let vaults = [pool.pool_base_token_account, pool.pool_quote_token_account];
+            let accounts = self.rpc.get_multiple_accounts(&vaults).await?;
+            let amounts = accounts
+                .into_iter()
+                .zip(vaults)
+                .map(|(acc, key)| {
+                    let acc = acc.ok_or_else(|| anyhow!("vault {} not found", key))?;
+                    token_account_amount(&acc.data)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let market = PumpSwapMarket {
+                pool,
+                config: config.clone(),
+                base_reserve: amounts[0],
+                quote_reserve: amounts[1],
+                base_mint_info: base_mint_info.clone(),
+            };
+            if best.as_ref().is_none_or(|b| market.quote_reserve > b.quote_reserve) {
+                best = Some(market);
+            }
+        }
+        best.ok_or_else(|| anyhow!("no PumpSwap SOL pool found for {}", mint))
+    }
+
+    async fn scan_pools(&self, mint: &Pubkey) -> Result<Vec<PumpSwapPool>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(POOL_BASE_MINT, mint.as_ref())),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(POOL_QUOTE_MINT, WSOL_MINT.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(&PUMPSWAP_PROGRAM_ID, config)
+            .await
+            .context("getProgramAccounts(PumpSwap)")?;
+        accounts
+            .into_iter()
+            .map(|(address, account)| PumpSwapPool::decode(address, &account.data))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(coin_creator: Pubkey) -> PumpSwapMarket {
+        PumpSwapMarket {
+            pool: PumpSwapPool {
+                address: Pubkey::new_unique(),
+                index: 0,
+                creator: Pubkey::new_unique(),
+                base_mint: Pubkey::new_unique(),
+                quote_mint: WSOL_MINT,
+                pool_base_token_account: Pubkey::new_unique(),
+                pool_quote_token_account: Pubkey::new_unique(),
+                coin_creator,
+            },
+            config: GlobalConfig {
+                lp_fee_bps: 20,
+                protocol_fee_bps: 5,
+                coin_creator_fee_bps: 5,
+                protocol_fee_recipients: vec![Pubkey::new_unique()],
+            },
+            base_reserve: 200_000_000_000_000,
+            quote_reserve: 85_000_000_000,
+            base_mint_info: MintInfo::legacy(6),
+        }
+    }
+
+    #[test]
+    fn decodes_pool_and_config() {
+        let base_mint = Pubkey::new_unique();
+        let coin_creator = Pubkey::new_unique();
+        let mut data = vec![0u8; 243];
+        data[POOL_INDEX..POOL_INDEX + 2].copy_from_slice(&7u16.to_le_bytes());
+        data[POOL_BASE_MINT..POOL_BASE_MINT + 32].copy_from_slice(base_mint.as_ref());
+        data[POOL_QUOTE_MINT..POOL_QUOTE_MINT + 32].copy_from_slice(WSOL_MINT.as_ref());
+        data[POOL_COIN_CREATOR..POOL_COIN_CREATOR + 32].copy_from_slice(coin_creator.as_ref());
+        let pool = PumpSwapPool::decode(Pubkey::new_unique(), &data).unwrap();
+        assert_eq!(pool.index, 7);
+        assert_eq!(pool.base_mint, base_mint);
+        assert_eq!(pool.coin_creator, coin_creator);
+        // Pre-creator-fee pools end before the coin_creator field
+        assert_eq!(PumpSwapPool::decode(Pubkey::new_unique(), &data[..211]).unwrap().coin_creator, Pubkey::default());
+
+        let recipient = Pubkey::new_unique();
+        let mut cfg = vec![0u8; 321];
+        cfg[CONFIG_LP_FEE_BPS..CONFIG_LP_FEE_BPS + 8].copy_from_slice(&20u64.to_le_bytes());
+        cfg[CONFIG_PROTOCOL_FEE_BPS..CONFIG_PROTOCOL_FEE_BPS + 8].copy_from_slice(&5u64.to_le_bytes());
+        cfg[CONFIG_FEE_RECIPIENTS + 32..CONFIG_FEE_RECIPIENTS + 64].copy_from_slice(recipient.as_ref());
+        let config = GlobalConfig::decode(&cfg).unwrap();
+        assert_eq!(config.protocol_fee_recipients, vec![recipient]);
+        assert_eq!(config.lp_fee_bps, 20);
+        assert!(GlobalConfig::decode(&vec![0u8; 321]).is_err());
+    }
+
+    #[test]
+    fn quotes_charge_creator_fee_only_when_set() {
+        let with_creator = market(Pubkey::new_unique());
+        let without = market(Pubkey::default());
+        // gross = 85e9 * 1e12 / 201e12 = 422_885_572
+        assert_eq!(without.quote_sell(1_000_000_000_000), 422_885_572 - 1_057_214);
+        assert_eq!(with_creator.quote_sell(1_000_000_000_000), 422_885_572 - 1_268_657);
+        assert!(with_creator.quote_buy(1_000_000_000) < without.quote_buy(1_000_000_000));
+        assert_eq!(without.quote_sell(0), 0);
+    }
+
+    #[test]
+    fn sell_sequence_unwraps_sol() {
+        let owner = Pubkey::new_unique();
+        let m = market(Pubkey::new_unique());
+        let (ixs, expected) = m.swap_instructions(&owner, SwapSide::Sell, 1_000_000_000_000, 100).unwrap();
+        assert!(expected > 0);
+        let swap = &ixs[1];
+        assert_eq!(swap.program_id, PUMPSWAP_PROGRAM_ID);
+        assert_eq!(&swap.data[..8], &SELL_DISCRIMINATOR);
+        assert_eq!(u64::from_le_bytes(swap.data[8..16].try_into().unwrap()), 1_000_000_000_000);
+        assert_eq!(
+            u64::from_le_bytes(swap.data[16..24].try_into().unwrap()),
+            min_out_with_slippage(expected, 100)
+        );
+        assert_eq!(swap.accounts.len(), 19);
+        assert!(swap.accounts[1].is_signer);
+        assert_eq!(ixs.last().unwrap().program_id, spl_token::id());
+
+        let (buy, _) = m.swap_instructions(&owner, SwapSide::Buy, 1_000_000_000, 100).unwrap();
+        let swap = buy.iter().find(|ix| ix.program_id == PUMPSWAP_PROGRAM_ID).unwrap();
+        assert_eq!(&swap.data[..8], &BUY_DISCRIMINATOR);
+        assert_eq!(u64::from_le_bytes(swap.data[16..24].try_into().unwrap()), 1_000_000_000);
+        assert_eq!(swap.accounts.len(), 21);
+    }
+}
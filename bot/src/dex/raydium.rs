@@ -0,0 +1,753 @@
+//! Native Raydium AMM v4 and CPMM support: pool discovery, state decoding,
+//! reserve-based quoting and swap-base-in instruction encoding.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use tracing::{debug, warn};
+
+use super::{
+    constant_product_out, min_out_with_slippage, mint::TransferFeeConfig, read_pubkey, read_u64, token_account_amount,
+    unwrap_sol_instruction, wrap_sol_instructions, SwapSide, WSOL_MINT,
+};
+
+pub const AMM_V4_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+pub const AMM_V4_AUTHORITY: Pubkey =
+    solana_sdk::pubkey!("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
+pub const CPMM_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+/// `AmmInfo` account size
+pub const AMM_V4_ACCOUNT_LEN: usize = 752;
+/// AMM v4 `SwapBaseIn` instruction tag
+const AMM_V4_SWAP_BASE_IN: u8 = 9;
+/// Anchor discriminator of CPMM `swap_base_input`
+const CPMM_SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+/// CPMM trade fee denominator
+const CPMM_FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+const CPMM_AUTH_SEED: &[u8] = b"vault_and_lp_mint_auth_seed";
+/// AMM v4 `AmmStatus` values that allow swaps: Initialized, SwapOnly, WaitingTrade
+const AMM_V4_SWAP_STATUSES: [u64; 3] = [1, 6, 7];
+/// CPMM `PoolStatusBitIndex::Swap`; a set bit disables swaps
+const CPMM_STATUS_SWAP_DISABLED: u8 = 1 << 2;
+
+// AmmInfo field offsets
+const V4_STATUS: usize = 0;
+const V4_SWAP_FEE_NUMERATOR: usize = 176;
+const V4_SWAP_FEE_DENOMINATOR: usize = 184;
+const V4_NEED_TAKE_PNL_COIN: usize = 192;
+const V4_NEED_TAKE_PNL_PC: usize = 200;
+const V4_COIN_VAULT: usize = 336;
+const V4_PC_VAULT: usize = 368;
+const V4_COIN_MINT: usize = 400;
+const V4_PC_MINT: usize = 432;
+const V4_OPEN_ORDERS: usize = 496;
+const V4_MARKET: usize = 528;
+const V4_MARKET_PROGRAM: usize = 560;
+const V4_TARGET_ORDERS: usize = 592;
+
+// CPMM PoolState offsets (after the 8-byte anchor discriminator)
+const CPMM_AMM_CONFIG: usize = 8;
+const CPMM_TOKEN_0_VAULT: usize = 72;
+const CPMM_TOKEN_1_VAULT: usize = 104;
+const CPMM_TOKEN_0_MINT: usize = 168;
+const CPMM_TOKEN_1_MINT: usize = 200;
+const CPMM_TOKEN_0_PROGRAM: usize = 232;
+const CPMM_TOKEN_1_PROGRAM: usize = 264;
+const CPMM_OBSERVATION: usize = 296;
+const CPMM_STATUS: usize = 329;
+const CPMM_PROTOCOL_FEES_0: usize = 341;
+const CPMM_PROTOCOL_FEES_1: usize = 349;
+const CPMM_FUND_FEES_0: usize = 357;
+const CPMM_FUND_FEES_1: usize = 365;
+const CPMM_POOL_LEN: usize = 637;
+/// AmmConfig.trade_fee_rate offset
+const CPMM_CONFIG_TRADE_FEE_RATE: usize = 12;
+
+// OpenBook/Serum MarketState offsets (after the 5-byte "serum" padding)
+const MARKET_VAULT_SIGNER_NONCE: usize = 45;
+const MARKET_BASE_VAULT: usize = 117;
+const MARKET_QUOTE_VAULT: usize = 165;
+const MARKET_EVENT_QUEUE: usize = 253;
+const MARKET_BIDS: usize = 285;
+const MARKET_ASKS: usize = 317;
+
+/// Decoded AMM v4 pool (the fields needed for quoting and swapping).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmmV4Pool {
+    pub address: Pubkey,
+    pub status: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+}
+
+impl AmmV4Pool {
+    pub fn decode(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() != AMM_V4_ACCOUNT_LEN {
+            return Err(anyhow!(
+                "AMM v4 account {} has {} bytes, expected {}",
+                address,
+                data.len(),
+                AMM_V4_ACCOUNT_LEN
+            ));
+        }
+        Ok(Self {
+            address,
+            status: read_u64(data, V4_STATUS)?,
+            swap_fee_numerator: read_u64(data, V4_SWAP_FEE_NUMERATOR)?,
+            swap_fee_denominator: read_u64(data, V4_SWAP_FEE_DENOMINATOR)?,
+            need_take_pnl_coin: read_u64(data, V4_NEED_TAKE_PNL_COIN)?,
+            need_take_pnl_pc: read_u64(data, V4_NEED_TAKE_PNL_PC)?,
+            coin_vault: read_pubkey(data, V4_COIN_VAULT)?,
+            pc_vault: read_pubkey(data, V4_PC_VAULT)?,
+            coin_mint: read_pubkey(data, V4_COIN_MINT)?,
+            pc_mint: read_pubkey(data, V4_PC_MINT)?,
+            open_orders: read_pubkey(data, V4_OPEN_ORDERS)?,
+            market: read_pubkey(data, V4_MARKET)?,
+            market_program: read_pubkey(data, V4_MARKET_PROGRAM)?,
+            target_orders: read_pubkey(data, V4_TARGET_ORDERS)?,
+        })
+    }
+
+    /// Whether the pool status allows swaps (disabled, withdraw-only etc. pools do not).
+    pub fn is_tradable(&self) -> bool {
+        AMM_V4_SWAP_STATUSES.contains(&self.status)
+    }
+
+    /// Tradable reserves (vault balances minus PnL owed to the protocol), as (coin, pc).
+    pub fn reserves(&self, coin_vault_amount: u64, pc_vault_amount: u64) -> (u64, u64) {
+        (
+            coin_vault_amount.saturating_sub(self.need_take_pnl_coin),
+            pc_vault_amount.saturating_sub(self.need_take_pnl_pc),
+        )
+    }
+}
+
+/// OpenBook market accounts referenced by the AMM v4 swap instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketAccounts {
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub event_queue: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
+
+impl MarketAccounts {
+    pub fn decode(market: &Pubkey, market_program: &Pubkey, data: &[u8]) -> Result<Self> {
+        let nonce = read_u64(data, MARKET_VAULT_SIGNER_NONCE)?;
+        let vault_signer =
+            Pubkey::create_program_address(&[market.as_ref(), &nonce.to_le_bytes()], market_program)
+                .map_err(|e| anyhow!("invalid market vault signer nonce: {}", e))?;
+        Ok(Self {
+            bids: read_pubkey(data, MARKET_BIDS)?,
+            asks: read_pubkey(data, MARKET_ASKS)?,
+            event_queue: read_pubkey(data, MARKET_EVENT_QUEUE)?,
+            base_vault: read_pubkey(data, MARKET_BASE_VAULT)?,
+            quote_vault: read_pubkey(data, MARKET_QUOTE_VAULT)?,
+            vault_signer,
+        })
+    }
+}
+
+/// Decoded CPMM pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpmmPool {
+    pub address: Pubkey,
+    pub amm_config: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation: Pubkey,
+    pub status: u8,
+    pub protocol_fees_0: u64,
+    pub protocol_fees_1: u64,
+    pub fund_fees_0: u64,
+    pub fund_fees_1: u64,
+}
+
+impl CpmmPool {
+    pub fn decode(address: Pubkey, data: &[u8]) -> Result<Self> {
+        if data.len() < CPMM_POOL_LEN {
+            return Err(anyhow!(
+                "CPMM pool {} has {} bytes, expected at least {}",
+                address,
+                data.len(),
+                CPMM_POOL_LEN
+            ));
+        }
+        Ok(Self {
+            address,
+            amm_config: read_pubkey(data, CPMM_AMM_CONFIG)?,
+            token_0_vault: read_pubkey(data, CPMM_TOKEN_0_VAULT)?,
+            token_1_vault: read_pubkey(data, CPMM_TOKEN_1_VAULT)?,
+            token_0_mint: read_pubkey(data, CPMM_TOKEN_0_MINT)?,
+            token_1_mint: read_pubkey(data, CPMM_TOKEN_1_MINT)?,
+            token_0_program: read_pubkey(data, CPMM_TOKEN_0_PROGRAM)?,
+            token_1_program: read_pubkey(data, CPMM_TOKEN_1_PROGRAM)?,
+            observation: read_pubkey(data, CPMM_OBSERVATION)?,
+            status: data[CPMM_STATUS],
+            protocol_fees_0: read_u64(data, CPMM_PROTOCOL_FEES_0)?,
+            protocol_fees_1: read_u64(data, CPMM_PROTOCOL_FEES_1)?,
+            fund_fees_0: read_u64(data, CPMM_FUND_FEES_0)?,
+            fund_fees_1: read_u64(data, CPMM_FUND_FEES_1)?,
+        })
+    }
+
+    /// Whether the swap bit of the pool status is clear.
+    pub fn is_tradable(&self) -> bool {
+        self.status & CPMM_STATUS_SWAP_DISABLED == 0
+    }
+
+    /// Tradable reserves (vault balances minus accrued protocol/fund fees), as (token_0, token_1).
+    pub fn reserves(&self, vault_0_amount: u64, vault_1_amount: u64) -> (u64, u64) {
+        (
+            vault_0_amount.saturating_sub(self.protocol_fees_0 + self.fund_fees_0),
+            vault_1_amount.saturating_sub(self.protocol_fees_1 + self.fund_fees_1),
+        )
+    }
+
+    pub fn authority() -> Pubkey {
+        Pubkey::find_program_address(&[CPMM_AUTH_SEED], &CPMM_PROGRAM_ID).0
+    }
+}
+
+/// A Raydium pool paired with SOL, with its live reserves.
+#[derive(Debug, Clone)]
+pub enum RaydiumPool {
+    AmmV4 {
+        pool: AmmV4Pool,
+        market: MarketAccounts,
+        coin_reserve: u64,
+        pc_reserve: u64,
+    },
+    Cpmm {
+        pool: CpmmPool,
+        trade_fee_rate: u64,
+        reserve_0: u64,
+        reserve_1: u64,
+    },
+}
+
+impl RaydiumPool {
+    pub fn address(&self) -> Pubkey {
+        match self {
+            RaydiumPool::AmmV4 { pool, .. } => pool.address,
+            RaydiumPool::Cpmm { pool, .. } => pool.address,
+        }
+    }
+
+    /// SOL-side reserve, used to pick the deepest pool.
+    pub fn sol_reserve(&self) -> u64 {
+        match self {
+            RaydiumPool::AmmV4 { pool, coin_reserve, pc_reserve, .. } => {
+                if pool.coin_mint == WSOL_MINT { *coin_reserve } else { *pc_reserve }
+            }
+            RaydiumPool::Cpmm { pool, reserve_0, reserve_1, .. } => {
+                if pool.token_0_mint == WSOL_MINT { *reserve_0 } else { *reserve_1 }
+            }
+        }
+    }
+
+    /// Token program that owns `mint`'s accounts in this pool.
+    pub fn token_program_for(&self, mint: &Pubkey) -> Pubkey {
+        match self {
+            RaydiumPool::AmmV4 { .. } => spl_token::id(),
+            RaydiumPool::Cpmm { pool, .. } => {
+                if pool.token_0_mint == *mint { pool.token_0_program } else { pool.token_1_program }
+            }
+        }
+    }
+
+    /// Exact-in quote for swapping `amount_in` of `input_mint`.
+    pub fn quote(&self, input_mint: &Pubkey, amount_in: u64) -> u64 {
+        match self {
+            RaydiumPool::AmmV4 { pool, coin_reserve, pc_reserve, .. } => {
+                let (reserve_in, reserve_out) = if pool.coin_mint == *input_mint {
+                    (*coin_reserve, *pc_reserve)
+                } else {
+                    (*pc_reserve, *coin_reserve)
+                };
+                constant_product_out(
+                    amount_in,
+                    reserve_in,
+                    reserve_out,
+                    pool.swap_fee_numerator,
+                    pool.swap_fee_denominator,
+                )
+            }
+            RaydiumPool::Cpmm { pool, trade_fee_rate, reserve_0, reserve_1 } => {
+                let (reserve_in, reserve_out) = if pool.token_0_mint == *input_mint {
+                    (*reserve_0, *reserve_1)
+                } else {
+                    (*reserve_1, *reserve_0)
+                };
+                constant_product_out(
+                    amount_in,
+                    reserve_in,
+                    reserve_out,
+                    *trade_fee_rate,
+                    CPMM_FEE_RATE_DENOMINATOR,
+                )
+            }
+        }
+    }
+
+    /// Encode the swap-base-in instruction for this pool.
+    pub fn swap_instruction(
+        &self,
+        owner: &Pubkey,
+        input_mint: &Pubkey,
+        user_source: &Pubkey,
+        user_destination: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Instruction {
+        match self {
+            RaydiumPool::AmmV4 { pool, market, .. } => amm_v4_swap_base_in(
+                pool,
+                market,
+                owner,
+                user_source,
+                user_destination,
+                amount_in,
+                minimum_amount_out,
+            ),
+            RaydiumPool::Cpmm { pool, .. } => cpmm_swap_base_input(
+                pool,
+                owner,
+                input_mint,
+                user_source,
+                user_destination,
+                amount_in,
+                minimum_amount_out,
+            ),
+        }
+    }
+}
+
+/// AMM v4 `SwapBaseIn` (18-account layout including the OpenBook market).
+pub fn amm_v4_swap_base_in(
+    pool: &AmmV4Pool,
+    market: &MarketAccounts,
+    owner: &Pubkey,
+    user_source: &Pubkey,
+    user_destination: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let mut data = Vec::with_capacity(17);
+    data.push(AMM_V4_SWAP_BASE_IN);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    let accounts = vec![
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(pool.address, false),
+        AccountMeta::new_readonly(AMM_V4_AUTHORITY, false),
+        AccountMeta::new(pool.open_orders, false),
+        AccountMeta::new(pool.target_orders, false),
+        AccountMeta::new(pool.coin_vault, false),
+        AccountMeta::new(pool.pc_vault, false),
+        AccountMeta::new_readonly(pool.market_program, false),
+        AccountMeta::new(pool.market, false),
+        AccountMeta::new(market.bids, false),
+        AccountMeta::new(market.asks, false),
+        AccountMeta::new(market.event_queue, false),
+        AccountMeta::new(market.base_vault, false),
+        AccountMeta::new(market.quote_vault, false),
+        AccountMeta::new_readonly(market.vault_signer, false),
+        AccountMeta::new(*user_source, false),
+        AccountMeta::new(*user_destination, false),
+        AccountMeta::new_readonly(*owner, true),
+    ];
+    Instruction::new_with_bytes(AMM_V4_PROGRAM_ID, &data, accounts)
+}
+
+/// CPMM `swap_base_input`.
+pub fn cpmm_swap_base_input(
+    pool: &CpmmPool,
+    owner: &Pubkey,
+    input_mint: &Pubkey,
+    user_source: &Pubkey,
+    user_destination: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let zero_is_input = pool.token_0_mint == *input_mint;
+    let (in_vault, out_vault, in_program, out_program, in_mint, out_mint) = if zero_is_input {
+        (pool.token_0_vault, pool.token_1_vault, pool.token_0_program, pool.token_1_program, pool.token_0_mint, pool.token_1_mint)
+    } else {
+        (pool.token_1_vault, pool.token_0_vault, pool.token_1_program, pool.token_0_program, pool.token_1_mint, pool.token_0_mint)
+    };
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&CPMM_SWAP_BASE_INPUT);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(CpmmPool::authority(), false),
+        AccountMeta::new_readonly(pool.amm_config, false),
+        AccountMeta::new(pool.address, false),
+        AccountMeta::new(*user_source, false),
+        AccountMeta::new(*user_destination, false),
+        AccountMeta::new(in_vault, false),
+        AccountMeta::new(out_vault, false),
+        AccountMeta::new_readonly(in_program, false),
+        AccountMeta::new_readonly(out_program, false),
+        AccountMeta::new_readonly(in_mint, false),
+        AccountMeta::new_readonly(out_mint, false),
+        AccountMeta::new(pool.observation, false),
+    ];
+    Instruction::new_with_bytes(CPMM_PROGRAM_ID, &data, accounts)
+}
+
+/// RPC-backed pool discovery and swap building.
+pub struct RaydiumClient {
+    rpc: Arc<RpcClient>,
+}
+
+impl RaydiumClient {
+    pub fn new(rpc: Arc<RpcClient>) -> Self {
+        Self { rpc }
+    }
+
+    /// Find the deepest SOL-paired AMM v4 or CPMM pool for `mint`.
+    pub async fn find_pool(&self, mint: &Pubkey) -> Result<RaydiumPool> {
+        let mut pools = self.find_amm_v4_pools(mint).await.unwrap_or_else(|e| {
+            debug!(mint = %mint, "AMM v4 pool discovery failed: {}", e);
+            Vec::new()
+        });
+        pools.extend(self.find_cpmm_pools(mint).await.unwrap_or_else(|e| {
+            debug!(mint = %mint, "CPMM pool discovery failed: {}", e);
+            Vec::new()
+        }));
+        pools
+            .into_iter()
+            .max_by_key(|p| p.sol_reserve())
+            .ok_or_else(|| anyhow!("no SOL-paired Raydium pool found for {}", mint))
+    }
+
+    async fn program_accounts(
+        &self,
+        program: &Pubkey,
+        data_size: Option<u64>,
+        mint_offset: usize,
+        mint: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            mint_offset,
+            mint.as_ref(),
+        ))];
+        if let Some(size) = data_size {
+            filters.push(RpcFilterType::DataSize(size));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(program, config)
+            .await
+            .with_context(|| format!("getProgramAccounts({})", program))?;
+        Ok(accounts.into_iter().map(|(k, a)| (k, a.data)).collect())
+    }
+
+    async fn vault_amounts(&self, vaults: &[Pubkey]) -> Result<Vec<u64>> {
+        let accounts = self.rpc.get_multiple_accounts(vaults).await?;
+        accounts
+            .into_iter()
+            .zip(vaults)
+            .map(|(acc, key)| {
+                let acc = acc.ok_or_else(|| anyhow!("vault {} not found", key))?;
+                token_account_amount(&acc.data)
+            })
+            .collect()
+    }
+
+    async fn find_amm_v4_pools(&self, mint: &Pubkey) -> Result<Vec<RaydiumPool>> {
+        let mut raw = self
+            .program_accounts(&AMM_V4_PROGRAM_ID, Some(AMM_V4_ACCOUNT_LEN as u64), V4_COIN_MINT, mint)
+            .await?;
+        raw.extend(
+            self.program_accounts(&AMM_V4_PROGRAM_ID, Some(AMM_V4_ACCOUNT_LEN as u64), V4_PC_MINT, mint)
+                .await?,
+        );
+
+        // One broken pool must not hide the others
+        let mut pools = Vec::new();
+        for (address, data) in raw {
+            match self.load_amm_v4_pool(address, &data).await {
+                Ok(Some(pool)) => pools.push(pool),
+                Ok(None) => {}
+                Err(e) => warn!(pool = %address, mint = %mint, "Skipping AMM v4 pool: {:#}", e),
+            }
+        }
+        Ok(pools)
+    }
+
+    /// Decode an AMM v4 pool and fetch its market and reserves; `None` if it is not a
+    /// tradable SOL pair.
+    async fn load_amm_v4_pool(&self, address: Pubkey, data: &[u8]) -> Result<Option<RaydiumPool>> {
+        let pool = AmmV4Pool::decode(address, data)?;
+        if pool.coin_mint != WSOL_MINT && pool.pc_mint != WSOL_MINT {
+            return Ok(None);
+        }
+        if !pool.is_tradable() {
+            debug!(pool = %address, status = pool.status, "Skipping AMM v4 pool that does not allow swaps");
+            return Ok(None);
+        }
+        let market_data = self.rpc.get_account_data(&pool.market).await?;
+        let market = MarketAccounts::decode(&pool.market, &pool.market_program, &market_data)?;
+        let amounts = self.vault_amounts(&[pool.coin_vault, pool.pc_vault]).await?;
+        let (coin_reserve, pc_reserve) = pool.reserves(amounts[0], amounts[1]);
+        Ok(Some(RaydiumPool::AmmV4 { pool, market, coin_reserve, pc_reserve }))
+    }
+
+    async fn find_cpmm_pools(&self, mint: &Pubkey) -> Result<Vec<RaydiumPool>> {
+        let mut raw = self
+            .program_accounts(&CPMM_PROGRAM_ID, None, CPMM_TOKEN_0_MINT, mint)
+            .await?;
+        raw.extend(self.program_accounts(&CPMM_PROGRAM_ID, None, CPMM_TOKEN_1_MINT, mint).await?);
+
+        let mut pools = Vec::new();
+        for (address, data) in raw {
+            match self.load_cpmm_pool(address, &data).await {
+                Ok(Some(pool)) => pools.push(pool),
+                Ok(None) => {}
+                Err(e) => warn!(pool = %address, mint = %mint, "Skipping CPMM pool: {:#}", e),
+            }
+        }
+        Ok(pools)
+    }
+
+    /// Decode a CPMM pool and fetch its fee rate and reserves; `None` if it is not a
+    /// tradable SOL pair.
+    async fn load_cpmm_pool(&self, address: Pubkey, data: &[u8]) -> Result<Option<RaydiumPool>> {
+        let pool = CpmmPool::decode(address, data)?;
+        if pool.token_0_mint != WSOL_MINT && pool.token_1_mint != WSOL_MINT {
+            return Ok(None);
+        }
+        if !pool.is_tradable() {
+            debug!(pool = %address, status = pool.status, "Skipping CPMM pool with swaps disabled");
+            return Ok(None);
+        }
+        let config_data = self.rpc.get_account_data(&pool.amm_config).await?;
+        let trade_fee_rate = read_u64(&config_data, CPMM_CONFIG_TRADE_FEE_RATE)?;
+        let amounts = self.vault_amounts(&[pool.token_0_vault, pool.token_1_vault]).await?;
+        let (reserve_0, reserve_1) = pool.reserves(amounts[0], amounts[1]);
+        Ok(Some(RaydiumPool::Cpmm { pool, trade_fee_rate, reserve_0, reserve_1 }))
+    }
+
+    /// Build the full instruction sequence (ATA setup, WSOL wrap/unwrap, swap) for a
+    /// SOL<->token swap of `amount_in` through `pool`. Returns instructions and the quote.
+    /// `transfer_fee` is the token's Token-2022 fee (CPMM only): sells are quoted on what the
+    /// vault receives, buys on what reaches our account.
+    pub fn swap_instructions(
+        pool: &RaydiumPool,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        side: SwapSide,
+        amount_in: u64,
+        slippage_bps: u64,
+        transfer_fee: Option<&TransferFeeConfig>,
+    ) -> Result<(Vec<Instruction>, u64)> {
+        let token_program = pool.token_program_for(mint);
+        let token_ata = get_associated_token_address_with_program_id(owner, mint, &token_program);
+        let wsol_ata = get_associated_token_address_with_program_id(owner, &WSOL_MINT, &spl_token::id());
+
+        let (input_mint, source, destination) = match side {
+            SwapSide::Buy => (WSOL_MINT, wsol_ata, token_ata),
+            SwapSide::Sell => (*mint, token_ata, wsol_ata),
+        };
+        let fee = |amount: u64| transfer_fee.map_or(0, |f| f.worst_case_fee(amount));
+        let expected_out = match side {
+            SwapSide::Buy => {
+                let gross = pool.quote(&input_mint, amount_in);
+                gross - fee(gross)
+            }
+            SwapSide::Sell => pool.quote(&input_mint, amount_in - fee(amount_in)),
+        };
+        if expected_out == 0 {
+            return Err(anyhow!("Raydium pool {} quoted zero output", pool.address()));
+        }
+        let min_out = min_out_with_slippage(expected_out, slippage_bps);
+
+        let mut instructions = Vec::with_capacity(6);
+        match side {
+            SwapSide::Buy => {
+                instructions.extend(wrap_sol_instructions(owner, amount_in)?);
+                instructions.push(create_associated_token_account_idempotent(
+                    owner, owner, mint, &token_program,
+                ));
+            }
+            SwapSide::Sell => {
+                instructions.push(create_associated_token_account_idempotent(
+                    owner, owner, &WSOL_MINT, &spl_token::id(),
+                ));
+            }
+        }
+        instructions.push(pool.swap_instruction(owner, &input_mint, &source, &destination, amount_in, min_out));
+        instructions.push(unwrap_sol_instruction(owner)?);
+        Ok((instructions, expected_out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_u64(buf: &mut [u8], off: usize, v: u64) {
+        buf[off..off + 8].copy_from_slice(&v.to_le_bytes());
+    }
+    fn put_key(buf: &mut [u8], off: usize, k: &Pubkey) {
+        buf[off..off + 32].copy_from_slice(k.as_ref());
+    }
+
+    fn sample_v4(mint: Pubkey) -> AmmV4Pool {
+        let mut data = vec![0u8; AMM_V4_ACCOUNT_LEN];
+        put_u64(&mut data, V4_STATUS, 6);
+        put_u64(&mut data, V4_SWAP_FEE_NUMERATOR, 25);
+        put_u64(&mut data, V4_SWAP_FEE_DENOMINATOR, 10_000);
+        put_u64(&mut data, V4_NEED_TAKE_PNL_COIN, 100);
+        put_u64(&mut data, V4_NEED_TAKE_PNL_PC, 0);
+        put_key(&mut data, V4_COIN_MINT, &mint);
+        put_key(&mut data, V4_PC_MINT, &WSOL_MINT);
+        put_key(&mut data, V4_MARKET_PROGRAM, &Pubkey::new_unique());
+        AmmV4Pool::decode(Pubkey::new_unique(), &data).unwrap()
+    }
+
+    #[test]
+    fn amm_v4_decode_and_quote() {
+        let mint = Pubkey::new_unique();
+        let pool = sample_v4(mint);
+        assert_eq!(pool.status, 6);
+        assert!(pool.is_tradable());
+        for status in [0, 2, 3, 4, 5] {
+            assert!(!AmmV4Pool { status, ..pool.clone() }.is_tradable());
+        }
+        assert_eq!(pool.coin_mint, mint);
+        assert_eq!(pool.pc_mint, WSOL_MINT);
+        assert_eq!(pool.reserves(1_000_100, 50), (1_000_000, 50));
+
+        let market = MarketAccounts {
+            bids: Pubkey::new_unique(),
+            asks: Pubkey::new_unique(),
+            event_queue: Pubkey::new_unique(),
+            base_vault: Pubkey::new_unique(),
+            quote_vault: Pubkey::new_unique(),
+            vault_signer: Pubkey::new_unique(),
+        };
+        let rp = RaydiumPool::AmmV4 {
+            pool,
+            market,
+            coin_reserve: 1_000_000,
+            pc_reserve: 100_000_000_000,
+        };
+        assert_eq!(rp.sol_reserve(), 100_000_000_000);
+        assert_eq!(rp.quote(&WSOL_MINT, 1_000_000_000), 9_876);
+        assert!(AmmV4Pool::decode(Pubkey::new_unique(), &[0u8; 10]).is_err());
+    }
+
+    #[test]
+    fn amm_v4_swap_encoding() {
+        let mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let pool = RaydiumPool::AmmV4 {
+            pool: sample_v4(mint),
+            market: MarketAccounts {
+                bids: Pubkey::new_unique(),
+                asks: Pubkey::new_unique(),
+                event_queue: Pubkey::new_unique(),
+                base_vault: Pubkey::new_unique(),
+                quote_vault: Pubkey::new_unique(),
+                vault_signer: Pubkey::new_unique(),
+            },
+            coin_reserve: 1_000_000,
+            pc_reserve: 100_000_000_000,
+        };
+
+        let (ixs, expected) =
+            RaydiumClient::swap_instructions(&pool, &owner, &mint, SwapSide::Buy, 1_000_000_000, 100, None).unwrap();
+        assert_eq!(expected, 9_876);
+        let swap = ixs.iter().find(|ix| ix.program_id == AMM_V4_PROGRAM_ID).unwrap();
+        assert_eq!(swap.accounts.len(), 18);
+        assert_eq!(swap.data[0], AMM_V4_SWAP_BASE_IN);
+        assert_eq!(u64::from_le_bytes(swap.data[1..9].try_into().unwrap()), 1_000_000_000);
+        assert_eq!(u64::from_le_bytes(swap.data[9..17].try_into().unwrap()), 9_777);
+        assert!(swap.accounts[17].is_signer);
+        // WSOL is wrapped first and closed last
+        assert_eq!(ixs.last().unwrap().program_id, spl_token::id());
+    }
+
+    #[test]
+    fn cpmm_decode_and_directional_accounts() {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; CPMM_POOL_LEN];
+        let vault_0 = Pubkey::new_unique();
+        let vault_1 = Pubkey::new_unique();
+        put_key(&mut data, CPMM_AMM_CONFIG, &Pubkey::new_unique());
+        put_key(&mut data, CPMM_TOKEN_0_VAULT, &vault_0);
+        put_key(&mut data, CPMM_TOKEN_1_VAULT, &vault_1);
+        put_key(&mut data, CPMM_TOKEN_0_MINT, &WSOL_MINT);
+        put_key(&mut data, CPMM_TOKEN_1_MINT, &mint);
+        put_key(&mut data, CPMM_TOKEN_0_PROGRAM, &spl_token::id());
+        put_key(&mut data, CPMM_TOKEN_1_PROGRAM, &spl_token::id());
+        put_u64(&mut data, CPMM_PROTOCOL_FEES_1, 10);
+        put_u64(&mut data, CPMM_FUND_FEES_1, 5);
+        let pool = CpmmPool::decode(Pubkey::new_unique(), &data).unwrap();
+        assert_eq!(pool.reserves(1_000, 1_015), (1_000, 1_000));
+        assert!(pool.is_tradable());
+        assert!(CpmmPool { status: 0b011, ..pool.clone() }.is_tradable(), "deposit/withdraw bits do not block swaps");
+        assert!(!CpmmPool { status: CPMM_STATUS_SWAP_DISABLED, ..pool.clone() }.is_tradable());
+
+        let owner = Pubkey::new_unique();
+        let src = Pubkey::new_unique();
+        let dst = Pubkey::new_unique();
+        let sell = cpmm_swap_base_input(&pool, &owner, &mint, &src, &dst, 500, 1);
+        assert_eq!(&sell.data[..8], &CPMM_SWAP_BASE_INPUT);
+        assert_eq!(sell.accounts.len(), 13);
+        assert_eq!(sell.accounts[6].pubkey, vault_1, "input vault follows direction");
+        assert_eq!(sell.accounts[7].pubkey, vault_0);
+        assert_eq!(sell.accounts[10].pubkey, mint);
+
+        let rp = RaydiumPool::Cpmm { pool, trade_fee_rate: 2_500, reserve_0: 100_000_000_000, reserve_1: 1_000_000 };
+        assert_eq!(rp.quote(&WSOL_MINT, 1_000_000_000), 9_876);
+    }
+}
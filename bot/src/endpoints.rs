@@ -1,4 +1,5 @@
 use crate::metrics::{metrics, MetricsSnapshot};
+use crate::rpc_manager::EndpointStatus;
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -9,6 +10,8 @@ use tokio::sync::RwLock;
 pub struct EndpointServer {
     /// Scoreboard data for ranking
     scoreboard: Arc<RwLock<HashMap<String, ScoreboardEntry>>>,
+    /// Latest RPC endpoint ranking published by the broadcaster
+    rpc_endpoints: Arc<RwLock<Vec<EndpointStatus>>>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -26,9 +29,15 @@ impl EndpointServer {
     pub fn new() -> Self {
         Self {
             scoreboard: Arc::new(RwLock::new(HashMap::new())),
+            rpc_endpoints: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
+    /// Replace the RPC endpoint ranking shown on `/status`
+    pub async fn update_rpc_endpoints(&self, statuses: Vec<EndpointStatus>) {
+        *self.rpc_endpoints.write().await = statuses;
+    }
+
     /// Update scoreboard entry
     pub async fn update_scoreboard(&self, mint: &str, program: &str, success: bool, latency_ms: u64) {
         let mut scoreboard = self.scoreboard.write().await;
@@ -89,6 +98,7 @@ impl EndpointServer {
     pub async fn get_status_response(&self) -> String {
         let metrics_snapshot = metrics().export_metrics();
         let scoreboard = self.scoreboard.read().await;
+        let rpc_endpoints = self.rpc_endpoints.read().await;
         
         json!({
             "metrics": {
@@ -97,6 +107,7 @@ impl EndpointServer {
                 "histograms": metrics_snapshot.histograms
             },
             "scoreboard_entries": scoreboard.len(),
+            "rpc_endpoints": *rpc_endpoints,
             "system": {
                 "uptime_seconds": std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -0,0 +1,312 @@
+//! Dynamic priority fee estimation.
+//!
+//! Queries `getRecentPrioritizationFees` scoped to the writable accounts touched by the
+//! target program, picks a configurable percentile and clamps the result between a floor
+//! and a cap. Buys of freshly launched tokens can be made more aggressive via a multiplier.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::metrics::metrics;
+
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// `ComputeBudgetInstruction::SetComputeUnitLimit` discriminator
+const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+/// `ComputeBudgetInstruction::SetComputeUnitPrice` discriminator
+const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+/// Priority fee estimation policy (all fees in micro-lamports per CU)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriorityFeeConfig {
+    /// Percentile of recent non-zero fees to use (0-100)
+    #[serde(default = "default_percentile")]
+    pub percentile: u8,
+    /// Never bid less than this
+    #[serde(default = "default_floor")]
+    pub floor_micro_lamports: u64,
+    /// Never bid more than this (applied after the launch multiplier)
+    #[serde(default = "default_cap")]
+    pub cap_micro_lamports: u64,
+    /// Multiplier applied to buys of freshly launched tokens
+    #[serde(default = "default_launch_multiplier")]
+    pub launch_multiplier: f64,
+    /// How long an estimate for the same account set is reused
+    #[serde(default = "default_cache_ttl_ms")]
+    pub cache_ttl_ms: u64,
+}
+
+fn default_percentile() -> u8 {
+    75
+}
+fn default_floor() -> u64 {
+    10_000
+}
+fn default_cap() -> u64 {
+    5_000_000
+}
+fn default_launch_multiplier() -> f64 {
+    1.5
+}
+fn default_cache_ttl_ms() -> u64 {
+    2_000
+}
+
+impl Default for PriorityFeeConfig {
+    fn default() -> Self {
+        Self {
+            percentile: default_percentile(),
+            floor_micro_lamports: default_floor(),
+            cap_micro_lamports: default_cap(),
+            launch_multiplier: default_launch_multiplier(),
+            cache_ttl_ms: default_cache_ttl_ms(),
+        }
+    }
+}
+
+impl PriorityFeeConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.percentile > 100 {
+            return Err("priority_fee.percentile must be <= 100".to_string());
+        }
+        if self.floor_micro_lamports > self.cap_micro_lamports {
+            return Err("priority_fee.floor_micro_lamports cannot exceed cap_micro_lamports".to_string());
+        }
+        if !self.launch_multiplier.is_finite() || self.launch_multiplier < 1.0 {
+            return Err("priority_fee.launch_multiplier must be >= 1.0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Turn raw recent fees into a bid: percentile, launch multiplier, then floor/cap.
+    pub fn select_fee(&self, recent_fees: &[u64], launch: bool) -> u64 {
+        let base = percentile_of(recent_fees, self.percentile);
+        let boosted = if launch {
+            (base as f64 * self.launch_multiplier).round() as u64
+        } else {
+            base
+        };
+        boosted.clamp(self.floor_micro_lamports, self.cap_micro_lamports)
+    }
+}
+
+/// Nearest-rank percentile over the non-zero samples; 0 when there are none.
+pub fn percentile_of(samples: &[u64], percentile: u8) -> u64 {
+    let mut non_zero: Vec<u64> = samples.iter().copied().filter(|f| *f > 0).collect();
+    if non_zero.is_empty() {
+        return 0;
+    }
+    non_zero.sort_unstable();
+    let pct = percentile.min(100) as usize;
+    let rank = (pct * non_zero.len()).div_ceil(100).max(1);
+    non_zero[rank - 1]
+}
+
+/// Writable, non-signer accounts of the given instructions (deduplicated, order preserved).
+pub fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut out: Vec<Pubkey> = Vec::new();
+    for ix in instructions {
+        for meta in &ix.accounts {
+            if meta.is_writable && !meta.is_signer && !out.contains(&meta.pubkey) {
+                out.push(meta.pubkey);
+            }
+        }
+    }
+    out
+}
+
+/// Read back the compute unit price a transaction bids, if it sets one.
+pub fn priority_fee_of(tx: &VersionedTransaction) -> Option<u64> {
+    compute_budget_value(tx, SET_COMPUTE_UNIT_PRICE_TAG, 8).map(|data| {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(data);
+        u64::from_le_bytes(buf)
+    })
+}
+
+/// Read back the compute unit limit a transaction requests, if it sets one.
+pub fn compute_unit_limit_of(tx: &VersionedTransaction) -> Option<u32> {
+    compute_budget_value(tx, SET_COMPUTE_UNIT_LIMIT_TAG, 4).map(|data| {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(data);
+        u32::from_le_bytes(buf)
+    })
+}
+
+fn compute_budget_value(tx: &VersionedTransaction, tag: u8, value_len: usize) -> Option<&[u8]> {
+    let keys = tx.message.static_account_keys();
+    tx.message.instructions().iter().find_map(|ix| {
+        let program = keys.get(ix.program_id_index as usize)?;
+        if *program == COMPUTE_BUDGET_PROGRAM_ID
+            && ix.data.len() == value_len + 1
+            && ix.data[0] == tag
+        {
+            Some(&ix.data[1..])
+        } else {
+            None
+        }
+    })
+}
+
+type FeeSampleCache = HashMap<Vec<Pubkey>, (Instant, Vec<u64>)>;
+
+/// Estimator backed by `getRecentPrioritizationFees` with a short per-account-set cache.
+pub struct PriorityFeeEstimator {
+    rpc: Arc<RpcClient>,
+    config: PriorityFeeConfig,
+    cache: RwLock<FeeSampleCache>,
+}
+
+impl std::fmt::Debug for PriorityFeeEstimator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PriorityFeeEstimator")
+            .field("rpc", &self.rpc.url())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl PriorityFeeEstimator {
+    pub fn new(rpc: Arc<RpcClient>, config: PriorityFeeConfig) -> Self {
+        Self {
+            rpc,
+            config,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &PriorityFeeConfig {
+        &self.config
+    }
+
+    /// Estimate a compute unit price for a transaction writing to `accounts`.
+    /// Falls back to `fallback` (still clamped) when the RPC call fails.
+    pub async fn estimate(&self, accounts: &[Pubkey], launch: bool, fallback: u64) -> u64 {
+        let fee = match self.recent_fees(accounts).await {
+            Some(samples) => self.config.select_fee(&samples, launch),
+            None => fallback.clamp(self.config.floor_micro_lamports, self.config.cap_micro_lamports),
+        };
+        metrics().set_gauge("priority_fee_estimate_micro_lamports", fee);
+        debug!(fee, launch, accounts = accounts.len(), "Priority fee estimated");
+        fee
+    }
+
+    async fn recent_fees(&self, accounts: &[Pubkey]) -> Option<Vec<u64>> {
+        let mut key = accounts.to_vec();
+        key.sort();
+        let ttl = Duration::from_millis(self.config.cache_ttl_ms);
+
+        {
+            let cache = self.cache.read().await;
+            if let Some((at, samples)) = cache.get(&key) {
+                if at.elapsed() < ttl {
+                    return Some(samples.clone());
+                }
+            }
+        }
+
+        match self.rpc.get_recent_prioritization_fees(&key).await {
+            Ok(fees) => {
+                let samples: Vec<u64> = fees.iter().map(|f| f.prioritization_fee).collect();
+                let mut cache = self.cache.write().await;
+                cache.retain(|_, (at, _)| at.elapsed() < ttl);
+                cache.insert(key, (Instant::now(), samples.clone()));
+                Some(samples)
+            }
+            Err(e) => {
+                metrics().increment_counter("priority_fee_estimate_errors");
+                warn!("getRecentPrioritizationFees failed: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[allow(deprecated)]
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::{
+        hash::Hash,
+        instruction::AccountMeta,
+        message::{v0::Message as MessageV0, VersionedMessage},
+        signature::Signature,
+    };
+
+    #[test]
+    fn percentile_ignores_zero_fees() {
+        let fees = [0, 0, 100, 200, 300, 400];
+        assert_eq!(percentile_of(&fees, 50), 200);
+        assert_eq!(percentile_of(&fees, 75), 300);
+        assert_eq!(percentile_of(&fees, 100), 400);
+        assert_eq!(percentile_of(&fees, 0), 100);
+        assert_eq!(percentile_of(&[0, 0], 90), 0);
+    }
+
+    #[test]
+    fn select_fee_applies_multiplier_floor_and_cap() {
+        let cfg = PriorityFeeConfig {
+            percentile: 50,
+            floor_micro_lamports: 1_000,
+            cap_micro_lamports: 50_000,
+            launch_multiplier: 2.0,
+            cache_ttl_ms: 0,
+        };
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.select_fee(&[10_000, 20_000, 30_000], false), 20_000);
+        assert_eq!(cfg.select_fee(&[10_000, 20_000, 30_000], true), 40_000);
+        assert_eq!(cfg.select_fee(&[40_000], true), 50_000);
+        assert_eq!(cfg.select_fee(&[], false), 1_000);
+    }
+
+    #[test]
+    fn invalid_config_rejected() {
+        let cfg = PriorityFeeConfig {
+            floor_micro_lamports: 10,
+            cap_micro_lamports: 5,
+            ..PriorityFeeConfig::default()
+        };
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn compute_budget_values_read_back_from_transaction() {
+        let payer = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let swap = Instruction::new_with_bytes(
+            program,
+            &[1],
+            vec![AccountMeta::new(payer, true), AccountMeta::new(pool, false)],
+        );
+        let ixs = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(123_456),
+            ComputeBudgetInstruction::set_compute_unit_price(77_000),
+            swap.clone(),
+        ];
+        let msg = MessageV0::try_compile(&payer, &ixs, &[], Hash::default()).unwrap();
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(msg),
+        };
+
+        assert_eq!(priority_fee_of(&tx), Some(77_000));
+        assert_eq!(compute_unit_limit_of(&tx), Some(123_456));
+        assert_eq!(writable_accounts(&[swap]), vec![pool]);
+    }
+}
@@ -0,0 +1,173 @@
+//! Fee ladder for the parallel buy transactions.
+//!
+//! Instead of fanning out identical copies, a buy sends one rung per lease: each rung is a
+//! priority fee multiplier, an optional in-transaction Jito tip and an optional slippage
+//! override. The rung that lands is recorded so the ladder can be tuned from data.
+//!
+//! Rungs are distinct transactions, so they are made mutually exclusive on-chain: every rung
+//! advances the same durable nonce first and uses its value as the blockhash. Once one rung
+//! executes the nonce has moved and the others are rejected, so all rungs go out at once.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::metrics::metrics;
+use crate::tx_builder::TransactionConfig;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeRung {
+    /// Multiplier applied to the resolved priority fee (static or estimated)
+    #[serde(default = "default_priority_fee_multiplier")]
+    pub priority_fee_multiplier: f64,
+    /// Tip paid to a Jito tip account from inside the transaction (requires `[jito]`)
+    #[serde(default)]
+    pub jito_tip_lamports: Option<u64>,
+    /// Slippage override for this rung
+    #[serde(default)]
+    pub slippage_bps: Option<u64>,
+}
+
+fn default_priority_fee_multiplier() -> f64 {
+    1.0
+}
+
+impl Default for FeeRung {
+    fn default() -> Self {
+        Self {
+            priority_fee_multiplier: default_priority_fee_multiplier(),
+            jito_tip_lamports: None,
+            slippage_bps: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeeLadderConfig {
+    /// Rungs, one per parallel transaction of a buy
+    #[serde(default)]
+    pub rungs: Vec<FeeRung>,
+    /// Durable nonce account every rung advances (base58); its authority must be the buying
+    /// wallet
+    #[serde(default)]
+    pub nonce_account: String,
+}
+
+impl FeeLadderConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rungs.is_empty() {
+            return Err("fee_ladder.rungs must contain at least one rung".to_string());
+        }
+        self.nonce_account()?;
+        for (i, rung) in self.rungs.iter().enumerate() {
+            if !rung.priority_fee_multiplier.is_finite() || rung.priority_fee_multiplier <= 0.0 {
+                return Err(format!("fee_ladder.rungs[{}].priority_fee_multiplier must be > 0", i));
+            }
+            if rung.slippage_bps.is_some_and(|bps| bps > 10_000) {
+                return Err(format!("fee_ladder.rungs[{}].slippage_bps must be <= 10000", i));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn nonce_account(&self) -> Result<Pubkey, String> {
+        Pubkey::from_str(&self.nonce_account).map_err(|e| format!("fee_ladder.nonce_account: {}", e))
+    }
+
+    /// Index of the rung used by the `step`-th transaction (wraps past the last rung).
+    pub fn rung_index(&self, step: usize) -> usize {
+        step % self.rungs.len().max(1)
+    }
+
+    /// Transaction config for the `step`-th transaction. Tips are only applied when a tip
+    /// account is available.
+    pub fn apply(
+        &self,
+        base: &TransactionConfig,
+        step: usize,
+        tip_account: Option<Pubkey>,
+    ) -> TransactionConfig {
+        let mut config = base.clone();
+        let Some(rung) = self.rungs.get(self.rung_index(step)) else {
+            return config;
+        };
+        config.priority_fee_multiplier = rung.priority_fee_multiplier;
+        if let Some(bps) = rung.slippage_bps {
+            config.slippage_bps = bps;
+        }
+        if let (Some(lamports), Some(account)) = (rung.jito_tip_lamports, tip_account) {
+            config.jito_tip = Some((account, lamports));
+        }
+        config
+    }
+}
+
+/// Count a transaction sent on `rung`.
+pub fn record_rung_sent(rung: usize) {
+    metrics().increment_counter(&format!("fee_ladder_rung_{}_sent", rung));
+}
+
+/// Count `rung` as the one that landed.
+pub fn record_rung_landed(rung: usize) {
+    metrics().increment_counter(&format!("fee_ladder_rung_{}_landed", rung));
+    metrics().set_gauge("fee_ladder_landed_rung", rung as u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ladder() -> FeeLadderConfig {
+        FeeLadderConfig {
+            rungs: vec![
+                FeeRung::default(),
+                FeeRung {
+                    priority_fee_multiplier: 2.0,
+                    jito_tip_lamports: Some(50_000),
+                    slippage_bps: Some(1_500),
+                },
+            ],
+            nonce_account: Pubkey::new_unique().to_string(),
+        }
+    }
+
+    #[test]
+    fn rungs_wrap_and_override_config() {
+        let ladder = ladder();
+        let base = TransactionConfig::default();
+        let tip_account = Pubkey::new_unique();
+
+        let first = ladder.apply(&base, 0, Some(tip_account));
+        assert_eq!(first.priority_fee_multiplier, 1.0);
+        assert_eq!(first.slippage_bps, base.slippage_bps);
+        assert_eq!(first.jito_tip, None);
+
+        let second = ladder.apply(&base, 1, Some(tip_account));
+        assert_eq!(second.priority_fee_multiplier, 2.0);
+        assert_eq!(second.slippage_bps, 1_500);
+        assert_eq!(second.jito_tip, Some((tip_account, 50_000)));
+
+        // A third transaction wraps to the first rung; no tip account means no tip
+        assert_eq!(ladder.rung_index(2), 0);
+        assert_eq!(ladder.apply(&base, 3, None).jito_tip, None);
+    }
+
+    #[test]
+    fn validation_rejects_bad_rungs() {
+        assert!(ladder().validate().is_ok());
+        assert!(FeeLadderConfig::default().validate().is_err());
+
+        let mut bad = ladder();
+        bad.rungs[0].priority_fee_multiplier = 0.0;
+        assert!(bad.validate().is_err());
+
+        let mut bad = ladder();
+        bad.rungs[1].slippage_bps = Some(20_000);
+        assert!(bad.validate().is_err());
+
+        let mut bad = ladder();
+        bad.nonce_account = "not-a-pubkey".to_string();
+        assert!(bad.validate().is_err());
+    }
+}
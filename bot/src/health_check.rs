@@ -0,0 +1,236 @@
+//! Background endpoint health checker.
+//!
+//! Every `interval_ms` each HTTP endpoint is asked for `getHealth` and `getSlot`, and each
+//! WSS endpoint for its next `slotSubscribe` notification. An endpoint's lag is how far its
+//! slot trails the best slot seen in the same round. Unhealthy, unreachable or lagging
+//! endpoints are demoted in the `rpc_pool`: broadcasts skip them and reads, polling and WSS
+//! reconnects prefer the others until they catch up. Lag is published as
+//! `http_endpoint_<i>_slot_lag` / `wss_endpoint_<i>_slot_lag` gauges.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{future::join_all, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use tokio::{sync::RwLock, task::JoinHandle, time::timeout};
+use tracing::{info, warn};
+
+use crate::metrics::metrics;
+use crate::rate_limit::RpcLane;
+use crate::rpc_pool::{rpc_pool, EndpointRole};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    /// Demote endpoints more than this many slots behind the best one
+    #[serde(default = "default_max_slot_lag")]
+    pub max_slot_lag: u64,
+    /// Per-probe timeout
+    #[serde(default = "default_probe_timeout_ms")]
+    pub probe_timeout_ms: u64,
+}
+
+fn default_interval_ms() -> u64 {
+    2_000
+}
+
+fn default_max_slot_lag() -> u64 {
+    10
+}
+
+fn default_probe_timeout_ms() -> u64 {
+    1_500
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_interval_ms(),
+            max_slot_lag: default_max_slot_lag(),
+            probe_timeout_ms: default_probe_timeout_ms(),
+        }
+    }
+}
+
+impl HealthCheckConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_ms == 0 || self.probe_timeout_ms == 0 {
+            return Err("health_check interval_ms and probe_timeout_ms must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointKind {
+    Http,
+    Wss,
+}
+
+/// Result of the last probe of one endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointHealth {
+    pub endpoint: String,
+    pub kind: EndpointKind,
+    /// Answered the probe (and, over HTTP, reported itself healthy)
+    pub healthy: bool,
+    pub slot: Option<u64>,
+    /// Slots behind the best endpoint of the round
+    pub slot_lag: Option<u64>,
+    pub demoted: bool,
+}
+
+/// `(healthy, slot)` answered by one endpoint
+type Probe = (bool, Option<u64>);
+
+pub struct HealthChecker {
+    http: Vec<String>,
+    wss: Vec<String>,
+    config: HealthCheckConfig,
+    last: RwLock<Vec<EndpointHealth>>,
+}
+
+impl HealthChecker {
+    pub fn new(http: Vec<String>, wss: Vec<String>, config: HealthCheckConfig) -> Self {
+        Self {
+            http,
+            wss,
+            config,
+            last: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Results of the last round, HTTP endpoints first.
+    pub async fn statuses(&self) -> Vec<EndpointHealth> {
+        self.last.read().await.clone()
+    }
+
+    async fn probe_http(&self, url: &str) -> Probe {
+        let client = rpc_pool().client(url, EndpointRole::Read, RpcLane::Background);
+        let limit = Duration::from_millis(self.config.probe_timeout_ms);
+        let (health, slot) = tokio::join!(timeout(limit, client.get_health()), timeout(limit, client.get_slot()));
+        let slot = match slot {
+            Ok(Ok(slot)) => Some(slot),
+            _ => None,
+        };
+        (matches!(health, Ok(Ok(()))) && slot.is_some(), slot)
+    }
+
+    /// Healthy once the next slot notification arrives, with its slot.
+    async fn probe_wss(&self, url: &str) -> Probe {
+        let probe = async {
+            let client = PubsubClient::new(url).await.ok()?;
+            let slot = {
+                let (mut slots, unsubscribe) = client.slot_subscribe().await.ok()?;
+                let slot = slots.next().await.map(|info| info.slot);
+                drop(slots);
+                unsubscribe().await;
+                slot
+            };
+            let _ = client.shutdown().await;
+            slot
+        };
+        let slot = timeout(Duration::from_millis(self.config.probe_timeout_ms), probe).await.ok().flatten();
+        (slot.is_some(), slot)
+    }
+
+    /// Probe every endpoint once, demote or restore them and publish their lag.
+    pub async fn check_once(&self) -> Vec<EndpointHealth> {
+        let http = join_all(self.http.iter().map(|url| self.probe_http(url)));
+        let wss = join_all(self.wss.iter().map(|url| self.probe_wss(url)));
+        let (http, wss) = tokio::join!(http, wss);
+
+        let probes: Vec<(&String, EndpointKind, Probe)> = self
+            .http
+            .iter()
+            .zip(http)
+            .map(|(url, probe)| (url, EndpointKind::Http, probe))
+            .chain(self.wss.iter().zip(wss).map(|(url, probe)| (url, EndpointKind::Wss, probe)))
+            .collect();
+        let best = probes.iter().filter_map(|(_, _, (_, slot))| *slot).max();
+        if let Some(best) = best {
+            metrics().set_gauge("rpc_best_slot", best);
+        }
+
+        let mut results = Vec::with_capacity(probes.len());
+        let (mut http_index, mut wss_index) = (0, 0);
+        for (url, kind, (healthy, slot)) in probes {
+            let slot_lag = slot.zip(best).map(|(slot, best)| best.saturating_sub(slot));
+            let demoted = !healthy || slot_lag.is_none_or(|lag| lag > self.config.max_slot_lag);
+
+            let prefix = match kind {
+                EndpointKind::Http => {
+                    http_index += 1;
+                    format!("http_endpoint_{}", http_index - 1)
+                }
+                EndpointKind::Wss => {
+                    wss_index += 1;
+                    format!("wss_endpoint_{}", wss_index - 1)
+                }
+            };
+            if let Some(lag) = slot_lag {
+                metrics().set_gauge(&format!("{}_slot_lag", prefix), lag);
+            }
+            metrics().set_gauge(&format!("{}_healthy", prefix), u64::from(!demoted));
+
+            match (rpc_pool().is_demoted(url), demoted) {
+                (false, true) => {
+                    metrics().increment_counter("rpc_endpoint_demotions");
+                    warn!(endpoint = %url, healthy, ?slot_lag, "Demoting endpoint");
+                }
+                (true, false) => info!(endpoint = %url, ?slot_lag, "Endpoint caught up; restoring"),
+                _ => {}
+            }
+            rpc_pool().set_demoted(url, demoted);
+
+            results.push(EndpointHealth {
+                endpoint: url.clone(),
+                kind,
+                healthy,
+                slot,
+                slot_lag,
+                demoted,
+            });
+        }
+        *self.last.write().await = results.clone();
+        results
+    }
+
+    /// Check endpoints every `interval_ms` until the handle is aborted.
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(self.config.interval_ms));
+            loop {
+                interval.tick().await;
+                self.check_once().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn unreachable_endpoints_are_demoted_without_a_lag() {
+        let http = "http://127.0.0.1:1".to_string();
+        let wss = "ws://127.0.0.1:1".to_string();
+        let config = HealthCheckConfig {
+            probe_timeout_ms: 500,
+            ..HealthCheckConfig::default()
+        };
+        let checker = HealthChecker::new(vec![http.clone()], vec![wss.clone()], config);
+
+        let results = checker.check_once().await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].kind, EndpointKind::Http);
+        assert_eq!(results[1].kind, EndpointKind::Wss);
+        assert!(results.iter().all(|r| !r.healthy && r.demoted && r.slot_lag.is_none()));
+        assert!(rpc_pool().is_demoted(&http) && rpc_pool().is_demoted(&wss));
+        assert_eq!(checker.statuses().await.len(), 2);
+    }
+}
@@ -0,0 +1,580 @@
+//! Jito block engine bundle submission.
+//!
+//! - appends a tip transfer to a rotating tip account
+//! - submits via `sendBundle` JSON-RPC to every configured block engine
+//! - returns our transaction signature as soon as an engine accepts the bundle
+//! - polls `getBundleStatuses` in the background until the bundle lands, fails or times out
+//!
+//! `JitoBroadcaster` implements `RpcBroadcaster`, so it can replace (or race) `RpcManager`.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use futures::future::{BoxFuture, FutureExt};
+use futures::stream::{FuturesUnordered, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::{
+    message::{v0::Message as MessageV0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use tokio::{task::JoinSet, time::sleep};
+use tracing::{debug, info, warn};
+
+use crate::metrics::metrics;
+use crate::observability::CorrelationId;
+use crate::rpc_manager::RpcBroadcaster;
+use crate::tx_builder::JitoBundleCandidate;
+use crate::signer::{SigningPolicy, TransactionSigner};
+
+/// Jito caps bundles at five transactions (including the tip).
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+/// Mainnet tip accounts published by Jito.
+pub const DEFAULT_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JitoConfig {
+    /// Block engine base URLs, e.g. "https://mainnet.block-engine.jito.wtf"
+    #[serde(default = "default_block_engine_urls")]
+    pub block_engine_urls: Vec<String>,
+    /// Tip accounts to rotate through
+    #[serde(default = "default_tip_accounts")]
+    pub tip_accounts: Vec<String>,
+    /// Tip paid per bundle
+    #[serde(default = "default_tip_lamports")]
+    pub tip_lamports: u64,
+    #[serde(default = "default_status_poll_interval_ms")]
+    pub status_poll_interval_ms: u64,
+    /// Give up waiting for a landed status after this long
+    #[serde(default = "default_status_timeout_ms")]
+    pub status_timeout_ms: u64,
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+}
+
+fn default_block_engine_urls() -> Vec<String> {
+    vec!["https://mainnet.block-engine.jito.wtf".to_string()]
+}
+fn default_tip_accounts() -> Vec<String> {
+    DEFAULT_TIP_ACCOUNTS.iter().map(|s| s.to_string()).collect()
+}
+fn default_tip_lamports() -> u64 {
+    100_000
+}
+fn default_status_poll_interval_ms() -> u64 {
+    400
+}
+fn default_status_timeout_ms() -> u64 {
+    30_000
+}
+fn default_request_timeout_ms() -> u64 {
+    5_000
+}
+
+impl Default for JitoConfig {
+    fn default() -> Self {
+        Self {
+            block_engine_urls: default_block_engine_urls(),
+            tip_accounts: default_tip_accounts(),
+            tip_lamports: default_tip_lamports(),
+            status_poll_interval_ms: default_status_poll_interval_ms(),
+            status_timeout_ms: default_status_timeout_ms(),
+            request_timeout_ms: default_request_timeout_ms(),
+        }
+    }
+}
+
+impl JitoConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.block_engine_urls.is_empty() {
+            return Err("jito.block_engine_urls must not be empty".to_string());
+        }
+        if self.tip_accounts.is_empty() {
+            return Err("jito.tip_accounts must not be empty".to_string());
+        }
+        for account in &self.tip_accounts {
+            Pubkey::from_str(account)
+                .map_err(|e| format!("jito.tip_accounts: invalid pubkey {}: {}", account, e))?;
+        }
+        if self.status_poll_interval_ms == 0 {
+            return Err("jito.status_poll_interval_ms must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Final state of a submitted bundle as reported by `getBundleStatuses`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleOutcome {
+    /// Bundle landed; carries the slot and the signatures the engine reported
+    Landed { slot: u64, signatures: Vec<String> },
+    /// Bundle landed with a transaction error
+    Failed(String),
+    /// No terminal status before `status_timeout_ms`
+    TimedOut,
+}
+
+/// Thin JSON-RPC client for a Jito block engine.
+pub struct JitoClient {
+    http: Client,
+    config: JitoConfig,
+    tip_accounts: Vec<Pubkey>,
+    tip_rotation: AtomicUsize,
+}
+
+impl std::fmt::Debug for JitoClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JitoClient")
+            .field("block_engine_urls", &self.config.block_engine_urls)
+            .field("tip_lamports", &self.config.tip_lamports)
+            .finish()
+    }
+}
+
+impl JitoClient {
+    pub fn new(config: JitoConfig) -> Result<Self> {
+        config.validate().map_err(|e| anyhow!(e))?;
+        let http = Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()?;
+        let tip_accounts = config
+            .tip_accounts
+            .iter()
+            .map(|s| Pubkey::from_str(s))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Self {
+            http,
+            config,
+            tip_accounts,
+            tip_rotation: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn config(&self) -> &JitoConfig {
+        &self.config
+    }
+
+    /// Next tip account in rotation (spreads write-lock contention across tip accounts).
+    pub fn next_tip_account(&self) -> Pubkey {
+        let idx = self.tip_rotation.fetch_add(1, Ordering::Relaxed) % self.tip_accounts.len();
+        self.tip_accounts[idx]
+    }
+
+    /// True if `tx` already pays one of our tip accounts (fee ladder rungs tip in-transaction).
+    pub fn carries_tip(&self, tx: &VersionedTransaction) -> bool {
+        tx.message
+            .static_account_keys()
+            .iter()
+            .any(|key| self.tip_accounts.contains(key))
+    }
+
+    /// Build and sign the tip transfer that closes a bundle.
+    pub async fn build_tip_transaction(
+        &self,
+        wallet: &dyn TransactionSigner,
+        recent_blockhash: solana_sdk::hash::Hash,
+    ) -> Result<VersionedTransaction> {
+        let payer = wallet.pubkey();
+        #[allow(deprecated)]
+        let tip_ix = solana_sdk::system_instruction::transfer(
+            &payer,
+            &self.next_tip_account(),
+            self.config.tip_lamports,
+        );
+        let message = MessageV0::try_compile(&payer, &[tip_ix], &[], recent_blockhash)
+            .map_err(|e| anyhow!("failed to compile tip message: {}", e))?;
+        let message = VersionedMessage::V0(message);
+        let required = message.header().num_required_signatures as usize;
+        let mut tx = VersionedTransaction {
+            signatures: vec![Signature::default(); required],
+            message,
+        };
+        wallet
+            .sign_transaction(&mut tx, &SigningPolicy::max_lamports_out(self.config.tip_lamports))
+            .await?;
+        Ok(tx)
+    }
+
+    /// Submit a bundle to a single block engine; returns the bundle id.
+    pub async fn send_bundle(&self, base_url: &str, bundle: &JitoBundleCandidate) -> Result<String> {
+        if bundle.transactions.is_empty() || bundle.transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(anyhow!(
+                "bundle must contain 1..={} transactions, got {}",
+                MAX_BUNDLE_TRANSACTIONS,
+                bundle.transactions.len()
+            ));
+        }
+
+        let encoded = bundle
+            .transactions
+            .iter()
+            .map(|tx| {
+                bincode_serialize(tx).map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let result = self
+            .call(
+                base_url,
+                "bundles",
+                "sendBundle",
+                json!([encoded, { "encoding": "base64" }]),
+            )
+            .await?;
+        result
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| anyhow!("sendBundle: unexpected result {}", result))
+    }
+
+    /// Query `getBundleStatuses` for one bundle. `None` while the engine has no record yet.
+    pub async fn bundle_status(&self, base_url: &str, bundle_id: &str) -> Result<Option<BundleStatus>> {
+        let result = self
+            .call(base_url, "getBundleStatuses", "getBundleStatuses", json!([[bundle_id]]))
+            .await?;
+        let entry = result
+            .get("value")
+            .and_then(|v| v.as_array())
+            .and_then(|arr| arr.iter().find(|e| !e.is_null()));
+        match entry {
+            Some(v) => Ok(Some(serde_json::from_value(v.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Poll a bundle until it reaches a terminal state or `status_timeout_ms` elapses.
+    pub async fn wait_for_bundle(&self, base_url: &str, bundle_id: &str) -> BundleOutcome {
+        let deadline = Instant::now() + Duration::from_millis(self.config.status_timeout_ms);
+        let interval = Duration::from_millis(self.config.status_poll_interval_ms);
+        while Instant::now() < deadline {
+            match self.bundle_status(base_url, bundle_id).await {
+                Ok(Some(status)) => {
+                    if let Some(err) = status.error() {
+                        return BundleOutcome::Failed(err);
+                    }
+                    if status.is_landed() {
+                        return BundleOutcome::Landed {
+                            slot: status.slot,
+                            signatures: status.transactions,
+                        };
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => debug!(bundle_id, "getBundleStatuses error: {}", e),
+            }
+            sleep(interval).await;
+        }
+        BundleOutcome::TimedOut
+    }
+
+    async fn call(&self, base_url: &str, path: &str, method: &str, params: Value) -> Result<Value> {
+        let url = format!("{}/api/v1/{}", base_url.trim_end_matches('/'), path);
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let resp = self.http.post(&url).json(&body).send().await?;
+        let status = resp.status();
+        let j: Value = resp.json().await?;
+        if let Some(err) = j.get("error") {
+            return Err(anyhow!("{} on {} failed: {}", method, base_url, err));
+        }
+        if !status.is_success() {
+            return Err(anyhow!("{} on {} returned HTTP {}", method, base_url, status));
+        }
+        j.get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("{} on {}: missing result", method, base_url))
+    }
+}
+
+/// One entry of a `getBundleStatuses` response.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BundleStatus {
+    pub bundle_id: String,
+    #[serde(default)]
+    pub transactions: Vec<String>,
+    #[serde(default)]
+    pub slot: u64,
+    #[serde(default)]
+    pub confirmation_status: Option<String>,
+    #[serde(default)]
+    pub err: Option<Value>,
+}
+
+impl BundleStatus {
+    pub fn is_landed(&self) -> bool {
+        matches!(
+            self.confirmation_status.as_deref(),
+            Some("confirmed") | Some("finalized")
+        )
+    }
+
+    /// Error reported for the bundle; `{"Ok": null}` counts as success.
+    pub fn error(&self) -> Option<String> {
+        match &self.err {
+            None | Some(Value::Null) => None,
+            Some(v) if v.get("Ok").is_some() => None,
+            Some(v) => Some(v.to_string()),
+        }
+    }
+}
+
+fn bincode_serialize(tx: &VersionedTransaction) -> Result<Vec<u8>> {
+    bincode::serialize(tx).map_err(|e| anyhow!("failed to serialize transaction: {}", e))
+}
+
+/// `RpcBroadcaster` that submits the best transaction plus a tip as a Jito bundle.
+///
+/// Bundles are atomic, so the parallel copies produced by the buy engine are not bundled
+/// together: the first transaction is bundled with the tip and sent to every block engine.
+/// Fee ladder transactions that carry their own tip are each sent as a single-transaction
+/// bundle; untipped ones sent alongside them go through the RPC broadcaster, all raced.
+pub struct JitoBroadcaster {
+    client: Arc<JitoClient>,
+    wallet: Arc<dyn TransactionSigner>,
+    rpc: Option<Arc<dyn RpcBroadcaster>>,
+}
+
+impl std::fmt::Debug for JitoBroadcaster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JitoBroadcaster")
+            .field("client", &self.client)
+            .field("payer", &self.wallet.pubkey())
+            .finish()
+    }
+}
+
+impl JitoBroadcaster {
+    pub fn new(client: Arc<JitoClient>, wallet: Arc<dyn TransactionSigner>) -> Self {
+        Self { client, wallet, rpc: None }
+    }
+
+    /// Send untipped transactions that accompany self-tipped ones through `rpc`.
+    pub fn with_rpc(mut self, rpc: Arc<dyn RpcBroadcaster>) -> Self {
+        self.rpc = Some(rpc);
+        self
+    }
+
+    /// Submit one bundle to every block engine and return its first transaction's signature
+    /// once any engine accepts it. The bundle status is tracked in the background; landing
+    /// is confirmed by the caller (confirmation tracker / rebroadcaster).
+    async fn submit_bundle(
+        &self,
+        bundle: JitoBundleCandidate,
+        correlation_id: Option<CorrelationId>,
+    ) -> Result<Signature> {
+        let our_sig = *bundle.transactions[0]
+            .signatures
+            .first()
+            .ok_or_else(|| anyhow!("JitoBroadcaster: transaction has no signatures"))?;
+
+        let submissions = self.client.config().block_engine_urls.iter().map(|url| {
+            let bundle = &bundle;
+            async move { (url.clone(), self.client.send_bundle(url, bundle).await) }
+        });
+        let mut accepted = Vec::new();
+        let mut last_err = None;
+        for (url, result) in futures::future::join_all(submissions).await {
+            match result {
+                Ok(bundle_id) => {
+                    info!(block_engine = %url, bundle_id = %bundle_id, sig = %our_sig, "Jito bundle submitted");
+                    metrics().increment_counter("jito_bundles_submitted");
+                    accepted.push((url, bundle_id));
+                }
+                Err(e) => {
+                    warn!("Jito bundle submission failed: {}", e);
+                    last_err = Some(e);
+                }
+            }
+        }
+        if accepted.is_empty() {
+            return Err(last_err.unwrap_or_else(|| anyhow!("no block engines configured")));
+        }
+
+        tokio::spawn(track_bundle(self.client.clone(), accepted, our_sig, correlation_id));
+        Ok(our_sig)
+    }
+}
+
+impl RpcBroadcaster for JitoBroadcaster {
+    fn send_on_many_rpc<'a>(
+        &'a self,
+        txs: Vec<VersionedTransaction>,
+        correlation_id: Option<CorrelationId>,
+    ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
+        Box::pin(async move {
+            if txs.is_empty() {
+                return Err(anyhow!("JitoBroadcaster: no transactions to send"));
+            }
+
+            // Fee ladder rungs tip from inside the transaction: one bundle each. Untipped
+            // transactions next to them go through RPC when available; otherwise the first
+            // untipped transaction is bundled with a separate tip transfer.
+            let (tipped, untipped): (Vec<_>, Vec<_>) =
+                txs.into_iter().partition(|tx| self.client.carries_tip(tx));
+            let mut bundles = tipped
+                .into_iter()
+                .map(|tx| JitoBundleCandidate {
+                    transactions: vec![tx],
+                    max_total_cost_lamports: self.client.config().tip_lamports,
+                    target_slot: None,
+                })
+                .collect::<Vec<_>>();
+            let via_rpc = match &self.rpc {
+                Some(rpc) if !bundles.is_empty() && !untipped.is_empty() => Some((rpc.clone(), untipped)),
+                _ => {
+                    if let Some(tx) = untipped.into_iter().next() {
+                        let tip_tx = self
+                            .client
+                            .build_tip_transaction(self.wallet.as_ref(), *tx.message.recent_blockhash())
+                            .await?;
+                        bundles.push(JitoBundleCandidate {
+                            transactions: vec![tx, tip_tx],
+                            max_total_cost_lamports: self.client.config().tip_lamports,
+                            target_slot: None,
+                        });
+                    }
+                    None
+                }
+            };
+
+            let mut pending: FuturesUnordered<BoxFuture<'_, Result<Signature>>> = bundles
+                .into_iter()
+                .map(|bundle| self.submit_bundle(bundle, correlation_id.clone()).boxed())
+                .collect();
+            if let Some((rpc, untipped)) = via_rpc {
+                let correlation_id = correlation_id.clone();
+                pending.push(async move { rpc.send_on_many_rpc(untipped, correlation_id).await }.boxed());
+            }
+            let mut last_err = None;
+            while let Some(result) = pending.next().await {
+                match result {
+                    Ok(sig) => return Ok(sig),
+                    Err(e) => last_err = Some(e),
+                }
+            }
+            Err(last_err.unwrap_or_else(|| anyhow!("JitoBroadcaster: no bundles submitted")))
+        })
+    }
+}
+
+/// Poll every engine that accepted the bundle until the first terminal outcome and record it.
+async fn track_bundle(
+    client: Arc<JitoClient>,
+    accepted: Vec<(String, String)>,
+    our_sig: Signature,
+    correlation_id: Option<CorrelationId>,
+) {
+    let mut set = JoinSet::new();
+    for (url, bundle_id) in accepted {
+        let client = client.clone();
+        set.spawn(async move { client.wait_for_bundle(&url, &bundle_id).await });
+    }
+
+    let mut outcome = BundleOutcome::TimedOut;
+    while let Some(joined) = set.join_next().await {
+        match joined {
+            Ok(BundleOutcome::TimedOut) => {}
+            Ok(terminal) => {
+                outcome = terminal;
+                if matches!(outcome, BundleOutcome::Landed { .. }) {
+                    set.abort_all();
+                    break;
+                }
+            }
+            Err(join_err) => warn!("Jito task join error: {}", join_err),
+        }
+    }
+
+    match outcome {
+        BundleOutcome::Landed { slot, signatures } => {
+            metrics().increment_counter("jito_bundles_landed");
+            // Map the engine's view back to our transaction
+            let ours = our_sig.to_string();
+            if !signatures.is_empty() && !signatures.contains(&ours) {
+                warn!(slot, sig = %our_sig, correlation_id = ?correlation_id, "Jito bundle landed without our transaction");
+            } else {
+                info!(slot, sig = %our_sig, correlation_id = ?correlation_id, "Jito bundle landed");
+            }
+        }
+        BundleOutcome::Failed(err) => {
+            metrics().increment_counter("jito_bundles_failed");
+            warn!(sig = %our_sig, correlation_id = ?correlation_id, "Jito bundle failed: {}", err);
+        }
+        BundleOutcome::TimedOut => {
+            metrics().increment_counter("jito_bundles_timed_out");
+            warn!(sig = %our_sig, correlation_id = ?correlation_id, "Jito bundle status timed out");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tip_accounts_rotate() {
+        let client = JitoClient::new(JitoConfig::default()).unwrap();
+        let first = client.next_tip_account();
+        let second = client.next_tip_account();
+        assert_ne!(first, second);
+        for _ in 0..DEFAULT_TIP_ACCOUNTS.len() - 2 {
+            client.next_tip_account();
+        }
+        assert_eq!(client.next_tip_account(), first);
+    }
+
+    #[test]
+    fn bundle_status_error_parsing() {
+        let ok: BundleStatus = serde_json::from_value(json!({
+            "bundle_id": "b1",
+            "transactions": ["s1"],
+            "slot": 10,
+            "confirmation_status": "confirmed",
+            "err": { "Ok": null }
+        }))
+        .unwrap();
+        assert!(ok.is_landed());
+        assert!(ok.error().is_none());
+
+        let failed: BundleStatus = serde_json::from_value(json!({
+            "bundle_id": "b2",
+            "slot": 10,
+            "confirmation_status": "processed",
+            "err": { "Err": "InstructionError" }
+        }))
+        .unwrap();
+        assert!(!failed.is_landed());
+        assert!(failed.error().is_some());
+    }
+
+    #[test]
+    fn invalid_tip_account_rejected() {
+        let cfg = JitoConfig {
+            tip_accounts: vec!["not-a-pubkey".to_string()],
+            ..JitoConfig::default()
+        };
+        assert!(JitoClient::new(cfg).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! Encrypted keypair files.
+//!
+//! A keystore is a JSON document holding the 64-byte keypair encrypted with XChaCha20-Poly1305
+//! under a key derived from a password with Argon2id. The public key is stored in the clear and
+//! bound to the ciphertext as associated data, so a file can be identified without the password
+//! and cannot be re-labelled. Passwords, derived keys and decrypted bytes are zeroized on drop.
+
+use std::{fs, path::Path, sync::Mutex};
+
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use solana_sdk::signature::{Keypair, Signer};
+use tracing::warn;
+use zeroize::Zeroizing;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+const KDF_ARGON2ID: &str = "argon2id";
+const CIPHER_XCHACHA20POLY1305: &str = "xchacha20poly1305";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreConfig {
+    /// Environment variable holding the keystore password
+    #[serde(default = "default_password_env")]
+    pub password_env: String,
+    /// File whose first line is the password (takes precedence over the env var)
+    #[serde(default)]
+    pub password_file: Option<String>,
+    /// Prompt on the terminal when neither source provides a password
+    #[serde(default = "default_prompt")]
+    pub prompt: bool,
+}
+
+fn default_password_env() -> String {
+    "SNIFFER_KEYSTORE_PASSWORD".to_string()
+}
+fn default_prompt() -> bool {
+    true
+}
+
+impl Default for KeystoreConfig {
+    fn default() -> Self {
+        Self {
+            password_env: default_password_env(),
+            password_file: None,
+            prompt: default_prompt(),
+        }
+    }
+}
+
+impl KeystoreConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.password_env.trim().is_empty() {
+            return Err("keystore.password_env must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Argon2id cost parameters, stored in each keystore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// On-disk keystore format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedKeystore {
+    pub version: u32,
+    pub pubkey: String,
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+    pub salt: String,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl EncryptedKeystore {
+    /// Encrypt `keypair` under `password`.
+    pub fn encrypt(keypair: &Keypair, password: &str, params: KdfParams) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let pubkey = keypair.pubkey().to_string();
+        let key = derive_key(password, &salt, params)?;
+        let secret = Zeroizing::new(keypair.to_bytes());
+        let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload { msg: secret.as_ref(), aad: pubkey.as_bytes() },
+            )
+            .map_err(|_| anyhow!("Keystore encryption failed"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            pubkey,
+            kdf: KDF_ARGON2ID.to_string(),
+            kdf_params: params,
+            salt: STANDARD.encode(salt),
+            cipher: CIPHER_XCHACHA20POLY1305.to_string(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the keypair; fails on a wrong password or a modified file.
+    pub fn decrypt(&self, password: &str) -> Result<Keypair> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(anyhow!("Unsupported keystore version {}", self.version));
+        }
+        if self.kdf != KDF_ARGON2ID || self.cipher != CIPHER_XCHACHA20POLY1305 {
+            return Err(anyhow!("Unsupported keystore algorithms: {} / {}", self.kdf, self.cipher));
+        }
+        let salt = decode_field("salt", &self.salt)?;
+        let nonce = decode_field("nonce", &self.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(anyhow!("Invalid keystore nonce length {}", nonce.len()));
+        }
+        let ciphertext = decode_field("ciphertext", &self.ciphertext)?;
+
+        let key = derive_key(password, &salt, self.kdf_params)?;
+        let secret = Zeroizing::new(
+            XChaCha20Poly1305::new(key.as_ref().into())
+                .decrypt(
+                    XNonce::from_slice(&nonce),
+                    Payload { msg: &ciphertext, aad: self.pubkey.as_bytes() },
+                )
+                .map_err(|_| anyhow!("Failed to decrypt keystore: wrong password or corrupted file"))?,
+        );
+        let keypair = Keypair::try_from(&secret[..])
+            .map_err(|e| anyhow!("Decrypted keystore is not a keypair: {}", e))?;
+        if keypair.pubkey().to_string() != self.pubkey {
+            return Err(anyhow!("Keystore pubkey {} does not match its key", self.pubkey));
+        }
+        Ok(keypair)
+    }
+
+    /// Parse `data` as a keystore; `None` for anything else (e.g. a plaintext keypair).
+    pub fn parse(data: &str) -> Option<Self> {
+        let data = data.trim();
+        if !data.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(data).ok()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json)
+            .map_err(|e| anyhow!("Failed to write keystore to {}: {}", path.as_ref().display(), e))
+    }
+}
+
+fn decode_field(name: &str, value: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| anyhow!("Invalid keystore {}: {}", name, e))
+}
+
+fn derive_key(password: &str, salt: &[u8], params: KdfParams) -> Result<Zeroizing<[u8; 32]>> {
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+        .map_err(|e| anyhow!("Invalid keystore KDF parameters: {}", e))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|e| anyhow!("Keystore key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Resolves the keystore password once (file, env var, then prompt) and keeps it until dropped.
+pub struct PasswordProvider {
+    config: KeystoreConfig,
+    cached: Mutex<Option<Zeroizing<String>>>,
+}
+
+impl std::fmt::Debug for PasswordProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasswordProvider")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl PasswordProvider {
+    pub fn new(config: KeystoreConfig) -> Self {
+        Self {
+            config,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Password for unlocking keystores.
+    pub fn password(&self) -> Result<Zeroizing<String>> {
+        let mut cached = self.cached.lock().map_err(|_| anyhow!("password cache poisoned"))?;
+        if let Some(password) = cached.as_ref() {
+            return Ok(password.clone());
+        }
+        let password = match self.configured()? {
+            Some(password) => password,
+            None => self.prompt("Keystore password: ")?,
+        };
+        *cached = Some(password.clone());
+        Ok(password)
+    }
+
+    /// Password for a new keystore; prompted passwords must be entered twice.
+    pub fn new_password(&self) -> Result<Zeroizing<String>> {
+        if let Some(password) = self.configured()? {
+            return Ok(password);
+        }
+        let password = self.prompt("New keystore password: ")?;
+        let confirm = self.prompt("Repeat password: ")?;
+        if *password != *confirm {
+            return Err(anyhow!("Passwords do not match"));
+        }
+        Ok(password)
+    }
+
+    /// Password from the password file or env var, if either provides one.
+    fn configured(&self) -> Result<Option<Zeroizing<String>>> {
+        if let Some(path) = &self.config.password_file {
+            warn_if_shared(path);
+            let contents = Zeroizing::new(
+                fs::read_to_string(path)
+                    .map_err(|e| anyhow!("Failed to read password file {}: {}", path, e))?,
+            );
+            let line = contents.lines().next().unwrap_or_default();
+            return non_empty(Zeroizing::new(line.to_string())).map(Some);
+        }
+        match std::env::var(&self.config.password_env) {
+            Ok(password) => non_empty(Zeroizing::new(password)).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn prompt(&self, message: &str) -> Result<Zeroizing<String>> {
+        if !self.config.prompt {
+            return Err(anyhow!(
+                "No keystore password: set {} or keystore.password_file",
+                self.config.password_env
+            ));
+        }
+        let password = rpassword::prompt_password(message)
+            .map_err(|e| anyhow!("Failed to read password: {}", e))?;
+        non_empty(Zeroizing::new(password))
+    }
+}
+
+fn non_empty(password: Zeroizing<String>) -> Result<Zeroizing<String>> {
+    if password.is_empty() {
+        return Err(anyhow!("Keystore password is empty"));
+    }
+    Ok(password)
+}
+
+#[cfg(unix)]
+fn warn_if_shared(path: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(meta) = fs::metadata(path) {
+        if meta.permissions().mode() & 0o077 != 0 {
+            warn!("Password file {} is readable by other users; chmod 600 it", path);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_shared(_path: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap parameters keep the tests fast; production files use `KdfParams::default()`
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn roundtrip_and_wrong_password() {
+        let keypair = Keypair::new();
+        let keystore = EncryptedKeystore::encrypt(&keypair, "hunter2", TEST_PARAMS).unwrap();
+        assert_eq!(keystore.pubkey, keypair.pubkey().to_string());
+
+        let decrypted = keystore.decrypt("hunter2").unwrap();
+        assert_eq!(decrypted.to_bytes(), keypair.to_bytes());
+        assert!(keystore.decrypt("hunter3").is_err());
+    }
+
+    #[test]
+    fn relabelled_or_tampered_files_are_rejected() {
+        let keystore = EncryptedKeystore::encrypt(&Keypair::new(), "pw", TEST_PARAMS).unwrap();
+
+        let mut relabelled = keystore.clone();
+        relabelled.pubkey = Keypair::new().pubkey().to_string();
+        assert!(relabelled.decrypt("pw").is_err());
+
+        let mut tampered = keystore.clone();
+        let mut bytes = STANDARD.decode(&tampered.ciphertext).unwrap();
+        bytes[0] ^= 1;
+        tampered.ciphertext = STANDARD.encode(bytes);
+        assert!(tampered.decrypt("pw").is_err());
+    }
+
+    #[test]
+    fn parse_distinguishes_plaintext_keypairs() {
+        let keystore = EncryptedKeystore::encrypt(&Keypair::new(), "pw", TEST_PARAMS).unwrap();
+        let json = serde_json::to_string(&keystore).unwrap();
+        assert!(EncryptedKeystore::parse(&json).is_some());
+
+        let plaintext = serde_json::to_string(&Keypair::new().to_bytes().to_vec()).unwrap();
+        assert!(EncryptedKeystore::parse(&plaintext).is_none());
+    }
+
+    #[test]
+    fn password_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("password");
+        fs::write(&file, "from-file\n").unwrap();
+
+        let env = "SNIFFER_KEYSTORE_PASSWORD_TEST_SOURCES";
+        std::env::set_var(env, "from-env");
+        let from_env = PasswordProvider::new(KeystoreConfig {
+            password_env: env.to_string(),
+            prompt: false,
+            ..KeystoreConfig::default()
+        });
+        assert_eq!(from_env.password().unwrap().as_str(), "from-env");
+
+        let from_file = PasswordProvider::new(KeystoreConfig {
+            password_env: env.to_string(),
+            password_file: Some(file.display().to_string()),
+            prompt: false,
+        });
+        assert_eq!(from_file.password().unwrap().as_str(), "from-file");
+
+        let missing = PasswordProvider::new(KeystoreConfig {
+            password_env: "SNIFFER_KEYSTORE_PASSWORD_TEST_UNSET".to_string(),
+            prompt: false,
+            ..KeystoreConfig::default()
+        });
+        assert!(missing.password().is_err());
+    }
+}
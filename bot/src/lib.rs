@@ -3,12 +3,30 @@ pub mod types;
 pub mod time_utils;
 pub mod candidate_buffer;
 pub mod rpc_manager;
+pub mod rate_limit;
+pub mod rpc_pool;
+pub mod health_check;
+pub mod jito;
+pub mod tpu;
+pub mod blockhash;
+pub mod confirmation;
+pub mod rebroadcast;
 pub mod nonce_manager;
 pub mod buy_engine;
 pub mod sniffer;
 pub mod gui;
+pub mod keystore;
+pub mod signer;
 pub mod wallet;
+pub mod wallet_pool;
 pub mod tx_builder;
+pub mod tx_guard;
+pub mod tx_template;
+pub mod token_accounts;
+pub mod fee_estimator;
+pub mod fee_ladder;
+pub mod compute_units;
+pub mod dex;
 pub mod metrics;
 pub mod structured_logging;
 pub mod security;
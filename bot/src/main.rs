@@ -4,19 +4,35 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::sync::{mpsc, Mutex};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 
 use sniffer_bot_light::buy_engine::BuyEngine;
-use sniffer_bot_light::config::{Config, SnifferMode};
+use sniffer_bot_light::blockhash::BlockhashService;
+use sniffer_bot_light::compute_units::ComputeUnitEstimator;
+use sniffer_bot_light::confirmation::ConfirmationTracker;
+use sniffer_bot_light::config::{BroadcastMode, Config, SnifferMode};
+use sniffer_bot_light::dex::pumpfun::MigrationTracker;
+use sniffer_bot_light::fee_estimator::PriorityFeeEstimator;
+use sniffer_bot_light::jito::{JitoBroadcaster, JitoClient};
 use sniffer_bot_light::gui::{launch_gui, GuiEvent, GuiEventSender};
+use sniffer_bot_light::health_check::HealthChecker;
 use sniffer_bot_light::nonce_manager::NonceManager;
+use sniffer_bot_light::rate_limit::{rate_limits, RpcLane};
+use sniffer_bot_light::rebroadcast::Rebroadcaster;
+use sniffer_bot_light::rpc_pool::{rpc_pool, EndpointRole};
 use sniffer_bot_light::rpc_manager::{RpcBroadcaster, RpcManager};
 use sniffer_bot_light::sniffer;
 use sniffer_bot_light::sniffer::runner::SnifferRunner;
+use sniffer_bot_light::tpu::{RaceBroadcaster, TpuBroadcaster, TpuMode};
 use sniffer_bot_light::tx_builder::{TransactionBuilder, TransactionConfig};
+use sniffer_bot_light::tx_guard::TransactionGuard;
+use sniffer_bot_light::tx_template::TemplateCache;
 use sniffer_bot_light::types::{AppState, CandidateReceiver, CandidateSender, Mode, ProgramLogEvent};
+use sniffer_bot_light::keystore::PasswordProvider;
+use sniffer_bot_light::signer::{RemoteSigner, TransactionSigner};
 use sniffer_bot_light::wallet::WalletManager;
+use sniffer_bot_light::wallet_pool::WalletPool;
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> anyhow::Result<()> {
@@ -27,6 +43,11 @@ async fn main() -> anyhow::Result<()> {
 
     let cfg = Config::load();
     info!("Loaded config: {:?}", cfg);
+    rate_limits().configure(cfg.rate_limits.clone());
+    rpc_pool().configure(&cfg.rpc_endpoints, cfg.rpc_pool.clone().unwrap_or_default());
+    let health_task = cfg.health_check.clone().map(|config| {
+        Arc::new(HealthChecker::new(rpc_pool().all_endpoints(), cfg.rpc_wss_endpoints.clone(), config)).spawn()
+    });
 
     let app_state = Arc::new(Mutex::new(AppState {
         mode: Mode::Sniffing,
@@ -37,47 +58,191 @@ async fn main() -> anyhow::Result<()> {
     }));
 
     let (cand_tx, cand_rx): (CandidateSender, CandidateReceiver) = mpsc::channel(1024);
-    let (raw_tx, _raw_rx): (mpsc::Sender<ProgramLogEvent>, mpsc::Receiver<ProgramLogEvent>) =
+    let (raw_tx, mut raw_rx): (mpsc::Sender<ProgramLogEvent>, mpsc::Receiver<ProgramLogEvent>) =
         mpsc::channel(256);
     let (gui_tx, mut gui_rx): (GuiEventSender, mpsc::Receiver<GuiEvent>) = mpsc::channel(64);
 
 
-    let prod = Arc::new(RpcManager::new_with_config(cfg.rpc_endpoints.clone(), cfg.clone()));
+    let prod = Arc::new(RpcManager::new_with_config(rpc_pool().endpoints(EndpointRole::Send), cfg.clone()));
     let rpc: Arc<dyn RpcBroadcaster> = prod.clone();
     let nonce_manager = Arc::new(NonceManager::new(cfg.nonce_count));
+    let mut migration_tracker: Option<Arc<MigrationTracker>> = None;
+    let mut template_task = None;
+    let mut blockhash_task = None;
+    let mut confirmations = None;
+
+    // Unlocks encrypted keystores; dropped (and the password wiped) once wallets are loaded
+    let passwords = PasswordProvider::new(cfg.keystore.clone().unwrap_or_default());
+
+    // Signer pool: wallet 0 doubles as the builder's primary wallet
+    let wallet_pool = match &cfg.wallet_pool {
+        Some(pool_cfg) => match WalletPool::load(pool_cfg, &passwords) {
+            Ok(pool) => {
+                info!("Wallet pool enabled: {} wallets, {:?} selection", pool.len(), pool_cfg.selection);
+                Some(Arc::new(pool))
+            }
+            Err(e) => {
+                error!("Failed to load wallet pool: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let mut wallet_task = None;
 
-    // Setup wallet and transaction builder if keypair is configured
-    let tx_builder = if let Some(keypair_path) = &cfg.keypair_path {
-        match WalletManager::from_file(keypair_path) {
-            Ok(wallet) => {
-                let primary_endpoint = cfg.rpc_endpoints.first()
-                    .unwrap_or(&"https://api.devnet.solana.com".to_string()).clone();
-                let config = TransactionConfig::default();
-                match TransactionBuilder::new(
-                    Arc::new(wallet), 
-                    vec![primary_endpoint], 
-                    nonce_manager.clone(), 
-                    &config
-                ).await {
-                    Ok(builder) => Some(builder),
-                    Err(e) => {
-                        error!("Failed to create transaction builder: {}", e);
-                        info!("Continuing without transaction builder - will use placeholder transactions");
-                        None
+    // Setup signer and transaction builder: a remote signing daemon, the pool's first
+    // wallet or the configured keypair
+    let primary_wallet: Option<Arc<dyn TransactionSigner>> = match (&cfg.remote_signer, &wallet_pool, &cfg.keypair_path) {
+        (Some(signer_cfg), _, _) => match RemoteSigner::new(signer_cfg) {
+            Ok(signer) => {
+                info!("Signing through remote signer {} ({})", signer_cfg.endpoint, signer_cfg.pubkey);
+                Some(Arc::new(signer))
+            }
+            Err(e) => {
+                error!("Failed to set up remote signer: {}", e);
+                info!("Continuing without transaction builder - will use placeholder transactions");
+                None
+            }
+        },
+        (None, Some(pool), _) => pool.wallet(0).map(|w| w.clone() as Arc<dyn TransactionSigner>),
+        (None, None, Some(keypair_path)) => match WalletManager::load(keypair_path, &passwords) {
+            Ok(wallet) => Some(Arc::new(wallet)),
+            Err(e) => {
+                error!("Failed to load wallet from {}: {}", keypair_path, e);
+                info!("Continuing without transaction builder - will use placeholder transactions");
+                None
+            }
+        },
+        (None, None, None) => {
+            info!("No keypair configured, using placeholder transactions for testing");
+            None
+        }
+    };
+    drop(passwords);
+    let tx_builder = if let Some(wallet) = primary_wallet {
+        // Builder lookups fail over across every read endpoint, healthiest first
+        let mut read_endpoints = rpc_pool().endpoints(EndpointRole::Read);
+        if read_endpoints.is_empty() {
+            read_endpoints.push("https://api.devnet.solana.com".to_string());
+        }
+        let primary_endpoint = read_endpoints[0].clone();
+        let config = TransactionConfig::default();
+        match TransactionBuilder::new(
+            wallet, 
+            read_endpoints, 
+            nonce_manager.clone(), 
+            &config
+        ).await {
+            Ok(mut builder) => {
+                // Feeds the build path; balance refreshes use the background lane
+                let rpc_client = rpc_pool().client(&primary_endpoint, EndpointRole::Read, RpcLane::Send);
+                let mut blockhash_service = None;
+                if let Some(bh_cfg) = &cfg.blockhash {
+                    info!("Slot-aware blockhash service enabled: {:?}", bh_cfg);
+                    let service = Arc::new(BlockhashService::new(rpc_client.clone(), bh_cfg.clone()));
+                    blockhash_task = Some(service.clone().spawn());
+                    builder = builder.with_blockhash_service(service.clone());
+                    confirmations = Some(Arc::new(ConfirmationTracker::new(rpc_client.clone(), service.clone())));
+                    blockhash_service = Some(service);
+                }
+                let mut fee_estimator = None;
+                if let Some(fee_cfg) = &cfg.priority_fee {
+                    info!("Dynamic priority fees enabled: {:?}", fee_cfg);
+                    let estimator = Arc::new(PriorityFeeEstimator::new(rpc_client.clone(), fee_cfg.clone()));
+                    builder = builder.with_fee_estimator(estimator.clone());
+                    fee_estimator = Some(estimator);
+                }
+                if let Some(cu_cfg) = &cfg.compute_units {
+                    info!("Simulated compute unit limits enabled: {:?}", cu_cfg);
+                    let estimator = ComputeUnitEstimator::new(rpc_client.clone(), cu_cfg.clone());
+                    builder = builder.with_cu_estimator(Arc::new(estimator));
+                }
+                let tracker = Arc::new(MigrationTracker::new(rpc_client.clone()));
+                builder = builder.with_migration_tracker(tracker.clone());
+                migration_tracker = Some(tracker);
+                if let Some(template_cfg) = &cfg.tx_templates {
+                    info!("Pre-built buy templates enabled: {:?}", template_cfg);
+                    let mut templates = TemplateCache::new(
+                        rpc_client.clone(),
+                        builder.wallet.pubkey(),
+                        template_cfg.clone(),
+                    );
+                    if let Some(estimator) = fee_estimator {
+                        templates = templates.with_fee_estimator(estimator);
+                    }
+                    if let Some(service) = blockhash_service {
+                        templates = templates.with_blockhash_service(service);
                     }
+                    let templates = Arc::new(templates);
+                    template_task = Some(templates.clone().spawn_refresh(config.priority_fee_lamports));
+                    builder = builder.with_templates(templates);
+                }
+                if let Some(pool) = &wallet_pool {
+                    let pool_cfg = cfg.wallet_pool.as_ref().expect("pool loaded from config");
+                    let balances = rpc_pool().client(&primary_endpoint, EndpointRole::Read, RpcLane::Background);
+                    wallet_task = Some(pool.clone().spawn_balance_refresh(balances, pool_cfg.balance_refresh_ms));
+                    builder = builder.with_wallet_pool(pool.clone());
                 }
+                if let Some(guard_cfg) = &cfg.tx_guard {
+                    info!("Transaction guard overrides: {:?}", guard_cfg);
+                    let guard = TransactionGuard::new(guard_cfg).expect("tx_guard validated with config");
+                    builder = builder.with_tx_guard(guard);
+                }
+                if let Some(policy) = &cfg.mint_policy {
+                    info!("Token-2022 mint policy: {:?}", policy);
+                    builder = builder.with_mint_policy(policy.clone());
+                }
+                Some(builder)
             }
             Err(e) => {
-                error!("Failed to load wallet from {}: {}", keypair_path, e);
+                error!("Failed to create transaction builder: {}", e);
                 info!("Continuing without transaction builder - will use placeholder transactions");
                 None
             }
         }
     } else {
-        info!("No keypair configured, using placeholder transactions for testing");
         None
     };
 
+    // Route broadcasts through Jito block engines when configured (tips need a wallet)
+    let mut jito_client = None;
+    let rpc: Arc<dyn RpcBroadcaster> = match (&cfg.jito, &tx_builder) {
+        (Some(jito_cfg), Some(builder)) => match JitoClient::new(jito_cfg.clone()) {
+            Ok(client) => {
+                info!("Jito bundle submission enabled: {:?}", jito_cfg.block_engine_urls);
+                let client = Arc::new(client);
+                jito_client = Some(client.clone());
+                Arc::new(JitoBroadcaster::new(client, builder.wallet.clone()).with_rpc(rpc.clone()))
+            }
+            Err(e) => {
+                error!("Invalid Jito configuration, falling back to RPC broadcast: {}", e);
+                rpc
+            }
+        },
+        (Some(_), None) => {
+            warn!("Jito configured but no wallet available for tips; using RPC broadcast");
+            rpc
+        }
+        _ => rpc,
+    };
+
+    // Send straight to the upcoming leaders over QUIC, alone or racing the broadcaster above
+    let rpc: Arc<dyn RpcBroadcaster> = match &cfg.tpu {
+        Some(tpu_cfg) => {
+            info!("Direct TPU submission enabled: {:?}", tpu_cfg);
+            let leader_endpoint = rpc_pool().endpoints(EndpointRole::Read).first()
+                .cloned()
+                .unwrap_or_else(|| "https://api.devnet.solana.com".to_string());
+            let leader_rpc = rpc_pool().client(&leader_endpoint, EndpointRole::Read, RpcLane::Send);
+            let tpu: Arc<dyn RpcBroadcaster> = Arc::new(TpuBroadcaster::new(leader_rpc, tpu_cfg.clone()));
+            match tpu_cfg.mode {
+                TpuMode::TpuOnly => tpu,
+                TpuMode::Race => Arc::new(RaceBroadcaster::new(vec![("rpc", rpc), ("tpu", tpu)])),
+            }
+        }
+        None => rpc,
+    };
+
     let engine_state = app_state.clone();
     let mut engine = BuyEngine::new(
         rpc.clone(),
@@ -87,6 +252,31 @@ async fn main() -> anyhow::Result<()> {
         cfg.clone(),
         tx_builder,
     );
+    if let Some(ladder) = &cfg.fee_ladder {
+        info!("Fee ladder enabled with {} rungs", ladder.rungs.len());
+        if cfg.broadcast_mode == BroadcastMode::Replicate {
+            warn!("broadcast_mode = replicate sends only the highest-fee fee_ladder rung");
+        }
+    }
+    if let Some(client) = jito_client {
+        engine = engine.with_jito_client(client);
+    }
+    let mut rebroadcaster = None;
+    if let Some(tracker) = confirmations {
+        if let Some(rb_cfg) = &cfg.rebroadcast {
+            info!("Rebroadcasting buys until confirmed or expired: {:?}", rb_cfg);
+            let service = Arc::new(Rebroadcaster::new(prod.clone(), tracker.clone(), rb_cfg.clone()));
+            engine = engine.with_rebroadcaster(service.clone());
+            rebroadcaster = Some(service);
+        }
+        engine = engine.with_confirmation_tracker(tracker);
+    } else if cfg.rebroadcast.is_some() {
+        warn!("rebroadcast needs the [blockhash] service to know when buys expire; sending once");
+    }
+    if let Some(pool) = &wallet_pool {
+        engine = engine.with_wallet_pool(pool.clone());
+    }
+    let sweep_task = engine.spawn_account_sweep();
 
     let sniffer_handle = match cfg.sniffer_mode {
         SnifferMode::Mock => {
@@ -102,42 +292,22 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    let engine_app_state = app_state.clone();
-    let rpc_for_sell: Arc<dyn RpcBroadcaster> = rpc.clone();
-    let nonce_for_sell = nonce_manager.clone();
-    let cfg_for_sell = cfg.clone();
-    let sell_task = tokio::spawn(async move {
-        struct SellHandle {
-            rpc: Arc<dyn RpcBroadcaster>,
-            state: Arc<Mutex<AppState>>,
-            nonce: Arc<NonceManager>,
-            cfg: Config,
-        }
-        impl SellHandle {
-            async fn sell(&self, percent: f64) -> anyhow::Result<()> {
-                let (_tx, rx) = mpsc::channel(1);
-                let engine = BuyEngine::new(
-                    self.rpc.clone(),
-                    self.nonce.clone(),
-                    rx,
-                    self.state.clone(),
-                    self.cfg.clone(),
-                    None, // No transaction builder needed for sell-only handle
-                );
-                engine.sell(percent).await?;
-                Ok(())
+    // Migration transactions from the sniffer's log stream invalidate cached curve state
+    let migration_task = tokio::spawn(async move {
+        while let Some(event) = raw_rx.recv().await {
+            if let Some(tracker) = &migration_tracker {
+                tracker.observe(&event).await;
             }
         }
-        let handle = SellHandle {
-            rpc: rpc_for_sell.clone(),
-            state: engine_app_state.clone(),
-            nonce: nonce_for_sell.clone(),
-            cfg: cfg_for_sell.clone(),
-        };
+    });
+
+    // GUI exits share the engine's builder, wallet pool and pending-buy guard
+    let seller = engine.seller();
+    let sell_task = tokio::spawn(async move {
         while let Some(ev) = gui_rx.recv().await {
             match ev {
                 GuiEvent::SellPercent(p) => {
-                    if let Err(e) = handle.sell(p).await {
+                    if let Err(e) = seller.sell(p).await {
                         error!(percent=p, error=%e, "Sell failed");
                     }
                 }
@@ -160,9 +330,16 @@ async fn main() -> anyhow::Result<()> {
         Duration::from_millis(cfg.gui_update_interval_ms),
     )?;
 
+    if let Some(rebroadcaster) = &rebroadcaster {
+        rebroadcaster.cancel_all();
+    }
     sniffer_handle.abort();
     engine_task.abort();
     sell_task.abort();
+    migration_task.abort();
+    for task in [template_task, blockhash_task, wallet_task, health_task, sweep_task].into_iter().flatten() {
+        task.abort();
+    }
 
     Ok(())
 }
\ No newline at end of file
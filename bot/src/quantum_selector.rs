@@ -37,6 +37,8 @@ use tokio_retry::{
 
 // Import types from crate
 use crate::types::{PremintCandidate, QuantumCandidateGui};
+use crate::rate_limit::RpcLane;
+use crate::rpc_pool::{rpc_pool, EndpointRole};
 
 // 1. Struktury danych
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,12 +54,12 @@ pub struct ScoredCandidate {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OracleConfig {
     pub weights: FeatureWeights,
+    /// Used only while the shared RPC pool has no endpoints (it serves archival ones otherwise)
     pub rpc_endpoints: Vec<String>,
     pub pump_fun_api_key: Option<String>,
     pub bitquery_api_key: Option<String>,
     pub thresholds: ScoreThresholds,
     pub rpc_retry_attempts: usize,
-    pub rpc_timeout_seconds: u64,
     pub cache_ttl_seconds: u64,
     pub max_parallel_requests: usize,
     pub rate_limit_requests_per_second: u32,
@@ -214,18 +216,15 @@ impl PredictiveOracle {
         scored_sender: mpsc::Sender<ScoredCandidate>,
         config: OracleConfig,
     ) -> Result<Self> {
-        // Validate that rpc_endpoints is not empty and convert to NonEmpty
-        let rpc_endpoints_nonempty = NonEmpty::from_vec(config.rpc_endpoints.clone())
+        // Holder and history analysis: archival endpoints, never ahead of sends
+        let archival = match rpc_pool().endpoints(EndpointRole::Archival) {
+            endpoints if endpoints.is_empty() => config.rpc_endpoints.clone(),
+            endpoints => endpoints,
+        };
+        let rpc_endpoints_nonempty = NonEmpty::from_vec(archival)
             .ok_or_else(|| anyhow::anyhow!("rpc_endpoints cannot be empty"))?;
-            
         let rpc_clients = rpc_endpoints_nonempty
-            .map(|endpoint| {
-                let client = RpcClient::new_with_timeout(
-                    endpoint,
-                    Duration::from_secs(config.rpc_timeout_seconds)
-                );
-                Arc::new(client)
-            });
+            .map(|endpoint| rpc_pool().client(&endpoint, EndpointRole::Archival, RpcLane::Background));
         
         let quota = Quota::per_second(NonZeroU32::new(config.rate_limit_requests_per_second)
             .unwrap_or(NonZeroU32::new(10).unwrap()));
@@ -438,7 +437,6 @@ impl Default for OracleConfig {
             bitquery_api_key: None,
             thresholds: ScoreThresholds::default(),
             rpc_retry_attempts: 3,
-            rpc_timeout_seconds: 10,
             cache_ttl_seconds: 300,
             max_parallel_requests: 10,
             rate_limit_requests_per_second: 20,
@@ -0,0 +1,240 @@
+//! Per-endpoint request budgets shared by every RPC user.
+//!
+//! Providers count requests per endpoint (and API key), not per component, so the broadcaster,
+//! the transaction builder, the sniffer's HTTP polling and metadata fetches and the quantum
+//! selector all draw from one token bucket per endpoint. Clients from the `rpc_pool` wait for
+//! a token before every request. Send-lane clients draw from the endpoint bucket only;
+//! background clients must also pass a smaller bucket, so the `send_reserved_percent` share
+//! of every endpoint's quota is always left for buys and sells.
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, RwLock};
+
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::metrics;
+
+/// Which share of an endpoint's quota a client may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RpcLane {
+    /// Transaction building and sending: the whole quota
+    Send,
+    /// Polling, metadata, scoring and maintenance: the quota minus the send reserve
+    Background,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EndpointQuota {
+    pub requests_per_second: u32,
+    /// Requests allowed at once after a quiet period (unset = one second's worth)
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Quota of every endpoint without an override
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: u32,
+    #[serde(default)]
+    pub burst: Option<u32>,
+    /// Share of each quota background clients can never use
+    #[serde(default = "default_send_reserved_percent")]
+    pub send_reserved_percent: u32,
+    /// Per-endpoint quotas keyed by URL
+    #[serde(default)]
+    pub endpoints: HashMap<String, EndpointQuota>,
+}
+
+fn default_requests_per_second() -> u32 {
+    40
+}
+
+fn default_send_reserved_percent() -> u32 {
+    25
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: default_requests_per_second(),
+            burst: None,
+            send_reserved_percent: default_send_reserved_percent(),
+            endpoints: HashMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.send_reserved_percent >= 100 {
+            return Err("rate_limits.send_reserved_percent must be below 100".to_string());
+        }
+        let quotas = std::iter::once((self.requests_per_second, self.burst))
+            .chain(self.endpoints.values().map(|q| (q.requests_per_second, q.burst)));
+        for (requests_per_second, burst) in quotas {
+            if requests_per_second == 0 || burst == Some(0) {
+                return Err("rate_limits: requests_per_second and burst must be > 0".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn quota_for(&self, endpoint: &str) -> EndpointQuota {
+        self.endpoints
+            .iter()
+            .find(|(url, _)| endpoint_key(url) == endpoint)
+            .map(|(_, quota)| *quota)
+            .unwrap_or(EndpointQuota {
+                requests_per_second: self.requests_per_second,
+                burst: self.burst,
+            })
+    }
+}
+
+fn endpoint_key(url: &str) -> &str {
+    url.trim_end_matches('/')
+}
+
+/// `rate` per second scaled to `percent` (at least 1).
+fn scaled(rate: u32, percent: u32) -> NonZeroU32 {
+    NonZeroU32::new((rate as u64 * percent as u64 / 100) as u32).unwrap_or(NonZeroU32::MIN)
+}
+
+struct EndpointLimiter {
+    total: DefaultDirectRateLimiter,
+    background: DefaultDirectRateLimiter,
+}
+
+impl EndpointLimiter {
+    fn new(quota: EndpointQuota, send_reserved_percent: u32) -> Self {
+        let rate = quota.requests_per_second;
+        let burst = quota.burst.unwrap_or(rate);
+        let background_percent = 100 - send_reserved_percent;
+        Self {
+            total: RateLimiter::direct(Quota::per_second(scaled(rate, 100)).allow_burst(scaled(burst, 100))),
+            background: RateLimiter::direct(
+                Quota::per_second(scaled(rate, background_percent)).allow_burst(scaled(burst, background_percent)),
+            ),
+        }
+    }
+}
+
+/// Token buckets of every endpoint, created on first use. Unconfigured, requests pass at once.
+#[derive(Default)]
+pub struct RateLimitRegistry {
+    config: RwLock<Option<RateLimitConfig>>,
+    limiters: Mutex<HashMap<String, Arc<EndpointLimiter>>>,
+}
+
+impl RateLimitRegistry {
+    pub fn new(config: Option<RateLimitConfig>) -> Self {
+        Self {
+            config: RwLock::new(config),
+            limiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the quotas; buckets restart full.
+    pub fn configure(&self, config: Option<RateLimitConfig>) {
+        *self.config.write().unwrap() = config;
+        self.limiters.lock().unwrap().clear();
+    }
+
+    fn limiter(&self, endpoint: &str) -> Option<Arc<EndpointLimiter>> {
+        let config = self.config.read().unwrap();
+        let config = config.as_ref()?;
+        let mut limiters = self.limiters.lock().unwrap();
+        let limiter = limiters.entry(endpoint.to_string()).or_insert_with(|| {
+            Arc::new(EndpointLimiter::new(config.quota_for(endpoint), config.send_reserved_percent))
+        });
+        Some(limiter.clone())
+    }
+
+    /// Wait until `endpoint` may take one more request on `lane`.
+    pub async fn acquire(&self, endpoint: &str, lane: RpcLane) {
+        let Some(limiter) = self.limiter(endpoint_key(endpoint)) else {
+            return;
+        };
+        let mut waited = false;
+        if lane == RpcLane::Background && limiter.background.check().is_err() {
+            waited = true;
+            limiter.background.until_ready().await;
+        }
+        if limiter.total.check().is_err() {
+            waited = true;
+            limiter.total.until_ready().await;
+        }
+        if waited {
+            metrics().increment_counter(match lane {
+                RpcLane::Send => "rpc_rate_limit_waits_send",
+                RpcLane::Background => "rpc_rate_limit_waits_background",
+            });
+        }
+    }
+}
+
+/// Global rate limit registry instance
+static GLOBAL_RATE_LIMITS: std::sync::OnceLock<RateLimitRegistry> = std::sync::OnceLock::new();
+
+/// Get global rate limit registry
+pub fn rate_limits() -> &'static RateLimitRegistry {
+    GLOBAL_RATE_LIMITS.get_or_init(RateLimitRegistry::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn acquired_at_once(registry: &RateLimitRegistry, endpoint: &str, lane: RpcLane) -> bool {
+        tokio::time::timeout(Duration::from_millis(50), registry.acquire(endpoint, lane))
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn background_lane_leaves_the_reserve_to_sends() {
+        let config = RateLimitConfig {
+            requests_per_second: 2,
+            send_reserved_percent: 50,
+            ..RateLimitConfig::default()
+        };
+        let registry = RateLimitRegistry::new(Some(config));
+        let endpoint = "http://rpc.test";
+
+        assert!(acquired_at_once(&registry, endpoint, RpcLane::Background).await);
+        assert!(!acquired_at_once(&registry, endpoint, RpcLane::Background).await);
+        assert!(acquired_at_once(&registry, endpoint, RpcLane::Send).await);
+        assert!(!acquired_at_once(&registry, endpoint, RpcLane::Send).await);
+
+        // Quotas are per endpoint
+        assert!(acquired_at_once(&registry, "http://other.test/", RpcLane::Background).await);
+    }
+
+    #[tokio::test]
+    async fn overrides_and_unconfigured_registry() {
+        let mut config = RateLimitConfig {
+            requests_per_second: 1,
+            ..RateLimitConfig::default()
+        };
+        config.endpoints.insert(
+            "http://paid.test/".to_string(),
+            EndpointQuota { requests_per_second: 100, burst: None },
+        );
+        assert!(config.validate().is_ok());
+        let registry = RateLimitRegistry::new(Some(config));
+        for _ in 0..10 {
+            assert!(acquired_at_once(&registry, "http://paid.test", RpcLane::Send).await);
+        }
+        assert!(acquired_at_once(&registry, "http://free.test", RpcLane::Send).await);
+        assert!(!acquired_at_once(&registry, "http://free.test", RpcLane::Send).await);
+
+        registry.configure(None);
+        for _ in 0..10 {
+            assert!(acquired_at_once(&registry, "http://free.test", RpcLane::Send).await);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Rebroadcast loop for sent transactions.
+//!
+//! A single `sendTransaction` often does not land under congestion. While the
+//! `ConfirmationTracker` waits for a transaction, the rebroadcaster resends the same signed
+//! transaction to the ranked endpoints every `interval_ms`. The loop ends when the tracker
+//! reports the transaction confirmed or failed, when its `lastValidBlockHeight` passes, or when
+//! it is cancelled. Resends are counted as `rebroadcast_sends` / `rebroadcast_duplicates` and
+//! loop outcomes as `rebroadcast_{confirmed,failed,expired,cancelled}`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use solana_sdk::transaction::VersionedTransaction;
+use tokio::{sync::Notify, time::Instant};
+use tracing::{debug, info};
+
+use crate::confirmation::{ConfirmationOutcome, ConfirmationTracker};
+use crate::metrics::metrics;
+use crate::rpc_manager::RpcManager;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebroadcastConfig {
+    /// Delay between resends of the same transaction
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+}
+
+fn default_interval_ms() -> u64 {
+    400
+}
+
+impl Default for RebroadcastConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_interval_ms(),
+        }
+    }
+}
+
+impl RebroadcastConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_ms == 0 {
+            return Err("rebroadcast.interval_ms must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub struct Rebroadcaster {
+    rpc: Arc<RpcManager>,
+    confirmations: Arc<ConfirmationTracker>,
+    interval: Duration,
+    /// Wakes every running loop on `cancel_all`
+    cancel: Notify,
+}
+
+impl std::fmt::Debug for Rebroadcaster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rebroadcaster")
+            .field("endpoints", &self.rpc.endpoints)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl Rebroadcaster {
+    pub fn new(rpc: Arc<RpcManager>, confirmations: Arc<ConfirmationTracker>, config: RebroadcastConfig) -> Self {
+        Self {
+            rpc,
+            confirmations,
+            interval: Duration::from_millis(config.interval_ms),
+            cancel: Notify::new(),
+        }
+    }
+
+    /// Stop every running loop; they return `None`.
+    pub fn cancel_all(&self) {
+        self.cancel.notify_waiters();
+    }
+
+    /// Resend `tx` (already broadcast once) until it confirms, fails or expires. `None` when
+    /// cancelled; dropping the future also stops the loop.
+    pub async fn run(
+        &self,
+        tx: &VersionedTransaction,
+        last_valid_block_height: u64,
+    ) -> Result<Option<ConfirmationOutcome>> {
+        let signature = tx.signatures[0];
+        let cancelled = self.cancel.notified();
+        let resend = async {
+            let mut interval = tokio::time::interval_at(Instant::now() + self.interval, self.interval);
+            loop {
+                interval.tick().await;
+                let round = self.rpc.resend(tx).await;
+                metrics().add_to_counter("rebroadcast_sends", round.sent as u64);
+                metrics().add_to_counter("rebroadcast_duplicates", round.duplicates as u64);
+                debug!(%signature, ?round, "Rebroadcast round");
+            }
+        };
+
+        let outcome = tokio::select! {
+            outcome = self.confirmations.wait(&signature, last_valid_block_height) => outcome?,
+            _ = resend => unreachable!("the resend loop never ends"),
+            _ = cancelled => {
+                metrics().increment_counter("rebroadcast_cancelled");
+                info!(%signature, "Rebroadcast cancelled");
+                return Ok(None);
+            }
+        };
+        metrics().increment_counter(match outcome {
+            ConfirmationOutcome::Confirmed { .. } => "rebroadcast_confirmed",
+            ConfirmationOutcome::Failed(_) => "rebroadcast_failed",
+            ConfirmationOutcome::Expired { .. } => "rebroadcast_expired",
+        });
+        Ok(Some(outcome))
+    }
+}
@@ -6,19 +6,33 @@ use solana_client::{
     rpc_request::RpcError,
 };
 use solana_sdk::{
-    commitment_config::{CommitmentConfig, CommitmentLevel},
+    commitment_config::CommitmentLevel,
     signature::Signature,
-    transaction::VersionedTransaction,
+    transaction::{TransactionError, VersionedTransaction},
 };
 
-use std::{collections::HashMap, future::Future, sync::Arc, time::Instant};
+use std::{
+    collections::HashSet,
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 use std::pin::Pin;
 use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
 use tokio::{sync::RwLock, task::JoinSet, time::timeout};
 use tracing::{debug, info, warn};
 
-use crate::config::Config;
+use crate::config::{BroadcastMode, Config};
+use crate::endpoints::endpoint_server;
+use crate::fee_estimator::priority_fee_of;
+use crate::metrics::metrics;
+use crate::rate_limit::RpcLane;
+use crate::rpc_pool::{rpc_pool, EndpointRole};
 use crate::observability::CorrelationId;
 
 /// Classification of RPC errors for handling logic
@@ -28,16 +42,31 @@ pub enum RpcErrorType {
     DuplicateSignature,
     BlockhashNotFound,
     RateLimited,
+    InsufficientFunds,
+    /// The transaction itself is unacceptable (fails on every endpoint)
+    InvalidTransaction(String),
     Other(String),
 }
 
 /// Classify a ClientError into an RpcErrorType for consistent handling
 pub fn classify_rpc_error(error: &ClientError) -> RpcErrorType {
+    // Preflight failures carry the simulated transaction error
+    match error.kind().get_transaction_error() {
+        Some(TransactionError::AlreadyProcessed) => return RpcErrorType::AlreadyProcessed,
+        Some(TransactionError::BlockhashNotFound) => return RpcErrorType::BlockhashNotFound,
+        // A fee payer without lamports is reported as an unknown account
+        Some(
+            TransactionError::AccountNotFound
+            | TransactionError::InsufficientFundsForFee
+            | TransactionError::InsufficientFundsForRent { .. },
+        ) => return RpcErrorType::InsufficientFunds,
+        _ => {}
+    }
     match error.kind() {
         ClientErrorKind::RpcError(rpc_error) => match rpc_error {
             RpcError::RpcResponseError { message, .. } => {
                 let msg = message.to_lowercase();
-                if msg.contains("already processed") {
+                if msg.contains("already processed") || msg.contains("already been processed") {
                     RpcErrorType::AlreadyProcessed
                 } else if msg.contains("duplicate signature") {
                     RpcErrorType::DuplicateSignature
@@ -45,23 +74,245 @@ pub fn classify_rpc_error(error: &ClientError) -> RpcErrorType {
                     RpcErrorType::BlockhashNotFound
                 } else if msg.contains("rate limit") || msg.contains("too many requests") {
                     RpcErrorType::RateLimited
+                } else if msg.contains("insufficient funds") || msg.contains("insufficient lamports") {
+                    RpcErrorType::InsufficientFunds
+                } else if msg.contains("account not found")
+                    || msg.contains("invalid signature")
+                    || msg.contains("transaction too large")
+                {
+                    RpcErrorType::InvalidTransaction(message.clone())
                 } else {
                     RpcErrorType::Other(message.clone())
                 }
             }
             _ => RpcErrorType::Other("Unknown RPC error".to_string()),
         },
+        // HTTP 429 surfaces as a transport error
+        ClientErrorKind::Reqwest(e) if e.status().is_some_and(|s| s.as_u16() == 429) => RpcErrorType::RateLimited,
         _ => RpcErrorType::Other(error.to_string()),
     }
 }
 
+/// What the broadcaster does with a send that failed with a given error class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryAction {
+    /// The transaction is on its way: report its signature
+    Success,
+    /// Report `BroadcastError::BlockhashNotFound` so the caller rebuilds with a fresh
+    /// blockhash and resends (unless another send succeeded)
+    RefreshBlockhash,
+    /// Keep this endpoint out of broadcasts for a growing backoff
+    BackoffEndpoint,
+    /// Cancel every other send of the broadcast and fail it
+    Abort,
+    /// Transaction-level refusal: no penalty for the endpoint, counts toward `early_cancel_threshold`
+    Reject,
+    /// Endpoint failure: counts toward its circuit breaker
+    Fail,
+}
+
+/// Retry policy keyed on `RpcErrorType` (`[retry_policy]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicyConfig {
+    #[serde(default = "RetryPolicyConfig::success")]
+    pub already_processed: RetryAction,
+    #[serde(default = "RetryPolicyConfig::success")]
+    pub duplicate_signature: RetryAction,
+    #[serde(default = "RetryPolicyConfig::refresh_blockhash")]
+    pub blockhash_not_found: RetryAction,
+    #[serde(default = "RetryPolicyConfig::backoff_endpoint")]
+    pub rate_limited: RetryAction,
+    #[serde(default = "RetryPolicyConfig::abort")]
+    pub insufficient_funds: RetryAction,
+    #[serde(default = "RetryPolicyConfig::reject")]
+    pub invalid_transaction: RetryAction,
+    #[serde(default = "RetryPolicyConfig::fail")]
+    pub other: RetryAction,
+    /// First backoff of a rate-limited endpoint; doubles per consecutive rate limit
+    #[serde(default = "default_rate_limit_backoff_ms")]
+    pub rate_limit_backoff_ms: u64,
+    #[serde(default = "default_max_rate_limit_backoff_ms")]
+    pub max_rate_limit_backoff_ms: u64,
+    /// Rebuilds with a fresh blockhash per buy or sell
+    #[serde(default = "default_max_blockhash_refreshes")]
+    pub max_blockhash_refreshes: u32,
+    /// Let endpoints simulate each send before forwarding it. Off, sends are accepted blind:
+    /// faster, but a stale blockhash, missing funds or an already processed transaction only
+    /// shows up at confirmation and the actions above never see those classes.
+    #[serde(default)]
+    pub preflight: bool,
+}
+
+fn default_rate_limit_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_rate_limit_backoff_ms() -> u64 {
+    10_000
+}
+
+fn default_max_blockhash_refreshes() -> u32 {
+    2
+}
+
+impl RetryPolicyConfig {
+    fn success() -> RetryAction {
+        RetryAction::Success
+    }
+
+    fn refresh_blockhash() -> RetryAction {
+        RetryAction::RefreshBlockhash
+    }
+
+    fn backoff_endpoint() -> RetryAction {
+        RetryAction::BackoffEndpoint
+    }
+
+    fn abort() -> RetryAction {
+        RetryAction::Abort
+    }
+
+    fn reject() -> RetryAction {
+        RetryAction::Reject
+    }
+
+    fn fail() -> RetryAction {
+        RetryAction::Fail
+    }
+
+    pub fn action_for(&self, error: &RpcErrorType) -> RetryAction {
+        match error {
+            RpcErrorType::AlreadyProcessed => self.already_processed,
+            RpcErrorType::DuplicateSignature => self.duplicate_signature,
+            RpcErrorType::BlockhashNotFound => self.blockhash_not_found,
+            RpcErrorType::RateLimited => self.rate_limited,
+            RpcErrorType::InsufficientFunds => self.insufficient_funds,
+            RpcErrorType::InvalidTransaction(_) => self.invalid_transaction,
+            RpcErrorType::Other(_) => self.other,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rate_limit_backoff_ms == 0 || self.max_rate_limit_backoff_ms < self.rate_limit_backoff_ms {
+            return Err("retry_policy: need 0 < rate_limit_backoff_ms <= max_rate_limit_backoff_ms".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for RetryPolicyConfig {
+    fn default() -> Self {
+        Self {
+            already_processed: RetryAction::Success,
+            duplicate_signature: RetryAction::Success,
+            blockhash_not_found: RetryAction::RefreshBlockhash,
+            rate_limited: RetryAction::BackoffEndpoint,
+            insufficient_funds: RetryAction::Abort,
+            invalid_transaction: RetryAction::Reject,
+            other: RetryAction::Fail,
+            rate_limit_backoff_ms: default_rate_limit_backoff_ms(),
+            max_rate_limit_backoff_ms: default_max_rate_limit_backoff_ms(),
+            max_blockhash_refreshes: default_max_blockhash_refreshes(),
+            preflight: false,
+        }
+    }
+}
+
+/// Broadcast failures callers act on; other failures are plain `anyhow` errors.
+#[derive(Debug, thiserror::Error)]
+pub enum BroadcastError {
+    /// Every endpoint that answered rejected the blockhash: rebuild and resend
+    #[error("blockhash {0} not found by any endpoint")]
+    BlockhashNotFound(solana_sdk::hash::Hash),
+    #[error("broadcast aborted: {0}")]
+    Aborted(String),
+}
+
+/// Endpoint ranking and circuit breaker tuning (`[endpoint_ranking]`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointRankingConfig {
+    /// Weight of the newest sample in the latency and success averages (0-1]
+    #[serde(default = "default_ewma_alpha")]
+    pub ewma_alpha: f64,
+    /// Consecutive failures that open an endpoint's breaker
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How long an open breaker keeps the endpoint out before a half-open trial
+    #[serde(default = "default_open_ms")]
+    pub open_ms: u64,
+}
+
+fn default_ewma_alpha() -> f64 {
+    0.3
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn default_open_ms() -> u64 {
+    30_000
+}
+
+impl Default for EndpointRankingConfig {
+    fn default() -> Self {
+        Self {
+            ewma_alpha: default_ewma_alpha(),
+            failure_threshold: default_failure_threshold(),
+            open_ms: default_open_ms(),
+        }
+    }
+}
+
+impl EndpointRankingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.ewma_alpha > 0.0 && self.ewma_alpha <= 1.0) {
+            return Err("endpoint_ranking.ewma_alpha must be in (0, 1]".to_string());
+        }
+        if self.failure_threshold == 0 {
+            return Err("endpoint_ranking.failure_threshold must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Circuit breaker state of one endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    /// Excluded from broadcasts until the timer runs out
+    Open,
+    /// Timer ran out: the next send decides between Closed and Open
+    HalfOpen,
+}
+
+impl BreakerState {
+    /// Gauge encoding: 0 closed, 1 half-open, 2 open
+    fn gauge(self) -> u64 {
+        match self {
+            BreakerState::Closed => 0,
+            BreakerState::HalfOpen => 1,
+            BreakerState::Open => 2,
+        }
+    }
+}
+
 /// Endpoint performance metrics for adaptive ranking
 #[derive(Debug, Clone)]
 struct EndpointMetrics {
     success_count: u64,
     error_count: u64,
-    total_latency_ms: u64,
+    /// Moving averages over recent sends; latency is unset until the first success
+    recent_latency_ms: Option<f64>,
+    recent_success: f64,
     last_success: Option<Instant>,
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// Rate-limit backoff (`retry_policy.rate_limited = "backoff_endpoint"`)
+    consecutive_rate_limits: u32,
+    backoff_until: Option<Instant>,
 }
 
 impl EndpointMetrics {
@@ -69,39 +320,114 @@ impl EndpointMetrics {
         Self {
             success_count: 0,
             error_count: 0,
-            total_latency_ms: 0,
+            recent_latency_ms: None,
+            recent_success: 1.0, // Assume good until proven otherwise
             last_success: None,
+            consecutive_failures: 0,
+            open_until: None,
+            consecutive_rate_limits: 0,
+            backoff_until: None,
         }
     }
 
     fn success_rate(&self) -> f64 {
         let total = self.success_count + self.error_count;
         if total == 0 {
-            1.0 // Assume good until proven otherwise
+            1.0
         } else {
             self.success_count as f64 / total as f64
         }
     }
 
-    fn avg_latency_ms(&self) -> f64 {
-        if self.success_count == 0 {
-            1000.0 // Default to 1s estimate
-        } else {
-            self.total_latency_ms as f64 / self.success_count as f64
+    /// Higher is better: recent success rate discounted by recent latency (100ms halves it).
+    /// Unmeasured endpoints score as if instant so each one gets sampled.
+    fn score(&self) -> f64 {
+        let latency_ms = self.recent_latency_ms.unwrap_or(0.0);
+        self.recent_success * 100.0 / (1.0 + latency_ms / 100.0)
+    }
+
+    fn breaker(&self, now: Instant) -> BreakerState {
+        match self.open_until {
+            Some(until) if now < until => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+            None => BreakerState::Closed,
         }
     }
 
-    fn record_success(&mut self, latency_ms: u64) {
+    fn record_success(&mut self, latency_ms: u64, alpha: f64) {
         self.success_count += 1;
-        self.total_latency_ms += latency_ms;
+        self.recent_success += alpha * (1.0 - self.recent_success);
+        self.recent_latency_ms = Some(match self.recent_latency_ms {
+            Some(avg) => avg + alpha * (latency_ms as f64 - avg),
+            None => latency_ms as f64,
+        });
         self.last_success = Some(Instant::now());
+        self.consecutive_failures = 0;
+        self.open_until = None;
+        self.consecutive_rate_limits = 0;
+        self.backoff_until = None;
     }
 
-    fn record_error(&mut self) {
+    fn backing_off(&self, now: Instant) -> bool {
+        matches!(self.backoff_until, Some(until) if now < until)
+    }
+
+    /// Back off after a rate limit: `rate_limit_backoff_ms`, doubling with every consecutive
+    /// one up to `max_rate_limit_backoff_ms`. Returns the backoff.
+    fn record_rate_limit(&mut self, policy: &RetryPolicyConfig) -> Duration {
+        let backoff_ms = policy
+            .rate_limit_backoff_ms
+            .saturating_mul(1u64 << self.consecutive_rate_limits.min(20))
+            .min(policy.max_rate_limit_backoff_ms);
+        self.consecutive_rate_limits += 1;
+        let backoff = Duration::from_millis(backoff_ms);
+        self.backoff_until = Some(Instant::now() + backoff);
+        backoff
+    }
+
+    /// Count a failure; returns true when it (re)opens the breaker.
+    fn record_error(&mut self, config: &EndpointRankingConfig) -> bool {
+        let now = Instant::now();
+        let half_open = self.breaker(now) == BreakerState::HalfOpen;
         self.error_count += 1;
+        self.recent_success -= config.ewma_alpha * self.recent_success;
+        self.consecutive_failures += 1;
+        if half_open || self.consecutive_failures >= config.failure_threshold {
+            self.open_until = Some(now + Duration::from_millis(config.open_ms));
+            return true;
+        }
+        false
     }
 }
 
+/// Ranking and breaker state of one endpoint, as reported on `/status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    pub endpoint: String,
+    /// Position in the broadcast order (0 = preferred)
+    pub rank: usize,
+    pub score: f64,
+    pub breaker: BreakerState,
+    pub success_rate: f64,
+    pub recent_latency_ms: Option<f64>,
+    pub consecutive_failures: u32,
+    pub last_success_secs_ago: Option<u64>,
+    /// Time left on a rate-limit backoff
+    pub backoff_remaining_ms: Option<u64>,
+    /// Demoted by the health checker for lagging or failing its probes
+    pub demoted: bool,
+}
+
+/// Replies to one `RpcManager::resend` round.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResendRound {
+    /// Endpoints the transaction went to
+    pub sent: usize,
+    pub accepted: usize,
+    /// Endpoints that already had it (already processed or duplicate signature)
+    pub duplicates: usize,
+}
+
 /// Trait for broadcasting transactions. Allows injecting mock implementations for tests.
 pub trait RpcBroadcaster: Send + Sync + std::fmt::Debug {
     /// Broadcast the prepared VersionedTransaction objects; return first successful Signature or Err.
@@ -113,30 +439,40 @@ pub trait RpcBroadcaster: Send + Sync + std::fmt::Debug {
 }
 
 
-/// Production RpcManager that broadcasts to multiple HTTP RPC endpoints with connection pooling.
+/// Production RpcManager that broadcasts to multiple HTTP RPC endpoints through the shared `rpc_pool`.
 pub struct RpcManager {
     pub endpoints: Vec<String>,
-    // Connection pool to avoid recreating clients on every request
-    client_pool: Arc<RwLock<HashMap<String, Arc<RpcClient>>>>,
     // Configuration for RPC operations
     config: Config,
+    // First endpoint of the next round-robin broadcast
+    next_endpoint: Arc<AtomicUsize>,
+    // Per-endpoint send statistics and breakers, indexed like `endpoints`
+    health: Arc<RwLock<Vec<EndpointMetrics>>>,
+    ranking: EndpointRankingConfig,
+    retry_policy: RetryPolicyConfig,
 }
 
 impl std::fmt::Debug for RpcManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RpcManager")
             .field("endpoints", &self.endpoints)
-            .field("client_pool_size", &"<pool>")
+            .field("broadcast_mode", &self.config.broadcast_mode)
             .finish()
     }
 }
 
 impl RpcManager {
     pub fn new(endpoints: Vec<String>, config: Config) -> Self {
-        Self { 
+        let health = vec![EndpointMetrics::new(); endpoints.len()];
+        let ranking = config.endpoint_ranking.clone().unwrap_or_default();
+        let retry_policy = config.retry_policy.clone().unwrap_or_default();
+        Self {
             endpoints,
-            client_pool: Arc::new(RwLock::new(HashMap::new())),
             config,
+            next_endpoint: Arc::new(AtomicUsize::new(0)),
+            health: Arc::new(RwLock::new(health)),
+            ranking,
+            retry_policy,
         }
     }
 
@@ -144,35 +480,164 @@ impl RpcManager {
         Self::new(endpoints, config)
     }
 
-    async fn get_or_create_client(&self, endpoint: &str, commitment: CommitmentConfig) -> Arc<RpcClient> {
-        // Try to get existing client first
-        {
-            let pool = self.client_pool.read().await;
-            if let Some(client) = pool.get(endpoint) {
-                return client.clone();
+    /// Pooled send client of `endpoint`.
+    fn client(&self, endpoint: &str) -> Arc<RpcClient> {
+        rpc_pool().client(endpoint, EndpointRole::Send, RpcLane::Send)
+    }
+
+    /// Endpoint indexes ordered by score, best first. Endpoints with an open breaker, a
+    /// rate-limit backoff or a health-check demotion are left out unless that leaves none.
+    pub async fn get_ranked_endpoints(&self) -> Vec<usize> {
+        let health = self.health.read().await;
+        let now = Instant::now();
+        let mut ranked: Vec<usize> = (0..self.endpoints.len()).collect();
+        ranked.sort_by(|&a, &b| health[b].score().total_cmp(&health[a].score()));
+        let available: Vec<usize> = ranked
+            .iter()
+            .copied()
+            .filter(|&i| {
+                health[i].breaker(now) != BreakerState::Open
+                    && !health[i].backing_off(now)
+                    && !rpc_pool().is_demoted(&self.endpoints[i])
+            })
+            .collect();
+        if available.is_empty() {
+            ranked
+        } else {
+            available
+        }
+    }
+
+    /// Ranking and breaker state of every endpoint, in broadcast order (unavailable ones last).
+    pub async fn endpoint_statuses(&self) -> Vec<EndpointStatus> {
+        let ranked = self.get_ranked_endpoints().await;
+        let health = self.health.read().await;
+        let now = Instant::now();
+        let mut order = ranked.clone();
+        order.extend((0..self.endpoints.len()).filter(|i| !ranked.contains(i)));
+        order
+            .into_iter()
+            .enumerate()
+            .map(|(rank, i)| EndpointStatus {
+                endpoint: self.endpoints[i].clone(),
+                rank,
+                score: health[i].score(),
+                breaker: health[i].breaker(now),
+                success_rate: health[i].success_rate(),
+                recent_latency_ms: health[i].recent_latency_ms,
+                consecutive_failures: health[i].consecutive_failures,
+                last_success_secs_ago: health[i].last_success.map(|t| t.elapsed().as_secs()),
+                backoff_remaining_ms: health[i]
+                    .backoff_until
+                    .filter(|_| health[i].backing_off(now))
+                    .map(|until| (until - now).as_millis() as u64),
+                demoted: rpc_pool().is_demoted(&self.endpoints[i]),
+            })
+            .collect()
+    }
+
+    /// Push endpoint state to `/status` and per-endpoint gauges (indexed like `endpoints`).
+    async fn publish_endpoint_status(&self) {
+        let statuses = self.endpoint_statuses().await;
+        for status in &statuses {
+            let Some(i) = self.endpoints.iter().position(|e| *e == status.endpoint) else {
+                continue;
+            };
+            metrics().set_gauge(&format!("rpc_endpoint_{}_rank", i), status.rank as u64);
+            metrics().set_gauge(&format!("rpc_endpoint_{}_score", i), status.score.round() as u64);
+            metrics().set_gauge(&format!("rpc_endpoint_{}_breaker", i), status.breaker.gauge());
+            if let Some(latency) = status.recent_latency_ms {
+                metrics().set_gauge(&format!("rpc_endpoint_{}_latency_ms", i), latency.round() as u64);
             }
         }
-        
-        // Create new client if not found
-        let client = Arc::new(RpcClient::new_with_commitment(endpoint.to_string(), commitment));
-        {
-            let mut pool = self.client_pool.write().await;
-            // Double-check pattern in case another task created it
-            if let Some(existing) = pool.get(endpoint) {
-                return existing.clone();
+        endpoint_server().update_rpc_endpoints(statuses).await;
+    }
+
+    /// Send an already broadcast transaction once more to every ranked endpoint. Endpoints
+    /// answering that they already have it count as duplicates; breakers and backoffs are left
+    /// to regular broadcasts.
+    pub async fn resend(&self, tx: &VersionedTransaction) -> ResendRound {
+        let timeout_duration = Duration::from_secs(self.config.rpc_timeout_sec);
+        // The rebroadcast loop is the retry: nodes should not queue their own
+        let send_cfg = RpcSendTransactionConfig {
+            skip_preflight: !self.retry_policy.preflight,
+            preflight_commitment: Some(CommitmentLevel::Confirmed),
+            max_retries: Some(0),
+            ..Default::default()
+        };
+        let ranked = self.get_ranked_endpoints().await;
+        let sends = ranked.iter().map(|&i| {
+            let endpoint = &self.endpoints[i];
+            let client = self.client(endpoint);
+            async move {
+                match timeout(timeout_duration, client.send_transaction_with_config(tx, send_cfg)).await {
+                    Ok(Ok(_)) => Some(false),
+                    Ok(Err(e)) => match classify_rpc_error(&e) {
+                        RpcErrorType::AlreadyProcessed | RpcErrorType::DuplicateSignature => Some(true),
+                        kind => {
+                            debug!(endpoint = %endpoint, error = ?kind, "RpcManager: resend failed");
+                            None
+                        }
+                    },
+                    Err(_elapsed) => None,
+                }
             }
-            pool.insert(endpoint.to_string(), client.clone());
+        });
+        let results = futures::future::join_all(sends).await;
+        ResendRound {
+            sent: results.len(),
+            accepted: results.iter().filter(|r| **r == Some(false)).count(),
+            duplicates: results.iter().filter(|r| **r == Some(true)).count(),
         }
-        client
     }
 
-    /// Check if an error indicates a fatal condition that should trigger early cancellation
-    fn is_fatal_error_type(error_msg: &str) -> bool {
-        // Simple implementation - consider some common fatal errors
-        error_msg.contains("insufficient funds") 
-            || error_msg.contains("account not found")
-            || error_msg.contains("invalid signature")
-            || error_msg.contains("transaction too large")
+    /// `(endpoint index, transaction index)` sends for one broadcast under the configured
+    /// `broadcast_mode`, over `ranked` endpoints. Signed transactions repeated in `txs` are
+    /// only sent once per endpoint. Round-robin rotates over the available endpoints in
+    /// configured order: re-sorting them by score between broadcasts would break the rotation.
+    fn send_plan(&self, txs: &[VersionedTransaction], ranked: &[usize]) -> Vec<(usize, usize)> {
+        let mut ranked = ranked.to_vec();
+        if self.config.broadcast_mode == BroadcastMode::RoundRobin {
+            ranked.sort_unstable();
+        }
+        let endpoints = ranked.len();
+        let mut seen = HashSet::new();
+        let unique: Vec<usize> = (0..txs.len())
+            .filter(|&i| match txs[i].signatures.first() {
+                // Unsigned transactions all carry the default signature; never merge them
+                Some(sig) if *sig != Signature::default() => seen.insert(*sig),
+                _ => true,
+            })
+            .collect();
+        if endpoints == 0 || unique.is_empty() {
+            return Vec::new();
+        }
+
+        let plan: Vec<(usize, usize)> = match self.config.broadcast_mode {
+            BroadcastMode::Pairwise => (0..endpoints.min(unique.len())).map(|i| (i, unique[i])).collect(),
+            BroadcastMode::Replicate => {
+                // Best = highest priority fee; the earliest wins ties
+                let best = unique
+                    .iter()
+                    .copied()
+                    .rev()
+                    .max_by_key(|&i| priority_fee_of(&txs[i]).unwrap_or(0))
+                    .expect("unique is non-empty");
+                (0..endpoints).map(|e| (e, best)).collect()
+            }
+            BroadcastMode::RoundRobin => {
+                let start = self.next_endpoint.fetch_add(unique.len(), Ordering::Relaxed);
+                unique
+                    .iter()
+                    .enumerate()
+                    .map(|(n, &i)| ((start + n) % endpoints, i))
+                    .collect()
+            }
+            BroadcastMode::FullFanout => (0..endpoints)
+                .flat_map(|e| unique.iter().map(move |&i| (e, i)))
+                .collect(),
+        };
+        plan.into_iter().map(|(e, i)| (ranked[e], i)).collect()
     }
 }
 
@@ -180,8 +645,11 @@ impl Clone for RpcManager {
     fn clone(&self) -> Self {
         Self {
             endpoints: self.endpoints.clone(),
-            client_pool: self.client_pool.clone(),
             config: self.config.clone(),
+            next_endpoint: self.next_endpoint.clone(),
+            health: self.health.clone(),
+            ranking: self.ranking.clone(),
+            retry_policy: self.retry_policy.clone(),
         }
     }
 }
@@ -190,7 +658,7 @@ impl RpcBroadcaster for RpcManager {
     fn send_on_many_rpc<'a>(
         &'a self,
         txs: Vec<VersionedTransaction>,
-        _correlation_id: Option<CorrelationId>,
+        correlation_id: Option<CorrelationId>,
     ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
         Box::pin(async move {
             if self.endpoints.is_empty() || txs.is_empty() {
@@ -205,75 +673,155 @@ impl RpcBroadcaster for RpcManager {
             
             // Fix commitment mismatch - use Confirmed consistently
             let send_cfg = RpcSendTransactionConfig {
-                skip_preflight: true,
+                skip_preflight: !self.retry_policy.preflight,
                 preflight_commitment: Some(CommitmentLevel::Confirmed),
                 max_retries: Some(3),
                 ..Default::default()
             };
 
-            let mut set: JoinSet<Result<Signature>> = JoinSet::new();
+            // Each send reports the transaction it carried and the policy's verdict on it
+            let mut set: JoinSet<(usize, RetryAction, Result<Signature>)> = JoinSet::new();
             let mut fatal_errors = 0;
+            let mut completed = 0;
+            let mut blockhash_rejections = 0;
+            let mut rejected_blockhash = None;
 
-            // Simple pairwise broadcast for now (minimal implementation)
-            let num_tasks = std::cmp::min(self.endpoints.len(), txs.len());
-            
-            for i in 0..num_tasks {
+            let ranked = self.get_ranked_endpoints().await;
+            let plan = self.send_plan(&txs, &ranked);
+            debug!(
+                correlation_id = ?correlation_id,
+                "RpcManager: {:?} broadcast of {} txs as {} sends",
+                self.config.broadcast_mode,
+                txs.len(),
+                plan.len()
+            );
+
+            for (i, tx_index) in plan {
                 let endpoint = self.endpoints[i].clone();
-                let tx = txs[i].clone();
-                let client_pool = self.client_pool.clone();
-                let commitment = CommitmentConfig::confirmed();
+                let tx = txs[tx_index].clone();
+                let client = self.client(&endpoint);
+                let health = self.health.clone();
+                let ranking = self.ranking.clone();
+                let policy = self.retry_policy.clone();
+                let correlation_id = correlation_id.clone();
 
                 set.spawn(async move {
-                    // Use the pooled client instead of creating a new one
-                    let rpc_manager = RpcManager {
-                        endpoints: vec![endpoint.clone()],
-                        client_pool,
-                        config: Config::default(), // Use default config for spawned tasks
-                    };
-                    let client = rpc_manager.get_or_create_client(&endpoint, commitment).await;
-                    debug!("RpcManager: sending tx on endpoint[{}]: {}", i, endpoint);
+                    debug!("RpcManager: sending tx[{}] on endpoint[{}]: {}", tx_index, i, endpoint);
 
                     let start_time = Instant::now();
                     let send_fut = client.send_transaction_with_config(&tx, send_cfg);
-                    match timeout(timeout_duration, send_fut).await {
+                    let (action, result) = match timeout(timeout_duration, send_fut).await {
                         Ok(Ok(sig)) => {
-                            let latency_ms = start_time.elapsed().as_millis() as u64;
-                            info!("RpcManager: success on {}: {} ({}ms)", endpoint, sig, latency_ms);
-                            Ok(sig)
+                            info!(
+                                correlation_id = ?correlation_id,
+                                "RpcManager: success on {}: {} ({}ms)",
+                                endpoint,
+                                sig,
+                                start_time.elapsed().as_millis()
+                            );
+                            (RetryAction::Success, Ok(sig))
                         }
                         Ok(Err(e)) => {
-                            let error_msg = e.to_string();
-                            warn!("RpcManager: endpoint {} failed: {}", endpoint, error_msg);
-                            Err(anyhow!(e).context("RPC failed"))
+                            let kind = classify_rpc_error(&e);
+                            let action = policy.action_for(&kind);
+                            info!(
+                                correlation_id = ?correlation_id,
+                                endpoint = %endpoint,
+                                tx = tx_index,
+                                error = ?kind,
+                                action = ?action,
+                                "RpcManager: send failed"
+                            );
+                            match action {
+                                // e.g. another endpoint (or a replica of this send) already delivered it
+                                RetryAction::Success => (action, Ok(tx.signatures[0])),
+                                _ => (action, Err(anyhow!(e).context("RPC failed"))),
+                            }
                         }
                         Err(_elapsed) => {
-                            warn!("RpcManager: endpoint {} timed out after {:?}", endpoint, timeout_duration);
-                            Err(anyhow!("RPC send timeout"))
+                            warn!(
+                                correlation_id = ?correlation_id,
+                                "RpcManager: endpoint {} timed out after {:?}",
+                                endpoint,
+                                timeout_duration
+                            );
+                            (RetryAction::Fail, Err(anyhow!("RPC send timeout")))
+                        }
+                    };
+
+                    let latency_ms = start_time.elapsed().as_millis() as u64;
+                    let mut health = health.write().await;
+                    match action {
+                        RetryAction::Success => health[i].record_success(latency_ms, ranking.ewma_alpha),
+                        RetryAction::BackoffEndpoint => {
+                            let backoff = health[i].record_rate_limit(&policy);
+                            metrics().increment_counter("rpc_endpoint_backoffs");
+                            warn!(
+                                correlation_id = ?correlation_id,
+                                endpoint = %endpoint,
+                                backoff_ms = backoff.as_millis() as u64,
+                                "RpcManager: backing off rate-limited endpoint"
+                            );
                         }
+                        RetryAction::Fail => {
+                            if health[i].record_error(&ranking) {
+                                metrics().increment_counter("rpc_circuit_breaker_opened");
+                                warn!(
+                                    "RpcManager: circuit breaker open for {} after {} consecutive failures",
+                                    endpoint, health[i].consecutive_failures
+                                );
+                            }
+                        }
+                        // The endpoint answered: its refusal says nothing about its health
+                        RetryAction::RefreshBlockhash | RetryAction::Reject | RetryAction::Abort => {}
                     }
+                    (tx_index, action, result)
                 });
             }
 
             // Wait for results with early cancellation
             while let Some(join_res) = set.join_next().await {
+                if join_res.is_ok() {
+                    completed += 1;
+                }
                 match join_res {
-                    Ok(Ok(sig)) => {
-                        set.abort_all();
+                    Ok((_, _, Ok(sig))) => {
+                        if self.config.broadcast_mode == BroadcastMode::Pairwise {
+                            set.abort_all();
+                        } else {
+                            // Redundant sends are the point of the other modes: let them land
+                            set.detach_all();
+                        }
+                        self.publish_endpoint_status().await;
                         return Ok(sig);
                     }
-                    Ok(Err(e)) => {
-                        let error_str = e.to_string();
-                        if Self::is_fatal_error_type(&error_str) {
-                            fatal_errors += 1;
-                            debug!("RpcManager: fatal error count: {}/{}", fatal_errors, self.config.early_cancel_threshold);
-                            
-                            // Early cancellation if too many fatal errors
-                            if fatal_errors >= self.config.early_cancel_threshold {
-                                warn!("RpcManager: cancelling remaining tasks due to {} fatal errors", fatal_errors);
-                                set.abort_all();
-                                break;
-                            }
+                    Ok((_, RetryAction::Abort, Err(e))) => {
+                        warn!(correlation_id = ?correlation_id, error = %e, "RpcManager: aborting broadcast");
+                        set.abort_all();
+                        self.publish_endpoint_status().await;
+                        return Err(BroadcastError::Aborted(format!("{:#}", e)).into());
+                    }
+                    Ok((tx_index, RetryAction::RefreshBlockhash, Err(_))) => {
+                        blockhash_rejections += 1;
+                        rejected_blockhash = Some(*txs[tx_index].message.recent_blockhash());
+                    }
+                    Ok((_, RetryAction::Reject, Err(e))) => {
+                        fatal_errors += 1;
+                        debug!("RpcManager: fatal error count: {}/{}", fatal_errors, self.config.early_cancel_threshold);
+
+                        // Early cancellation if too many fatal errors
+                        if fatal_errors >= self.config.early_cancel_threshold {
+                            warn!(
+                                correlation_id = ?correlation_id,
+                                error = %e,
+                                "RpcManager: cancelling remaining tasks due to {} fatal errors",
+                                fatal_errors
+                            );
+                            set.abort_all();
+                            break;
                         }
+                    }
+                    Ok((_, _, Err(e))) => {
                         debug!("RpcManager: task returned error: {:?}", e);
                     }
                     Err(join_err) => {
@@ -282,10 +830,108 @@ impl RpcBroadcaster for RpcManager {
                 }
             }
 
+            self.publish_endpoint_status().await;
+            // Only a blockhash every answering endpoint rejected is worth a rebuild; otherwise
+            // the other failures decide
+            if let Some(hash) = rejected_blockhash.filter(|_| blockhash_rejections == completed) {
+                info!(correlation_id = ?correlation_id, %hash, "RpcManager: blockhash rejected; rebuild and resend");
+                return Err(BroadcastError::BlockhashNotFound(hash).into());
+            }
             Err(anyhow!(
-                "RpcManager: all sends failed (fatal_errors: {})", 
-                fatal_errors
+                "RpcManager: all sends failed (fatal_errors: {}, blockhash_rejections: {})",
+                fatal_errors,
+                blockhash_rejections
             ))
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_prefers_recent_success_and_low_latency() {
+        let alpha = 0.5;
+        let fresh = EndpointMetrics::new();
+        let mut fast = EndpointMetrics::new();
+        fast.record_success(20, alpha);
+        let mut slow = EndpointMetrics::new();
+        slow.record_success(300, alpha);
+        assert!(fresh.score() > fast.score() && fast.score() > slow.score());
+
+        // Latency is a moving average: one fast send moves it halfway
+        slow.record_success(100, alpha);
+        assert_eq!(slow.recent_latency_ms, Some(200.0));
+
+        let mut flaky = fast.clone();
+        flaky.record_error(&EndpointRankingConfig::default());
+        assert!(flaky.score() < fast.score());
+        assert_eq!(flaky.success_rate(), 0.5);
+    }
+
+    #[test]
+    fn breaker_opens_after_consecutive_failures_and_half_opens_on_timer() {
+        let config = EndpointRankingConfig {
+            failure_threshold: 2,
+            open_ms: 0,
+            ..EndpointRankingConfig::default()
+        };
+        let mut m = EndpointMetrics::new();
+        assert!(!m.record_error(&config));
+        m.record_success(10, config.ewma_alpha);
+        assert!(!m.record_error(&config), "a success resets the streak");
+        assert!(m.record_error(&config));
+        // open_ms = 0: the timer has already run out
+        assert_eq!(m.breaker(Instant::now()), BreakerState::HalfOpen);
+        assert!(m.record_error(&config), "a failed trial reopens at once");
+        m.record_success(10, config.ewma_alpha);
+        assert_eq!(m.breaker(Instant::now()), BreakerState::Closed);
+
+        let long = EndpointRankingConfig { open_ms: 60_000, ..config };
+        m.record_error(&long);
+        m.record_error(&long);
+        assert_eq!(m.breaker(Instant::now()), BreakerState::Open);
+    }
+
+    #[test]
+    fn retry_policy_defaults_and_overrides() {
+        let policy = RetryPolicyConfig::default();
+        assert_eq!(policy.action_for(&RpcErrorType::AlreadyProcessed), RetryAction::Success);
+        assert_eq!(policy.action_for(&RpcErrorType::DuplicateSignature), RetryAction::Success);
+        assert_eq!(policy.action_for(&RpcErrorType::BlockhashNotFound), RetryAction::RefreshBlockhash);
+        assert_eq!(policy.action_for(&RpcErrorType::RateLimited), RetryAction::BackoffEndpoint);
+        assert_eq!(policy.action_for(&RpcErrorType::InsufficientFunds), RetryAction::Abort);
+        assert_eq!(
+            policy.action_for(&RpcErrorType::InvalidTransaction("invalid signature".into())),
+            RetryAction::Reject
+        );
+        assert_eq!(policy.action_for(&RpcErrorType::Other("boom".into())), RetryAction::Fail);
+
+        let custom: RetryPolicyConfig = toml::from_str("rate_limited = \"fail\"\ninsufficient_funds = \"reject\"").unwrap();
+        assert_eq!(custom.action_for(&RpcErrorType::RateLimited), RetryAction::Fail);
+        assert_eq!(custom.action_for(&RpcErrorType::InsufficientFunds), RetryAction::Reject);
+        assert_eq!(custom.action_for(&RpcErrorType::AlreadyProcessed), RetryAction::Success);
+        assert!(custom.validate().is_ok());
+        assert!(RetryPolicyConfig { rate_limit_backoff_ms: 0, ..custom }.validate().is_err());
+    }
+
+    #[test]
+    fn rate_limit_backoff_doubles_and_resets_on_success() {
+        let policy = RetryPolicyConfig {
+            rate_limit_backoff_ms: 100,
+            max_rate_limit_backoff_ms: 300,
+            ..RetryPolicyConfig::default()
+        };
+        let mut m = EndpointMetrics::new();
+        assert_eq!(m.record_rate_limit(&policy), Duration::from_millis(100));
+        assert_eq!(m.record_rate_limit(&policy), Duration::from_millis(200));
+        assert_eq!(m.record_rate_limit(&policy), Duration::from_millis(300));
+        assert!(m.backing_off(Instant::now()));
+        assert_eq!(m.consecutive_failures, 0, "rate limits leave the breaker alone");
+
+        m.record_success(10, 0.3);
+        assert!(!m.backing_off(Instant::now()));
+        assert_eq!(m.record_rate_limit(&policy), Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,300 @@
+//! Shared pool of RPC clients with endpoint roles.
+//!
+//! Every subsystem gets its `RpcClient`s here instead of building its own: one client per
+//! endpoint, role and lane, reused for the life of the process. Roles decide which endpoints
+//! a subsystem talks to (`send` for broadcasts, `read` for state lookups, `archival` for
+//! history) and the per-call timeout; every request goes through the shared rate limits and
+//! is counted as `rpc_<role>_requests` / `rpc_<role>_errors` with an `rpc_<role>_latency`
+//! histogram. Endpoints the health checker demoted go last in every endpoint order.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    client_error::Result as ClientResult,
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::metrics::metrics;
+use crate::rate_limit::{rate_limits, RpcLane};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointRole {
+    /// Transaction broadcasts
+    Send,
+    /// Account, blockhash, fee and recent transaction lookups
+    Read,
+    /// Historical queries beyond what pruned nodes keep
+    Archival,
+}
+
+impl EndpointRole {
+    pub const ALL: [EndpointRole; 3] = [EndpointRole::Send, EndpointRole::Read, EndpointRole::Archival];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            EndpointRole::Send => "send",
+            EndpointRole::Read => "read",
+            EndpointRole::Archival => "archival",
+        }
+    }
+}
+
+/// An endpoint restricted to some roles, or one not in `rpc_endpoints` (e.g. an archival node).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolEndpoint {
+    pub url: String,
+    pub roles: Vec<EndpointRole>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcPoolConfig {
+    /// Role overrides; `rpc_endpoints` not listed here serve every role
+    #[serde(default)]
+    pub endpoints: Vec<PoolEndpoint>,
+    #[serde(default = "default_send_timeout_ms")]
+    pub send_timeout_ms: u64,
+    #[serde(default = "default_read_timeout_ms")]
+    pub read_timeout_ms: u64,
+    #[serde(default = "default_archival_timeout_ms")]
+    pub archival_timeout_ms: u64,
+}
+
+fn default_send_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_read_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_archival_timeout_ms() -> u64 {
+    30_000
+}
+
+impl Default for RpcPoolConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            send_timeout_ms: default_send_timeout_ms(),
+            read_timeout_ms: default_read_timeout_ms(),
+            archival_timeout_ms: default_archival_timeout_ms(),
+        }
+    }
+}
+
+impl RpcPoolConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.send_timeout_ms == 0 || self.read_timeout_ms == 0 || self.archival_timeout_ms == 0 {
+            return Err("rpc_pool timeouts must be > 0".to_string());
+        }
+        if let Some(endpoint) = self.endpoints.iter().find(|e| e.roles.is_empty()) {
+            return Err(format!("rpc_pool endpoint {} has no roles", endpoint.url));
+        }
+        Ok(())
+    }
+
+    fn timeout(&self, role: EndpointRole) -> Duration {
+        Duration::from_millis(match role {
+            EndpointRole::Send => self.send_timeout_ms,
+            EndpointRole::Read => self.read_timeout_ms,
+            EndpointRole::Archival => self.archival_timeout_ms,
+        })
+    }
+}
+
+#[derive(Default)]
+struct PoolState {
+    config: RpcPoolConfig,
+    /// Configured endpoints with their roles, in `rpc_endpoints` order
+    endpoints: Vec<PoolEndpoint>,
+}
+
+/// Pooled clients keyed by endpoint, role and lane. Unconfigured, every URL asked for is
+/// served with default timeouts.
+#[derive(Default)]
+pub struct RpcPool {
+    state: RwLock<PoolState>,
+    clients: Mutex<HashMap<(String, EndpointRole, RpcLane), Arc<RpcClient>>>,
+    /// Unhealthy or lagging endpoints (HTTP or WSS), set by the health checker
+    demoted: RwLock<HashSet<String>>,
+}
+
+impl RpcPool {
+    /// Set the endpoints and their roles; clients built under the old settings are dropped.
+    pub fn configure(&self, rpc_endpoints: &[String], config: RpcPoolConfig) {
+        let mut endpoints: Vec<PoolEndpoint> = rpc_endpoints
+            .iter()
+            .map(|url| PoolEndpoint {
+                url: url.clone(),
+                roles: EndpointRole::ALL.to_vec(),
+            })
+            .collect();
+        for listed in &config.endpoints {
+            match endpoints.iter_mut().find(|e| e.url == listed.url) {
+                Some(endpoint) => endpoint.roles = listed.roles.clone(),
+                None => endpoints.push(listed.clone()),
+            }
+        }
+        *self.state.write().unwrap() = PoolState { config, endpoints };
+        self.clients.lock().unwrap().clear();
+    }
+
+    /// Configured endpoints serving `role` (all of them when none does), demoted ones last.
+    pub fn endpoints(&self, role: EndpointRole) -> Vec<String> {
+        let state = self.state.read().unwrap();
+        let serving: Vec<String> = state
+            .endpoints
+            .iter()
+            .filter(|e| e.roles.contains(&role))
+            .map(|e| e.url.clone())
+            .collect();
+        let urls = if serving.is_empty() {
+            state.endpoints.iter().map(|e| e.url.clone()).collect()
+        } else {
+            serving
+        };
+        self.healthy_first(&urls).into_iter().map(|i| urls[i].clone()).collect()
+    }
+
+    /// Every configured endpoint, whatever its roles, in configuration order.
+    pub fn all_endpoints(&self) -> Vec<String> {
+        self.state.read().unwrap().endpoints.iter().map(|e| e.url.clone()).collect()
+    }
+
+    pub fn set_demoted(&self, url: &str, demoted: bool) {
+        let mut set = self.demoted.write().unwrap();
+        if demoted {
+            set.insert(url.to_string());
+        } else {
+            set.remove(url);
+        }
+    }
+
+    pub fn is_demoted(&self, url: &str) -> bool {
+        self.demoted.read().unwrap().contains(url)
+    }
+
+    /// Indexes of `urls` with demoted endpoints moved to the end, order otherwise kept.
+    pub fn healthy_first(&self, urls: &[String]) -> Vec<usize> {
+        let demoted = self.demoted.read().unwrap();
+        let mut order: Vec<usize> = (0..urls.len()).collect();
+        order.sort_by_key(|&i| demoted.contains(&urls[i]));
+        order
+    }
+
+    /// The shared client for `url` in `role`, drawing on the `lane` share of its quota.
+    pub fn client(&self, url: &str, role: EndpointRole, lane: RpcLane) -> Arc<RpcClient> {
+        let key = (url.to_string(), role, lane);
+        if let Some(client) = self.clients.lock().unwrap().get(&key) {
+            return client.clone();
+        }
+        let timeout = self.state.read().unwrap().config.timeout(role);
+        let client = Arc::new(RpcClient::new_sender(
+            PooledSender {
+                inner: RpcClient::new_with_timeout(url.to_string(), timeout),
+                role,
+                lane,
+            },
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        ));
+        // Another task may have built the same client meanwhile; keep the first
+        self.clients.lock().unwrap().entry(key).or_insert(client).clone()
+    }
+
+    /// Clients of every endpoint serving `role`.
+    pub fn clients(&self, role: EndpointRole, lane: RpcLane) -> Vec<Arc<RpcClient>> {
+        self.endpoints(role)
+            .iter()
+            .map(|url| self.client(url, role, lane))
+            .collect()
+    }
+}
+
+/// Global RPC pool instance
+static GLOBAL_RPC_POOL: std::sync::OnceLock<RpcPool> = std::sync::OnceLock::new();
+
+/// Get global RPC pool
+pub fn rpc_pool() -> &'static RpcPool {
+    GLOBAL_RPC_POOL.get_or_init(RpcPool::default)
+}
+
+/// HTTP transport that waits for the endpoint's quota and records per-role metrics.
+struct PooledSender {
+    inner: RpcClient,
+    role: EndpointRole,
+    lane: RpcLane,
+}
+
+#[async_trait]
+impl RpcSender for PooledSender {
+    async fn send(&self, request: RpcRequest, params: serde_json::Value) -> ClientResult<serde_json::Value> {
+        rate_limits().acquire(&self.inner.url(), self.lane).await;
+        let role = self.role.as_str();
+        let started = Instant::now();
+        let result = self.inner.send(request, params).await;
+        metrics().increment_counter(&format!("rpc_{}_requests", role));
+        metrics().record_histogram(&format!("rpc_{}_latency", role), started.elapsed());
+        if result.is_err() {
+            metrics().increment_counter(&format!("rpc_{}_errors", role));
+        }
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_select_endpoints_and_clients_are_reused() {
+        let pool = RpcPool::default();
+        let config = RpcPoolConfig {
+            endpoints: vec![
+                PoolEndpoint {
+                    url: "http://sender.test".to_string(),
+                    roles: vec![EndpointRole::Send],
+                },
+                PoolEndpoint {
+                    url: "http://archive.test".to_string(),
+                    roles: vec![EndpointRole::Archival],
+                },
+            ],
+            ..RpcPoolConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        pool.configure(&["http://sender.test".to_string(), "http://full.test".to_string()], config);
+
+        assert_eq!(pool.endpoints(EndpointRole::Send), vec!["http://sender.test", "http://full.test"]);
+        assert_eq!(pool.endpoints(EndpointRole::Read), vec!["http://full.test"]);
+        assert_eq!(pool.endpoints(EndpointRole::Archival), vec!["http://full.test", "http://archive.test"]);
+
+        let a = pool.client("http://full.test", EndpointRole::Read, RpcLane::Send);
+        let b = pool.client("http://full.test", EndpointRole::Read, RpcLane::Send);
+        assert!(Arc::ptr_eq(&a, &b));
+        let background = pool.client("http://full.test", EndpointRole::Read, RpcLane::Background);
+        assert!(!Arc::ptr_eq(&a, &background));
+        assert_eq!(pool.clients(EndpointRole::Send, RpcLane::Send).len(), 2);
+
+        pool.set_demoted("http://sender.test", true);
+        assert_eq!(pool.endpoints(EndpointRole::Send), vec!["http://full.test", "http://sender.test"]);
+        pool.set_demoted("http://sender.test", false);
+        assert_eq!(pool.endpoints(EndpointRole::Send)[0], "http://sender.test");
+    }
+}
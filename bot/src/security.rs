@@ -225,6 +225,8 @@ mod tests {
                 .as_secs(),
             instruction_summary: Some("Test instruction".to_string()),
             is_jito_bundle: Some(false),
+            detected_at_ms: 0,
+            token_program: None,
         };
 
         let result = validator.validate_candidate(&valid_candidate);
@@ -238,6 +240,8 @@ mod tests {
             timestamp: 0,
             instruction_summary: None,
             is_jito_bundle: None,
+            detected_at_ms: 0,
+            token_program: None,
         };
 
         let result = validator.validate_candidate(&invalid_candidate);
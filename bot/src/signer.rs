@@ -0,0 +1,354 @@
+//! Transaction signing backends.
+//!
+//! `TransactionSigner` abstracts over where the fee payer's key lives. `WalletManager` signs
+//! in-process; `RemoteSigner` sends the serialized message to an external signing daemon over
+//! a local Unix socket or HTTP so hot keys can be kept out of the bot process. Each request
+//! carries a `SigningPolicy` the daemon enforces before signing.
+//!
+//! Wire format (one request per connection; newline-terminated JSON over Unix sockets,
+//! `POST /sign` over HTTP):
+//!
+//! ```text
+//! -> {"pubkey": "<base58>", "message": "<base64 message bytes>", "policy": {"max_lamports_out": 1000}}
+//! <- {"signature": "<base58>"}   or   {"error": "<reason>"}
+//! ```
+
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::time::timeout;
+use tracing::{debug, warn};
+
+use crate::metrics::metrics;
+use crate::wallet::WalletManager;
+
+/// Limits the signer enforces for one transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPolicy {
+    /// Most lamports the transaction may move out of the signer, excluding network fees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lamports_out: Option<u64>,
+}
+
+impl SigningPolicy {
+    pub fn max_lamports_out(lamports: u64) -> Self {
+        Self {
+            max_lamports_out: Some(lamports),
+        }
+    }
+
+    /// The stricter of this policy and a global `cap`.
+    pub fn capped(self, cap: Option<u64>) -> Self {
+        let max_lamports_out = match (self.max_lamports_out, cap) {
+            (Some(limit), Some(cap)) => Some(limit.min(cap)),
+            (limit, cap) => limit.or(cap),
+        };
+        Self { max_lamports_out }
+    }
+}
+
+#[async_trait]
+pub trait TransactionSigner: Send + Sync + std::fmt::Debug {
+    /// Fee payer public key
+    fn pubkey(&self) -> Pubkey;
+
+    /// Sign `tx` as its fee payer (first signer).
+    async fn sign_transaction(&self, tx: &mut VersionedTransaction, policy: &SigningPolicy) -> Result<()>;
+}
+
+/// In-process keypair. The policy is not enforced locally: the bot builds the transaction
+/// itself, and the policy exists to constrain a signer that does not trust the bot.
+#[async_trait]
+impl TransactionSigner for WalletManager {
+    fn pubkey(&self) -> Pubkey {
+        WalletManager::pubkey(self)
+    }
+
+    async fn sign_transaction(&self, tx: &mut VersionedTransaction, _policy: &SigningPolicy) -> Result<()> {
+        WalletManager::sign_transaction(self, tx)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSignerConfig {
+    /// `unix:///path/to/signer.sock` or `http(s)://host:port`
+    pub endpoint: String,
+    /// Public key of the key held by the daemon
+    pub pubkey: String,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Cap applied to every request; per-transaction limits are tightened to it
+    #[serde(default)]
+    pub max_lamports_out: Option<u64>,
+    /// Environment variable holding a bearer token for the daemon (HTTP only)
+    #[serde(default)]
+    pub auth_token_env: Option<String>,
+}
+
+fn default_timeout_ms() -> u64 {
+    2_000
+}
+
+impl RemoteSignerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.endpoint.starts_with("unix://")
+            || self.endpoint.starts_with("http://")
+            || self.endpoint.starts_with("https://"))
+        {
+            return Err("remote_signer.endpoint must start with unix://, http:// or https://".to_string());
+        }
+        Pubkey::from_str(&self.pubkey).map_err(|e| format!("remote_signer.pubkey: {}", e))?;
+        if self.timeout_ms == 0 {
+            return Err("remote_signer.timeout_ms must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SignRequest<'a> {
+    pubkey: String,
+    message: String,
+    policy: &'a SigningPolicy,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignResponse {
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug)]
+enum Transport {
+    Unix(String),
+    Http { url: String, client: reqwest::Client },
+}
+
+/// Signs through an external daemon. Returned signatures are verified before use.
+pub struct RemoteSigner {
+    pubkey: Pubkey,
+    transport: Transport,
+    timeout: Duration,
+    max_lamports_out: Option<u64>,
+    auth_token: Option<String>,
+}
+
+impl std::fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteSigner")
+            .field("pubkey", &self.pubkey)
+            .field("transport", &self.transport)
+            .field("max_lamports_out", &self.max_lamports_out)
+            .finish()
+    }
+}
+
+impl RemoteSigner {
+    pub fn new(config: &RemoteSignerConfig) -> Result<Self> {
+        config.validate().map_err(|e| anyhow!(e))?;
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let transport = match config.endpoint.strip_prefix("unix://") {
+            Some(path) => Transport::Unix(path.to_string()),
+            None => Transport::Http {
+                url: format!("{}/sign", config.endpoint.trim_end_matches('/')),
+                client: reqwest::Client::builder().timeout(timeout).build()?,
+            },
+        };
+        let auth_token = match &config.auth_token_env {
+            Some(var) => Some(std::env::var(var).map_err(|_| anyhow!("remote_signer: {} is not set", var))?),
+            None => None,
+        };
+        Ok(Self {
+            pubkey: Pubkey::from_str(&config.pubkey)?,
+            transport,
+            timeout,
+            max_lamports_out: config.max_lamports_out,
+            auth_token,
+        })
+    }
+
+    async fn request(&self, body: &SignRequest<'_>) -> Result<SignResponse> {
+        match &self.transport {
+            Transport::Unix(path) => {
+                let exchange = async {
+                    let mut stream = tokio::net::UnixStream::connect(path).await?;
+                    let mut line = serde_json::to_vec(body)?;
+                    line.push(b'\n');
+                    stream.write_all(&line).await?;
+                    let mut response = String::new();
+                    BufReader::new(stream).read_line(&mut response).await?;
+                    Ok::<_, anyhow::Error>(serde_json::from_str(&response)?)
+                };
+                timeout(self.timeout, exchange)
+                    .await
+                    .map_err(|_| anyhow!("signer socket {} timed out", path))?
+            }
+            Transport::Http { url, client } => {
+                let mut req = client.post(url).json(body);
+                if let Some(token) = &self.auth_token {
+                    req = req.bearer_auth(token);
+                }
+                let resp = req.send().await?;
+                let status = resp.status();
+                let parsed: SignResponse = resp
+                    .json()
+                    .await
+                    .map_err(|e| anyhow!("signer returned HTTP {} without JSON: {}", status, e))?;
+                if !status.is_success() && parsed.error.is_none() {
+                    return Err(anyhow!("signer returned HTTP {}", status));
+                }
+                Ok(parsed)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_transaction(&self, tx: &mut VersionedTransaction, policy: &SigningPolicy) -> Result<()> {
+        let required = tx.message.header().num_required_signatures as usize;
+        if required == 0 || tx.message.static_account_keys().first() != Some(&self.pubkey) {
+            return Err(anyhow!("remote signer {} is not the fee payer of this transaction", self.pubkey));
+        }
+
+        let message = tx.message.serialize();
+        let policy = policy.capped(self.max_lamports_out);
+        let started = Instant::now();
+        metrics().increment_counter("remote_signer_requests");
+        let response = self
+            .request(&SignRequest {
+                pubkey: self.pubkey.to_string(),
+                message: STANDARD.encode(&message),
+                policy: &policy,
+            })
+            .await
+            .inspect_err(|_| metrics().increment_counter("remote_signer_errors"))?;
+        metrics().record_histogram("remote_signer_latency", started.elapsed());
+
+        if let Some(reason) = response.error {
+            metrics().increment_counter("remote_signer_rejections");
+            warn!(?policy, "Remote signer refused transaction: {}", reason);
+            return Err(anyhow!("remote signer refused: {}", reason));
+        }
+        let signature = response
+            .signature
+            .ok_or_else(|| anyhow!("remote signer response has no signature"))?;
+        let signature = Signature::from_str(&signature)
+            .map_err(|e| anyhow!("remote signer returned invalid signature: {}", e))?;
+        // Never trust the daemon to have signed what we sent
+        if !signature.verify(self.pubkey.as_ref(), &message) {
+            metrics().increment_counter("remote_signer_errors");
+            return Err(anyhow!("remote signer signature does not verify"));
+        }
+
+        if tx.signatures.len() != required {
+            tx.signatures = vec![Signature::default(); required];
+        }
+        tx.signatures[0] = signature;
+        debug!(pubkey = %self.pubkey, ?policy, "Transaction signed remotely");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        hash::Hash,
+        message::{v0::Message as MessageV0, VersionedMessage},
+        signature::{Keypair, Signer},
+    };
+
+    fn transfer_tx(payer: &Pubkey) -> VersionedTransaction {
+        #[allow(deprecated)]
+        let ix = solana_sdk::system_instruction::transfer(payer, &Pubkey::new_unique(), 1);
+        let msg = MessageV0::try_compile(payer, &[ix], &[], Hash::new_unique()).unwrap();
+        VersionedTransaction { signatures: vec![], message: VersionedMessage::V0(msg) }
+    }
+
+    /// Stand-in daemon on a Unix socket: signs unless the policy allows less than `min_out`.
+    fn spawn_daemon(path: std::path::PathBuf, keypair: Keypair, min_out: u64) {
+        let listener = tokio::net::UnixListener::bind(path).unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else { return };
+                let (read, mut write) = stream.into_split();
+                let mut line = String::new();
+                BufReader::new(read).read_line(&mut line).await.unwrap();
+                let req: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let allowed = req["policy"]["max_lamports_out"].as_u64().unwrap_or(u64::MAX);
+                let resp = if allowed < min_out {
+                    serde_json::json!({"error": "max_lamports_out below transfer"})
+                } else {
+                    let message = STANDARD.decode(req["message"].as_str().unwrap()).unwrap();
+                    serde_json::json!({"signature": keypair.sign_message(&message).to_string()})
+                };
+                write.write_all(format!("{}\n", resp).as_bytes()).await.unwrap();
+            }
+        });
+    }
+
+    fn config(endpoint: String, pubkey: &Pubkey) -> RemoteSignerConfig {
+        RemoteSignerConfig {
+            endpoint,
+            pubkey: pubkey.to_string(),
+            timeout_ms: default_timeout_ms(),
+            max_lamports_out: Some(10_000),
+            auth_token_env: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn unix_socket_signer_signs_and_enforces_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("signer.sock");
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        spawn_daemon(socket.clone(), keypair, 500);
+
+        let signer = RemoteSigner::new(&config(format!("unix://{}", socket.display()), &pubkey)).unwrap();
+        let mut tx = transfer_tx(&pubkey);
+        signer.sign_transaction(&mut tx, &SigningPolicy::max_lamports_out(1_000)).await.unwrap();
+        assert!(tx.verify_with_results().into_iter().all(|ok| ok));
+
+        // The daemon refuses a policy that does not cover the spend
+        let mut tx = transfer_tx(&pubkey);
+        let err = signer.sign_transaction(&mut tx, &SigningPolicy::max_lamports_out(100)).await.unwrap_err();
+        assert!(err.to_string().contains("refused"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn signatures_from_the_wrong_key_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("signer.sock");
+        let expected = Pubkey::new_unique();
+        spawn_daemon(socket.clone(), Keypair::new(), 0);
+
+        let signer = RemoteSigner::new(&config(format!("unix://{}", socket.display()), &expected)).unwrap();
+        let mut tx = transfer_tx(&expected);
+        let err = signer.sign_transaction(&mut tx, &SigningPolicy::default()).await.unwrap_err();
+        assert!(err.to_string().contains("does not verify"), "{}", err);
+    }
+
+    #[test]
+    fn policy_is_capped_by_config() {
+        assert_eq!(SigningPolicy::max_lamports_out(50).capped(Some(10)).max_lamports_out, Some(10));
+        assert_eq!(SigningPolicy::max_lamports_out(5).capped(Some(10)).max_lamports_out, Some(5));
+        assert_eq!(SigningPolicy::default().capped(Some(10)).max_lamports_out, Some(10));
+        assert_eq!(SigningPolicy::default().capped(None).max_lamports_out, None);
+    }
+}
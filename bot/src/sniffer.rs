@@ -69,10 +69,8 @@ pub fn run_mock_sniffer(sender: CandidateSender) -> JoinHandle<()> {
 
             let mint = Keypair::new().pubkey();
             let creator = Keypair::new().pubkey();
-            let now_secs = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
+            let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            let now_secs = since_epoch.as_secs();
 
             let candidate = PremintCandidate {
                 mint,
@@ -82,6 +80,8 @@ pub fn run_mock_sniffer(sender: CandidateSender) -> JoinHandle<()> {
                 timestamp: now_secs,
                 instruction_summary: Some("Mock candidate".to_string()),
                 is_jito_bundle: None,
+                detected_at_ms: since_epoch.as_millis() as u64,
+                token_program: None,
             };
 
             let now = Instant::now();
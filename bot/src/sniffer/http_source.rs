@@ -12,7 +12,6 @@ use tokio::{
 };
 use tracing::{debug, error, warn};
 
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
 use solana_client::rpc_config::RpcTransactionConfig;
 use solana_sdk::{
@@ -22,7 +21,9 @@ use solana_sdk::{
 use solana_transaction_status::UiTransactionEncoding;
 
 use crate::config::Config;
-use crate::sniffer::real::parse_pump_logs;
+use crate::rate_limit::RpcLane;
+use crate::rpc_pool::{rpc_pool, EndpointRole};
+use crate::sniffer::real::{parse_pump_logs, token_program_from_logs};
 use crate::sniffer::source::{pump_fun_program_pk, CandidateSource};
 use crate::time_utils::now_ms;
 use crate::types::{PremintCandidate, ProgramLogEvent};
@@ -80,8 +81,8 @@ impl CandidateSource for HttpSource {
         cand_tx: Sender<PremintCandidate>,
         raw_log_tx: Option<Sender<ProgramLogEvent>>,
     ) {
-        if self.cfg.rpc_endpoints.is_empty() {
-            warn!(target:"sniffer", "HTTP source: no rpc_endpoints configured");
+        if rpc_pool().endpoints(EndpointRole::Read).is_empty() {
+            warn!(target:"sniffer", "HTTP source: no read endpoints in the RPC pool");
             loop {
                 tokio::select! {
                     _ = self.stop_notify.notified() => {
@@ -94,10 +95,6 @@ impl CandidateSource for HttpSource {
         }
 
         let program = pump_fun_program_pk();
-        let http = RpcClient::new_with_commitment(
-            self.cfg.rpc_endpoints[0].clone(),
-            self.commitment_config(),
-        );
 
         loop {
             let notified = self.stop_notify.notified();
@@ -109,10 +106,16 @@ impl CandidateSource for HttpSource {
                     return;
                 }
                 _ = time::sleep(Duration::from_millis(self.cfg.http_poll_interval_ms)) => {
+                    // Poll the first read endpoint the health checker has not demoted
+                    let Some(endpoint) = rpc_pool().endpoints(EndpointRole::Read).into_iter().next() else {
+                        continue;
+                    };
+                    let http = rpc_pool().client(&endpoint, EndpointRole::Read, RpcLane::Background);
                     let res = http.get_signatures_for_address_with_config(
                         &program,
                         GetConfirmedSignaturesForAddress2Config {
                             limit: Some(self.cfg.http_sig_depth.min(1000)),
+                            commitment: Some(self.commitment_config()),
                             ..Default::default()
                         }
                     ).await;
@@ -141,7 +144,7 @@ impl CandidateSource for HttpSource {
                     let sem = Arc::new(tokio::sync::Semaphore::new(self.cfg.http_max_parallel_tx_fetch.max(1)));
                     let mut tasks = Vec::with_capacity(new_sigs.len());
                     for sig in new_sigs {
-                        let endpoint = self.cfg.rpc_endpoints[0].clone();
+                        let http = http.clone();
                         let sem = sem.clone();
                         let raw_log_tx = raw_log_tx.clone();
                         let cand_tx = cand_tx.clone();
@@ -150,8 +153,7 @@ impl CandidateSource for HttpSource {
 
                         tasks.push(tokio::spawn(async move {
                             let _permit = sem.acquire().await.expect("semaphore");
-                            
-                            let http = RpcClient::new_with_commitment(endpoint, commitment);
+
                             let tx = http.get_transaction_with_config(
                                 &sig,
                                 RpcTransactionConfig {
@@ -187,6 +189,8 @@ impl CandidateSource for HttpSource {
                                                 timestamp: ts_ms / 1000,
                                                 instruction_summary: Some("HTTP mint".to_string()),
                                                 is_jito_bundle: None,
+                                                detected_at_ms: ts_ms,
+                                                token_program: token_program_from_logs(&logs),
                                             }).await;
                                         }
                                     }
@@ -1,7 +1,6 @@
 //! REAL sniffer utilities: stricter pump.fun-like heuristics and metadata backfill.
 
 use regex::Regex;
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_client::rpc_config::RpcTransactionConfig;
 use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
 use solana_sdk::pubkey::Pubkey;
@@ -9,6 +8,10 @@ use solana_sdk::signature::Signature;
 use solana_transaction_status::UiTransactionEncoding;
 use std::str::FromStr;
 
+use crate::dex::mint::TOKEN_2022_PROGRAM_ID;
+use crate::rate_limit::RpcLane;
+use crate::rpc_pool::{rpc_pool, EndpointRole};
+
 /// Extract potential mint and creator from logs using pump.fun-like patterns.
 /// Returns (maybe_mint, maybe_creator, all_pubkeys_seen)
 pub fn parse_pump_logs(logs: &[String]) -> (Option<Pubkey>, Option<Pubkey>, Vec<Pubkey>) {
@@ -54,6 +57,16 @@ pub fn parse_pump_logs(logs: &[String]) -> (Option<Pubkey>, Option<Pubkey>, Vec<
     (maybe_mint, maybe_creator, keys)
 }
 
+/// Token program the create transaction initialized the mint under, from its invoke logs.
+/// Token-2022 wins when both appear, since SPL Token launches never invoke it.
+pub fn token_program_from_logs(logs: &[String]) -> Option<Pubkey> {
+    let invoked = |program: &Pubkey| {
+        let prefix = format!("Program {} invoke", program);
+        logs.iter().any(|line| line.starts_with(&prefix))
+    };
+    [TOKEN_2022_PROGRAM_ID, spl_token::id()].into_iter().find(|program| invoked(program))
+}
+
 fn first_key_in_line(re: &Regex, line: &str) -> Option<Pubkey> {
     re.find_iter(line)
         .filter_map(|m| Pubkey::from_str(m.as_str()).ok())
@@ -66,7 +79,7 @@ pub async fn fetch_meta_from_rpc(
     sig: &str,
     commitment: &str,
 ) -> anyhow::Result<(Option<Pubkey>, Option<Pubkey>)> {
-    let client = RpcClient::new(rpc_http_url.to_string());
+    let client = rpc_pool().client(rpc_http_url, EndpointRole::Read, RpcLane::Background);
 
     let commitment_cfg = match commitment.to_ascii_lowercase().as_str() {
         "processed" => CommitmentConfig {
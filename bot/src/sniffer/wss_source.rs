@@ -16,7 +16,8 @@ use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilt
 use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
 
 use crate::config::Config;
-use crate::sniffer::real::{fetch_meta_from_rpc, parse_pump_logs};
+use crate::rpc_pool::{rpc_pool, EndpointRole};
+use crate::sniffer::real::{fetch_meta_from_rpc, parse_pump_logs, token_program_from_logs};
 use crate::sniffer::source::{pump_fun_program_pk, CandidateSource};
 use crate::time_utils::now_ms;
 use crate::types::{PremintCandidate, ProgramLogEvent};
@@ -92,10 +93,12 @@ impl CandidateSource for WssSource {
             let notified = self.stop_notify.notified();
             tokio::pin!(notified);
 
+            // Reconnect to the first endpoint the health checker has not demoted
+            let wss_endpoint = &self.cfg.rpc_wss_endpoints[rpc_pool().healthy_first(&self.cfg.rpc_wss_endpoints)[0]];
             debug!(target: "sniffer", "WSS connecting…");
-            match PubsubClient::new(&self.cfg.rpc_wss_endpoints[0]).await {
+            match PubsubClient::new(wss_endpoint).await {
                 Ok(client) => {
-                    info!(target: "sniffer", "WSS connected to {}", &self.cfg.rpc_wss_endpoints[0]);
+                    info!(target: "sniffer", "WSS connected to {}", wss_endpoint);
 
                     let commitment_cfg = self.commitment_config();
                     let (mut sub, unsub) = match client
@@ -148,9 +151,13 @@ impl CandidateSource for WssSource {
 
                                         let (maybe_mint, maybe_creator, _keys) = parse_pump_logs(&logs);
                                         if maybe_mint.is_none() || maybe_creator.is_none() {
-                                            if self.cfg.meta_fetch_enabled {
+                                            // Backfill from the first read endpoint the health checker has not demoted
+                                            let read_endpoint = self.cfg.meta_fetch_enabled
+                                                .then(|| rpc_pool().endpoints(EndpointRole::Read).into_iter().next())
+                                                .flatten();
+                                            if let Some(read_endpoint) = read_endpoint {
                                                 if let Ok((m, c)) = fetch_meta_from_rpc(
-                                                    &self.cfg.rpc_endpoints[0],
+                                                    &read_endpoint,
                                                     &sig,
                                                     self.cfg.meta_fetch_commitment.as_deref().unwrap_or("confirmed"),
                                                 ).await {
@@ -163,6 +170,8 @@ impl CandidateSource for WssSource {
                                                             timestamp: ts_ms / 1000,
                                                             instruction_summary: Some("WSS mint".to_string()),
                                                             is_jito_bundle: None,
+                                                            detected_at_ms: ts_ms,
+                                                            token_program: token_program_from_logs(&logs),
                                                         }).await;
                                                         continue;
                                                     }
@@ -179,6 +188,8 @@ impl CandidateSource for WssSource {
                                             timestamp: ts_ms / 1000,
                                             instruction_summary: Some("WSS mint".to_string()),
                                             is_jito_bundle: None,
+                                            detected_at_ms: ts_ms,
+                                            token_program: token_program_from_logs(&logs),
                                         }).await;
                                     }
                                     None => {
@@ -385,6 +385,8 @@ impl TestEnvironment {
                 .as_secs(),
             instruction_summary: Some("Test instruction".to_string()),
             is_jito_bundle: Some(false),
+            detected_at_ms: 0,
+            token_program: None,
         };
 
         info!("✅ Mock candidate created: {}", mock_candidate.mint);
@@ -0,0 +1,197 @@
+//! Empty token account discovery for rent reclamation.
+//!
+//! Every position leaves an associated token account behind holding ~0.002 SOL of rent. Full
+//! exits close the account in the sell transaction where the route allows it; the sweep finds
+//! whatever is left (provider-built sells, fee-bearing Token-2022 mints, manual transfers)
+//! among the wallet's SPL Token and Token-2022 accounts and closes the ones that can be.
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder_client_types::UiAccountData;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_request::RpcRequest, rpc_response::Response,
+    rpc_response::RpcKeyedAccount,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::dex::mint::TOKEN_2022_PROGRAM_ID;
+use crate::dex::{read_pubkey, read_u64};
+
+/// Token account size without extensions
+const ACCOUNT_LEN: usize = 165;
+const AMOUNT_OFFSET: usize = 64;
+const STATE_OFFSET: usize = 108;
+const STATE_FROZEN: u8 = 2;
+const CLOSE_AUTHORITY_OFFSET: usize = 129;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+const TLV_START: usize = ACCOUNT_LEN + 1;
+const EXT_TRANSFER_FEE_AMOUNT: u16 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSweepConfig {
+    /// Sweep again every this many seconds (0 = only at startup)
+    #[serde(default)]
+    pub interval_secs: u64,
+    /// Close instructions per transaction
+    #[serde(default = "default_max_closes_per_tx")]
+    pub max_closes_per_tx: usize,
+}
+
+fn default_max_closes_per_tx() -> usize {
+    20
+}
+
+impl Default for AccountSweepConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 0,
+            max_closes_per_tx: default_max_closes_per_tx(),
+        }
+    }
+}
+
+impl AccountSweepConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_closes_per_tx == 0 || self.max_closes_per_tx > 25 {
+            return Err("account_sweep.max_closes_per_tx must be between 1 and 25".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A zero-balance token account `owner` can close.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyTokenAccount {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    /// Rent returned to the owner on close
+    pub lamports: u64,
+}
+
+/// Whether `owner` can close the token account holding `data`: zero balance, not frozen, no
+/// foreign close authority and, on Token-2022, no withheld transfer fees.
+pub fn is_closable(owner: &Pubkey, data: &[u8]) -> Result<bool> {
+    if data.len() < ACCOUNT_LEN {
+        return Err(anyhow!("token account data too short ({} bytes)", data.len()));
+    }
+    if read_pubkey(data, 32)? != *owner || read_u64(data, AMOUNT_OFFSET)? != 0 {
+        return Ok(false);
+    }
+    if data[STATE_OFFSET] == STATE_FROZEN {
+        return Ok(false);
+    }
+    if data[CLOSE_AUTHORITY_OFFSET] == 1 && read_pubkey(data, CLOSE_AUTHORITY_OFFSET + 4)? != *owner {
+        return Ok(false);
+    }
+    Ok(withheld_transfer_fee(data)? == 0)
+}
+
+/// Transfer fees withheld in a Token-2022 account (0 for accounts without the extension).
+/// The account cannot be closed until they are harvested to the mint.
+fn withheld_transfer_fee(data: &[u8]) -> Result<u64> {
+    if data.len() <= ACCOUNT_LEN || data[ACCOUNT_LEN] != ACCOUNT_TYPE_ACCOUNT {
+        return Ok(0);
+    }
+    let mut offset = TLV_START;
+    while offset + 4 <= data.len() {
+        let ext_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let len = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        match ext_type {
+            0 => break,
+            EXT_TRANSFER_FEE_AMOUNT => return read_u64(data, offset + 4),
+            _ => offset += 4 + len,
+        }
+    }
+    Ok(0)
+}
+
+/// All token accounts of `owner` under both token programs that it can close.
+pub async fn find_empty_token_accounts(rpc: &RpcClient, owner: &Pubkey) -> Result<Vec<EmptyTokenAccount>> {
+    let mut empty = Vec::new();
+    for token_program in [spl_token::id(), TOKEN_2022_PROGRAM_ID] {
+        let accounts: Response<Vec<RpcKeyedAccount>> = rpc
+            .send(
+                RpcRequest::GetTokenAccountsByOwner,
+                serde_json::json!([
+                    owner.to_string(),
+                    {"programId": token_program.to_string()},
+                    {"encoding": "base64", "commitment": "confirmed"}
+                ]),
+            )
+            .await
+            .map_err(|e| anyhow!("getTokenAccountsByOwner ({}): {}", token_program, e))?;
+
+        for keyed in accounts.value {
+            let UiAccountData::Binary(encoded, _) = &keyed.account.data else {
+                return Err(anyhow!("unexpected encoding for token account {}", keyed.pubkey));
+            };
+            let data = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+            if !is_closable(owner, &data)? {
+                continue;
+            }
+            empty.push(EmptyTokenAccount {
+                address: keyed.pubkey.parse()?,
+                mint: read_pubkey(&data, 0)?,
+                token_program,
+                lamports: keyed.account.lamports,
+            });
+        }
+    }
+    Ok(empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_account(owner: &Pubkey, amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; ACCOUNT_LEN];
+        data[0..32].copy_from_slice(Pubkey::new_unique().as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[AMOUNT_OFFSET..AMOUNT_OFFSET + 8].copy_from_slice(&amount.to_le_bytes());
+        data[STATE_OFFSET] = 1;
+        data
+    }
+
+    #[test]
+    fn only_empty_unlocked_accounts_are_closable() {
+        let owner = Pubkey::new_unique();
+        assert!(is_closable(&owner, &token_account(&owner, 0)).unwrap());
+        assert!(!is_closable(&owner, &token_account(&owner, 1)).unwrap());
+        assert!(!is_closable(&Pubkey::new_unique(), &token_account(&owner, 0)).unwrap());
+
+        let mut frozen = token_account(&owner, 0);
+        frozen[STATE_OFFSET] = STATE_FROZEN;
+        assert!(!is_closable(&owner, &frozen).unwrap());
+
+        let mut foreign_authority = token_account(&owner, 0);
+        foreign_authority[CLOSE_AUTHORITY_OFFSET] = 1;
+        foreign_authority[CLOSE_AUTHORITY_OFFSET + 4..].copy_from_slice(Pubkey::new_unique().as_ref());
+        assert!(!is_closable(&owner, &foreign_authority).unwrap());
+        let mut own_authority = foreign_authority.clone();
+        own_authority[CLOSE_AUTHORITY_OFFSET + 4..].copy_from_slice(owner.as_ref());
+        assert!(is_closable(&owner, &own_authority).unwrap());
+
+        assert!(is_closable(&owner, &[0u8; 100]).is_err());
+    }
+
+    #[test]
+    fn withheld_fees_block_closing() {
+        let owner = Pubkey::new_unique();
+        let with_withheld = |withheld: u64| {
+            let mut data = token_account(&owner, 0);
+            data.push(ACCOUNT_TYPE_ACCOUNT);
+            // an unrelated extension first (immutable owner, empty value)
+            data.extend_from_slice(&7u16.to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(&EXT_TRANSFER_FEE_AMOUNT.to_le_bytes());
+            data.extend_from_slice(&8u16.to_le_bytes());
+            data.extend_from_slice(&withheld.to_le_bytes());
+            data
+        };
+        assert!(is_closable(&owner, &with_withheld(0)).unwrap());
+        assert!(!is_closable(&owner, &with_withheld(5)).unwrap());
+    }
+}
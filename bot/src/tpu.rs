@@ -0,0 +1,428 @@
+//! Direct TPU submission over QUIC.
+//!
+//! - reads the leader schedule for the next few slots with `getSlotLeaders`
+//! - resolves the leaders' TPU QUIC addresses from `getClusterNodes` (cached)
+//! - writes the signed transactions to the current and next leaders over QUIC
+//!
+//! `TpuBroadcaster` implements `RpcBroadcaster`. With `mode = "race"` it is raced against the
+//! RPC (or Jito) broadcaster through `RaceBroadcaster`; the first accepted send wins and the
+//! other keeps going.
+
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use solana_client::{connection_cache::ConnectionCache, nonblocking::rpc_client::RpcClient};
+use solana_connection_cache::nonblocking::client_connection::ClientConnection;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use tokio::{sync::RwLock, task::JoinSet, time::timeout};
+use tracing::{debug, info, warn};
+
+use crate::metrics::metrics;
+use crate::observability::CorrelationId;
+use crate::rpc_manager::{BroadcastError, RpcBroadcaster};
+
+/// Consecutive slots each leader produces.
+pub const LEADER_SLOTS: u64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TpuMode {
+    /// Send to leaders only
+    TpuOnly,
+    /// Race leaders against the RPC broadcaster
+    Race,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TpuConfig {
+    #[serde(default = "default_mode")]
+    pub mode: TpuMode,
+    /// Distinct leaders to send to: the current one and the next `leader_fanout - 1`
+    #[serde(default = "default_leader_fanout")]
+    pub leader_fanout: usize,
+    /// How long TPU addresses from `getClusterNodes` are reused
+    #[serde(default = "default_cluster_refresh_ms")]
+    pub cluster_refresh_ms: u64,
+    #[serde(default = "default_send_timeout_ms")]
+    pub send_timeout_ms: u64,
+    /// QUIC connections kept per leader
+    #[serde(default = "default_connection_pool_size")]
+    pub connection_pool_size: usize,
+}
+
+fn default_mode() -> TpuMode {
+    TpuMode::Race
+}
+fn default_leader_fanout() -> usize {
+    2
+}
+fn default_cluster_refresh_ms() -> u64 {
+    60_000
+}
+fn default_send_timeout_ms() -> u64 {
+    2_000
+}
+fn default_connection_pool_size() -> usize {
+    2
+}
+
+impl Default for TpuConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_mode(),
+            leader_fanout: default_leader_fanout(),
+            cluster_refresh_ms: default_cluster_refresh_ms(),
+            send_timeout_ms: default_send_timeout_ms(),
+            connection_pool_size: default_connection_pool_size(),
+        }
+    }
+}
+
+impl TpuConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.leader_fanout == 0 || self.connection_pool_size == 0 {
+            return Err("tpu.leader_fanout and tpu.connection_pool_size must be > 0".to_string());
+        }
+        if self.send_timeout_ms == 0 {
+            return Err("tpu.send_timeout_ms must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// TPU QUIC addresses of the first `fanout` distinct leaders in `leaders` (slot order).
+/// Leaders without a known address are skipped but still count toward the fanout.
+pub fn upcoming_tpus(leaders: &[Pubkey], tpus: &HashMap<Pubkey, SocketAddr>, fanout: usize) -> Vec<SocketAddr> {
+    let mut seen = HashSet::new();
+    leaders
+        .iter()
+        .filter(|leader| seen.insert(**leader))
+        .take(fanout)
+        .filter_map(|leader| tpus.get(leader).copied())
+        .collect()
+}
+
+#[derive(Default)]
+struct ClusterTpus {
+    tpus: HashMap<Pubkey, SocketAddr>,
+    refreshed: Option<Instant>,
+}
+
+pub struct TpuBroadcaster {
+    rpc: Arc<RpcClient>,
+    connections: ConnectionCache,
+    config: TpuConfig,
+    cluster: RwLock<ClusterTpus>,
+}
+
+impl std::fmt::Debug for TpuBroadcaster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TpuBroadcaster")
+            .field("rpc", &self.rpc.url())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl TpuBroadcaster {
+    /// `rpc` answers the leader schedule and cluster node lookups.
+    pub fn new(rpc: Arc<RpcClient>, config: TpuConfig) -> Self {
+        Self {
+            rpc,
+            connections: ConnectionCache::new_quic("sniffer_bot_tpu", config.connection_pool_size),
+            config,
+            cluster: RwLock::new(ClusterTpus::default()),
+        }
+    }
+
+    pub fn config(&self) -> &TpuConfig {
+        &self.config
+    }
+
+    /// TPU QUIC addresses by node identity, refreshed every `cluster_refresh_ms` or when a
+    /// leader is missing.
+    async fn cluster_tpus(&self, leaders: &[Pubkey]) -> Result<HashMap<Pubkey, SocketAddr>> {
+        {
+            let cluster = self.cluster.read().await;
+            let fresh = cluster
+                .refreshed
+                .is_some_and(|at| at.elapsed() < Duration::from_millis(self.config.cluster_refresh_ms));
+            if fresh && leaders.iter().all(|leader| cluster.tpus.contains_key(leader)) {
+                return Ok(cluster.tpus.clone());
+            }
+        }
+        let nodes = self
+            .rpc
+            .get_cluster_nodes()
+            .await
+            .map_err(|e| anyhow!("getClusterNodes: {}", e))?;
+        let tpus: HashMap<Pubkey, SocketAddr> = nodes
+            .into_iter()
+            .filter_map(|node| Some((Pubkey::from_str(&node.pubkey).ok()?, node.tpu_quic?)))
+            .collect();
+        debug!(nodes = tpus.len(), "TPU QUIC addresses refreshed");
+        let mut cluster = self.cluster.write().await;
+        cluster.tpus = tpus.clone();
+        cluster.refreshed = Some(Instant::now());
+        Ok(tpus)
+    }
+
+    /// TPU QUIC addresses of the current and next leaders.
+    pub async fn leader_tpus(&self) -> Result<Vec<SocketAddr>> {
+        let slot = self
+            .rpc
+            .get_slot_with_commitment(CommitmentConfig::processed())
+            .await
+            .map_err(|e| anyhow!("getSlot: {}", e))?;
+        let leaders = self
+            .rpc
+            .get_slot_leaders(slot, self.config.leader_fanout as u64 * LEADER_SLOTS)
+            .await
+            .map_err(|e| anyhow!("getSlotLeaders: {}", e))?;
+        let tpus = self.cluster_tpus(&leaders).await?;
+        let addrs = upcoming_tpus(&leaders, &tpus, self.config.leader_fanout);
+        metrics().set_gauge("tpu_leader_targets", addrs.len() as u64);
+        Ok(addrs)
+    }
+
+    /// Write `wire` to one leader's TPU.
+    async fn send_wire(&self, addr: SocketAddr, wire: &[u8]) -> Result<()> {
+        let connection = self.connections.get_nonblocking_connection(&addr);
+        match timeout(Duration::from_millis(self.config.send_timeout_ms), connection.send_data(wire)).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(anyhow!("TPU send to {} failed: {}", addr, e)),
+            Err(_elapsed) => Err(anyhow!("TPU send to {} timed out", addr)),
+        }
+    }
+}
+
+impl RpcBroadcaster for TpuBroadcaster {
+    fn send_on_many_rpc<'a>(
+        &'a self,
+        txs: Vec<VersionedTransaction>,
+        correlation_id: Option<CorrelationId>,
+    ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
+        Box::pin(async move {
+            let first = txs
+                .first()
+                .and_then(|tx| tx.signatures.first().copied())
+                .ok_or_else(|| anyhow!("TpuBroadcaster: no transactions to send"))?;
+            let leaders = self.leader_tpus().await?;
+            if leaders.is_empty() {
+                return Err(anyhow!("TpuBroadcaster: no TPU QUIC address for the upcoming leaders"));
+            }
+
+            let wires = txs
+                .iter()
+                .map(|tx| {
+                    let wire = bincode::serialize(tx).map_err(|e| anyhow!("failed to serialize transaction: {}", e))?;
+                    Ok((tx.signatures[0], wire))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let sends = leaders
+                .iter()
+                .flat_map(|addr| wires.iter().map(move |(sig, wire)| (*addr, *sig, wire)))
+                .map(|(addr, sig, wire)| async move { (addr, sig, self.send_wire(addr, wire).await) });
+
+            let mut delivered = Vec::new();
+            for (addr, sig, result) in join_all(sends).await {
+                metrics().increment_counter("tpu_sends");
+                match result {
+                    Ok(()) => {
+                        debug!(correlation_id = ?correlation_id, leader = %addr, %sig, "TpuBroadcaster: sent");
+                        delivered.push(sig);
+                    }
+                    Err(e) => {
+                        metrics().increment_counter("tpu_send_errors");
+                        warn!(correlation_id = ?correlation_id, error = %e, "TpuBroadcaster: send failed");
+                    }
+                }
+            }
+            // Report the first transaction whenever it went out
+            match delivered.first() {
+                Some(_) if delivered.contains(&first) => Ok(first),
+                Some(sig) => Ok(*sig),
+                None => Err(anyhow!("TpuBroadcaster: every send to {} leaders failed", leaders.len())),
+            }
+        })
+    }
+}
+
+/// `RpcBroadcaster` racing several broadcasters with the same transactions. The first
+/// signature wins; the others keep sending in the background.
+pub struct RaceBroadcaster {
+    racers: Vec<(&'static str, Arc<dyn RpcBroadcaster>)>,
+}
+
+impl std::fmt::Debug for RaceBroadcaster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RaceBroadcaster")
+            .field("racers", &self.racers)
+            .finish()
+    }
+}
+
+impl RaceBroadcaster {
+    /// Wins are counted as `broadcast_race_wins_<name>`.
+    pub fn new(racers: Vec<(&'static str, Arc<dyn RpcBroadcaster>)>) -> Self {
+        Self { racers }
+    }
+}
+
+impl RpcBroadcaster for RaceBroadcaster {
+    fn send_on_many_rpc<'a>(
+        &'a self,
+        txs: Vec<VersionedTransaction>,
+        correlation_id: Option<CorrelationId>,
+    ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut set = JoinSet::new();
+            for (name, racer) in &self.racers {
+                let (name, racer) = (*name, racer.clone());
+                let txs = txs.clone();
+                let correlation_id = correlation_id.clone();
+                set.spawn(async move { (name, racer.send_on_many_rpc(txs, correlation_id).await) });
+            }
+
+            let mut errors = Vec::new();
+            while let Some(joined) = set.join_next().await {
+                match joined {
+                    Ok((name, Ok(sig))) => {
+                        set.detach_all();
+                        metrics().increment_counter(&format!("broadcast_race_wins_{}", name));
+                        info!(correlation_id = ?correlation_id, winner = name, %sig, "Broadcast race won");
+                        return Ok(sig);
+                    }
+                    Ok((name, Err(e))) => errors.push((name, e)),
+                    Err(join_err) => warn!("Broadcast race task join error: {}", join_err),
+                }
+            }
+
+            let summary = errors
+                .iter()
+                .map(|(name, e)| format!("{}: {:#}", name, e))
+                .collect::<Vec<_>>()
+                .join("; ");
+            // Pass a typed failure through so callers can still act on it (e.g. rebuild on a
+            // rejected blockhash); racers are tried in configured order
+            let typed = self
+                .racers
+                .iter()
+                .find_map(|(racer, _)| {
+                    let at = errors
+                        .iter()
+                        .position(|(name, e)| name == racer && e.is::<BroadcastError>())?;
+                    Some(errors.swap_remove(at).1)
+                });
+            match typed {
+                Some(e) => Err(e.context(format!("every broadcaster failed: {}", summary))),
+                None => Err(anyhow!("every broadcaster failed: {}", summary)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upcoming_tpus_takes_distinct_leaders_in_slot_order() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let addr = |port| SocketAddr::from(([127, 0, 0, 1], port));
+        let tpus = HashMap::from([(a, addr(1)), (b, addr(2)), (c, addr(3))]);
+        let leaders = [a, a, a, a, b, b, b, b, c, c];
+
+        assert_eq!(upcoming_tpus(&leaders, &tpus, 2), vec![addr(1), addr(2)]);
+        assert_eq!(upcoming_tpus(&leaders, &tpus, 5), vec![addr(1), addr(2), addr(3)]);
+
+        // A leader without a QUIC address uses up its place in the fanout
+        let unknown = HashMap::from([(b, addr(2))]);
+        assert_eq!(upcoming_tpus(&leaders, &unknown, 2), vec![addr(2)]);
+    }
+
+    /// Broadcaster answering after `delay`, with the first signature or an error.
+    #[derive(Debug)]
+    struct Fixed {
+        delay: Duration,
+        ok: bool,
+    }
+
+    impl RpcBroadcaster for Fixed {
+        fn send_on_many_rpc<'a>(
+            &'a self,
+            txs: Vec<VersionedTransaction>,
+            _correlation_id: Option<CorrelationId>,
+        ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
+            Box::pin(async move {
+                tokio::time::sleep(self.delay).await;
+                if self.ok {
+                    Ok(txs[0].signatures[0])
+                } else {
+                    Err(anyhow!("rejected"))
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn race_returns_the_first_success() {
+        let racer = |delay_ms, ok| Arc::new(Fixed { delay: Duration::from_millis(delay_ms), ok }) as Arc<dyn RpcBroadcaster>;
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::from([7u8; 64])],
+            ..VersionedTransaction::default()
+        };
+
+        let race = RaceBroadcaster::new(vec![("rpc", racer(50, true)), ("tpu", racer(0, false))]);
+        let before = metrics().get_counter("broadcast_race_wins_rpc");
+        assert_eq!(race.send_on_many_rpc(vec![tx.clone()], None).await.unwrap(), tx.signatures[0]);
+        assert_eq!(metrics().get_counter("broadcast_race_wins_rpc"), before + 1);
+
+        let race = RaceBroadcaster::new(vec![("rpc", racer(0, false)), ("tpu", racer(0, false))]);
+        let err = race.send_on_many_rpc(vec![tx], None).await.unwrap_err();
+        assert!(err.to_string().contains("rpc: rejected") && err.to_string().contains("tpu: rejected"), "{err}");
+    }
+
+    /// Broadcaster failing at once with the error its function builds.
+    #[derive(Debug)]
+    struct Failing(fn() -> anyhow::Error);
+
+    impl RpcBroadcaster for Failing {
+        fn send_on_many_rpc<'a>(
+            &'a self,
+            _txs: Vec<VersionedTransaction>,
+            _correlation_id: Option<CorrelationId>,
+        ) -> Pin<Box<dyn Future<Output = Result<Signature>> + Send + 'a>> {
+            Box::pin(async move { Err((self.0)()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn race_keeps_the_typed_broadcast_error() {
+        let stale = || anyhow::Error::from(BroadcastError::BlockhashNotFound(solana_sdk::hash::Hash::new_from_array([3u8; 32])));
+        let race = RaceBroadcaster::new(vec![
+            ("rpc", Arc::new(Failing(stale)) as Arc<dyn RpcBroadcaster>),
+            ("tpu", Arc::new(Failing(|| anyhow!("no leader reachable")))),
+        ]);
+
+        let err = race.send_on_many_rpc(vec![VersionedTransaction::default()], None).await.unwrap_err();
+        match err.downcast_ref::<BroadcastError>() {
+            Some(BroadcastError::BlockhashNotFound(hash)) => assert_eq!(hash.to_bytes(), [3u8; 32]),
+            other => panic!("expected BlockhashNotFound, got {other:?}"),
+        }
+        assert!(format!("{err:#}").contains("tpu: no leader reachable"), "{err:#}");
+    }
+}
@@ -4,12 +4,12 @@
 //! - supports LetsBonk (external HTTP provider) for liquidity/quote lookup
 //! - validates config values
 //! - retry/backoff + multi-RPC fallback for blockhash
-//! - signs VersionedTransaction via WalletManager
+//! - signs VersionedTransaction via a TransactionSigner (local keypair or remote daemon)
 //! - prepares simple Jito bundle wrapper (struct) for later submission
 //! - careful logging and safe fallbacks (memo fallback when no program integration)
 //
 // Integration with other components:
-// - WalletManager for signing and public key
+// - TransactionSigner for signing and public key
 // - NonceManager for parallel transaction preparation
 // - RpcBroadcaster for transaction broadcasting
 // - Security validator for pre-transaction checks
@@ -17,23 +17,25 @@
 // - supports LetsBonk (external HTTP provider) for liquidity/quote lookup
 // - validates config values
 // - retry/backoff + multi-RPC fallback for blockhash
-// - signs VersionedTransaction via WalletManager
+// - signs VersionedTransaction via a TransactionSigner
 // - prepares simple Jito bundle wrapper (struct) for later submission
 // - careful logging and safe fallbacks (memo fallback when no program integration
 
 use anyhow::anyhow;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::{nonblocking::rpc_client::RpcClient, nonce_utils};
 use solana_sdk::{
+    commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
     hash::Hash,
     instruction::{AccountMeta, Instruction},
-    message::{v0::Message as MessageV0, VersionedMessage},
+    message::{v0::Message as MessageV0, AddressLookupTableAccount, VersionedMessage},
     pubkey::Pubkey,
     signature::Signature,
     transaction::VersionedTransaction,
 };
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{sync::Arc, time::Duration};
@@ -41,23 +43,42 @@ use thiserror::Error;
 use tokio::sync::RwLock;
 use tokio_retry::{
     strategy::{jitter, ExponentialBackoff},
-    Retry,
+    Retry, RetryIf,
 };
 use tracing::{debug, info, warn};
 
+use crate::blockhash::BlockhashService;
+use crate::compute_units::ComputeUnitEstimator;
+use crate::dex::{
+    jupiter::{fetch_lookup_tables, JupiterClient},
+    mint::{MintInfo, MintPolicy},
+    pumpfun::MigrationTracker,
+    pumpswap::PumpSwapClient,
+    raydium::RaydiumClient,
+    close_token_account_instruction, token_account_amount, SwapSide, WSOL_MINT,
+};
+use crate::fee_estimator::{writable_accounts, PriorityFeeEstimator};
+use crate::metrics::metrics;
+use crate::rate_limit::RpcLane;
+use crate::rpc_pool::{rpc_pool, EndpointRole};
 use crate::nonce_manager::NonceManager;
+use crate::token_accounts::{find_empty_token_accounts, AccountSweepConfig, EmptyTokenAccount};
+use crate::tx_guard::TransactionGuard;
+use crate::tx_template::{BuyTemplate, TemplateCache};
 use crate::types::PremintCandidate;
-use crate::wallet::WalletManager;
+use crate::signer::{SigningPolicy, TransactionSigner};
+use crate::wallet_pool::WalletPool;
 
 // Optional integration: `pumpfun` crate
 #[cfg(feature = "pumpfun")]
 use pumpfun::{accounts::BondingCurveAccount, common::types::{Cluster, PriorityFee}, PumpFun};
 
-// Optional integrations: Raydium/Orca (behind feature flags)
-#[cfg(feature = "raydium")]
-use raydium_sdk_v2::AmmSwapClient;
+// Optional integration: Orca (behind feature flag)
 #[cfg(feature = "orca")]
-use orca_whirlpools::{SwapInput, WhirlpoolClient};
+use orca_whirlpools::{
+    fetch_whirlpools_by_token_pair, set_native_mint_wrapping_strategy, swap_instructions,
+    NativeMintWrappingStrategy, PoolInfo, SwapQuote, SwapType,
+};
 
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::id as token_program_id;
@@ -65,10 +86,22 @@ use spl_token::instruction::close_account;
 
 // Configuration
 
+/// Durable nonce a transaction is built on instead of a recent blockhash: it advances
+/// `account` first, so transactions sharing one value exclude each other on-chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+    /// Stored nonce value, used as the transaction's blockhash
+    pub blockhash: Hash,
+}
+
 #[derive(Debug, Clone)]
 pub struct TransactionConfig {
     /// Compute unit price in micro-lamports per CU (for priority fees)
     pub priority_fee_lamports: u64,
+    /// Multiplier applied to the resolved priority fee (fee ladder rungs)
+    pub priority_fee_multiplier: f64,
     /// Compute unit limit for the transaction
     pub compute_unit_limit: u32,
     /// Amount to buy in SOL lamports
@@ -79,22 +112,33 @@ pub struct TransactionConfig {
     pub rpc_endpoints: Vec<String>,
     /// Max attempts per endpoint
     pub rpc_retry_attempts: usize,
-    /// HTTP and RPC request timeout (ms)
-    pub rpc_timeout_ms: u64,
+    /// Request timeout of the HTTP providers (ms); RPC calls use the `[rpc_pool]` role timeouts
+    pub http_timeout_ms: u64,
     /// PumpPortal HTTP endpoint and API key
     pub pumpportal_url: Option<String>,
     pub pumpportal_api_key: Option<String>,
     /// LetsBonk HTTP endpoint and API key
     pub letsbonk_api_url: Option<String>,
     pub letsbonk_api_key: Option<String>,
+    /// Minimum SOL (lamports) to accept on provider-built sells (0 = provider applies `slippage_bps`)
+    pub sell_min_sol_out_lamports: u64,
+    /// Jupiter-compatible quote/swap API base URL (e.g. https://quote-api.jup.ag/v6).
+    /// When set, sells of unrecognised programs fall back to this route.
+    pub jupiter_api_url: Option<String>,
     /// Jito bundle toggle
     pub jito_bundle_enabled: bool,
+    /// Tip transfer (tip account, lamports) appended to buy transactions
+    pub jito_tip: Option<(Pubkey, u64)>,
     /// Optional signer keypair index (for multi-signer wallets)
     pub signer_keypair_index: Option<usize>,
+    /// Build buys on this durable nonce (fee ladder rungs); its authority must be the signer
+    pub durable_nonce: Option<DurableNonce>,
     /// Nonce semaphore capacity (parallel builds control)
     pub nonce_count: usize,
     /// Allowlist of programs (empty = allow all)
     pub allowed_programs: Vec<Pubkey>,
+    /// Close the token account in the same transaction when a native route sells 100%
+    pub close_token_account_on_exit: bool,
     /// Cluster configuration for pumpfun SDK
     #[cfg(feature = "pumpfun")]
     pub cluster: Cluster,
@@ -104,20 +148,26 @@ impl Default for TransactionConfig {
     fn default() -> Self {
         Self {
             priority_fee_lamports: 10_000,
+            priority_fee_multiplier: 1.0,
             compute_unit_limit: 200_000,
             buy_amount_lamports: 10_000_000,
             slippage_bps: 1000, // 10%
             rpc_endpoints: vec!["https://api.mainnet-beta.solana.com".to_string()],
             rpc_retry_attempts: 3,
-            rpc_timeout_ms: 8_000,
+            http_timeout_ms: 8_000,
             pumpportal_url: None,
             pumpportal_api_key: None,
             letsbonk_api_url: None,
             letsbonk_api_key: None,
+            sell_min_sol_out_lamports: 0,
+            jupiter_api_url: None,
             jito_bundle_enabled: false,
+            jito_tip: None,
             signer_keypair_index: None,
+            durable_nonce: None,
             nonce_count: 5,
             allowed_programs: vec![],
+            close_token_account_on_exit: true,
             #[cfg(feature = "pumpfun")]
             cluster: Cluster::mainnet(Default::default(), Default::default()),
         }
@@ -146,6 +196,11 @@ impl TransactionConfig {
                 "nonce_count must be > 0".to_string(),
             ));
         }
+        if !self.priority_fee_multiplier.is_finite() || self.priority_fee_multiplier <= 0.0 {
+            return Err(TransactionBuilderError::ConfigValidation(
+                "priority_fee_multiplier must be > 0".to_string(),
+            ));
+        }
         Ok(())
     }
 
@@ -181,6 +236,10 @@ pub enum TransactionBuilderError {
     Serialization(String),
     #[error("Program {0} is not allowed by configuration")]
     ProgramNotAllowed(Pubkey),
+    #[error("Mint {mint} rejected: {reason}")]
+    MintRejected { mint: Pubkey, reason: String },
+    #[error("Refused to sign: {0}")]
+    GuardRejected(#[from] crate::tx_guard::GuardViolation),
     #[error("Feature not enabled: {feature} for {action}")]
     FeatureNotEnabled { feature: String, action: String },
 }
@@ -189,9 +248,13 @@ pub enum TransactionBuilderError {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DexProgram {
     PumpFun,
+    /// pump.fun AMM that graduated curves migrate into
+    PumpSwap,
     LetsBonk,
     Raydium,
     Orca,
+    /// Aggregator route through a Jupiter-compatible API
+    Jupiter,
     Unknown(String),
 }
 
@@ -199,9 +262,11 @@ impl From<&str> for DexProgram {
     fn from(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "pump.fun" | "pumpfun" | "pumpportal" => DexProgram::PumpFun,
+            "pumpswap" | "pump-amm" => DexProgram::PumpSwap,
             "letsbonk.fun" | "letsbonk" | "bonk" => DexProgram::LetsBonk,
             "raydium" => DexProgram::Raydium,
             "orca" => DexProgram::Orca,
+            "jupiter" | "jup" => DexProgram::Jupiter,
             _ => DexProgram::Unknown(s.to_string()),
         }
     }
@@ -209,42 +274,54 @@ impl From<&str> for DexProgram {
 
 // TransactionBuilder
 pub struct TransactionBuilder {
-    pub wallet: Arc<WalletManager>,
+    pub wallet: Arc<dyn TransactionSigner>,
     http: Client,
     rpc_endpoints: Vec<String>,
     rpc_rotation_index: AtomicUsize,
     blockhash_cache: RwLock<Option<(std::time::Instant, Hash)>>,
     // Reduced to 15s as requested
     blockhash_cache_ttl: Duration,
+    // Last blockhash endpoints reported unknown; never handed out again
+    rejected_blockhash: RwLock<Option<Hash>>,
     nonce_manager: Arc<NonceManager>,
     rpc_clients: Vec<Arc<RpcClient>>,
+    fee_estimator: Option<Arc<PriorityFeeEstimator>>,
+    cu_estimator: Option<Arc<ComputeUnitEstimator>>,
+    migration_tracker: Option<Arc<MigrationTracker>>,
+    templates: Option<Arc<TemplateCache>>,
+    blockhashes: Option<Arc<BlockhashService>>,
+    wallet_pool: Option<Arc<WalletPool>>,
+    guard: TransactionGuard,
+    mint_policy: MintPolicy,
+    // Mint owner program and extensions by mint
+    mints: RwLock<HashMap<Pubkey, MintInfo>>,
     #[cfg(feature = "pumpfun")]
     pumpfun_client: PumpFun,
 }
 
 impl TransactionBuilder {
     pub async fn new(
-        wallet: Arc<WalletManager>,
+        wallet: Arc<dyn TransactionSigner>,
         rpc_endpoints: Vec<String>,
         nonce_manager: Arc<NonceManager>,
         config: &TransactionConfig,
     ) -> Result<Self, TransactionBuilderError> {
         let http = Client::builder()
-            .timeout(Duration::from_millis(config.rpc_timeout_ms))
+            .timeout(Duration::from_millis(config.http_timeout_ms))
             .build()
             .map_err(|e| TransactionBuilderError::RpcConnection(e.to_string()))?;
 
-        // Pre-initialize RPC clients for connection pooling
+        // Lookups on the build path share the pooled clients and the send lane
         let rpc_clients = rpc_endpoints
             .iter()
-            .map(|endpoint| {
-                Arc::new(RpcClient::new_with_timeout(
-                    endpoint.clone(),
-                    Duration::from_millis(config.rpc_timeout_ms),
-                ))
-            })
+            .map(|endpoint| rpc_pool().client(endpoint, EndpointRole::Read, RpcLane::Send))
             .collect();
 
+        // Whirlpool swaps wrap SOL through the wallet's ATA so the wallet is the only signer
+        #[cfg(feature = "orca")]
+        set_native_mint_wrapping_strategy(NativeMintWrappingStrategy::Ata)
+            .map_err(|e| TransactionBuilderError::ConfigValidation(e.to_string()))?;
+
         #[cfg(feature = "pumpfun")]
         let pumpfun_client = PumpFun::new(wallet.clone(), config.cluster.clone()).await.map_err(
             |e| TransactionBuilderError::InstructionBuild {
@@ -260,22 +337,266 @@ impl TransactionBuilder {
             rpc_rotation_index: AtomicUsize::new(0),
             blockhash_cache: RwLock::new(None),
             blockhash_cache_ttl: Duration::from_secs(15),
+            rejected_blockhash: RwLock::new(None),
             nonce_manager,
             rpc_clients,
+            fee_estimator: None,
+            cu_estimator: None,
+            migration_tracker: None,
+            templates: None,
+            blockhashes: None,
+            wallet_pool: None,
+            guard: TransactionGuard::default(),
+            mint_policy: MintPolicy::default(),
+            mints: RwLock::new(HashMap::new()),
             #[cfg(feature = "pumpfun")]
             pumpfun_client,
         })
     }
 
+    /// Use a dynamic priority fee estimator instead of the static `priority_fee_lamports`.
+    pub fn with_fee_estimator(mut self, estimator: Arc<PriorityFeeEstimator>) -> Self {
+        self.fee_estimator = Some(estimator);
+        self
+    }
+
+    /// Resolve the compute unit price for a transaction carrying `program_ixs`.
+    /// Buys of fresh launches (`launch = true`) get the estimator's aggressive multiplier.
+    async fn resolve_priority_fee(
+        &self,
+        program_ixs: &[Instruction],
+        config: &TransactionConfig,
+        launch: bool,
+    ) -> u64 {
+        let fee = match &self.fee_estimator {
+            Some(estimator) => {
+                let accounts = writable_accounts(program_ixs);
+                estimator
+                    .estimate(&accounts, launch, config.priority_fee_lamports)
+                    .await
+            }
+            None => config.priority_fee_lamports,
+        };
+        let fee = (fee as f64 * config.priority_fee_multiplier).round() as u64;
+        metrics().set_gauge("priority_fee_used_micro_lamports", fee);
+        fee
+    }
+
+    /// Size the compute unit limit by simulation instead of the static `compute_unit_limit`.
+    pub fn with_cu_estimator(mut self, estimator: Arc<ComputeUnitEstimator>) -> Self {
+        self.cu_estimator = Some(estimator);
+        self
+    }
+
+    /// Route pump.fun sells of graduated mints to PumpSwap.
+    pub fn with_migration_tracker(mut self, tracker: Arc<MigrationTracker>) -> Self {
+        self.migration_tracker = Some(tracker);
+        self
+    }
+
+    /// Build templated DEX buys from pre-assembled templates and reuse their warm blockhash.
+    pub fn with_templates(mut self, templates: Arc<TemplateCache>) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Take blockhashes from the slot-aware service instead of the TTL cache.
+    pub fn with_blockhash_service(mut self, service: Arc<BlockhashService>) -> Self {
+        self.blockhashes = Some(service);
+        self
+    }
+
+    /// Sign with pool wallets selected through `TransactionConfig::signer_keypair_index`.
+    /// Wallet 0 of the pool should be the builder's own wallet.
+    pub fn with_wallet_pool(mut self, pool: Arc<WalletPool>) -> Self {
+        self.wallet_pool = Some(pool);
+        self
+    }
+
+    /// Replace the default pre-sign guard (built-in program allowlist, no global cap).
+    pub fn with_tx_guard(mut self, guard: TransactionGuard) -> Self {
+        self.guard = guard;
+        self
+    }
+
+    /// Replace the default Token-2022 buy policy (no transfer hooks or permanent delegates).
+    pub fn with_mint_policy(mut self, policy: MintPolicy) -> Self {
+        self.mint_policy = policy;
+        self
+    }
+
+    /// Inspect the final message with the guard, then sign it under `policy` (capped by the
+    /// guard's global limit). `lookup_tables` are the tables the message was compiled against.
+    async fn sign_guarded(
+        &self,
+        tx: &mut VersionedTransaction,
+        config: &TransactionConfig,
+        policy: SigningPolicy,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<(), TransactionBuilderError> {
+        let policy = policy.capped(self.guard.max_lamports_out());
+        let tip_account: Vec<Pubkey> = config.jito_tip.iter().map(|(account, _)| *account).collect();
+        if let Err(violation) =
+            self.guard
+                .inspect(&tx.message, lookup_tables, &config.allowed_programs, &tip_account, &policy)
+        {
+            metrics().increment_counter("tx_guard_rejections");
+            warn!(reason = %violation, "Transaction guard refused to sign");
+            return Err(violation.into());
+        }
+        self.signer(config)
+            .sign_transaction(tx, &policy)
+            .await
+            .map_err(|e| TransactionBuilderError::SigningFailed(e.to_string()))
+    }
+
+    /// Wallet that pays for and signs transactions built with `config`.
+    fn signer(&self, config: &TransactionConfig) -> &dyn TransactionSigner {
+        match (config.signer_keypair_index, &self.wallet_pool) {
+            (Some(index), Some(pool)) => match pool.wallet(index) {
+                Some(wallet) => wallet.as_ref(),
+                None => self.wallet.as_ref(),
+            },
+            _ => self.wallet.as_ref(),
+        }
+    }
+
+    /// Reject signer indexes outside the pool instead of silently using another wallet.
+    fn check_signer(&self, config: &TransactionConfig) -> Result<(), TransactionBuilderError> {
+        let Some(index) = config.signer_keypair_index else {
+            return Ok(());
+        };
+        let wallets = self.wallet_pool.as_ref().map_or(1, |pool| pool.len());
+        if index >= wallets {
+            return Err(TransactionBuilderError::ConfigValidation(format!(
+                "signer_keypair_index {} out of range ({} wallets)",
+                index, wallets
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether a buy for `program` would currently take the templated path.
+    pub async fn has_template_for(&self, program: &str) -> bool {
+        match &self.templates {
+            Some(templates) => templates.template_for(program).await.is_some(),
+            None => false,
+        }
+    }
+
+    /// Resolve the compute unit limit for a transaction carrying `program_ixs`.
+    /// Only simulates when no limit is cached for the instructions' shape.
+    async fn resolve_compute_unit_limit(
+        &self,
+        dex: &str,
+        program_ixs: &[Instruction],
+        config: &TransactionConfig,
+        recent_blockhash: Hash,
+    ) -> u32 {
+        match &self.cu_estimator {
+            Some(estimator) if config.compute_unit_limit > 0 => {
+                estimator
+                    .limit_for(
+                        dex,
+                        program_ixs,
+                        &self.signer(config).pubkey(),
+                        recent_blockhash,
+                        config.compute_unit_limit,
+                    )
+                    .await
+            }
+            _ => config.compute_unit_limit,
+        }
+    }
+
+    fn compute_budget_instructions(compute_unit_limit: u32, priority_fee: u64) -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(2);
+        if compute_unit_limit > 0 {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+                compute_unit_limit,
+            ));
+        }
+        if priority_fee > 0 {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(priority_fee));
+        }
+        instructions
+    }
+
+    /// Current value and authority of the durable nonce `account`. Read at processed
+    /// commitment so a value the previous buy already advanced is not reused.
+    pub async fn durable_nonce(&self, account: &Pubkey) -> Result<DurableNonce, TransactionBuilderError> {
+        let fetched = nonce_utils::nonblocking::get_account_with_commitment(
+            &self.rpc_client_for(0),
+            account,
+            CommitmentConfig::processed(),
+        )
+        .await
+        .map_err(|e| TransactionBuilderError::RpcConnection(format!("nonce account {}: {}", account, e)))?;
+        let data = nonce_utils::nonblocking::data_from_account(&fetched)
+            .map_err(|e| TransactionBuilderError::BlockhashFetch(format!("nonce account {}: {}", account, e)))?;
+        Ok(DurableNonce {
+            account: *account,
+            authority: data.authority,
+            blockhash: data.blockhash(),
+        })
+    }
+
+    /// `advance_nonce_account`, which must be the first instruction of a durable nonce
+    /// transaction; empty without a nonce.
+    fn advance_nonce_instruction(
+        &self,
+        config: &TransactionConfig,
+    ) -> Result<Vec<Instruction>, TransactionBuilderError> {
+        let Some(nonce) = &config.durable_nonce else {
+            return Ok(Vec::new());
+        };
+        let signer = self.signer(config).pubkey();
+        if nonce.authority != signer {
+            return Err(TransactionBuilderError::ConfigValidation(format!(
+                "nonce account {} is controlled by {}, not the signing wallet {}",
+                nonce.account, nonce.authority, signer
+            )));
+        }
+        #[allow(deprecated)]
+        let advance = solana_sdk::system_instruction::advance_nonce_account(&nonce.account, &signer);
+        Ok(vec![advance])
+    }
+
+    /// Stop handing out `hash` after endpoints reported it unknown (`BlockhashNotFound`):
+    /// the next build fetches a fresh blockhash instead of reusing a cached one.
+    pub async fn invalidate_blockhash(&self, hash: Hash) {
+        *self.rejected_blockhash.write().await = Some(hash);
+        metrics().increment_counter("blockhash_invalidations");
+    }
+
     pub async fn get_recent_blockhash(
         &self,
         config: &TransactionConfig,
     ) -> Result<Hash, TransactionBuilderError> {
+        let rejected = *self.rejected_blockhash.read().await;
+
+        if let Some(service) = &self.blockhashes {
+            let info = match service.latest().await {
+                Ok(info) if Some(info.hash) == rejected => service.refresh().await,
+                other => other,
+            };
+            return info
+                .map(|info| info.hash)
+                .map_err(|e| TransactionBuilderError::BlockhashFetch(e.to_string()));
+        }
+
+        // A warm template blockhash is the freshest we have
+        if let Some(templates) = &self.templates {
+            if let Some(hash) = templates.fresh_blockhash().await.filter(|hash| Some(*hash) != rejected) {
+                return Ok(hash);
+            }
+        }
+
         // Check cache first
         {
             let cache = self.blockhash_cache.read().await;
             if let Some((instant, hash)) = cache.as_ref() {
-                if instant.elapsed() < self.blockhash_cache_ttl {
+                if instant.elapsed() < self.blockhash_cache_ttl && Some(*hash) != rejected {
                     return Ok(*hash);
                 }
             }
@@ -284,11 +605,16 @@ impl TransactionBuilder {
         let mut last_err = None;
         let attempts = config.rpc_retry_attempts.max(1);
 
+        // Rotate over endpoints the health checker has not demoted, if any
+        let order = rpc_pool().healthy_first(&self.rpc_endpoints);
+        let healthy = order
+            .iter()
+            .filter(|&&i| !rpc_pool().is_demoted(&self.rpc_endpoints[i]))
+            .count();
+        let rotation_len = if healthy == 0 { order.len() } else { healthy };
+
         for attempt in 0..attempts {
-            let index =
-                self.rpc_rotation_index
-                    .fetch_add(1, Ordering::Relaxed)
-                    % self.rpc_endpoints.len();
+            let index = order[self.rpc_rotation_index.fetch_add(1, Ordering::Relaxed) % rotation_len];
             let rpc_client = &self.rpc_clients[index];
 
             let retry_strategy = ExponentialBackoff::from_millis(50)
@@ -335,6 +661,7 @@ impl TransactionBuilder {
         sign: bool,
     ) -> Result<VersionedTransaction, TransactionBuilderError> {
         config.validate()?;
+        self.check_signer(config)?;
         info!(
             mint = %candidate.mint,
             program = %candidate.program,
@@ -348,37 +675,83 @@ impl TransactionBuilder {
             .await
             .map_err(|e| TransactionBuilderError::NonceAcquisition(e.to_string()))?;
 
-        let recent_blockhash = self.get_recent_blockhash(config).await?;
-
-        let mut instructions: Vec<Instruction> = Vec::with_capacity(4);
-
-        // Compute budget instructions
-        if config.compute_unit_limit > 0 {
-            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
-                config.compute_unit_limit,
-            ));
-        }
-        if config.priority_fee_lamports > 0 {
-            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
-                config.priority_fee_lamports,
-            ));
+        // Templated DEXes only need the mint-dependent accounts patched in. The token program
+        // comes from the create transaction, so the hot path does not read the mint; the mint
+        // policy is not consulted here because pump.fun fixes the extensions of every mint it
+        // creates.
+        if let Some(templates) = &self.templates {
+            if let Some(template) = templates
+                .template_for(&candidate.program)
+                .await
+                .filter(|t| t.payer == self.signer(config).pubkey())
+            {
+                let token_program = match candidate.token_program {
+                    Some(program) => program,
+                    None => self.mint_info(&candidate.mint).await?.token_program,
+                };
+                return self
+                    .build_templated_buy(&template, candidate, &token_program, config, sign)
+                    .await;
+            }
         }
 
-        // Build program-specific instruction
+        // Every other route touches the mint's token accounts: refuse Token-2022 mints the
+        // policy does not allow before building anything
         let dex_program = DexProgram::from(candidate.program.as_str());
-        let buy_instruction = match dex_program {
-            DexProgram::PumpFun => self.build_pumpfun_instruction(candidate, config).await,
-            DexProgram::LetsBonk => self.build_letsbonk_instruction(candidate, config).await,
-            DexProgram::Raydium => self.build_raydium_instruction(candidate, config).await,
+        if !matches!(dex_program, DexProgram::Unknown(_)) {
+            let info = self.mint_info(&candidate.mint).await?;
+            self.mint_policy.check(&info).map_err(|reason| {
+                metrics().increment_counter("mint_policy_rejections");
+                TransactionBuilderError::MintRejected { mint: candidate.mint, reason }
+            })?;
+        }
+
+        let recent_blockhash = match &config.durable_nonce {
+            Some(nonce) => nonce.blockhash,
+            None => self.get_recent_blockhash(config).await?,
+        };
+
+        // Build program-specific instructions (native swaps may include ATA/WSOL setup)
+        let mut lookup_tables = Vec::new();
+        let buy_instructions = match dex_program {
+            DexProgram::PumpFun => self.build_pumpfun_instruction(candidate, config).await.map(|ix| vec![ix]),
+            DexProgram::PumpSwap => self.build_pumpswap_instructions(candidate, config).await,
+            DexProgram::LetsBonk => self.build_letsbonk_instruction(candidate, config).await.map(|ix| vec![ix]),
+            DexProgram::Raydium => self.build_raydium_instructions(candidate, config).await,
             DexProgram::Orca => self.build_orca_instruction(candidate, config).await,
-            DexProgram::Unknown(_) => self.build_placeholder_buy_instruction(candidate, config).await,
+            DexProgram::Jupiter => self
+                .build_jupiter_instructions(WSOL_MINT, candidate.mint, config.buy_amount_lamports, config)
+                .await
+                .map(|(ixs, tables)| {
+                    lookup_tables = tables;
+                    ixs
+                }),
+            DexProgram::Unknown(_) => self
+                .build_placeholder_buy_instruction(candidate, config)
+                .await
+                .map(|ix| vec![ix]),
         }?;
 
-        instructions.push(buy_instruction);
+        // Compute budget instructions (fee scoped to the accounts the buy writes)
+        let priority_fee = self.resolve_priority_fee(&buy_instructions, config, true).await;
+        let compute_unit_limit = self
+            .resolve_compute_unit_limit(&candidate.program, &buy_instructions, config, recent_blockhash)
+            .await;
+        let mut instructions = self.advance_nonce_instruction(config)?;
+        instructions.extend(Self::compute_budget_instructions(compute_unit_limit, priority_fee));
+        instructions.extend(buy_instructions);
+        if let Some((tip_account, lamports)) = config.jito_tip {
+            #[allow(deprecated)]
+            instructions.push(solana_sdk::system_instruction::transfer(
+                &self.signer(config).pubkey(),
+                &tip_account,
+                lamports,
+            ));
+        }
 
         // Compile message (V0)
-        let payer = self.wallet.pubkey();
-        let message_v0 = MessageV0::try_compile(&payer, &instructions, &[], recent_blockhash)
+        let payer = self.signer(config).pubkey();
+        let message_v0 = MessageV0::try_compile(&payer, &instructions, &lookup_tables, recent_blockhash)
             .map_err(|e| TransactionBuilderError::InstructionBuild {
                 program: candidate.program.clone(),
                 reason: format!("Failed to compile message: {}", e),
@@ -391,16 +764,81 @@ impl TransactionBuilder {
         };
 
         if sign {
-            self.wallet
-                .sign_transaction(&mut tx)
-                .map_err(|e| TransactionBuilderError::SigningFailed(e.to_string()))?;
+            self.sign_guarded(&mut tx, config, buy_signing_policy(config), &lookup_tables)
+                .await?;
         } else {
             // Initialize with default signatures matching required number of signers
             let required = tx.message.header().num_required_signatures as usize;
             tx.signatures = vec![Signature::default(); required];
         }
 
-        debug!(mint = %candidate.mint, "Buy transaction built successfully");
+        debug!(mint = %candidate.mint, priority_fee, compute_unit_limit, "Buy transaction built successfully");
+        Ok(tx)
+    }
+
+    /// Buy from a pre-built template: no blockhash lookup, fee estimate or account derivation
+    /// beyond the mint-dependent accounts. A template blockhash endpoints rejected is replaced
+    /// by a fresh one so rebuilds after `BlockhashNotFound` do not resend the same hash.
+    async fn build_templated_buy(
+        &self,
+        template: &BuyTemplate,
+        candidate: &PremintCandidate,
+        token_program: &Pubkey,
+        config: &TransactionConfig,
+        sign: bool,
+    ) -> Result<VersionedTransaction, TransactionBuilderError> {
+        let buy_instructions = template.program_instructions(candidate, token_program, config);
+        if let Some(ix) = buy_instructions.iter().find(|ix| !config.is_program_allowed(&ix.program_id)) {
+            return Err(TransactionBuilderError::ProgramNotAllowed(ix.program_id));
+        }
+
+        let rejected = *self.rejected_blockhash.read().await;
+        let blockhash = match &config.durable_nonce {
+            Some(nonce) => nonce.blockhash,
+            None if rejected == Some(template.blockhash) => self.get_recent_blockhash(config).await?,
+            None => template.blockhash,
+        };
+
+        let priority_fee = (template.priority_fee as f64 * config.priority_fee_multiplier).round() as u64;
+        metrics().set_gauge("priority_fee_used_micro_lamports", priority_fee);
+        let compute_unit_limit = self
+            .resolve_compute_unit_limit(&candidate.program, &buy_instructions, config, blockhash)
+            .await;
+        let mut instructions = self.advance_nonce_instruction(config)?;
+        instructions.extend(Self::compute_budget_instructions(compute_unit_limit, priority_fee));
+        instructions.extend(buy_instructions);
+        if let Some((tip_account, lamports)) = config.jito_tip {
+            #[allow(deprecated)]
+            instructions.push(solana_sdk::system_instruction::transfer(
+                &self.signer(config).pubkey(),
+                &tip_account,
+                lamports,
+            ));
+        }
+
+        let message_v0 = MessageV0::try_compile(&template.payer, &instructions, &[], blockhash)
+            .map_err(|e| TransactionBuilderError::InstructionBuild {
+                program: candidate.program.clone(),
+                reason: format!("Failed to compile message: {}", e),
+            })?;
+        let mut tx = VersionedTransaction {
+            signatures: vec![],
+            message: VersionedMessage::V0(message_v0),
+        };
+        if sign {
+            self.sign_guarded(&mut tx, config, buy_signing_policy(config), &[]).await?;
+        } else {
+            let required = tx.message.header().num_required_signatures as usize;
+            tx.signatures = vec![Signature::default(); required];
+        }
+
+        metrics().increment_counter("tx_template_buys");
+        debug!(
+            mint = %candidate.mint,
+            template_age_ms = template.age().as_millis() as u64,
+            priority_fee,
+            "Templated buy transaction built"
+        );
         Ok(tx)
     }
 
@@ -413,6 +851,7 @@ impl TransactionBuilder {
         sign: bool,
     ) -> Result<VersionedTransaction, TransactionBuilderError> {
         config.validate()?;
+        self.check_signer(config)?;
         let sell_percent = sell_percent.clamp(0.0, 1.0);
         info!(mint = %mint, "Building sell transaction");
 
@@ -424,40 +863,65 @@ impl TransactionBuilder {
 
         let recent_blockhash = self.get_recent_blockhash(config).await?;
 
-        let mut instructions: Vec<Instruction> = Vec::new();
-
-        if config.compute_unit_limit > 0 {
-            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
-                config.compute_unit_limit,
-            ));
-        }
-        if config.priority_fee_lamports > 0 {
-            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
-                config.priority_fee_lamports,
-            ));
-        }
-
-        let dex_program = DexProgram::from(program);
-        let sell_instruction = match dex_program {
-            DexProgram::PumpFun => {
-                self.build_pumpfun_sell_instruction(mint, sell_percent, config).await
+        let dex_program = match DexProgram::from(program) {
+            DexProgram::PumpFun if self.is_migrated(mint).await => {
+                info!(mint = %mint, "Bonding curve complete; selling through PumpSwap");
+                DexProgram::PumpSwap
             }
-            DexProgram::LetsBonk => {
-                self.build_letsbonk_sell_instruction(mint, sell_percent, config).await
+            DexProgram::Unknown(name) if config.jupiter_api_url.is_some() => {
+                info!(mint = %mint, program = %name, "No native sell route; using Jupiter");
+                DexProgram::Jupiter
             }
-            DexProgram::Raydium => {
-                self.build_raydium_sell_instruction(mint, sell_percent, config).await
+            other => other,
+        };
+        let mut lookup_tables = Vec::new();
+        let mut sell_instructions = match dex_program {
+            DexProgram::PumpFun => self
+                .build_pumpfun_sell_instruction(mint, sell_percent, config)
+                .await
+                .map(|ix| vec![ix]),
+            DexProgram::PumpSwap => {
+                self.build_pumpswap_sell_instructions(mint, sell_percent, config).await
             }
-            DexProgram::Orca => self.build_orca_sell_instruction(mint, sell_percent, config).await,
-            DexProgram::Unknown(_) => {
-                self.build_placeholder_sell_instruction(mint, sell_percent, config).await
+            DexProgram::LetsBonk => self
+                .build_letsbonk_sell_instruction(mint, sell_percent, config)
+                .await
+                .map(|ix| vec![ix]),
+            DexProgram::Raydium => {
+                self.build_raydium_sell_instructions(mint, sell_percent, config).await
             }
+            DexProgram::Orca => self.build_orca_sell_instructions(mint, sell_percent, config).await,
+            DexProgram::Jupiter => self
+                .build_jupiter_sell_instructions(mint, sell_percent, config)
+                .await
+                .map(|(ixs, tables)| {
+                    lookup_tables = tables;
+                    ixs
+                }),
+            DexProgram::Unknown(_) => self
+                .build_placeholder_sell_instruction(mint, sell_percent, config)
+                .await
+                .map(|ix| vec![ix]),
         }?;
+        let native_route = matches!(
+            dex_program,
+            DexProgram::PumpFun | DexProgram::PumpSwap | DexProgram::Raydium | DexProgram::Orca | DexProgram::Jupiter
+        );
+        if sell_percent >= 1.0 && native_route && config.close_token_account_on_exit {
+            if let Some(close) = self.full_exit_close(mint, &sell_instructions, config).await {
+                sell_instructions.push(close);
+            }
+        }
 
-        instructions.push(sell_instruction);
+        let priority_fee = self.resolve_priority_fee(&sell_instructions, config, false).await;
+        let compute_unit_limit = self
+            .resolve_compute_unit_limit(program, &sell_instructions, config, recent_blockhash)
+            .await;
+        let mut instructions = Self::compute_budget_instructions(compute_unit_limit, priority_fee);
+        instructions.extend(sell_instructions);
 
-        let payer = self.wallet.pubkey();
-        let message_v0 = MessageV0::try_compile(&payer, &instructions, &[], recent_blockhash)
+        let payer = self.signer(config).pubkey();
+        let message_v0 = MessageV0::try_compile(&payer, &instructions, &lookup_tables, recent_blockhash)
             .map_err(|e| TransactionBuilderError::InstructionBuild {
                 program: program.to_string(),
                 reason: format!("Failed to compile sell message: {}", e),
@@ -470,15 +934,14 @@ impl TransactionBuilder {
         };
 
         if sign {
-            self.wallet
-                .sign_transaction(&mut tx)
-                .map_err(|e| TransactionBuilderError::SigningFailed(e.to_string()))?;
+            self.sign_guarded(&mut tx, config, exit_signing_policy(), &lookup_tables)
+                .await?;
         } else {
             let required = tx.message.header().num_required_signatures as usize;
             tx.signatures = vec![Signature::default(); required];
         }
 
-        debug!(mint = %mint, "Sell transaction built successfully");
+        debug!(mint = %mint, priority_fee, compute_unit_limit, "Sell transaction built successfully");
         Ok(tx)
     }
 
@@ -495,9 +958,10 @@ impl TransactionBuilder {
         }
     }
 
+    /// Client of the `idx`-th endpoint, endpoints demoted by the health checker counted last.
     pub fn rpc_client_for(&self, idx: usize) -> Arc<RpcClient> {
-        let index = idx % self.rpc_clients.len();
-        self.rpc_clients[index].clone()
+        let order = rpc_pool().healthy_first(&self.rpc_endpoints);
+        self.rpc_clients[order[idx % order.len()]].clone()
     }
 
     // --- Instruction builders ---
@@ -507,8 +971,9 @@ impl TransactionBuilder {
         candidate: &PremintCandidate,
         config: &TransactionConfig,
     ) -> Result<Instruction, TransactionBuilderError> {
+        // The SDK client is bound to the builder's own wallet
         #[cfg(feature = "pumpfun")]
-        {
+        if self.signer(config).pubkey() == self.wallet.pubkey() {
             // Pobierz bonding curve do obliczeń slippage
             let bonding_curve = self
                 .pumpfun_client
@@ -569,136 +1034,146 @@ impl TransactionBuilder {
                 "mint": candidate.mint.to_string(),
                 "amount": config.buy_amount_lamports,
                 "slippage": config.slippage_bps as f64 / 100.0,
-                "payer": self.wallet.pubkey().to_string(),
+                "payer": self.signer(config).pubkey().to_string(),
             });
 
-            let mut req = self.http.post(url).json(&payload);
-            if let Some(k) = &config.letsbonk_api_key {
-                req = req.header("X-API-KEY", k);
-            }
-
-            match req.send().await {
-                Ok(resp) if resp.status().is_success() => {
-                    let j: serde_json::Value = resp.json().await.map_err(|e| {
-                        TransactionBuilderError::InstructionBuild {
-                            program: "letsbonk".to_string(),
-                            reason: format!("JSON parse error: {}", e),
-                        }
-                    })?;
-                    return self.parse_external_api_response(&j, "letsbonk", config);
-                }
-                Ok(resp) => {
-                    warn!("LetsBonk API error: {}", resp.status());
-                }
-                Err(e) => {
-                    warn!("LetsBonk request error: {}", e);
-                }
+            match self.post_letsbonk(url, &payload, config).await {
+                Ok(j) => return self.parse_external_api_response(&j, "letsbonk", config),
+                Err(e) => warn!("LetsBonk buy request failed: {}", e),
             }
         }
 
         self.build_placeholder_buy_instruction(candidate, config).await
     }
 
-    async fn build_raydium_instruction(
+    /// POST to the LetsBonk provider, retrying transport errors, 429 and 5xx with backoff.
+    /// Other 4xx responses are returned immediately.
+    async fn post_letsbonk(
         &self,
-        _candidate: &PremintCandidate,
-        _config: &TransactionConfig,
-    ) -> Result<Instruction, TransactionBuilderError> {
-        #[cfg(feature = "raydium")]
-        {
-            let sol_mint =
-                Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
-            let raydium_client = AmmSwapClient::new(
-                self.rpc_client_for(0).clone(),
-                sol_mint,
-                candidate.mint,
-                self.wallet.clone(),
-            );
-
-            let expected_tokens = raydium_client
-                .get_swap_amount_out(config.buy_amount_lamports, true) // true = SOL -> token
-                .await
-                .map_err(|e| TransactionBuilderError::InstructionBuild {
-                    program: "raydium".to_string(),
-                    reason: e.to_string(),
-                })?;
-
-            let min_token_out = ((expected_tokens as u128)
-                * (10000u128 - config.slippage_bps as u128)
-                / 10000u128) as u64;
+        url: &str,
+        payload: &serde_json::Value,
+        config: &TransactionConfig,
+    ) -> Result<serde_json::Value, TransactionBuilderError> {
+        enum ProviderFailure {
+            Transient(String),
+            Rejected(String),
+        }
 
-            let tx = raydium_client
-                .swap(config.buy_amount_lamports, min_token_out, true)
-                .await
-                .map_err(|e| TransactionBuilderError::InstructionBuild {
-                    program: "raydium".to_string(),
-                    reason: e.to_string(),
-                })?;
+        let attempts = config.rpc_retry_attempts.max(1);
+        let retry_strategy = ExponentialBackoff::from_millis(50)
+            .max_delay(Duration::from_millis(1000))
+            .map(jitter)
+            .take(attempts - 1);
+
+        let result = RetryIf::spawn(
+            retry_strategy,
+            || async {
+                let mut req = self.http.post(url).json(payload);
+                if let Some(k) = &config.letsbonk_api_key {
+                    req = req.header("X-API-KEY", k);
+                }
+                let resp = req
+                    .send()
+                    .await
+                    .map_err(|e| ProviderFailure::Transient(e.to_string()))?;
+                let status = resp.status();
+                if status.is_success() {
+                    resp.json::<serde_json::Value>()
+                        .await
+                        .map_err(|e| ProviderFailure::Rejected(format!("JSON parse error: {}", e)))
+                } else if status.is_server_error() || status.as_u16() == 429 {
+                    debug!(status = %status, "LetsBonk provider error; retrying");
+                    Err(ProviderFailure::Transient(format!("HTTP {}", status)))
+                } else {
+                    Err(ProviderFailure::Rejected(format!("HTTP {}", status)))
+                }
+            },
+            |e: &ProviderFailure| matches!(e, ProviderFailure::Transient(_)),
+        )
+        .await;
 
-            if let Some(ix) = tx.message.instructions.last() {
-                return Ok(ix.clone());
-            } else {
-                return Err(TransactionBuilderError::InstructionBuild {
-                    program: "raydium".to_string(),
-                    reason: "No instruction in tx".to_string(),
-                });
+        result.map_err(|e| {
+            let reason = match e {
+                ProviderFailure::Transient(e) => {
+                    metrics().increment_counter("letsbonk_provider_unavailable");
+                    format!("provider unavailable after {} attempts: {}", attempts, e)
+                }
+                ProviderFailure::Rejected(e) => format!("provider rejected request: {}", e),
+            };
+            TransactionBuilderError::InstructionBuild {
+                program: "letsbonk".to_string(),
+                reason,
             }
-        }
+        })
+    }
 
-        #[cfg(not(feature = "raydium"))]
-        {
-            Err(TransactionBuilderError::FeatureNotEnabled {
-                feature: "raydium".to_string(),
-                action: "Raydium buy instruction".to_string(),
-            })
-        }
+    /// Native Raydium buy: picks the deepest SOL-paired AMM v4 or CPMM pool and swaps
+    /// `buy_amount_lamports` of wrapped SOL into the token.
+    async fn build_raydium_instructions(
+        &self,
+        candidate: &PremintCandidate,
+        config: &TransactionConfig,
+    ) -> Result<Vec<Instruction>, TransactionBuilderError> {
+        let raydium_err = |e: anyhow::Error| TransactionBuilderError::InstructionBuild {
+            program: "raydium".to_string(),
+            reason: e.to_string(),
+        };
+        let client = RaydiumClient::new(self.rpc_client_for(0));
+        let pool = client.find_pool(&candidate.mint).await.map_err(raydium_err)?;
+        let mint_info = self.mint_info(&candidate.mint).await?;
+        let (instructions, expected_tokens) = RaydiumClient::swap_instructions(
+            &pool,
+            &self.signer(config).pubkey(),
+            &candidate.mint,
+            SwapSide::Buy,
+            config.buy_amount_lamports,
+            config.slippage_bps,
+            mint_info.transfer_fee.as_ref(),
+        )
+        .map_err(raydium_err)?;
+        debug!(mint = %candidate.mint, pool = %pool.address(), expected_tokens, "Raydium buy quoted");
+        Ok(instructions)
+    }
+
+    /// PumpSwap buy for mints that already trade on the pump.fun AMM.
+    async fn build_pumpswap_instructions(
+        &self,
+        candidate: &PremintCandidate,
+        config: &TransactionConfig,
+    ) -> Result<Vec<Instruction>, TransactionBuilderError> {
+        let pumpswap_err = |e: anyhow::Error| TransactionBuilderError::InstructionBuild {
+            program: "pumpswap".to_string(),
+            reason: e.to_string(),
+        };
+        let market = PumpSwapClient::new(self.rpc_client_for(0))
+            .find_market(&candidate.mint)
+            .await
+            .map_err(pumpswap_err)?;
+        let (instructions, expected_tokens) = market
+            .swap_instructions(
+                &self.signer(config).pubkey(),
+                SwapSide::Buy,
+                config.buy_amount_lamports,
+                config.slippage_bps,
+            )
+            .map_err(pumpswap_err)?;
+        debug!(mint = %candidate.mint, pool = %market.pool.address, expected_tokens, "PumpSwap buy quoted");
+        Ok(instructions)
     }
 
+    #[cfg_attr(not(feature = "orca"), allow(unused_variables))]
     async fn build_orca_instruction(
         &self,
-        _candidate: &PremintCandidate,
-        _config: &TransactionConfig,
-    ) -> Result<Instruction, TransactionBuilderError> {
+        candidate: &PremintCandidate,
+        config: &TransactionConfig,
+    ) -> Result<Vec<Instruction>, TransactionBuilderError> {
         #[cfg(feature = "orca")]
         {
-            let client = WhirlpoolClient::new(self.rpc_client_for(0).clone());
-            let whirlpool_address = client.derive_whirlpool_pda(
-                Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap(),
-                candidate.mint,
-            );
-            let whirlpool = client
-                .get_whirlpool(&whirlpool_address)
-                .await
-                .map_err(|e| TransactionBuilderError::InstructionBuild {
-                    program: "orca".to_string(),
-                    reason: e.to_string(),
-                })?;
-
-            let quote = client
-                .swap_quote_a_to_b(config.buy_amount_lamports, false, &whirlpool) // false => exact in
-                .await
-                .map_err(|e| TransactionBuilderError::InstructionBuild {
-                    program: "orca".to_string(),
-                    reason: e.to_string(),
-                })?;
-
-            let min_token_out = ((quote.amount_out as u128)
-                * (10000u128 - config.slippage_bps as u128)
-                / 10000u128) as u64;
-
-            let swap_input = SwapInput {
-                amount: config.buy_amount_lamports,
-                other_amount_threshold: min_token_out,
-                sqrt_price_limit: quote.sqrt_price_limit,
-                amount_specified_is_input: true,
-                a_to_b: true,
-            };
-
-            let ix = client
-                .build_swap_ix(&whirlpool_address, &swap_input, &self.wallet.pubkey())
-                .instruction;
-
-            Ok(ix)
+            let (instructions, min_token_out) = self
+                .build_orca_swap(&candidate.mint, WSOL_MINT, config.buy_amount_lamports, config)
+                .await?;
+            debug!(mint = %candidate.mint, min_token_out, "Orca buy quoted");
+            Ok(instructions)
         }
 
         #[cfg(not(feature = "orca"))]
@@ -710,6 +1185,66 @@ impl TransactionBuilder {
         }
     }
 
+    /// Exact-in swap of `amount` `input_mint` through the deepest SOL/`mint` Whirlpool.
+    /// The SDK resolves tick arrays and derives the minimum output from its quote.
+    #[cfg(feature = "orca")]
+    async fn build_orca_swap(
+        &self,
+        mint: &Pubkey,
+        input_mint: Pubkey,
+        amount: u64,
+        config: &TransactionConfig,
+    ) -> Result<(Vec<Instruction>, u64), TransactionBuilderError> {
+        let orca_err = |reason: String| TransactionBuilderError::InstructionBuild {
+            program: "orca".to_string(),
+            reason,
+        };
+        let rpc = self.rpc_client_for(0);
+        // The SDK's pool lookup holds a std mutex guard across an await (its future is not
+        // Send), so drive it to completion on a blocking thread.
+        let lookup_rpc = rpc.clone();
+        let token = *mint;
+        let pools = tokio::task::spawn_blocking(move || {
+            tokio::runtime::Handle::current()
+                .block_on(fetch_whirlpools_by_token_pair(&lookup_rpc, WSOL_MINT, token))
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| orca_err(e.to_string()))?
+        .map_err(orca_err)?;
+        let whirlpool = pools
+            .into_iter()
+            .filter_map(|p| match p {
+                PoolInfo::Initialized(pool) if pool.data.liquidity > 0 => Some(pool),
+                _ => None,
+            })
+            .max_by_key(|pool| pool.data.liquidity)
+            .ok_or_else(|| orca_err(format!("no initialized SOL Whirlpool for {}", mint)))?;
+
+        let slippage_bps = u16::try_from(config.slippage_bps).unwrap_or(u16::MAX);
+        let swap = swap_instructions(
+            &rpc,
+            whirlpool.address,
+            amount,
+            input_mint,
+            SwapType::ExactIn,
+            Some(slippage_bps),
+            Some(self.signer(config).pubkey()),
+        )
+        .await
+        .map_err(|e| orca_err(e.to_string()))?;
+
+        // Only the wallet signs; the ATA wrapping strategy avoids auxiliary keypairs
+        if !swap.additional_signers.is_empty() {
+            return Err(orca_err("swap requires additional signers".to_string()));
+        }
+        let min_out = match swap.quote {
+            SwapQuote::ExactIn(quote) => quote.token_min_out,
+            SwapQuote::ExactOut(_) => return Err(orca_err("unexpected exact-out quote".to_string())),
+        };
+        Ok((swap.instructions, min_out))
+    }
+
     async fn build_pumpportal_or_memo(
         &self,
         candidate: &PremintCandidate,
@@ -720,7 +1255,7 @@ impl TransactionBuilder {
                 "mint": candidate.mint.to_string(),
                 "buy_amount": config.buy_amount_lamports,
                 "slippage": config.slippage_bps as f64 / 100.0,
-                "payer": self.wallet.pubkey().to_string(),
+                "payer": self.signer(config).pubkey().to_string(),
             });
 
             let mut req = self.http.post(url).json(&payload);
@@ -805,9 +1340,9 @@ impl TransactionBuilder {
 
                 // Parse accounts if provided, otherwise use default (payer as readonly)
                 let accounts = if let Some(accounts_val) = obj.get("accounts") {
-                    self.parse_accounts(accounts_val, api_name)?
+                    self.parse_accounts(accounts_val, api_name, config)?
                 } else {
-                    vec![AccountMeta::new_readonly(self.wallet.pubkey(), false)]
+                    vec![AccountMeta::new_readonly(self.signer(config).pubkey(), false)]
                 };
 
                 return Ok(Instruction::new_with_bytes(pid, &data, accounts));
@@ -835,7 +1370,7 @@ impl TransactionBuilder {
                 }
 
                 // For legacy format, we can't determine program_id, so use memo as fallback
-                return Ok(spl_memo::build_memo(&data, &[&self.wallet.pubkey()]));
+                return Ok(spl_memo::build_memo(&data, &[&self.signer(config).pubkey()]));
             }
         }
 
@@ -845,12 +1380,13 @@ impl TransactionBuilder {
         })
     }
 
-    /// Parse account metas from JSON; rejects unexpected signers (signers other than wallet).
+    /// Parse account metas from JSON; rejects unexpected signers (signers other than the signing wallet).
     /// Exposed as public to enable integration testing from bot/tests.
     pub fn parse_accounts(
         &self,
         accounts_val: &serde_json::Value,
         api_name: &str,
+        config: &TransactionConfig,
     ) -> Result<Vec<AccountMeta>, TransactionBuilderError> {
         let accounts_array = accounts_val.as_array().ok_or_else(|| {
             TransactionBuilderError::InstructionBuild {
@@ -894,7 +1430,7 @@ impl TransactionBuilder {
                 .unwrap_or(false);
 
             // Reject unexpected signer accounts
-            if is_signer && pubkey != self.wallet.pubkey() {
+            if is_signer && pubkey != self.signer(config).pubkey() {
                 return Err(TransactionBuilderError::InstructionBuild {
                     program: api_name.to_string(),
                     reason: format!("unexpected signer account: {}", pubkey),
@@ -923,7 +1459,7 @@ impl TransactionBuilder {
         );
         Ok(spl_memo::build_memo(
             memo_data.as_bytes(),
-            &[&self.wallet.pubkey()],
+            &[&self.signer(config).pubkey()],
         ))
     }
 
@@ -931,13 +1467,13 @@ impl TransactionBuilder {
         &self,
         mint: &Pubkey,
         sell_percent: f64,
-        _config: &TransactionConfig,
+        config: &TransactionConfig,
     ) -> Result<Instruction, TransactionBuilderError> {
         debug!(mint = %mint, "Creating placeholder sell memo");
         let memo_data = format!("PLACEHOLDER_SELL:{}:{:.6}", mint, sell_percent);
         Ok(spl_memo::build_memo(
             memo_data.as_bytes(),
-            &[&self.wallet.pubkey()],
+            &[&self.signer(config).pubkey()],
         ))
     }
 
@@ -948,9 +1484,15 @@ impl TransactionBuilder {
         sell_percent: f64,
         config: &TransactionConfig,
     ) -> Result<Instruction, TransactionBuilderError> {
+        // The SDK client is bound to the builder's own wallet
         #[cfg(feature = "pumpfun")]
-        {
-            let ata = get_associated_token_address(&self.wallet.pubkey(), mint);
+        if self.signer(config).pubkey() == self.wallet.pubkey() {
+            let token_program = self.mint_info(mint).await?.token_program;
+            let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+                &self.wallet.pubkey(),
+                mint,
+                &token_program,
+            );
             let token_balance = self
                 .pumpfun_client
                 .get_token_balance(ata)
@@ -960,7 +1502,7 @@ impl TransactionBuilder {
                     reason: e.to_string(),
                 })?
                 .unwrap_or(0);
-            let sell_amount = ((token_balance as f64) * sell_percent) as u64;
+            let sell_amount = sell_token_amount(token_balance, sell_percent);
 
             let bonding_curve = self
                 .pumpfun_client
@@ -1003,34 +1545,391 @@ impl TransactionBuilder {
             .await
     }
 
+    /// Close of the payer's token account for `mint`, appended to a sell of the whole balance.
+    /// Only when the sell instructions spend from that account (placeholder memos do not) and
+    /// the mint charges no transfer fee: withheld fees keep Token-2022 accounts open, and a
+    /// failing close would revert the sell. Anything skipped here is left to the sweep.
+    async fn full_exit_close(
+        &self,
+        mint: &Pubkey,
+        sell_instructions: &[Instruction],
+        config: &TransactionConfig,
+    ) -> Option<Instruction> {
+        let info = self.mint_info(mint).await.ok()?;
+        if info.transfer_fee.is_some() {
+            debug!(mint = %mint, "Transfer fee mint; leaving token account for the sweep");
+            return None;
+        }
+        let owner = self.signer(config).pubkey();
+        let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &owner,
+            mint,
+            &info.token_program,
+        );
+        let spends_ata = sell_instructions
+            .iter()
+            .any(|ix| ix.accounts.iter().any(|meta| meta.pubkey == ata && meta.is_writable));
+        if !spends_ata {
+            return None;
+        }
+        metrics().increment_counter("token_account_closes_on_exit");
+        Some(close_token_account_instruction(&info.token_program, &ata, &owner))
+    }
+
+    /// Close every empty token account of the wallet selected by `config`, in batches of
+    /// `sweep.max_closes_per_tx`. Failed batches are logged and skipped; the accounts closed
+    /// by confirmed batches are returned.
+    pub async fn sweep_empty_token_accounts(
+        &self,
+        config: &TransactionConfig,
+        sweep: &AccountSweepConfig,
+    ) -> Result<Vec<EmptyTokenAccount>, TransactionBuilderError> {
+        self.check_signer(config)?;
+        let owner = self.signer(config).pubkey();
+        let rpc = self.rpc_client_for(0);
+        let empty = find_empty_token_accounts(&rpc, &owner)
+            .await
+            .map_err(|e| TransactionBuilderError::RpcConnection(e.to_string()))?;
+        if empty.is_empty() {
+            debug!(owner = %owner, "No empty token accounts to close");
+            return Ok(Vec::new());
+        }
+
+        let mut closed = Vec::new();
+        for batch in empty.chunks(sweep.max_closes_per_tx.max(1)) {
+            let instructions: Vec<Instruction> = batch
+                .iter()
+                .map(|account| close_token_account_instruction(&account.token_program, &account.address, &owner))
+                .collect();
+            let recent_blockhash = self.get_recent_blockhash(config).await?;
+            let message_v0 = MessageV0::try_compile(&owner, &instructions, &[], recent_blockhash)
+                .map_err(|e| TransactionBuilderError::InstructionBuild {
+                    program: "token_sweep".to_string(),
+                    reason: format!("Failed to compile message: {}", e),
+                })?;
+            let mut tx = VersionedTransaction {
+                signatures: vec![],
+                message: VersionedMessage::V0(message_v0),
+            };
+            // Closing only returns rent to the owner
+            self.sign_guarded(&mut tx, config, SigningPolicy::max_lamports_out(0), &[])
+                .await?;
+
+            match rpc.send_and_confirm_transaction(&tx).await {
+                Ok(signature) => {
+                    let lamports: u64 = batch.iter().map(|account| account.lamports).sum();
+                    info!(owner = %owner, %signature, accounts = batch.len(), lamports, "Closed empty token accounts");
+                    metrics().add_to_counter("token_accounts_closed", batch.len() as u64);
+                    metrics().add_to_counter("token_account_rent_reclaimed_lamports", lamports);
+                    closed.extend_from_slice(batch);
+                }
+                Err(e) => warn!(owner = %owner, accounts = batch.len(), "Token account sweep batch failed: {}", e),
+            }
+        }
+        Ok(closed)
+    }
+
+    /// Sweep every wallet: the primary one, or each wallet of the pool when one is attached.
+    pub async fn sweep_all_wallets(
+        &self,
+        config: &TransactionConfig,
+        sweep: &AccountSweepConfig,
+    ) -> Vec<EmptyTokenAccount> {
+        let indexes: Vec<Option<usize>> = match &self.wallet_pool {
+            Some(pool) => (0..pool.len()).map(Some).collect(),
+            None => vec![None],
+        };
+        let mut closed = Vec::new();
+        for index in indexes {
+            let config = TransactionConfig {
+                signer_keypair_index: index,
+                ..config.clone()
+            };
+            match self.sweep_empty_token_accounts(&config, sweep).await {
+                Ok(accounts) => closed.extend(accounts),
+                Err(e) => warn!(wallet = ?index, "Token account sweep failed: {}", e),
+            }
+        }
+        closed
+    }
+
+    /// Whether a pump.fun mint has graduated (false when no tracker is attached).
+    async fn is_migrated(&self, mint: &Pubkey) -> bool {
+        match &self.migration_tracker {
+            Some(tracker) => tracker.is_migrated(mint).await,
+            None => false,
+        }
+    }
+
+    /// PumpSwap sell of `sell_percent` of the token balance, used once the curve migrated.
+    async fn build_pumpswap_sell_instructions(
+        &self,
+        mint: &Pubkey,
+        sell_percent: f64,
+        config: &TransactionConfig,
+    ) -> Result<Vec<Instruction>, TransactionBuilderError> {
+        let pumpswap_err = |e: anyhow::Error| TransactionBuilderError::InstructionBuild {
+            program: "pumpswap".to_string(),
+            reason: e.to_string(),
+        };
+        let market = PumpSwapClient::new(self.rpc_client_for(0))
+            .find_market(mint)
+            .await
+            .map_err(pumpswap_err)?;
+
+        let owner = self.signer(config).pubkey();
+        let balance = self
+            .owned_token_amount(&self.signer(config).pubkey(), mint, &market.base_mint_info.token_program)
+            .await
+            .map_err(pumpswap_err)?;
+        let sell_amount = sell_token_amount(balance, sell_percent);
+        if sell_amount == 0 {
+            return Err(pumpswap_err(anyhow!("nothing to sell ({} held)", balance)));
+        }
+
+        let (instructions, expected_sol) = market
+            .swap_instructions(&owner, SwapSide::Sell, sell_amount, config.slippage_bps)
+            .map_err(pumpswap_err)?;
+        debug!(mint = %mint, pool = %market.pool.address, sell_amount, expected_sol, "PumpSwap sell quoted");
+        Ok(instructions)
+    }
+
+    /// Sell through the LetsBonk provider. Unlike buys, provider failures are returned as
+    /// errors instead of degrading to a memo, so a failed exit is never reported as sent.
     async fn build_letsbonk_sell_instruction(
         &self,
         mint: &Pubkey,
         sell_percent: f64,
         config: &TransactionConfig,
     ) -> Result<Instruction, TransactionBuilderError> {
-        self.build_placeholder_sell_instruction(mint, sell_percent, config)
+        let Some(url) = &config.letsbonk_api_url else {
+            return self
+                .build_placeholder_sell_instruction(mint, sell_percent, config)
+                .await;
+        };
+
+        // Prefer an exact token amount; let the provider resolve the percent if the
+        // balance cannot be read
+        let balance = match self.mint_info(mint).await {
+            Ok(info) => self
+                .owned_token_amount(&self.signer(config).pubkey(), mint, &info.token_program)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let token_amount = match balance {
+            Ok(balance) => Some(sell_token_amount(balance, sell_percent)),
+            Err(e) => {
+                debug!(mint = %mint, "Token balance unavailable, selling by percent: {}", e);
+                None
+            }
+        };
+
+        let payload = serde_json::json!({
+            "mint": mint.to_string(),
+            "side": "sell",
+            "token_amount": token_amount,
+            "percent": sell_percent * 100.0,
+            "min_sol_out": config.sell_min_sol_out_lamports,
+            "slippage": config.slippage_bps as f64 / 100.0,
+            "payer": self.signer(config).pubkey().to_string(),
+        });
+
+        let j = self.post_letsbonk(url, &payload, config).await?;
+        self.parse_external_api_response(&j, "letsbonk", config)
+    }
+
+    /// Native Raydium sell: swaps `sell_percent` of the token balance back to SOL and
+    /// closes the temporary WSOL account.
+    async fn build_raydium_sell_instructions(
+        &self,
+        mint: &Pubkey,
+        sell_percent: f64,
+        config: &TransactionConfig,
+    ) -> Result<Vec<Instruction>, TransactionBuilderError> {
+        let raydium_err = |e: anyhow::Error| TransactionBuilderError::InstructionBuild {
+            program: "raydium".to_string(),
+            reason: e.to_string(),
+        };
+        let client = RaydiumClient::new(self.rpc_client_for(0));
+        let pool = client.find_pool(mint).await.map_err(raydium_err)?;
+
+        let owner = self.signer(config).pubkey();
+        let balance = self
+            .owned_token_amount(&self.signer(config).pubkey(), mint, &pool.token_program_for(mint))
             .await
+            .map_err(raydium_err)?;
+        let sell_amount = sell_token_amount(balance, sell_percent);
+        if sell_amount == 0 {
+            return Err(raydium_err(anyhow!("nothing to sell ({} held)", balance)));
+        }
+
+        let mint_info = self.mint_info(mint).await?;
+        let (instructions, expected_sol) = RaydiumClient::swap_instructions(
+            &pool,
+            &owner,
+            mint,
+            SwapSide::Sell,
+            sell_amount,
+            config.slippage_bps,
+            mint_info.transfer_fee.as_ref(),
+        )
+        .map_err(raydium_err)?;
+        debug!(mint = %mint, pool = %pool.address(), sell_amount, expected_sol, "Raydium sell quoted");
+        Ok(instructions)
     }
 
-    async fn build_raydium_sell_instruction(
+    /// Orca Whirlpool sell of `sell_percent` of the token balance into SOL.
+    #[cfg_attr(not(feature = "orca"), allow(unused_variables))]
+    async fn build_orca_sell_instructions(
         &self,
         mint: &Pubkey,
         sell_percent: f64,
         config: &TransactionConfig,
-    ) -> Result<Instruction, TransactionBuilderError> {
-        self.build_placeholder_sell_instruction(mint, sell_percent, config)
+    ) -> Result<Vec<Instruction>, TransactionBuilderError> {
+        #[cfg(feature = "orca")]
+        {
+            let token_program = self.mint_info(mint).await?.token_program;
+            let balance = self
+                .owned_token_amount(&self.signer(config).pubkey(), mint, &token_program)
+                .await
+                .map_err(|e| TransactionBuilderError::InstructionBuild {
+                    program: "orca".to_string(),
+                    reason: e.to_string(),
+                })?;
+            let sell_amount = sell_token_amount(balance, sell_percent);
+            if sell_amount == 0 {
+                return Err(TransactionBuilderError::InstructionBuild {
+                    program: "orca".to_string(),
+                    reason: format!("nothing to sell ({} held)", balance),
+                });
+            }
+
+            let (instructions, min_sol_out) = self.build_orca_swap(mint, *mint, sell_amount, config).await?;
+            debug!(mint = %mint, sell_amount, min_sol_out, "Orca sell quoted");
+            Ok(instructions)
+        }
+
+        #[cfg(not(feature = "orca"))]
+        {
+            Err(TransactionBuilderError::FeatureNotEnabled {
+                feature: "orca".to_string(),
+                action: "Orca sell instruction".to_string(),
+            })
+        }
+    }
+
+    /// Route a swap through the configured Jupiter-compatible API. Jupiter's compute budget
+    /// instructions are dropped (ours are prepended later); its lookup tables are fetched.
+    async fn build_jupiter_instructions(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+        config: &TransactionConfig,
+    ) -> Result<(Vec<Instruction>, Vec<AddressLookupTableAccount>), TransactionBuilderError> {
+        let jupiter_err = |reason: String| TransactionBuilderError::InstructionBuild {
+            program: "jupiter".to_string(),
+            reason,
+        };
+        let url = config
+            .jupiter_api_url
+            .as_deref()
+            .ok_or_else(|| jupiter_err("jupiter_api_url is not configured".to_string()))?;
+
+        let route = JupiterClient::new(self.http.clone(), url)
+            .route(&input_mint, &output_mint, amount, config.slippage_bps, &self.signer(config).pubkey())
+            .await
+            .map_err(|e| jupiter_err(e.to_string()))?;
+        if !config.is_program_allowed(&route.swap_program) {
+            return Err(TransactionBuilderError::ProgramNotAllowed(route.swap_program));
+        }
+
+        let lookup_tables = fetch_lookup_tables(&self.rpc_client_for(0), &route.lookup_table_addresses)
             .await
+            .map_err(|e| jupiter_err(e.to_string()))?;
+        debug!(
+            input = %input_mint,
+            output = %output_mint,
+            amount,
+            out_amount = route.out_amount,
+            min_out_amount = route.min_out_amount,
+            lookup_tables = lookup_tables.len(),
+            "Jupiter route quoted"
+        );
+        Ok((route.instructions, lookup_tables))
     }
 
-    async fn build_orca_sell_instruction(
+    /// Jupiter sell of `sell_percent` of the token balance into SOL.
+    async fn build_jupiter_sell_instructions(
         &self,
         mint: &Pubkey,
         sell_percent: f64,
         config: &TransactionConfig,
-    ) -> Result<Instruction, TransactionBuilderError> {
-        self.build_placeholder_sell_instruction(mint, sell_percent, config)
+    ) -> Result<(Vec<Instruction>, Vec<AddressLookupTableAccount>), TransactionBuilderError> {
+        let token_program = self.mint_info(mint).await?.token_program;
+        let balance = self
+            .owned_token_amount(&self.signer(config).pubkey(), mint, &token_program)
             .await
+            .map_err(|e| TransactionBuilderError::InstructionBuild {
+                program: "jupiter".to_string(),
+                reason: e.to_string(),
+            })?;
+        let sell_amount = sell_token_amount(balance, sell_percent);
+        if sell_amount == 0 {
+            return Err(TransactionBuilderError::InstructionBuild {
+                program: "jupiter".to_string(),
+                reason: format!("nothing to sell ({} held)", balance),
+            });
+        }
+        self.build_jupiter_instructions(*mint, WSOL_MINT, sell_amount, config)
+            .await
+    }
+
+    /// Token program (SPL Token or Token-2022) and extensions of `mint`, cached per mint.
+    /// Read at processed commitment so freshly created mints resolve.
+    async fn mint_info(&self, mint: &Pubkey) -> Result<MintInfo, TransactionBuilderError> {
+        if let Some(info) = self.mints.read().await.get(mint) {
+            return Ok(info.clone());
+        }
+        let account = self
+            .rpc_client_for(0)
+            .get_account_with_commitment(mint, CommitmentConfig::processed())
+            .await
+            .map_err(|e| TransactionBuilderError::RpcConnection(e.to_string()))?
+            .value
+            .ok_or_else(|| TransactionBuilderError::RpcConnection(format!("mint {} not found", mint)))?;
+        let info = MintInfo::decode(&account.owner, &account.data).map_err(|e| {
+            TransactionBuilderError::InstructionBuild {
+                program: "token".to_string(),
+                reason: format!("mint {}: {}", mint, e),
+            }
+        })?;
+        if info.is_token_2022() {
+            debug!(mint = %mint, ?info, "Token-2022 mint");
+        }
+        self.mints.write().await.insert(*mint, info.clone());
+        Ok(info)
+    }
+
+    /// Balance of `owner`'s associated token account for `mint`.
+    async fn owned_token_amount(
+        &self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> anyhow::Result<u64> {
+        let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+            owner,
+            mint,
+            token_program,
+        );
+        let data = self
+            .rpc_client_for(0)
+            .get_account_data(&ata)
+            .await
+            .map_err(|e| anyhow!("token account {}: {}", ata, e))?;
+        token_account_amount(&data)
     }
 
     /// Unwrap WSOL ATA back to native SOL
@@ -1038,14 +1937,15 @@ impl TransactionBuilder {
         &self,
         config: &TransactionConfig,
     ) -> Result<Signature, TransactionBuilderError> {
+        self.check_signer(config)?;
         let wsol_mint = Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
-        let wsol_ata = get_associated_token_address(&self.wallet.pubkey(), &wsol_mint);
+        let wsol_ata = get_associated_token_address(&self.signer(config).pubkey(), &wsol_mint);
 
         let close_ix = close_account(
             &token_program_id(),
             &wsol_ata,
-            &self.wallet.pubkey(),
-            &self.wallet.pubkey(),
+            &self.signer(config).pubkey(),
+            &self.signer(config).pubkey(),
             &[],
         )
         .map_err(|e| TransactionBuilderError::InstructionBuild {
@@ -1056,7 +1956,7 @@ impl TransactionBuilder {
         let recent_blockhash = self.get_recent_blockhash(config).await?;
 
         let instructions = vec![close_ix];
-        let payer = self.wallet.pubkey();
+        let payer = self.signer(config).pubkey();
         let message_v0 = MessageV0::try_compile(&payer, &instructions, &[], recent_blockhash)
             .map_err(|e| TransactionBuilderError::InstructionBuild {
                 program: "unwrap_wsol".to_string(),
@@ -1070,9 +1970,8 @@ impl TransactionBuilder {
         };
 
         let mut tx_to_sign = tx;
-        self.wallet
-            .sign_transaction(&mut tx_to_sign)
-            .map_err(|e| TransactionBuilderError::SigningFailed(e.to_string()))?;
+        self.sign_guarded(&mut tx_to_sign, config, exit_signing_policy(), &[])
+            .await?;
 
         // Simple send via first RPC client
         let rpc = self.rpc_client_for(0);
@@ -1092,6 +1991,32 @@ impl TransactionBuilder {
     }
 }
 
+/// Rent-exempt minimum of an SPL token account (ATA or temporary WSOL account creation)
+const TOKEN_ACCOUNT_RENT_LAMPORTS: u64 = 2_039_280;
+
+/// Most a buy may spend: the slippage-bounded amount, the Jito tip and a token account's rent.
+fn buy_signing_policy(config: &TransactionConfig) -> SigningPolicy {
+    let max_spend = (config.buy_amount_lamports as u128) * (10_000u128 + config.slippage_bps as u128) / 10_000u128;
+    let tip = config.jito_tip.map_or(0, |(_, lamports)| lamports);
+    SigningPolicy::max_lamports_out((max_spend as u64).saturating_add(tip).saturating_add(TOKEN_ACCOUNT_RENT_LAMPORTS))
+}
+
+/// Sells and unwraps bring SOL in; only a temporary token account's rent may leave.
+fn exit_signing_policy() -> SigningPolicy {
+    SigningPolicy::max_lamports_out(TOKEN_ACCOUNT_RENT_LAMPORTS)
+}
+
+/// Tokens to sell for `sell_percent` of `balance`. A full exit sells the exact balance: f64
+/// rounding could leave dust behind, and the close appended to full exits would then revert
+/// the whole sell.
+fn sell_token_amount(balance: u64, sell_percent: f64) -> u64 {
+    if sell_percent >= 1.0 {
+        balance
+    } else {
+        ((balance as f64) * sell_percent) as u64
+    }
+}
+
 // Pomocnicze funkcje obliczeniowe dla pump.fun
 #[cfg(feature = "pumpfun")]
 fn calculate_expected_tokens(curve: &BondingCurveAccount, sol_in: u64) -> u64 {
@@ -0,0 +1,488 @@
+//! Pre-sign transaction guard.
+//!
+//! External providers (PumpPortal, LetsBonk, Jupiter) hand us program IDs, account metas and
+//! instruction data that end up signed by our wallet. `TransactionGuard` decodes every
+//! top-level instruction of the final message right before signing and refuses to sign when:
+//! - an instruction targets a program outside the allowlist
+//! - SOL or tokens signed for by the wallet leave for an account that is not ours
+//! - the wallet hands out authority (token `SetAuthority`/`Approve`, system `Assign`, nonce
+//!   authority changes)
+//! - the lamports moved out by system instructions exceed the signing policy's cap
+//!
+//! SOL spent inside a swap program is invisible at this level; those paths are bounded by the
+//! program's own slippage arguments.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    message::{AddressLookupTableAccount, VersionedMessage},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token::instruction::TokenInstruction;
+use thiserror::Error;
+
+use crate::dex::{
+    mint::TOKEN_2022_PROGRAM_ID,
+    pumpfun::PUMP_FUN_PROGRAM_ID,
+    pumpswap::PUMPSWAP_PROGRAM_ID,
+    raydium::{AMM_V4_PROGRAM_ID, CPMM_PROGRAM_ID},
+    read_u64, WSOL_MINT,
+};
+use crate::signer::SigningPolicy;
+
+const SYSTEM_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("11111111111111111111111111111111");
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111");
+pub const MEMO_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+pub const MEMO_V1_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo");
+pub const JUPITER_V6_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+/// Raydium LaunchLab, the program behind LetsBonk launches
+pub const LETSBONK_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj");
+
+/// Programs every build path may emit.
+fn builtin_programs() -> [Pubkey; 14] {
+    [
+        SYSTEM_PROGRAM_ID,
+        COMPUTE_BUDGET_PROGRAM_ID,
+        spl_token::id(),
+        TOKEN_2022_PROGRAM_ID,
+        spl_associated_token_account::id(),
+        MEMO_PROGRAM_ID,
+        MEMO_V1_PROGRAM_ID,
+        PUMP_FUN_PROGRAM_ID,
+        PUMPSWAP_PROGRAM_ID,
+        AMM_V4_PROGRAM_ID,
+        CPMM_PROGRAM_ID,
+        ORCA_WHIRLPOOL_PROGRAM_ID,
+        JUPITER_V6_PROGRAM_ID,
+        LETSBONK_PROGRAM_ID,
+    ]
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TxGuardConfig {
+    /// Programs allowed on top of the built-in DEX/system set (base58)
+    #[serde(default)]
+    pub extra_programs: Vec<String>,
+    /// Third-party accounts the wallet may send SOL or tokens to, e.g. fee collectors (base58)
+    #[serde(default)]
+    pub allowed_recipients: Vec<String>,
+    /// Hard cap on lamports moved out per transaction, applied on top of each signing policy
+    #[serde(default)]
+    pub max_lamports_out: Option<u64>,
+}
+
+impl TxGuardConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for key in self.extra_programs.iter().chain(&self.allowed_recipients) {
+            Pubkey::from_str(key).map_err(|e| format!("tx_guard: invalid pubkey {}: {}", key, e))?;
+        }
+        if self.max_lamports_out == Some(0) {
+            return Err("tx_guard.max_lamports_out must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Why a transaction was refused. `index` is the top-level instruction position.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GuardViolation {
+    #[error("instruction {index}: program {program} is not on the allowlist")]
+    ProgramNotAllowed { index: usize, program: Pubkey },
+    #[error("instruction {index}: sends {lamports} lamports to third-party account {to}")]
+    SolTransfer { index: usize, to: Pubkey, lamports: u64 },
+    #[error("instruction {index}: moves tokens from {from} to third-party account {destination}")]
+    TokenTransfer { index: usize, from: Pubkey, destination: Pubkey },
+    #[error("instruction {index}: closes {account} into third-party account {destination}")]
+    CloseToThirdParty { index: usize, account: Pubkey, destination: Pubkey },
+    #[error("instruction {index}: {action} hands authority over {account} to another key")]
+    AuthorityChange { index: usize, action: &'static str, account: Pubkey },
+    #[error("instruction {index}: undecodable {program} instruction")]
+    Undecodable { index: usize, program: &'static str },
+    #[error("instruction {index}: account #{position} is not in the message or its lookup tables")]
+    UnresolvedAccount { index: usize, position: usize },
+    #[error("moves {outflow} lamports out of the wallet, cap is {cap}")]
+    OutflowCap { outflow: u64, cap: u64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionGuard {
+    programs: HashSet<Pubkey>,
+    recipients: HashSet<Pubkey>,
+    max_lamports_out: Option<u64>,
+}
+
+impl Default for TransactionGuard {
+    fn default() -> Self {
+        Self {
+            programs: builtin_programs().into_iter().collect(),
+            recipients: HashSet::new(),
+            max_lamports_out: None,
+        }
+    }
+}
+
+impl TransactionGuard {
+    pub fn new(cfg: &TxGuardConfig) -> Result<Self, String> {
+        cfg.validate()?;
+        let parse = |keys: &[String]| keys.iter().map(|k| Pubkey::from_str(k).unwrap()).collect::<Vec<_>>();
+        let mut guard = Self::default();
+        guard.programs.extend(parse(&cfg.extra_programs));
+        guard.recipients.extend(parse(&cfg.allowed_recipients));
+        guard.max_lamports_out = cfg.max_lamports_out;
+        Ok(guard)
+    }
+
+    /// Global per-transaction cap, to be folded into each signing policy.
+    pub fn max_lamports_out(&self) -> Option<u64> {
+        self.max_lamports_out
+    }
+
+    /// Inspect `message` before the fee payer signs it. `programs` and `recipients` extend the
+    /// allowlists for this transaction (configured allowlist, Jito tip account). Returns the
+    /// lamports the message moves out of the payer.
+    pub fn inspect(
+        &self,
+        message: &VersionedMessage,
+        lookup_tables: &[AddressLookupTableAccount],
+        programs: &[Pubkey],
+        recipients: &[Pubkey],
+        policy: &SigningPolicy,
+    ) -> Result<u64, GuardViolation> {
+        let keys = resolve_account_keys(message, lookup_tables);
+        let payer = keys.first().copied().flatten().unwrap_or_default();
+        let ctx = Inspection {
+            payer,
+            recipients: self.recipients.iter().chain(recipients).copied().collect(),
+        };
+
+        let mut outflow = 0u64;
+        for (index, ix) in message.instructions().iter().enumerate() {
+            let program = keys
+                .get(ix.program_id_index as usize)
+                .copied()
+                .flatten()
+                .ok_or(GuardViolation::UnresolvedAccount { index, position: ix.program_id_index as usize })?;
+            if !self.programs.contains(&program) && !programs.contains(&program) {
+                return Err(GuardViolation::ProgramNotAllowed { index, program });
+            }
+            let account = |position: usize| -> Result<Pubkey, GuardViolation> {
+                ix.accounts
+                    .get(position)
+                    .and_then(|&i| keys.get(i as usize).copied().flatten())
+                    .ok_or(GuardViolation::UnresolvedAccount { index, position })
+            };
+
+            if program == SYSTEM_PROGRAM_ID {
+                outflow = outflow.saturating_add(ctx.check_system(index, &ix.data, account)?);
+            } else if program == spl_token::id() || program == TOKEN_2022_PROGRAM_ID {
+                ctx.check_token(index, &program, &ix.data, account)?;
+            }
+        }
+
+        if let Some(cap) = policy.max_lamports_out {
+            if outflow > cap {
+                return Err(GuardViolation::OutflowCap { outflow, cap });
+            }
+        }
+        Ok(outflow)
+    }
+}
+
+/// Static keys followed by writable then readonly lookup-table addresses, in message order.
+/// Addresses from tables we were not given stay `None`.
+fn resolve_account_keys(
+    message: &VersionedMessage,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Vec<Option<Pubkey>> {
+    let mut keys: Vec<Option<Pubkey>> = message.static_account_keys().iter().copied().map(Some).collect();
+    let Some(lookups) = message.address_table_lookups() else {
+        return keys;
+    };
+    let table_of = |key: &Pubkey| lookup_tables.iter().find(|t| t.key == *key);
+    let loaded = |indexes: &[u8], table: Option<&AddressLookupTableAccount>| -> Vec<Option<Pubkey>> {
+        indexes
+            .iter()
+            .map(|&i| table.and_then(|t| t.addresses.get(i as usize).copied()))
+            .collect()
+    };
+    for lookup in lookups {
+        keys.extend(loaded(&lookup.writable_indexes, table_of(&lookup.account_key)));
+    }
+    for lookup in lookups {
+        keys.extend(loaded(&lookup.readonly_indexes, table_of(&lookup.account_key)));
+    }
+    keys
+}
+
+struct Inspection {
+    payer: Pubkey,
+    recipients: HashSet<Pubkey>,
+}
+
+impl Inspection {
+    /// The payer itself, its WSOL accounts (wrapping) and explicitly allowed recipients.
+    fn may_receive_sol(&self, to: &Pubkey) -> bool {
+        *to == self.payer
+            || self.recipients.contains(to)
+            || [spl_token::id(), TOKEN_2022_PROGRAM_ID]
+                .iter()
+                .any(|p| *to == get_associated_token_address_with_program_id(&self.payer, &WSOL_MINT, p))
+    }
+
+    /// Returns the lamports the instruction moves out of the payer.
+    fn check_system(
+        &self,
+        index: usize,
+        data: &[u8],
+        account: impl Fn(usize) -> Result<Pubkey, GuardViolation>,
+    ) -> Result<u64, GuardViolation> {
+        let undecodable = GuardViolation::Undecodable { index, program: "system" };
+        let tag = read_u32(data, 0).ok_or(undecodable.clone())?;
+        let lamports_at = |offset: usize| read_u64(data, offset).map_err(|_| undecodable.clone());
+        match tag {
+            // CreateAccount { lamports, space, owner }: funds a fresh account
+            0 => Ok(if account(0)? == self.payer { lamports_at(4)? } else { 0 }),
+            // Assign
+            1 if account(0)? == self.payer => {
+                Err(GuardViolation::AuthorityChange { index, action: "Assign", account: self.payer })
+            }
+            // Transfer { lamports }
+            2 => self.check_transfer(index, account(0)? == self.payer, account(1)?, lamports_at(4)?),
+            // CreateAccountWithSeed { base, seed, lamports, space, owner }
+            3 => {
+                let seed_len = read_u64(data, 36).map_err(|_| undecodable.clone())? as usize;
+                let lamports = lamports_at(44usize.saturating_add(seed_len))?;
+                Ok(if account(0)? == self.payer { lamports } else { 0 })
+            }
+            // WithdrawNonceAccount { lamports }: [nonce, to, blockhashes, rent, authority]
+            5 if account(4)? == self.payer => {
+                self.check_transfer(index, false, account(1)?, lamports_at(4)?)
+            }
+            // AuthorizeNonceAccount: [nonce, authority]
+            7 if account(1)? == self.payer => Err(GuardViolation::AuthorityChange {
+                index,
+                action: "AuthorizeNonceAccount",
+                account: account(0)?,
+            }),
+            // AssignWithSeed: [account, base]
+            10 if account(1)? == self.payer => Err(GuardViolation::AuthorityChange {
+                index,
+                action: "AssignWithSeed",
+                account: account(0)?,
+            }),
+            // TransferWithSeed { lamports, .. }: [from, base, to]
+            11 => self.check_transfer(index, account(1)? == self.payer, account(2)?, lamports_at(4)?),
+            _ => Ok(0),
+        }
+    }
+
+    fn check_transfer(&self, index: usize, from_payer: bool, to: Pubkey, lamports: u64) -> Result<u64, GuardViolation> {
+        if !self.may_receive_sol(&to) {
+            return Err(GuardViolation::SolTransfer { index, to, lamports });
+        }
+        Ok(if from_payer { lamports } else { 0 })
+    }
+
+    fn check_token(
+        &self,
+        index: usize,
+        program: &Pubkey,
+        data: &[u8],
+        account: impl Fn(usize) -> Result<Pubkey, GuardViolation>,
+    ) -> Result<(), GuardViolation> {
+        let ix = TokenInstruction::unpack(data).map_err(|_| GuardViolation::Undecodable { index, program: "token" })?;
+        match ix {
+            // [source, destination, authority]; unchecked transfers carry no mint, so the
+            // destination can only be an explicitly allowed recipient
+            TokenInstruction::Transfer { .. } if account(2)? == self.payer => {
+                let destination = account(1)?;
+                if !self.recipients.contains(&destination) {
+                    return Err(GuardViolation::TokenTransfer { index, from: account(0)?, destination });
+                }
+            }
+            // [source, mint, destination, authority]
+            TokenInstruction::TransferChecked { .. } if account(3)? == self.payer => {
+                let destination = account(2)?;
+                let own = get_associated_token_address_with_program_id(&self.payer, &account(1)?, program);
+                if destination != own && !self.recipients.contains(&destination) {
+                    return Err(GuardViolation::TokenTransfer { index, from: account(0)?, destination });
+                }
+            }
+            TokenInstruction::Approve { .. } | TokenInstruction::ApproveChecked { .. } => {
+                return Err(GuardViolation::AuthorityChange { index, action: "Approve", account: account(0)? });
+            }
+            TokenInstruction::SetAuthority { .. } => {
+                return Err(GuardViolation::AuthorityChange { index, action: "SetAuthority", account: account(0)? });
+            }
+            // [account, destination, owner]: rent and wrapped SOL go to `destination`
+            TokenInstruction::CloseAccount => {
+                let destination = account(1)?;
+                if !self.may_receive_sol(&destination) {
+                    return Err(GuardViolation::CloseToThirdParty { index, account: account(0)?, destination });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::v0::Message as MessageV0,
+    };
+
+    fn message(payer: &Pubkey, ixs: &[Instruction], tables: &[AddressLookupTableAccount]) -> VersionedMessage {
+        VersionedMessage::V0(MessageV0::try_compile(payer, ixs, tables, Hash::new_unique()).unwrap())
+    }
+
+    #[allow(deprecated)]
+    fn transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+        solana_sdk::system_instruction::transfer(from, to, lamports)
+    }
+
+    fn check(guard: &TransactionGuard, payer: &Pubkey, ixs: &[Instruction], recipients: &[Pubkey], cap: Option<u64>) -> Result<u64, GuardViolation> {
+        let policy = SigningPolicy { max_lamports_out: cap };
+        guard.inspect(&message(payer, ixs, &[]), &[], &[], recipients, &policy)
+    }
+
+    #[test]
+    fn native_buy_shape_passes_and_counts_outflow() {
+        let payer = Pubkey::new_unique();
+        let tip = Pubkey::new_unique();
+        let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(200_000)];
+        ixs.extend(crate::dex::wrap_sol_instructions(&payer, 1_000_000).unwrap());
+        ixs.push(Instruction::new_with_bytes(PUMPSWAP_PROGRAM_ID, &[1], vec![AccountMeta::new(payer, true)]));
+        ixs.push(crate::dex::unwrap_sol_instruction(&payer).unwrap());
+        ixs.push(transfer(&payer, &tip, 10_000));
+
+        let guard = TransactionGuard::default();
+        assert_eq!(check(&guard, &payer, &ixs, &[tip], Some(1_010_000)), Ok(1_010_000));
+        assert_eq!(
+            check(&guard, &payer, &ixs, &[tip], Some(1_000_000)),
+            Err(GuardViolation::OutflowCap { outflow: 1_010_000, cap: 1_000_000 })
+        );
+        // Without the tip account allowed, the tip is a third-party transfer
+        assert_eq!(
+            check(&guard, &payer, &ixs, &[], None),
+            Err(GuardViolation::SolTransfer { index: 6, to: tip, lamports: 10_000 })
+        );
+    }
+
+    #[test]
+    fn unknown_programs_need_an_allowlist_entry() {
+        let payer = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let ixs = [Instruction::new_with_bytes(program, &[7], vec![AccountMeta::new(payer, true)])];
+        let msg = message(&payer, &ixs, &[]);
+        let policy = SigningPolicy::default();
+
+        let guard = TransactionGuard::default();
+        assert_eq!(
+            guard.inspect(&msg, &[], &[], &[], &policy),
+            Err(GuardViolation::ProgramNotAllowed { index: 0, program })
+        );
+        assert!(guard.inspect(&msg, &[], &[program], &[], &policy).is_ok());
+
+        let configured = TransactionGuard::new(&TxGuardConfig {
+            extra_programs: vec![program.to_string()],
+            ..TxGuardConfig::default()
+        })
+        .unwrap();
+        assert!(configured.inspect(&msg, &[], &[], &[], &policy).is_ok());
+    }
+
+    #[test]
+    fn token_transfers_and_authority_changes_are_refused() {
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let thief = Pubkey::new_unique();
+        let ours = get_associated_token_address_with_program_id(&payer, &Pubkey::new_unique(), &spl_token::id());
+        let own_ata = get_associated_token_address_with_program_id(&payer, &mint, &spl_token::id());
+        let guard = TransactionGuard::default();
+        let token = spl_token::id();
+
+        let to_self = spl_token::instruction::transfer_checked(&token, &ours, &mint, &own_ata, &payer, &[], 5, 6).unwrap();
+        assert!(check(&guard, &payer, &[to_self], &[], None).is_ok());
+
+        let to_thief = spl_token::instruction::transfer_checked(&token, &own_ata, &mint, &thief, &payer, &[], 5, 6).unwrap();
+        assert_eq!(
+            check(&guard, &payer, &[to_thief], &[], None),
+            Err(GuardViolation::TokenTransfer { index: 0, from: own_ata, destination: thief })
+        );
+
+        let unchecked = spl_token::instruction::transfer(&token, &own_ata, &thief, &payer, &[], 5).unwrap();
+        assert!(matches!(check(&guard, &payer, std::slice::from_ref(&unchecked), &[], None), Err(GuardViolation::TokenTransfer { .. })));
+        assert!(check(&guard, &payer, &[unchecked], &[thief], None).is_ok());
+
+        let set_authority = spl_token::instruction::set_authority(
+            &token,
+            &own_ata,
+            Some(&thief),
+            spl_token::instruction::AuthorityType::AccountOwner,
+            &payer,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            check(&guard, &payer, &[set_authority], &[], None),
+            Err(GuardViolation::AuthorityChange { index: 0, action: "SetAuthority", account: own_ata })
+        );
+
+        let approve = spl_token::instruction::approve(&token, &own_ata, &thief, &payer, &[], 5).unwrap();
+        assert!(matches!(check(&guard, &payer, &[approve], &[], None), Err(GuardViolation::AuthorityChange { .. })));
+
+        let close = spl_token::instruction::close_account(&token, &own_ata, &thief, &payer, &[]).unwrap();
+        assert_eq!(
+            check(&guard, &payer, &[close], &[], None),
+            Err(GuardViolation::CloseToThirdParty { index: 0, account: own_ata, destination: thief })
+        );
+
+        #[allow(deprecated)]
+        let assign = solana_sdk::system_instruction::assign(&payer, &Pubkey::new_unique());
+        assert!(matches!(check(&guard, &payer, &[assign], &[], None), Err(GuardViolation::AuthorityChange { action: "Assign", .. })));
+    }
+
+    #[test]
+    fn lookup_table_accounts_are_resolved_before_judging_transfers() {
+        let payer = Pubkey::new_unique();
+        let thief = Pubkey::new_unique();
+        let table = AddressLookupTableAccount { key: Pubkey::new_unique(), addresses: vec![thief] };
+        let msg = message(&payer, &[transfer(&payer, &thief, 42)], std::slice::from_ref(&table));
+        assert!(msg.static_account_keys().iter().all(|k| *k != thief));
+
+        let guard = TransactionGuard::default();
+        let policy = SigningPolicy::default();
+        assert_eq!(
+            guard.inspect(&msg, &[], &[], &[], &policy),
+            Err(GuardViolation::UnresolvedAccount { index: 0, position: 1 })
+        );
+        assert_eq!(
+            guard.inspect(&msg, &[table], &[], &[], &policy),
+            Err(GuardViolation::SolTransfer { index: 0, to: thief, lamports: 42 })
+        );
+    }
+
+    #[test]
+    fn config_validation() {
+        assert!(TxGuardConfig::default().validate().is_ok());
+        let bad = TxGuardConfig { allowed_recipients: vec!["nope".to_string()], ..TxGuardConfig::default() };
+        assert!(bad.validate().is_err());
+        let zero = TxGuardConfig { max_lamports_out: Some(0), ..TxGuardConfig::default() };
+        assert!(zero.validate().is_err());
+    }
+}
@@ -0,0 +1,352 @@
+//! Pre-built buy transaction templates.
+//!
+//! Everything a buy needs that does not depend on the mint is assembled ahead of time and
+//! refreshed in the background: the blockhash, the priority fee estimate and the DEX's fixed
+//! accounts and PDAs. When a candidate arrives only the mint-dependent accounts and amounts
+//! are patched in before the message is compiled and signed.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::{debug, warn};
+
+use crate::blockhash::BlockhashService;
+use crate::dex::pumpfun::{self, BondingCurveState, BuyFixedAccounts};
+use crate::fee_estimator::PriorityFeeEstimator;
+use crate::metrics::metrics;
+use crate::tx_builder::{DexProgram, TransactionConfig};
+use crate::types::PremintCandidate;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateConfig {
+    /// How often templates (blockhash, fee estimate, fixed accounts) are rebuilt
+    #[serde(default = "default_refresh_interval_ms")]
+    pub refresh_interval_ms: u64,
+    /// Templates older than this are ignored and buys take the full build path
+    #[serde(default = "default_max_age_ms")]
+    pub max_age_ms: u64,
+}
+
+fn default_refresh_interval_ms() -> u64 {
+    1_000
+}
+fn default_max_age_ms() -> u64 {
+    5_000
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_ms: default_refresh_interval_ms(),
+            max_age_ms: default_max_age_ms(),
+        }
+    }
+}
+
+impl TemplateConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.refresh_interval_ms == 0 {
+            return Err("tx_templates.refresh_interval_ms must be greater than 0".to_string());
+        }
+        if self.max_age_ms <= self.refresh_interval_ms {
+            return Err("tx_templates.max_age_ms must exceed refresh_interval_ms".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Mint-independent part of a DEX's buy instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DexTemplate {
+    PumpFun(BuyFixedAccounts),
+}
+
+impl DexTemplate {
+    fn key(&self) -> &'static str {
+        match self {
+            DexTemplate::PumpFun(_) => "pumpfun",
+        }
+    }
+
+    /// Fixed accounts the buy writes (scopes the priority fee estimate).
+    fn writable_accounts(&self) -> Vec<Pubkey> {
+        match self {
+            DexTemplate::PumpFun(fixed) => vec![fixed.fee_recipient, fixed.global_volume_accumulator],
+        }
+    }
+}
+
+/// A ready-to-patch buy for one DEX.
+#[derive(Debug, Clone)]
+pub struct BuyTemplate {
+    pub dex: DexTemplate,
+    pub payer: Pubkey,
+    pub blockhash: Hash,
+    /// Priority fee estimated over the DEX's fixed accounts (before any ladder multiplier)
+    pub priority_fee: u64,
+    built_at: Instant,
+}
+
+impl BuyTemplate {
+    pub fn age(&self) -> Duration {
+        self.built_at.elapsed()
+    }
+
+    /// Patch the mint-dependent accounts and amounts into the program instructions.
+    /// `token_program` owns the candidate's mint (pump.fun creates both SPL Token and
+    /// Token-2022 mints).
+    pub fn program_instructions(
+        &self,
+        candidate: &PremintCandidate,
+        token_program: &Pubkey,
+        config: &TransactionConfig,
+    ) -> Vec<Instruction> {
+        match &self.dex {
+            DexTemplate::PumpFun(fixed) => {
+                let (token_amount, max_sol_cost) = launch_price_quote(config);
+                vec![
+                    create_associated_token_account_idempotent(
+                        &self.payer,
+                        &self.payer,
+                        &candidate.mint,
+                        token_program,
+                    ),
+                    pumpfun::buy_instruction(
+                        fixed,
+                        &self.payer,
+                        &candidate.mint,
+                        &candidate.creator,
+                        token_program,
+                        token_amount,
+                        max_sol_cost,
+                    ),
+                ]
+            }
+        }
+    }
+}
+
+/// Quote a templated pump.fun buy without reading the bonding curve, which usually does not
+/// exist on the node yet when the launch is detected.
+///
+/// The token amount is priced at the launch reserves and `slippage_bps` is the ceiling over
+/// that launch price: `max_sol_cost` is `buy_amount_lamports * (1 + slippage)`. If buys
+/// ahead of ours (including a dev buy in the create transaction) moved the curve further
+/// than that, the program rejects ours instead of paying more.
+fn launch_price_quote(config: &TransactionConfig) -> (u64, u64) {
+    let token_amount = BondingCurveState::initial().buy_quote(config.buy_amount_lamports);
+    let max_sol_cost = ((config.buy_amount_lamports as u128)
+        * (10_000u128 + config.slippage_bps as u128)
+        / 10_000u128) as u64;
+    (token_amount, max_sol_cost)
+}
+
+/// Background-refreshed buy templates, one per supported DEX.
+pub struct TemplateCache {
+    rpc: Arc<RpcClient>,
+    payer: Pubkey,
+    config: TemplateConfig,
+    fee_estimator: Option<Arc<PriorityFeeEstimator>>,
+    blockhashes: Option<Arc<BlockhashService>>,
+    templates: RwLock<HashMap<&'static str, BuyTemplate>>,
+}
+
+impl std::fmt::Debug for TemplateCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TemplateCache")
+            .field("rpc", &self.rpc.url())
+            .field("payer", &self.payer)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl TemplateCache {
+    pub fn new(rpc: Arc<RpcClient>, payer: Pubkey, config: TemplateConfig) -> Self {
+        Self {
+            rpc,
+            payer,
+            config,
+            fee_estimator: None,
+            blockhashes: None,
+            templates: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Pre-estimate priority fees instead of using the static fee.
+    pub fn with_fee_estimator(mut self, estimator: Arc<PriorityFeeEstimator>) -> Self {
+        self.fee_estimator = Some(estimator);
+        self
+    }
+
+    /// Take blockhashes from the slot-aware service.
+    pub fn with_blockhash_service(mut self, service: Arc<BlockhashService>) -> Self {
+        self.blockhashes = Some(service);
+        self
+    }
+
+    /// Fresh template for `program`, if that DEX is templated.
+    pub async fn template_for(&self, program: &str) -> Option<BuyTemplate> {
+        let key = match DexProgram::from(program) {
+            DexProgram::PumpFun => "pumpfun",
+            _ => return None,
+        };
+        let template = self.templates.read().await.get(key).cloned()?;
+        if template.age() > Duration::from_millis(self.config.max_age_ms) {
+            metrics().increment_counter("tx_template_stale");
+            return None;
+        }
+        Some(template)
+    }
+
+    /// Most recent blockhash fetched by the refresher, if still fresh.
+    pub async fn fresh_blockhash(&self) -> Option<Hash> {
+        let max_age = Duration::from_millis(self.config.max_age_ms);
+        self.templates
+            .read()
+            .await
+            .values()
+            .filter(|t| t.age() <= max_age)
+            .min_by_key(|t| t.age())
+            .map(|t| t.blockhash)
+    }
+
+    /// Rebuild every template. `fallback_fee` is used without an estimator.
+    pub async fn refresh(&self, fallback_fee: u64) -> Result<()> {
+        let blockhash = match &self.blockhashes {
+            Some(service) => service.latest().await?.hash,
+            None => self
+                .rpc
+                .get_latest_blockhash()
+                .await
+                .map_err(|e| anyhow!("blockhash: {}", e))?,
+        };
+        let global = self
+            .rpc
+            .get_account_data(&pumpfun::global_address())
+            .await
+            .map_err(|e| anyhow!("pump.fun global: {}", e))?;
+        let fee_recipient = pumpfun::decode_fee_recipient(&global)?;
+
+        let dex_templates = [DexTemplate::PumpFun(BuyFixedAccounts::derive(&self.payer, fee_recipient))];
+        for dex in dex_templates {
+            let priority_fee = match &self.fee_estimator {
+                Some(estimator) => estimator.estimate(&dex.writable_accounts(), true, fallback_fee).await,
+                None => fallback_fee,
+            };
+            let template = BuyTemplate {
+                payer: self.payer,
+                blockhash,
+                priority_fee,
+                built_at: Instant::now(),
+                dex,
+            };
+            self.templates.write().await.insert(template.dex.key(), template);
+        }
+        metrics().increment_counter("tx_template_refreshes");
+        debug!(%blockhash, "Buy templates refreshed");
+        Ok(())
+    }
+
+    /// Keep templates warm until the handle is aborted.
+    pub fn spawn_refresh(self: Arc<Self>, fallback_fee: u64) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_millis(self.config.refresh_interval_ms));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.refresh(fallback_fee).await {
+                    metrics().increment_counter("tx_template_refresh_failures");
+                    warn!("Buy template refresh failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(built_at: Instant) -> BuyTemplate {
+        let payer = Pubkey::new_unique();
+        BuyTemplate {
+            dex: DexTemplate::PumpFun(BuyFixedAccounts::derive(&payer, Pubkey::new_unique())),
+            payer,
+            blockhash: Hash::new_unique(),
+            priority_fee: 42,
+            built_at,
+        }
+    }
+
+    #[test]
+    fn patches_mint_and_amounts() {
+        let template = template(Instant::now());
+        let candidate = PremintCandidate {
+            mint: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            program: "pump.fun".to_string(),
+            slot: 1,
+            timestamp: 0,
+            instruction_summary: None,
+            is_jito_bundle: None,
+            detected_at_ms: 0,
+            token_program: None,
+        };
+        let config = TransactionConfig {
+            buy_amount_lamports: 1_000_000_000,
+            slippage_bps: 500,
+            ..TransactionConfig::default()
+        };
+        let ixs = template.program_instructions(&candidate, &spl_token::id(), &config);
+        assert_eq!(ixs.len(), 2);
+        let buy = &ixs[1];
+        assert_eq!(buy.program_id, pumpfun::PUMP_FUN_PROGRAM_ID);
+        assert_eq!(buy.accounts[2].pubkey, candidate.mint);
+        assert!(buy.accounts.iter().any(|m| m.pubkey == spl_token::id()));
+        let max_sol_cost = u64::from_le_bytes(buy.data[16..24].try_into().unwrap());
+        assert_eq!(max_sol_cost, 1_050_000_000);
+        let tokens = u64::from_le_bytes(buy.data[8..16].try_into().unwrap());
+        assert!(tokens > 30_000_000_000_000 && tokens < 35_000_000_000_000, "{}", tokens);
+
+        // Token-2022 mints derive the payer's ATA under the Token-2022 program
+        let token_2022 = crate::dex::mint::TOKEN_2022_PROGRAM_ID;
+        let ixs = template.program_instructions(&candidate, &token_2022, &config);
+        let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &template.payer,
+            &candidate.mint,
+            &token_2022,
+        );
+        assert!(ixs[1].accounts.iter().any(|m| m.pubkey == ata));
+        assert!(!ixs[1].accounts.iter().any(|m| m.pubkey == spl_token::id()));
+    }
+
+    #[tokio::test]
+    async fn stale_templates_are_not_served() {
+        let cache = TemplateCache::new(
+            Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())),
+            Pubkey::new_unique(),
+            TemplateConfig::default(),
+        );
+        assert!(cache.template_for("pump.fun").await.is_none());
+
+        cache.templates.write().await.insert("pumpfun", template(Instant::now()));
+        assert!(cache.template_for("pump.fun").await.is_some());
+        assert!(cache.template_for("raydium").await.is_none());
+        assert!(cache.fresh_blockhash().await.is_some());
+
+        let old = Instant::now() - Duration::from_secs(60);
+        cache.templates.write().await.insert("pumpfun", template(old));
+        assert!(cache.template_for("pump.fun").await.is_none());
+        assert!(cache.fresh_blockhash().await.is_none());
+    }
+}
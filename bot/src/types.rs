@@ -16,6 +16,12 @@ pub struct PremintCandidate {
     pub timestamp: u64,
     pub instruction_summary: Option<String>,
     pub is_jito_bundle: Option<bool>,
+    /// Wall-clock milliseconds at which the sniffer saw the launch; 0 when unknown
+    #[serde(default)]
+    pub detected_at_ms: u64,
+    /// Token program owning the mint, when the create transaction's logs reveal it
+    #[serde(default)]
+    pub token_program: Option<Pubkey>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
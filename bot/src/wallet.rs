@@ -1,9 +1,16 @@
 //! Wallet management for keypair loading and transaction signing.
+//!
+//! Keypairs load from plaintext JSON/base58 files or from encrypted keystores (see
+//! `keystore`). Intermediate copies of secret bytes are zeroized; the keypair itself wipes
+//! its secret key when the wallet is dropped.
 
 use anyhow::{anyhow, Result};
-use solana_sdk::{pubkey::Pubkey, signature::{Keypair, Signer}, transaction::VersionedTransaction};
+use solana_sdk::{pubkey::Pubkey, signature::{Keypair, Signature, Signer}, transaction::VersionedTransaction};
 use std::{fs, path::Path};
-use tracing::{info, debug};
+use tracing::{info, debug, warn};
+use zeroize::Zeroizing;
+
+use crate::keystore::{EncryptedKeystore, KdfParams, PasswordProvider};
 
 /// Wallet manager for handling keypair operations
 #[derive(Debug)]
@@ -12,11 +19,13 @@ pub struct WalletManager {
 }
 
 impl WalletManager {
-    /// Create a new wallet manager by loading keypair from file
+    /// Create a new wallet manager by loading a plaintext keypair from file
     pub fn from_file<P: AsRef<Path>>(keypair_path: P) -> Result<Self> {
         let path = keypair_path.as_ref();
-        let keypair_data = fs::read_to_string(path)
-            .map_err(|e| anyhow!("Failed to read keypair file {}: {}", path.display(), e))?;
+        let keypair_data = Self::read_key_file(path)?;
+        if EncryptedKeystore::parse(&keypair_data).is_some() {
+            return Err(anyhow!("{} is an encrypted keystore; a password is required", path.display()));
+        }
 
         let keypair = Self::parse_keypair(&keypair_data)?;
         
@@ -25,6 +34,29 @@ impl WalletManager {
         Ok(Self { keypair })
     }
 
+    /// Load an encrypted keystore or a plaintext keypair file. The password is only
+    /// requested for keystores.
+    pub fn load<P: AsRef<Path>>(keypair_path: P, passwords: &PasswordProvider) -> Result<Self> {
+        let path = keypair_path.as_ref();
+        let keypair_data = Self::read_key_file(path)?;
+        let Some(keystore) = EncryptedKeystore::parse(&keypair_data) else {
+            warn!("{} is a plaintext keypair; convert it with `keystore encrypt`", path.display());
+            return Self::from_file(path);
+        };
+
+        let keypair = keystore
+            .decrypt(&passwords.password()?)
+            .map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        info!("Unlocked keystore {}, pubkey: {}", path.display(), keypair.pubkey());
+        Ok(Self { keypair })
+    }
+
+    fn read_key_file(path: &Path) -> Result<Zeroizing<String>> {
+        fs::read_to_string(path)
+            .map(Zeroizing::new)
+            .map_err(|e| anyhow!("Failed to read keypair file {}: {}", path.display(), e))
+    }
+
     /// Create a wallet manager with a provided keypair
     pub fn from_keypair(keypair: Keypair) -> Self {
         Self { keypair }
@@ -42,10 +74,16 @@ impl WalletManager {
         self.keypair.pubkey()
     }
 
-    /// Sign a transaction
+    /// Sign a transaction as its fee payer (first signer)
     pub fn sign_transaction(&self, tx: &mut VersionedTransaction) -> Result<()> {
-        // Simple signature placeholder - in production, proper signing would be implemented
-        tx.signatures[0] = self.keypair.sign_message(b"placeholder_message");
+        let required = tx.message.header().num_required_signatures as usize;
+        if required == 0 || tx.message.static_account_keys().first() != Some(&self.keypair.pubkey()) {
+            return Err(anyhow!("wallet {} is not the fee payer of this transaction", self.keypair.pubkey()));
+        }
+        if tx.signatures.len() != required {
+            tx.signatures = vec![Signature::default(); required];
+        }
+        tx.signatures[0] = self.keypair.sign_message(&tx.message.serialize());
         debug!("Transaction signed with pubkey: {}", self.keypair.pubkey());
         Ok(())
     }
@@ -61,7 +99,8 @@ impl WalletManager {
         
         // Try JSON array format first (most common for Solana CLI)
         if data.starts_with('[') && data.ends_with(']') {
-            let bytes: Vec<u8> = serde_json::from_str(data)
+            let bytes: Zeroizing<Vec<u8>> = serde_json::from_str(data)
+                .map(Zeroizing::new)
                 .map_err(|e| anyhow!("Failed to parse keypair JSON array: {}", e))?;
             
             if bytes.len() != 64 {
@@ -75,7 +114,7 @@ impl WalletManager {
         }
 
         // Try base58 format
-        if let Ok(bytes) = bs58::decode(data).into_vec() {
+        if let Ok(bytes) = bs58::decode(data).into_vec().map(Zeroizing::new) {
             if bytes.len() == 64 {
                 if let Ok(keypair) = Keypair::from_bytes(&bytes) {
                     return Ok(keypair);
@@ -88,13 +127,21 @@ impl WalletManager {
 
     /// Save keypair to file in JSON format
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.keypair.to_bytes().to_vec())?;
-        fs::write(&path, json)
+        let bytes = Zeroizing::new(self.keypair.to_bytes().to_vec());
+        let json = Zeroizing::new(serde_json::to_string_pretty(&*bytes)?);
+        fs::write(&path, json.as_bytes())
             .map_err(|e| anyhow!("Failed to write keypair to {}: {}", path.as_ref().display(), e))?;
         
         info!("Saved keypair to {}", path.as_ref().display());
         Ok(())
     }
+
+    /// Save keypair as an encrypted keystore
+    pub fn save_encrypted<P: AsRef<Path>>(&self, path: P, password: &str, params: KdfParams) -> Result<()> {
+        EncryptedKeystore::encrypt(&self.keypair, password, params)?.save(&path)?;
+        info!("Saved encrypted keystore to {}", path.as_ref().display());
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -128,4 +175,49 @@ mod tests {
         
         assert_eq!(original_wallet.pubkey(), loaded_wallet.pubkey());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sign_transaction_verifies() {
+        use solana_sdk::{hash::Hash, message::{v0::Message as MessageV0, VersionedMessage}};
+
+        let wallet = WalletManager::new_random();
+        #[allow(deprecated)]
+        let ix = solana_sdk::system_instruction::transfer(&wallet.pubkey(), &Pubkey::new_unique(), 1);
+        let msg = MessageV0::try_compile(&wallet.pubkey(), &[ix], &[], Hash::new_unique()).unwrap();
+        let mut tx = VersionedTransaction { signatures: vec![], message: VersionedMessage::V0(msg) };
+
+        wallet.sign_transaction(&mut tx).unwrap();
+        assert_eq!(tx.signatures.len(), 1);
+        assert!(tx.verify_with_results().into_iter().all(|ok| ok));
+
+        // Someone else's transaction is refused
+        let other = WalletManager::new_random();
+        assert!(other.sign_transaction(&mut tx).is_err());
+    }
+
+    #[test]
+    fn test_load_encrypted_and_plaintext() {
+        use crate::keystore::KeystoreConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let wallet = WalletManager::new_random();
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+        let encrypted = dir.path().join("wallet.keystore.json");
+        wallet.save_encrypted(&encrypted, "s3cret", params).unwrap();
+        let plaintext = dir.path().join("wallet.json");
+        wallet.save_to_file(&plaintext).unwrap();
+
+        let env = "SNIFFER_KEYSTORE_PASSWORD_TEST_WALLET";
+        std::env::set_var(env, "s3cret");
+        let passwords = PasswordProvider::new(KeystoreConfig {
+            password_env: env.to_string(),
+            prompt: false,
+            ..KeystoreConfig::default()
+        });
+        assert_eq!(WalletManager::load(&encrypted, &passwords).unwrap().pubkey(), wallet.pubkey());
+        assert_eq!(WalletManager::load(&plaintext, &passwords).unwrap().pubkey(), wallet.pubkey());
+
+        // Plaintext loading refuses keystores instead of misparsing them
+        assert!(WalletManager::from_file(&encrypted).is_err());
+    }
+}
@@ -0,0 +1,387 @@
+//! Pool of trading wallets.
+//!
+//! Buys are spread over several keypairs so no single wallet carries all the exposure. Each
+//! trade picks a wallet by the configured selection policy; the pool tracks SOL balances and
+//! the lamports committed per mint so exits are signed by the wallet that holds the position.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::{info, warn};
+
+use crate::keystore::PasswordProvider;
+use crate::metrics::metrics;
+use crate::wallet::WalletManager;
+
+/// How a wallet is picked for a new buy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletSelection {
+    /// Cycle through the wallets in load order
+    #[default]
+    RoundRobin,
+    /// Wallet with the fewest lamports committed to open positions
+    LeastExposure,
+    /// Wallet mapped to the candidate's strategy in `strategies` (round-robin otherwise)
+    Dedicated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletPoolConfig {
+    /// Directory of keypair files (`*.json`), loaded in file name order
+    #[serde(default)]
+    pub keypair_dir: Option<String>,
+    /// Explicit keypair files, loaded before `keypair_dir`
+    #[serde(default)]
+    pub keypair_paths: Vec<String>,
+    #[serde(default)]
+    pub selection: WalletSelection,
+    /// Strategy (candidate program, e.g. "pump.fun") -> wallet index, for `dedicated`
+    #[serde(default)]
+    pub strategies: HashMap<String, usize>,
+    /// How often wallet SOL balances are refreshed
+    #[serde(default = "default_balance_refresh_ms")]
+    pub balance_refresh_ms: u64,
+}
+
+fn default_balance_refresh_ms() -> u64 {
+    10_000
+}
+
+impl Default for WalletPoolConfig {
+    fn default() -> Self {
+        Self {
+            keypair_dir: None,
+            keypair_paths: Vec::new(),
+            selection: WalletSelection::default(),
+            strategies: HashMap::new(),
+            balance_refresh_ms: default_balance_refresh_ms(),
+        }
+    }
+}
+
+impl WalletPoolConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keypair_dir.is_none() && self.keypair_paths.is_empty() {
+            return Err("wallet_pool needs keypair_dir or keypair_paths".to_string());
+        }
+        if self.balance_refresh_ms == 0 {
+            return Err("wallet_pool.balance_refresh_ms must be greater than 0".to_string());
+        }
+        if self.selection == WalletSelection::Dedicated && self.strategies.is_empty() {
+            return Err("wallet_pool.selection = \"dedicated\" requires wallet_pool.strategies".to_string());
+        }
+        Ok(())
+    }
+
+    /// Keypair files in load order: `keypair_paths`, then `keypair_dir` sorted by name.
+    pub fn keypair_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = self.keypair_paths.iter().map(PathBuf::from).collect();
+        if let Some(dir) = &self.keypair_dir {
+            let mut found: Vec<PathBuf> = fs::read_dir(dir)
+                .map_err(|e| anyhow!("Failed to read keypair dir {}: {}", dir, e))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            found.sort();
+            files.extend(found);
+        }
+        Ok(files)
+    }
+}
+
+#[derive(Debug, Default)]
+struct WalletState {
+    balance_lamports: u64,
+    /// Mint -> lamports spent on the open position
+    positions: HashMap<Pubkey, u64>,
+}
+
+impl WalletState {
+    fn exposure(&self) -> u64 {
+        self.positions.values().sum()
+    }
+}
+
+/// Per-wallet view for the status endpoints and logs.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletSnapshot {
+    pub index: usize,
+    pub pubkey: String,
+    pub balance_lamports: u64,
+    pub exposure_lamports: u64,
+    pub open_positions: usize,
+}
+
+pub struct WalletPool {
+    wallets: Vec<Arc<WalletManager>>,
+    selection: WalletSelection,
+    strategies: HashMap<String, usize>,
+    next: AtomicUsize,
+    state: RwLock<Vec<WalletState>>,
+}
+
+impl std::fmt::Debug for WalletPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pubkeys: Vec<Pubkey> = self.wallets.iter().map(|w| w.pubkey()).collect();
+        f.debug_struct("WalletPool")
+            .field("wallets", &pubkeys)
+            .field("selection", &self.selection)
+            .finish()
+    }
+}
+
+impl WalletPool {
+    pub fn new(wallets: Vec<Arc<WalletManager>>, config: &WalletPoolConfig) -> Result<Self> {
+        if wallets.is_empty() {
+            return Err(anyhow!("wallet pool is empty"));
+        }
+        if let Some((strategy, index)) = config.strategies.iter().find(|(_, i)| **i >= wallets.len()) {
+            return Err(anyhow!(
+                "wallet_pool.strategies.{} points at wallet {} but only {} are loaded",
+                strategy,
+                index,
+                wallets.len()
+            ));
+        }
+        let state = wallets.iter().map(|_| WalletState::default()).collect();
+        Ok(Self {
+            wallets,
+            selection: config.selection,
+            strategies: config.strategies.clone(),
+            next: AtomicUsize::new(0),
+            state: RwLock::new(state),
+        })
+    }
+
+    /// Load every configured keypair file (encrypted keystores or plaintext).
+    pub fn load(config: &WalletPoolConfig, passwords: &PasswordProvider) -> Result<Self> {
+        let wallets = config
+            .keypair_files()?
+            .iter()
+            .map(|path| WalletManager::load(path, passwords).map(Arc::new))
+            .collect::<Result<Vec<_>>>()?;
+        info!(wallets = wallets.len(), selection = ?config.selection, "Wallet pool loaded");
+        Self::new(wallets, config)
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+
+    pub fn wallet(&self, index: usize) -> Option<&Arc<WalletManager>> {
+        self.wallets.get(index)
+    }
+
+    pub fn index_of(&self, pubkey: &Pubkey) -> Option<usize> {
+        self.wallets.iter().position(|w| w.pubkey() == *pubkey)
+    }
+
+    /// Pick the wallet for a new buy of `strategy`.
+    pub async fn select(&self, strategy: &str) -> usize {
+        match self.selection {
+            WalletSelection::RoundRobin => self.round_robin(),
+            WalletSelection::LeastExposure => {
+                let state = self.state.read().await;
+                // Ties go to the better-funded wallet, then load order
+                state
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(i, s)| (s.exposure(), std::cmp::Reverse(s.balance_lamports), *i))
+                    .map(|(i, _)| i)
+                    .unwrap_or(0)
+            }
+            WalletSelection::Dedicated => match self.strategies.get(strategy) {
+                Some(index) => *index,
+                None => self.round_robin(),
+            },
+        }
+    }
+
+    fn round_robin(&self) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % self.wallets.len()
+    }
+
+    /// Record `lamports` spent by wallet `index` on `mint`.
+    pub async fn record_buy(&self, index: usize, mint: Pubkey, lamports: u64) {
+        let mut state = self.state.write().await;
+        if let Some(wallet) = state.get_mut(index) {
+            *wallet.positions.entry(mint).or_insert(0) += lamports;
+            metrics().set_gauge(&format!("wallet_{}_exposure_lamports", index), wallet.exposure());
+        }
+    }
+
+    /// Reduce the `mint` position by `fraction` (1.0 closes it); returns the holding wallet.
+    pub async fn record_exit(&self, mint: &Pubkey, fraction: f64) -> Option<usize> {
+        let mut state = self.state.write().await;
+        let index = state.iter().position(|s| s.positions.contains_key(mint))?;
+        let wallet = &mut state[index];
+        if fraction >= 1.0 - f64::EPSILON {
+            wallet.positions.remove(mint);
+        } else if let Some(lamports) = wallet.positions.get_mut(mint) {
+            *lamports = ((*lamports as f64) * (1.0 - fraction.max(0.0))) as u64;
+        }
+        metrics().set_gauge(&format!("wallet_{}_exposure_lamports", index), wallet.exposure());
+        Some(index)
+    }
+
+    /// Wallet holding an open position in `mint`.
+    pub async fn holder_of(&self, mint: &Pubkey) -> Option<usize> {
+        self.state.read().await.iter().position(|s| s.positions.contains_key(mint))
+    }
+
+    pub async fn exposure(&self, index: usize) -> u64 {
+        self.state.read().await.get(index).map_or(0, WalletState::exposure)
+    }
+
+    /// Fetch every wallet's SOL balance; wallets that fail keep their last value.
+    pub async fn refresh_balances(&self, rpc: &RpcClient) {
+        for (index, wallet) in self.wallets.iter().enumerate() {
+            match rpc.get_balance(&wallet.pubkey()).await {
+                Ok(balance) => {
+                    self.state.write().await[index].balance_lamports = balance;
+                    metrics().set_gauge(&format!("wallet_{}_balance_lamports", index), balance);
+                }
+                Err(e) => warn!(wallet = %wallet.pubkey(), "Wallet balance refresh failed: {}", e),
+            }
+        }
+    }
+
+    /// Keep balances fresh until the handle is aborted.
+    pub fn spawn_balance_refresh(self: Arc<Self>, rpc: Arc<RpcClient>, interval_ms: u64) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(interval_ms));
+            loop {
+                interval.tick().await;
+                self.refresh_balances(&rpc).await;
+            }
+        })
+    }
+
+    pub async fn snapshot(&self) -> Vec<WalletSnapshot> {
+        let state = self.state.read().await;
+        self.wallets
+            .iter()
+            .zip(state.iter())
+            .enumerate()
+            .map(|(index, (wallet, s))| WalletSnapshot {
+                index,
+                pubkey: wallet.pubkey().to_string(),
+                balance_lamports: s.balance_lamports,
+                exposure_lamports: s.exposure(),
+                open_positions: s.positions.len(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(selection: WalletSelection, strategies: HashMap<String, usize>) -> WalletPool {
+        let wallets = (0..3).map(|_| Arc::new(WalletManager::new_random())).collect();
+        let config = WalletPoolConfig {
+            keypair_paths: vec!["unused".to_string()],
+            selection,
+            strategies,
+            ..WalletPoolConfig::default()
+        };
+        WalletPool::new(wallets, &config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_robin_cycles_wallets() {
+        let pool = pool(WalletSelection::RoundRobin, HashMap::new());
+        let mut picks = Vec::new();
+        for _ in 0..4 {
+            picks.push(pool.select("pump.fun").await);
+        }
+        assert_eq!(picks, vec![0, 1, 2, 0]);
+    }
+
+    #[tokio::test]
+    async fn least_exposure_spreads_positions() {
+        let pool = pool(WalletSelection::LeastExposure, HashMap::new());
+        pool.record_buy(0, Pubkey::new_unique(), 500).await;
+        pool.record_buy(1, Pubkey::new_unique(), 100).await;
+        assert_eq!(pool.select("pump.fun").await, 2);
+
+        let mint = Pubkey::new_unique();
+        pool.record_buy(2, mint, 1_000).await;
+        assert_eq!(pool.select("pump.fun").await, 1);
+
+        // Partial exit shrinks the position, a full exit closes it
+        assert_eq!(pool.holder_of(&mint).await, Some(2));
+        assert_eq!(pool.record_exit(&mint, 0.5).await, Some(2));
+        assert_eq!(pool.exposure(2).await, 500);
+        pool.record_exit(&mint, 1.0).await;
+        assert_eq!(pool.holder_of(&mint).await, None);
+        assert_eq!(pool.select("pump.fun").await, 2);
+    }
+
+    #[tokio::test]
+    async fn dedicated_wallets_per_strategy() {
+        let strategies = HashMap::from([("raydium".to_string(), 2)]);
+        let pool = pool(WalletSelection::Dedicated, strategies);
+        assert_eq!(pool.select("raydium").await, 2);
+        assert_eq!(pool.select("raydium").await, 2);
+        // Unmapped strategies fall back to round-robin
+        assert_eq!(pool.select("pump.fun").await, 0);
+        assert_eq!(pool.select("pump.fun").await, 1);
+    }
+
+    #[test]
+    fn strategy_indexes_must_exist() {
+        let wallets = vec![Arc::new(WalletManager::new_random())];
+        let config = WalletPoolConfig {
+            keypair_paths: vec!["unused".to_string()],
+            selection: WalletSelection::Dedicated,
+            strategies: HashMap::from([("pump.fun".to_string(), 1)]),
+            ..WalletPoolConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        assert!(WalletPool::new(wallets, &config).is_err());
+        assert!(WalletPoolConfig::default().validate().is_err());
+    }
+
+    #[test]
+    fn loads_keypairs_from_dir_in_name_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = WalletManager::new_random();
+        let b = WalletManager::new_random();
+        b.save_to_file(dir.path().join("b.json")).unwrap();
+        a.save_to_file(dir.path().join("a.json")).unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let config = WalletPoolConfig {
+            keypair_dir: Some(dir.path().display().to_string()),
+            ..WalletPoolConfig::default()
+        };
+        let passwords = PasswordProvider::new(crate::keystore::KeystoreConfig {
+            prompt: false,
+            ..Default::default()
+        });
+        let pool = WalletPool::load(&config, &passwords).unwrap();
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.index_of(&a.pubkey()), Some(0));
+        assert_eq!(pool.index_of(&b.pubkey()), Some(1));
+    }
+}
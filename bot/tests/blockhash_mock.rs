@@ -0,0 +1,174 @@
+//! Blockhash service and confirmation tracker against a local mock RPC.
+
+mod common;
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use common::MockHttpServer;
+use serde_json::{json, Value};
+use sniffer_bot_light::blockhash::{BlockhashConfig, BlockhashService};
+use sniffer_bot_light::confirmation::{ConfirmationOutcome, ConfirmationTracker};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, signature::Signature};
+
+/// Chain state the mock reports; the blockhash changes with the slot.
+struct Chain {
+    slot: AtomicU64,
+    block_height: AtomicU64,
+}
+
+fn hash_for_slot(slot: u64) -> Hash {
+    Hash::new_from_array([slot as u8; 32])
+}
+
+fn start_mock(chain: Arc<Chain>, status: Value) -> MockHttpServer {
+    MockHttpServer::start(move |req| answer(&chain, &status, req))
+}
+
+fn answer(chain: &Chain, status: &Value, req: &common::RecordedRequest) -> (u16, Value) {
+    let id = req.body["id"].clone();
+    let slot = chain.slot.load(Ordering::SeqCst);
+    let height = chain.block_height.load(Ordering::SeqCst);
+    let result = match req.body["method"].as_str() {
+        Some("getEpochInfo") => json!({
+            "absoluteSlot": slot,
+            "blockHeight": height,
+            "epoch": 1,
+            "slotIndex": slot,
+            "slotsInEpoch": 432000,
+            "transactionCount": null
+        }),
+        Some("getLatestBlockhash") => json!({
+            "context": {"slot": slot},
+            "value": {"blockhash": hash_for_slot(slot).to_string(), "lastValidBlockHeight": height + 150}
+        }),
+        Some("getSignatureStatuses") => json!({"context": {"slot": slot}, "value": [status.clone()]}),
+        other => panic!("unexpected RPC call {:?}", other),
+    };
+    (200, json!({"jsonrpc": "2.0", "id": id, "result": result}))
+}
+
+fn service(server: &MockHttpServer) -> Arc<BlockhashService> {
+    let config = BlockhashConfig {
+        confirm_poll_ms: 10,
+        ..BlockhashConfig::default()
+    };
+    Arc::new(BlockhashService::new(Arc::new(RpcClient::new(server.url.clone())), config))
+}
+
+fn chain(slot: u64, block_height: u64) -> Arc<Chain> {
+    Arc::new(Chain {
+        slot: AtomicU64::new(slot),
+        block_height: AtomicU64::new(block_height),
+    })
+}
+
+#[tokio::test]
+async fn refreshes_on_slot_cadence_and_tracks_last_valid_height() {
+    let chain = chain(10, 1_000);
+    let server = start_mock(chain.clone(), Value::Null);
+    let service = service(&server);
+
+    service.tick().await.unwrap();
+    let first = service.latest().await.unwrap();
+    assert_eq!(first.hash, hash_for_slot(10));
+    assert_eq!(first.last_valid_block_height, 1_150);
+    assert_eq!(service.block_height(), 1_000);
+
+    // Fewer slots than `refresh_every_slots`: the cached hash is kept
+    chain.slot.store(12, Ordering::SeqCst);
+    service.tick().await.unwrap();
+    assert_eq!(service.latest().await.unwrap().hash, first.hash);
+    assert_eq!(server.rpc_calls("getLatestBlockhash").len(), 1);
+
+    chain.slot.store(14, Ordering::SeqCst);
+    chain.block_height.store(1_004, Ordering::SeqCst);
+    service.tick().await.unwrap();
+    let second = service.latest().await.unwrap();
+    assert_eq!(second.hash, hash_for_slot(14));
+    assert_eq!(second.last_valid_block_height, 1_154);
+
+    // Older hashes stay resolvable for confirmation
+    assert_eq!(service.last_valid_block_height(&first.hash).await, Some(1_150));
+    assert!(!service.is_expired(first.last_valid_block_height));
+}
+
+#[tokio::test]
+async fn confirmed_status_is_reported() {
+    let chain = chain(20, 2_000);
+    let status = json!({
+        "slot": 21,
+        "confirmations": 3,
+        "err": null,
+        "status": {"Ok": null},
+        "confirmationStatus": "confirmed"
+    });
+    let server = start_mock(chain, status);
+    let service = service(&server);
+    let tracker = ConfirmationTracker::new(Arc::new(RpcClient::new(server.url.clone())), service);
+
+    let outcome = tracker.wait(&Signature::from([1u8; 64]), 2_150).await.unwrap();
+    assert_eq!(outcome, ConfirmationOutcome::Confirmed { slot: 21 });
+}
+
+#[tokio::test]
+async fn unseen_transaction_expires_with_its_blockhash() {
+    let chain = chain(30, 3_000);
+    let server = start_mock(chain.clone(), Value::Null);
+    let service = service(&server);
+    service.tick().await.unwrap();
+    let info = service.latest().await.unwrap();
+    let tracker = ConfirmationTracker::new(Arc::new(RpcClient::new(server.url.clone())), service);
+
+    let waiter = tokio::spawn(async move {
+        tracker.wait(&Signature::from([2u8; 64]), info.last_valid_block_height).await
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!waiter.is_finished(), "still valid, still waiting");
+
+    chain.block_height.store(info.last_valid_block_height + 1, Ordering::SeqCst);
+    let outcome = tokio::time::timeout(std::time::Duration::from_secs(5), waiter)
+        .await
+        .expect("expiry is detected from block height")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        outcome,
+        ConfirmationOutcome::Expired {
+            last_valid_block_height: 3_150,
+            block_height: 3_151
+        }
+    );
+}
+
+#[tokio::test]
+async fn transient_rpc_errors_do_not_end_the_wait() {
+    let chain = chain(40, 4_000);
+    let status = json!({
+        "slot": 41,
+        "confirmations": 1,
+        "err": null,
+        "status": {"Ok": null},
+        "confirmationStatus": "confirmed"
+    });
+    // The first few polls of either kind fail
+    let limited = AtomicUsize::new(0);
+    let server = MockHttpServer::start(move |req| {
+        if limited.fetch_add(1, Ordering::SeqCst) < 4 {
+            return (200, json!({"jsonrpc": "2.0", "id": req.body["id"].clone(), "error": {"code": -32005, "message": "Node is behind"}}));
+        }
+        answer(&chain, &status, req)
+    });
+    let service = service(&server);
+    let tracker = ConfirmationTracker::new(Arc::new(RpcClient::new(server.url.clone())), service);
+
+    let outcome = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        tracker.wait(&Signature::from([3u8; 64]), 4_150),
+    )
+    .await
+    .expect("retries until the status is known")
+    .unwrap();
+    assert_eq!(outcome, ConfirmationOutcome::Confirmed { slot: 41 });
+}
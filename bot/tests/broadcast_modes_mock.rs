@@ -0,0 +1,163 @@
+//! RpcManager broadcast modes against several local mock RPC endpoints.
+
+mod common;
+
+use std::time::Duration;
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::config::{BroadcastMode, Config};
+use sniffer_bot_light::rpc_manager::{RpcBroadcaster, RpcManager};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::VersionedTransaction,
+};
+
+const COMPUTE_BUDGET_PROGRAM: Pubkey =
+    solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Endpoint accepting every transaction; with `already_processed` it answers like a node that
+/// has seen the transaction from another sender.
+fn endpoint(already_processed: bool) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        assert_eq!(req.body["method"], "sendTransaction");
+        if already_processed {
+            return (200, json!({"jsonrpc": "2.0", "id": id, "error": {
+                "code": -32002, "message": "Transaction simulation failed: This transaction has already been processed"
+            }}));
+        }
+        let tx = decode(&req.body["params"][0]);
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": tx.signatures[0].to_string()}))
+    })
+}
+
+fn decode(param: &serde_json::Value) -> VersionedTransaction {
+    let wire = base64::engine::general_purpose::STANDARD
+        .decode(param.as_str().unwrap())
+        .unwrap();
+    bincode::deserialize(&wire).unwrap()
+}
+
+/// Signed transaction bidding `priority_fee` micro-lamports per CU.
+fn signed_tx(priority_fee: u64) -> VersionedTransaction {
+    let payer = Keypair::new();
+    let mut data = vec![3u8];
+    data.extend_from_slice(&priority_fee.to_le_bytes());
+    let ix = Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM, &data, vec![]);
+    let message = v0::Message::try_compile(&payer.pubkey(), &[ix], &[], Hash::new_unique()).unwrap();
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer]).unwrap()
+}
+
+fn manager(servers: &[MockHttpServer], mode: BroadcastMode) -> RpcManager {
+    let config = Config {
+        broadcast_mode: mode,
+        rpc_timeout_sec: 2,
+        ..Config::default()
+    };
+    RpcManager::new(servers.iter().map(|s| s.url.clone()).collect(), config)
+}
+
+/// Signatures each endpoint received, once `total` sends arrived (redundant sends may still
+/// be in flight when the broadcast returns).
+async fn received(servers: &[MockHttpServer], total: usize) -> Vec<Vec<Signature>> {
+    for _ in 0..200 {
+        let count: usize = servers.iter().map(|s| s.rpc_calls("sendTransaction").len()).sum();
+        if count >= total {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    servers
+        .iter()
+        .map(|s| {
+            s.rpc_calls("sendTransaction")
+                .iter()
+                .map(|r| decode(&r.body["params"][0]).signatures[0])
+                .collect()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn pairwise_sends_one_transaction_per_endpoint() {
+    let servers: Vec<_> = (0..3).map(|_| endpoint(false)).collect();
+    let txs = vec![signed_tx(1), signed_tx(2)];
+    let sig = manager(&servers, BroadcastMode::Pairwise).send_on_many_rpc(txs.clone(), None).await.unwrap();
+    assert!(sig == txs[0].signatures[0] || sig == txs[1].signatures[0]);
+
+    let got = received(&servers, 2).await;
+    assert!(got[0].is_empty() || got[0] == vec![txs[0].signatures[0]]);
+    assert!(got[1].is_empty() || got[1] == vec![txs[1].signatures[0]]);
+    assert!(got[2].is_empty(), "more endpoints than transactions leaves the rest idle");
+}
+
+#[tokio::test]
+async fn replicate_sends_the_highest_fee_transaction_everywhere() {
+    let servers: Vec<_> = (0..3).map(|_| endpoint(false)).collect();
+    let txs = vec![signed_tx(100), signed_tx(5_000), signed_tx(5_000)];
+    let best = txs[1].signatures[0];
+    let sig = manager(&servers, BroadcastMode::Replicate).send_on_many_rpc(txs, None).await.unwrap();
+    assert_eq!(sig, best);
+
+    let got = received(&servers, 3).await;
+    assert_eq!(got, vec![vec![best]; 3]);
+}
+
+#[tokio::test]
+async fn round_robin_spreads_transactions_and_rotates_between_broadcasts() {
+    let servers: Vec<_> = (0..2).map(|_| endpoint(false)).collect();
+    let rpc = manager(&servers, BroadcastMode::RoundRobin);
+    let first = vec![signed_tx(1), signed_tx(2), signed_tx(3)];
+    rpc.send_on_many_rpc(first.clone(), None).await.unwrap();
+    let got = received(&servers, 3).await;
+    assert_eq!(got[0].len() + got[1].len(), 3);
+    let mut expected_0 = vec![first[0].signatures[0], first[2].signatures[0]];
+    let mut got_0 = got[0].clone();
+    expected_0.sort();
+    got_0.sort();
+    assert_eq!(got_0, expected_0);
+    assert_eq!(got[1], vec![first[1].signatures[0]]);
+
+    // The next broadcast starts where the last one stopped, whatever the endpoint scores
+    let second = signed_tx(4);
+    rpc.send_on_many_rpc(vec![second.clone()], None).await.unwrap();
+    let got = received(&servers, 4).await;
+    assert_eq!(got[1].len(), 2);
+    assert!(got[1].contains(&second.signatures[0]));
+}
+
+#[tokio::test]
+async fn full_fanout_sends_every_transaction_everywhere_once() {
+    let servers: Vec<_> = (0..2).map(|_| endpoint(false)).collect();
+    let a = signed_tx(1);
+    let b = signed_tx(2);
+    // `a` twice: duplicates are sent once per endpoint
+    let txs = vec![a.clone(), b.clone(), a.clone()];
+    manager(&servers, BroadcastMode::FullFanout).send_on_many_rpc(txs, None).await.unwrap();
+
+    let got = received(&servers, 4).await;
+    for per_endpoint in got {
+        let mut per_endpoint = per_endpoint;
+        per_endpoint.sort();
+        let mut expected = vec![a.signatures[0], b.signatures[0]];
+        expected.sort();
+        assert_eq!(per_endpoint, expected);
+    }
+}
+
+#[tokio::test]
+async fn already_processed_counts_as_delivered() {
+    let servers = vec![endpoint(true), endpoint(true)];
+    let tx = signed_tx(1);
+    let sig = manager(&servers, BroadcastMode::Replicate)
+        .send_on_many_rpc(vec![tx.clone()], None)
+        .await
+        .unwrap();
+    assert_eq!(sig, tx.signatures[0]);
+}
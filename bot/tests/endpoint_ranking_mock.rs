@@ -0,0 +1,101 @@
+//! Endpoint ranking and circuit breakers in RpcManager against local mock endpoints.
+
+mod common;
+
+use std::time::Duration;
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::config::{BroadcastMode, Config};
+use sniffer_bot_light::endpoints::endpoint_server;
+use sniffer_bot_light::metrics::metrics;
+use sniffer_bot_light::rpc_manager::{BreakerState, EndpointRankingConfig, RpcBroadcaster, RpcManager};
+use solana_sdk::{
+    hash::Hash,
+    message::{v0, VersionedMessage},
+    signature::{Keypair, Signer},
+    transaction::VersionedTransaction,
+};
+
+/// Endpoint answering `sendTransaction`; unhealthy ones fail with HTTP 503.
+fn endpoint(healthy: bool) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        if !healthy {
+            return (503, json!({"error": "unavailable"}));
+        }
+        let wire = base64::engine::general_purpose::STANDARD
+            .decode(req.body["params"][0].as_str().unwrap())
+            .unwrap();
+        let tx: VersionedTransaction = bincode::deserialize(&wire).unwrap();
+        (200, json!({"jsonrpc": "2.0", "id": req.body["id"].clone(), "result": tx.signatures[0].to_string()}))
+    })
+}
+
+fn signed_tx() -> VersionedTransaction {
+    let payer = Keypair::new();
+    let message = v0::Message::try_compile(&payer.pubkey(), &[], &[], Hash::new_unique()).unwrap();
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer]).unwrap()
+}
+
+/// Replicate so every endpoint sees every broadcast; failed sends are recorded in the
+/// background, so wait for `sends` requests to `server` before looking at the state.
+async fn broadcast_and_settle(rpc: &RpcManager, server: &MockHttpServer, sends: usize) {
+    let _ = rpc.send_on_many_rpc(vec![signed_tx()], None).await;
+    for _ in 0..200 {
+        if server.requests().len() >= sends {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+#[tokio::test]
+async fn failing_endpoint_is_ranked_last_then_tripped_and_retried_half_open() {
+    let bad = endpoint(false);
+    let good = endpoint(true);
+    let config = Config {
+        broadcast_mode: BroadcastMode::Replicate,
+        rpc_timeout_sec: 2,
+        endpoint_ranking: Some(EndpointRankingConfig {
+            failure_threshold: 2,
+            open_ms: 300,
+            ..EndpointRankingConfig::default()
+        }),
+        ..Config::default()
+    };
+    let rpc = RpcManager::new(vec![bad.url.clone(), good.url.clone()], config);
+    assert_eq!(rpc.get_ranked_endpoints().await, vec![0, 1], "unmeasured endpoints keep config order");
+
+    broadcast_and_settle(&rpc, &bad, 1).await;
+    assert_eq!(rpc.get_ranked_endpoints().await, vec![1, 0]);
+    let statuses = rpc.endpoint_statuses().await;
+    assert_eq!(statuses[0].endpoint, good.url);
+    assert_eq!(statuses[1].breaker, BreakerState::Closed);
+    assert_eq!(statuses[1].consecutive_failures, 1);
+
+    // Second consecutive failure opens the breaker: the endpoint leaves the rotation
+    let opened_before = metrics().get_counter("rpc_circuit_breaker_opened");
+    broadcast_and_settle(&rpc, &bad, 2).await;
+    assert_eq!(rpc.get_ranked_endpoints().await, vec![1]);
+    assert_eq!(rpc.endpoint_statuses().await[1].breaker, BreakerState::Open);
+    assert!(metrics().get_counter("rpc_circuit_breaker_opened") > opened_before);
+
+    broadcast_and_settle(&rpc, &bad, 3).await;
+    assert_eq!(bad.requests().len(), 2, "open breaker receives no sends");
+
+    // Published to /status and gauges
+    let status: serde_json::Value = serde_json::from_str(&endpoint_server().get_status_response().await).unwrap();
+    assert_eq!(status["rpc_endpoints"][1]["endpoint"], bad.url);
+    assert_eq!(status["rpc_endpoints"][1]["breaker"], "open");
+    assert_eq!(metrics().get_gauge("rpc_endpoint_0_breaker"), 2);
+
+    // After the timer the endpoint gets one trial; failing it reopens the breaker at once
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    assert_eq!(rpc.endpoint_statuses().await[1].breaker, BreakerState::HalfOpen);
+    assert_eq!(rpc.get_ranked_endpoints().await, vec![1, 0]);
+    broadcast_and_settle(&rpc, &bad, 3).await;
+    assert_eq!(bad.requests().len(), 3);
+    assert_eq!(rpc.endpoint_statuses().await[1].breaker, BreakerState::Open);
+}
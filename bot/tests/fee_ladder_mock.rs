@@ -0,0 +1,216 @@
+//! Fee ladder rungs against a local mock RPC serving a durable nonce account on which only
+//! the second rung lands.
+
+mod common;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::blockhash::{BlockhashConfig, BlockhashService};
+use sniffer_bot_light::buy_engine::BuyEngine;
+use sniffer_bot_light::config::Config;
+use sniffer_bot_light::confirmation::ConfirmationTracker;
+use sniffer_bot_light::fee_estimator::priority_fee_of;
+use sniffer_bot_light::fee_ladder::{FeeLadderConfig, FeeRung};
+use sniffer_bot_light::metrics::metrics;
+use sniffer_bot_light::nonce_manager::NonceManager;
+use sniffer_bot_light::observability::CorrelationId;
+use sniffer_bot_light::rpc_manager::RpcBroadcaster;
+use sniffer_bot_light::tx_builder::{TransactionBuilder, TransactionConfig};
+use sniffer_bot_light::types::{AppState, Mode, PremintCandidate};
+use sniffer_bot_light::wallet::WalletManager;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use tokio::sync::mpsc;
+
+const NONCE_VALUE: Hash = Hash::new_from_array([7; 32]);
+
+fn account(owner: &str, data: &[u8]) -> serde_json::Value {
+    json!({"context": {"slot": 1}, "value": {
+        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        "executable": false,
+        "lamports": 1_000_000,
+        "owner": owner,
+        "rentEpoch": 0,
+        "space": data.len()
+    }})
+}
+
+/// An initialized nonce account (`Versions::Current(State::Initialized(..))`) holding
+/// `NONCE_VALUE` under `authority`.
+fn nonce_account_data(authority: &Pubkey) -> Vec<u8> {
+    let mut data = Vec::with_capacity(80);
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(NONCE_VALUE.as_ref());
+    data.extend_from_slice(&5_000u64.to_le_bytes());
+    data
+}
+
+/// The chain is at height 200 with blockhashes valid through 300; of every
+/// `getSignatureStatuses` batch only the second signature is confirmed.
+fn start_mock(nonce_account: Pubkey, authority: Pubkey) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        let result = match req.body["method"].as_str() {
+            Some("getLatestBlockhash") => json!({
+                "context": {"slot": 1},
+                "value": {"blockhash": Hash::new_from_array([1; 32]).to_string(), "lastValidBlockHeight": 300}
+            }),
+            Some("getEpochInfo") => json!({
+                "absoluteSlot": 200, "blockHeight": 200, "epoch": 1,
+                "slotIndex": 200, "slotsInEpoch": 432000, "transactionCount": null
+            }),
+            Some("getSignatureStatuses") => {
+                let count = req.body["params"][0].as_array().map_or(0, |sigs| sigs.len());
+                let value: Vec<serde_json::Value> = (0..count)
+                    .map(|i| match i {
+                        1 => json!({
+                            "slot": 201, "confirmations": 1, "err": null,
+                            "status": {"Ok": null}, "confirmationStatus": "confirmed"
+                        }),
+                        _ => serde_json::Value::Null,
+                    })
+                    .collect();
+                json!({"context": {"slot": 201}, "value": value})
+            }
+            Some("getAccountInfo") if req.body["params"][0] == nonce_account.to_string() => account(
+                &solana_sdk::system_program::id().to_string(),
+                &nonce_account_data(&authority),
+            ),
+            Some("getAccountInfo") => {
+                let mut mint = vec![0u8; 82];
+                mint[44] = 6;
+                mint[45] = 1;
+                account(&spl_token::id().to_string(), &mint)
+            }
+            other => {
+                return (200, json!({"jsonrpc": "2.0", "id": id, "error": {
+                    "code": -32601, "message": format!("unexpected method {:?}", other)
+                }}))
+            }
+        };
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    })
+}
+
+/// Accepts every broadcast, recording the transactions of each call; the first one "wins".
+#[derive(Debug, Default)]
+struct RecordingBroadcaster {
+    sends: Mutex<Vec<Vec<VersionedTransaction>>>,
+}
+
+impl RpcBroadcaster for RecordingBroadcaster {
+    fn send_on_many_rpc<'a>(
+        &'a self,
+        txs: Vec<VersionedTransaction>,
+        _correlation_id: Option<CorrelationId>,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Signature>> + Send + 'a>> {
+        let sig = txs[0].signatures[0];
+        self.sends.lock().unwrap().push(txs);
+        Box::pin(async move { Ok(sig) })
+    }
+}
+
+#[tokio::test]
+async fn rungs_share_one_nonce_and_the_landed_one_is_recorded() {
+    let wallet = Arc::new(WalletManager::new_random());
+    let nonce_account = Pubkey::new_unique();
+    let server = start_mock(nonce_account, wallet.pubkey());
+    let rpc_client = Arc::new(RpcClient::new(server.url.clone()));
+    let service = Arc::new(BlockhashService::new(
+        rpc_client.clone(),
+        BlockhashConfig { confirm_poll_ms: 10, ..BlockhashConfig::default() },
+    ));
+    let tracker = Arc::new(ConfirmationTracker::new(rpc_client, service.clone()));
+
+    let tx_config = TransactionConfig {
+        rpc_endpoints: vec![server.url.clone()],
+        ..TransactionConfig::default()
+    };
+    let builder = TransactionBuilder::new(
+        wallet,
+        vec![server.url.clone()],
+        Arc::new(NonceManager::new(4)),
+        &tx_config,
+    )
+    .await
+    .unwrap()
+    .with_blockhash_service(service);
+
+    let ladder = FeeLadderConfig {
+        rungs: vec![
+            FeeRung::default(),
+            FeeRung { priority_fee_multiplier: 2.0, ..FeeRung::default() },
+            FeeRung { priority_fee_multiplier: 4.0, ..FeeRung::default() },
+        ],
+        nonce_account: nonce_account.to_string(),
+    };
+    let app_state = Arc::new(tokio::sync::Mutex::new(AppState {
+        mode: Mode::Sniffing,
+        active_token: None,
+        last_buy_price: None,
+        holdings_percent: 0.0,
+        quantum_suggestions: Vec::new(),
+    }));
+    let (tx, rx) = mpsc::channel(1);
+    let rpc = Arc::new(RecordingBroadcaster::default());
+    let mut engine = BuyEngine::new(
+        rpc.clone(),
+        Arc::new(NonceManager::new(3)),
+        rx,
+        app_state.clone(),
+        Config { nonce_count: 3, fee_ladder: Some(ladder), ..Config::default() },
+        Some(builder),
+    )
+    .with_confirmation_tracker(tracker);
+
+    let landed_before = metrics().get_counter("fee_ladder_rung_1_landed");
+    tx.send(PremintCandidate {
+        mint: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        program: "pump.fun".to_string(),
+        slot: 1,
+        timestamp: 0,
+        instruction_summary: None,
+        is_jito_bundle: None,
+        detected_at_ms: 0,
+        token_program: None,
+    })
+    .await
+    .unwrap();
+    drop(tx);
+    engine.run().await;
+
+    // Every rung goes out in one broadcast
+    let sends = rpc.sends.lock().unwrap().clone();
+    assert_eq!(sends.len(), 1, "{} broadcasts", sends.len());
+    let rungs = &sends[0];
+    assert_eq!(rungs.len(), 3);
+
+    // Each rung advances the shared nonce first and is built on its value
+    for rung in rungs {
+        assert_eq!(*rung.message.recent_blockhash(), NONCE_VALUE);
+        let keys = rung.message.static_account_keys();
+        let advance = &rung.message.instructions()[0];
+        assert_eq!(keys[advance.program_id_index as usize], solana_sdk::system_program::id());
+        assert_eq!(advance.data, 4u32.to_le_bytes());
+        assert_eq!(keys[advance.accounts[0] as usize], nonce_account);
+    }
+
+    // The rungs bid differently, so they are distinct transactions
+    let fee = priority_fee_of(&rungs[0]).unwrap();
+    assert_eq!(priority_fee_of(&rungs[1]), Some(fee * 2));
+    assert_eq!(priority_fee_of(&rungs[2]), Some(fee * 4));
+
+    // Only the second rung's signature confirmed, and that is the rung recorded
+    assert_eq!(metrics().get_counter("fee_ladder_rung_1_landed"), landed_before + 1);
+    assert_eq!(metrics().get_gauge("fee_ladder_landed_rung"), 1);
+    assert_eq!(metrics().get_gauge("buy_landed_priority_fee_micro_lamports"), fee * 2);
+    assert!(!app_state.lock().await.is_sniffing());
+}
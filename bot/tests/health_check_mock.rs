@@ -0,0 +1,69 @@
+//! Health checker slot-lag demotion against local mock RPC endpoints.
+
+mod common;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::config::Config;
+use sniffer_bot_light::health_check::{HealthCheckConfig, HealthChecker};
+use sniffer_bot_light::metrics::metrics;
+use sniffer_bot_light::rpc_manager::RpcManager;
+use sniffer_bot_light::rpc_pool::{rpc_pool, EndpointRole};
+
+/// Endpoint reporting `slot`, and `getHealth` "ok" unless `healthy` is false.
+fn endpoint(slot: Arc<AtomicU64>, healthy: bool) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        match req.body["method"].as_str().unwrap() {
+            "getHealth" if !healthy => (200, json!({"jsonrpc": "2.0", "id": id, "error": {
+                "code": -32005, "message": "Node is unhealthy"
+            }})),
+            "getHealth" => (200, json!({"jsonrpc": "2.0", "id": id, "result": "ok"})),
+            "getSlot" => (200, json!({"jsonrpc": "2.0", "id": id, "result": slot.load(Ordering::SeqCst)})),
+            other => panic!("unexpected {other}"),
+        }
+    })
+}
+
+#[tokio::test]
+async fn lagging_and_unhealthy_endpoints_are_demoted_until_they_recover() {
+    let lagging_slot = Arc::new(AtomicU64::new(80));
+    let servers = [
+        endpoint(Arc::new(AtomicU64::new(100)), true),
+        endpoint(lagging_slot.clone(), true),
+        endpoint(Arc::new(AtomicU64::new(100)), false),
+    ];
+    let urls: Vec<String> = servers.iter().map(|s| s.url.clone()).collect();
+    rpc_pool().configure(&urls, Default::default());
+    let checker = HealthChecker::new(urls.clone(), Vec::new(), HealthCheckConfig::default());
+
+    let results = checker.check_once().await;
+    assert_eq!(results[0].slot_lag, Some(0));
+    assert!(!results[0].demoted);
+    assert_eq!(results[1].slot_lag, Some(20));
+    assert!(results[1].demoted);
+    assert!(!results[2].healthy && results[2].demoted);
+
+    assert_eq!(metrics().get_gauge("rpc_best_slot"), 100);
+    assert_eq!(metrics().get_gauge("http_endpoint_1_slot_lag"), 20);
+    assert_eq!(metrics().get_gauge("http_endpoint_0_healthy"), 1);
+    assert_eq!(metrics().get_gauge("http_endpoint_1_healthy"), 0);
+    assert!(metrics().get_counter("rpc_endpoint_demotions") >= 2);
+
+    // Demoted endpoints are skipped by broadcasts and ordered last for reads
+    let rpc = RpcManager::new(urls.clone(), Config::default());
+    assert_eq!(rpc.get_ranked_endpoints().await, vec![0]);
+    assert!(rpc.endpoint_statuses().await[1].demoted);
+    assert_eq!(rpc_pool().endpoints(EndpointRole::Read)[0], urls[0]);
+
+    lagging_slot.store(95, Ordering::SeqCst);
+    let results = checker.check_once().await;
+    assert_eq!(results[1].slot_lag, Some(5));
+    assert!(!results[1].demoted);
+    let mut ranked = rpc.get_ranked_endpoints().await;
+    ranked.sort();
+    assert_eq!(ranked, vec![0, 1]);
+}
@@ -0,0 +1,235 @@
+//! JitoBroadcaster against a local mock block engine.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::jito::{JitoBroadcaster, JitoClient, JitoConfig};
+use sniffer_bot_light::metrics::metrics;
+use sniffer_bot_light::rpc_manager::RpcBroadcaster;
+use sniffer_bot_light::wallet::WalletManager;
+use solana_sdk::{
+    hash::Hash,
+    message::{v0::Message as MessageV0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+
+fn jito_config(url: &str) -> JitoConfig {
+    JitoConfig {
+        block_engine_urls: vec![url.to_string()],
+        tip_lamports: 5_000,
+        status_poll_interval_ms: 10,
+        status_timeout_ms: 2_000,
+        ..JitoConfig::default()
+    }
+}
+
+fn signed_tx(payer: &Pubkey, sig: Signature) -> VersionedTransaction {
+    #[allow(deprecated)]
+    let ix = solana_sdk::system_instruction::transfer(payer, &Pubkey::new_unique(), 1);
+    let msg = MessageV0::try_compile(payer, &[ix], &[], Hash::new_unique()).unwrap();
+    VersionedTransaction {
+        signatures: vec![sig],
+        message: VersionedMessage::V0(msg),
+    }
+}
+
+/// Wait (bounded) for background status polling to reach `check`.
+async fn eventually(check: impl Fn() -> bool) -> bool {
+    for _ in 0..200 {
+        if check() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    check()
+}
+
+#[tokio::test]
+async fn bundle_is_submitted_with_a_signed_tip_and_tracked_in_background() {
+    let our_sig = Signature::from([3u8; 64]);
+    let polls = Arc::new(AtomicUsize::new(0));
+    let polls_in_handler = polls.clone();
+
+    let server = MockHttpServer::start(move |req| {
+        match req.body["method"].as_str() {
+            Some("sendBundle") => (200, json!({"jsonrpc": "2.0", "id": 1, "result": "bundle-1"})),
+            Some("getBundleStatuses") => {
+                // First poll: unknown yet, then confirmed
+                if polls_in_handler.fetch_add(1, Ordering::SeqCst) == 0 {
+                    (200, json!({"jsonrpc": "2.0", "id": 1, "result": {"context": {"slot": 1}, "value": [null]}}))
+                } else {
+                    (200, json!({"jsonrpc": "2.0", "id": 1, "result": {"context": {"slot": 2}, "value": [{
+                        "bundle_id": "bundle-1",
+                        "transactions": [Signature::from([3u8; 64]).to_string(), "tip-sig"],
+                        "slot": 2,
+                        "confirmation_status": "confirmed",
+                        "err": {"Ok": null}
+                    }]}}))
+                }
+            }
+            _ => (404, json!({"error": "unknown method"})),
+        }
+    });
+
+    let wallet = Arc::new(WalletManager::new_random());
+    let client = Arc::new(JitoClient::new(jito_config(&server.url)).unwrap());
+    let broadcaster = JitoBroadcaster::new(client, wallet.clone());
+
+    let sig = broadcaster
+        .send_on_many_rpc(vec![signed_tx(&wallet.pubkey(), our_sig)], None)
+        .await
+        .expect("bundle should be accepted");
+    assert_eq!(sig, our_sig);
+
+    let sends = server.rpc_calls("sendBundle");
+    assert_eq!(sends.len(), 1);
+    assert_eq!(sends[0].path, "/api/v1/bundles");
+    let params = &sends[0].body["params"];
+    assert_eq!(params[0].as_array().unwrap().len(), 2, "transaction plus tip");
+    assert_eq!(params[1]["encoding"], "base64");
+
+    // The tip transfer carries a real signature from the wallet
+    let tip_bytes = base64::engine::general_purpose::STANDARD
+        .decode(params[0][1].as_str().unwrap())
+        .unwrap();
+    let tip_tx: VersionedTransaction = bincode::deserialize(&tip_bytes).unwrap();
+    assert_eq!(tip_tx.message.static_account_keys()[0], wallet.pubkey());
+    assert!(tip_tx.verify_with_results().into_iter().all(|ok| ok));
+
+    // Status is polled after the broadcast returned
+    assert!(eventually(|| polls.load(Ordering::SeqCst) >= 2).await);
+}
+
+#[tokio::test]
+async fn failed_bundle_is_reported_in_background() {
+    let server = MockHttpServer::start(|req| match req.body["method"].as_str() {
+        Some("sendBundle") => (200, json!({"jsonrpc": "2.0", "id": 1, "result": "bundle-2"})),
+        _ => (200, json!({"jsonrpc": "2.0", "id": 1, "result": {"context": {"slot": 1}, "value": [{
+            "bundle_id": "bundle-2",
+            "transactions": [],
+            "slot": 1,
+            "confirmation_status": "processed",
+            "err": {"Err": "BundleFailed"}
+        }]}})),
+    });
+
+    let wallet = Arc::new(WalletManager::new_random());
+    let client = Arc::new(JitoClient::new(jito_config(&server.url)).unwrap());
+    let broadcaster = JitoBroadcaster::new(client, wallet.clone());
+
+    let failed_before = metrics().get_counter("jito_bundles_failed");
+    let sig = broadcaster
+        .send_on_many_rpc(vec![signed_tx(&wallet.pubkey(), Signature::from([4u8; 64]))], None)
+        .await
+        .expect("submission is accepted before the bundle fails");
+    assert_eq!(sig, Signature::from([4u8; 64]));
+    assert!(eventually(|| metrics().get_counter("jito_bundles_failed") > failed_before).await);
+}
+
+#[tokio::test]
+async fn block_engine_rejection_surfaces_error() {
+    let server = MockHttpServer::start(|_| {
+        (200, json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "bundle contains an expired blockhash"}}))
+    });
+
+    let wallet = Arc::new(WalletManager::new_random());
+    let client = Arc::new(JitoClient::new(jito_config(&server.url)).unwrap());
+    let broadcaster = JitoBroadcaster::new(client, wallet.clone());
+
+    let err = broadcaster
+        .send_on_many_rpc(vec![signed_tx(&wallet.pubkey(), Signature::from([5u8; 64]))], None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("expired blockhash"));
+    assert!(server.rpc_calls("getBundleStatuses").is_empty());
+}
+
+#[tokio::test]
+async fn self_tipped_ladder_transactions_are_bundled_individually() {
+    let server = MockHttpServer::start(|req| match req.body["method"].as_str() {
+        Some("sendBundle") => (200, json!({"jsonrpc": "2.0", "id": 1, "result": "bundle-3"})),
+        Some("getBundleStatuses") => (200, json!({"jsonrpc": "2.0", "id": 1, "result": {"context": {"slot": 3}, "value": [{
+            "bundle_id": "bundle-3",
+            "slot": 3,
+            "confirmation_status": "confirmed",
+            "err": {"Ok": null}
+        }]}})),
+        _ => (404, json!({"error": "unknown method"})),
+    });
+
+    let wallet = Arc::new(WalletManager::new_random());
+    let client = Arc::new(JitoClient::new(jito_config(&server.url)).unwrap());
+    let tipped = |sig: Signature, lamports: u64| {
+        #[allow(deprecated)]
+        let tip = solana_sdk::system_instruction::transfer(&wallet.pubkey(), &client.next_tip_account(), lamports);
+        let msg = MessageV0::try_compile(&wallet.pubkey(), &[tip], &[], Hash::new_unique()).unwrap();
+        VersionedTransaction { signatures: vec![sig], message: VersionedMessage::V0(msg) }
+    };
+    let rungs = vec![tipped(Signature::from([6u8; 64]), 10_000), tipped(Signature::from([7u8; 64]), 50_000)];
+    assert!(rungs.iter().all(|tx| client.carries_tip(tx)));
+
+    let broadcaster = JitoBroadcaster::new(client.clone(), wallet.clone());
+    let sig = broadcaster.send_on_many_rpc(rungs, None).await.expect("a rung should land");
+    assert!(sig == Signature::from([6u8; 64]) || sig == Signature::from([7u8; 64]));
+
+    let sends = server.rpc_calls("sendBundle");
+    assert_eq!(sends.len(), 2, "one bundle per rung");
+    assert!(sends.iter().all(|s| s.body["params"][0].as_array().unwrap().len() == 1), "no separate tip");
+}
+
+/// Records what reaches the RPC path and fails, so every route runs to completion.
+#[derive(Debug, Default)]
+struct RecordingRpc {
+    sent: std::sync::Mutex<Vec<Vec<Signature>>>,
+}
+
+impl RpcBroadcaster for RecordingRpc {
+    fn send_on_many_rpc<'a>(
+        &'a self,
+        txs: Vec<VersionedTransaction>,
+        _correlation_id: Option<sniffer_bot_light::observability::CorrelationId>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = anyhow::Result<Signature>> + Send + 'a>> {
+        self.sent.lock().unwrap().push(txs.iter().map(|tx| tx.signatures[0]).collect());
+        Box::pin(async { Err(anyhow::anyhow!("rpc unavailable")) })
+    }
+}
+
+#[tokio::test]
+async fn mixed_ladder_bundles_tipped_rungs_and_sends_the_rest_over_rpc() {
+    // Reject every bundle so all routes complete and can be inspected
+    let server = MockHttpServer::start(|_| {
+        (200, json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "rejected"}}))
+    });
+
+    let wallet = Arc::new(WalletManager::new_random());
+    let client = Arc::new(JitoClient::new(jito_config(&server.url)).unwrap());
+    let tipped = |sig: Signature| {
+        #[allow(deprecated)]
+        let tip = solana_sdk::system_instruction::transfer(&wallet.pubkey(), &client.next_tip_account(), 10_000);
+        let msg = MessageV0::try_compile(&wallet.pubkey(), &[tip], &[], Hash::new_unique()).unwrap();
+        VersionedTransaction { signatures: vec![sig], message: VersionedMessage::V0(msg) }
+    };
+    let untipped_sig = Signature::from([8u8; 64]);
+    let rungs = vec![
+        tipped(Signature::from([6u8; 64])),
+        signed_tx(&wallet.pubkey(), untipped_sig),
+        tipped(Signature::from([7u8; 64])),
+    ];
+
+    let rpc = Arc::new(RecordingRpc::default());
+    let broadcaster = JitoBroadcaster::new(client.clone(), wallet.clone()).with_rpc(rpc.clone());
+    assert!(broadcaster.send_on_many_rpc(rungs, None).await.is_err());
+
+    let sends = server.rpc_calls("sendBundle");
+    assert_eq!(sends.len(), 2, "one bundle per tipped rung");
+    assert!(sends.iter().all(|s| s.body["params"][0].as_array().unwrap().len() == 1), "no separate tip");
+    assert_eq!(*rpc.sent.lock().unwrap(), vec![vec![untipped_sig]], "untipped rung goes over RPC");
+}
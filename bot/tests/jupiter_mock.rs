@@ -0,0 +1,358 @@
+//! Jupiter fallback route against a local mock quote/swap API (the same server answers RPC).
+
+mod common;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::{json, Value};
+use sniffer_bot_light::buy_engine::BuyEngine;
+use sniffer_bot_light::config::Config;
+use sniffer_bot_light::dex::WSOL_MINT;
+use sniffer_bot_light::fee_estimator::priority_fee_of;
+use sniffer_bot_light::nonce_manager::NonceManager;
+use sniffer_bot_light::observability::CorrelationId;
+use sniffer_bot_light::rpc_manager::RpcBroadcaster;
+use sniffer_bot_light::tx_builder::{TransactionBuilder, TransactionConfig};
+use sniffer_bot_light::types::{AppState, Mode, PremintCandidate};
+use sniffer_bot_light::wallet::WalletManager;
+use solana_sdk::{
+    hash::Hash, message::VersionedMessage, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use tokio::sync::mpsc;
+
+const COMPUTE_BUDGET_PROGRAM: Pubkey =
+    solana_sdk::pubkey!("ComputeBudget111111111111111111111111111111");
+
+struct Fixture {
+    mint: Pubkey,
+    swap_program: Pubkey,
+    lookup_table: Pubkey,
+    /// Account only reachable through the lookup table
+    pool_account: Pubkey,
+}
+
+fn b64(data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+fn account(owner: &Pubkey, data: &[u8]) -> Value {
+    json!({
+        "data": [b64(data), "base64"],
+        "executable": false,
+        "lamports": 2_039_280,
+        "owner": owner.to_string(),
+        "rentEpoch": 0,
+        "space": data.len()
+    })
+}
+
+fn start_mock(fx: &Fixture, token_balance: u64) -> MockHttpServer {
+    let mint = fx.mint;
+    let swap_program = fx.swap_program;
+    let lookup_table = fx.lookup_table;
+    let pool_account = fx.pool_account;
+
+    MockHttpServer::start(move |req| {
+        if req.path.starts_with("/quote") {
+            return (200, json!({
+                "inputMint": mint.to_string(),
+                "outputMint": WSOL_MINT.to_string(),
+                "inAmount": "500000",
+                "outAmount": "123456",
+                "otherAmountThreshold": "111110",
+                "slippageBps": 1000
+            }));
+        }
+        if req.path == "/swap-instructions" {
+            let user = req.body["userPublicKey"].as_str().unwrap().to_string();
+            let source = spl_associated_token_account::get_associated_token_address(&user.parse().unwrap(), &mint);
+            return (200, json!({
+                "computeBudgetInstructions": [{
+                    "programId": COMPUTE_BUDGET_PROGRAM.to_string(),
+                    "accounts": [],
+                    "data": b64(&[3, 0xe7, 0x03, 0, 0, 0, 0, 0, 0])
+                }],
+                "setupInstructions": [{
+                    "programId": spl_associated_token_account::id().to_string(),
+                    "accounts": [{"pubkey": user, "isSigner": true, "isWritable": true}],
+                    "data": b64(&[1])
+                }],
+                "swapInstruction": {
+                    "programId": swap_program.to_string(),
+                    "accounts": [
+                        {"pubkey": user, "isSigner": true, "isWritable": false},
+                        {"pubkey": source.to_string(), "isSigner": false, "isWritable": true},
+                        {"pubkey": pool_account.to_string(), "isSigner": false, "isWritable": true}
+                    ],
+                    "data": b64(&[9, 9, 9])
+                },
+                "cleanupInstruction": null,
+                "otherInstructions": [],
+                "addressLookupTableAddresses": [lookup_table.to_string()]
+            }));
+        }
+
+        let id = req.body["id"].clone();
+        let result = match req.body["method"].as_str() {
+            Some("getLatestBlockhash") => json!({
+                "context": {"slot": 1},
+                "value": {"blockhash": Hash::new_unique().to_string(), "lastValidBlockHeight": 100}
+            }),
+            Some("getAccountInfo") if req.body["params"][0] == mint.to_string() => {
+                json!({"context": {"slot": 1}, "value": account(&spl_token::id(), &[0u8; 82])})
+            }
+            Some("getAccountInfo") => {
+                let mut data = vec![0u8; 165];
+                data[64..72].copy_from_slice(&token_balance.to_le_bytes());
+                json!({"context": {"slot": 1}, "value": account(&spl_token::id(), &data)})
+            }
+            Some("getMultipleAccounts") => {
+                let mut table = vec![0u8; 56];
+                table[..4].copy_from_slice(&1u32.to_le_bytes());
+                table.extend_from_slice(pool_account.as_ref());
+                let alt_program = solana_sdk::pubkey!("AddressLookupTab1e1111111111111111111111111");
+                json!({"context": {"slot": 1}, "value": [account(&alt_program, &table)]})
+            }
+            other => panic!("unexpected RPC call {:?}", other),
+        };
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    })
+}
+
+async fn builder_for(server: &MockHttpServer) -> (TransactionBuilder, TransactionConfig) {
+    let config = TransactionConfig {
+        rpc_endpoints: vec![server.url.clone()],
+        jupiter_api_url: Some(format!("{}/", server.url)),
+        ..TransactionConfig::default()
+    };
+    let builder = TransactionBuilder::new(
+        Arc::new(WalletManager::new_random()),
+        vec![server.url.clone()],
+        Arc::new(NonceManager::new(2)),
+        &config,
+    )
+    .await
+    .unwrap();
+    (builder, config)
+}
+
+/// Accepts every broadcast, keeping the sent transactions.
+#[derive(Debug, Default)]
+struct RecordingBroadcaster {
+    sent: Mutex<Vec<VersionedTransaction>>,
+}
+
+impl RpcBroadcaster for RecordingBroadcaster {
+    fn send_on_many_rpc<'a>(
+        &'a self,
+        txs: Vec<VersionedTransaction>,
+        _correlation_id: Option<CorrelationId>,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Signature>> + Send + 'a>> {
+        self.sent.lock().unwrap().extend(txs);
+        Box::pin(async { Ok(Signature::from([5u8; 64])) })
+    }
+}
+
+/// Engine holding a position in `fx.mint` bought on `program`, with the Jupiter fallback configured.
+async fn engine_holding(
+    server: &MockHttpServer,
+    fx: &Fixture,
+    program: &str,
+) -> (BuyEngine, Arc<RecordingBroadcaster>, Arc<tokio::sync::Mutex<AppState>>) {
+    let app_state = Arc::new(tokio::sync::Mutex::new(AppState {
+        mode: Mode::PassiveToken(fx.mint),
+        active_token: Some(PremintCandidate {
+            mint: fx.mint,
+            creator: Pubkey::new_unique(),
+            program: program.to_string(),
+            slot: 1,
+            timestamp: 0,
+            instruction_summary: None,
+            is_jito_bundle: None,
+            detected_at_ms: 0,
+            token_program: None,
+        }),
+        last_buy_price: Some(1.0),
+        holdings_percent: 1.0,
+        quantum_suggestions: Vec::new(),
+    }));
+    let rpc = Arc::new(RecordingBroadcaster::default());
+    let (_tx, rx) = mpsc::channel(1);
+    let engine = BuyEngine::new(
+        rpc.clone(),
+        Arc::new(NonceManager::new(2)),
+        rx,
+        app_state.clone(),
+        Config {
+            jupiter_api_url: Some(format!("{}/", server.url)),
+            ..Config::default()
+        },
+        Some(builder_for(server).await.0),
+    );
+    (engine, rpc, app_state)
+}
+
+fn fixture() -> Fixture {
+    Fixture {
+        mint: Pubkey::new_unique(),
+        swap_program: Pubkey::new_unique(),
+        lookup_table: Pubkey::new_unique(),
+        pool_account: Pubkey::new_unique(),
+    }
+}
+
+#[tokio::test]
+async fn unknown_program_sell_falls_back_to_jupiter_with_our_budget_and_alts() {
+    let fx = fixture();
+    let server = start_mock(&fx, 1_000_000);
+    let (builder, config) = builder_for(&server).await;
+
+    let tx = builder
+        .build_sell_transaction(&fx.mint, "some-new-launchpad", 0.5, &config, false)
+        .await
+        .unwrap();
+
+    let VersionedMessage::V0(msg) = &tx.message else { panic!("expected v0 message") };
+    assert_eq!(msg.address_table_lookups.len(), 1);
+    assert_eq!(msg.address_table_lookups[0].account_key, fx.lookup_table);
+    assert!(!msg.account_keys.contains(&fx.pool_account), "pool account resolved through the ALT");
+    assert!(msg.account_keys.contains(&fx.swap_program));
+
+    // Only our two compute budget instructions survive
+    let budget_ixs = msg
+        .instructions
+        .iter()
+        .filter(|ix| msg.account_keys[ix.program_id_index as usize] == COMPUTE_BUDGET_PROGRAM)
+        .count();
+    assert_eq!(budget_ixs, 2);
+    assert_eq!(priority_fee_of(&tx), Some(config.priority_fee_lamports));
+
+    let quote = server.requests().into_iter().find(|r| r.path.starts_with("/quote")).unwrap();
+    assert!(quote.path.contains(&format!("inputMint={}", fx.mint)));
+    assert!(quote.path.contains(&format!("outputMint={}", WSOL_MINT)));
+    assert!(quote.path.contains("amount=500000"));
+    assert!(quote.path.contains(&format!("slippageBps={}", config.slippage_bps)));
+
+    let swap = server.requests().into_iter().find(|r| r.path == "/swap-instructions").unwrap();
+    assert_eq!(swap.body["quoteResponse"]["outAmount"], "123456");
+    assert_eq!(swap.body["userPublicKey"], builder.wallet.pubkey().to_string());
+}
+
+#[tokio::test]
+async fn jupiter_is_selectable_and_respects_allowlist() {
+    let fx = fixture();
+    let server = start_mock(&fx, 1_000_000);
+    let (builder, mut config) = builder_for(&server).await;
+
+    let tx = builder
+        .build_sell_transaction(&fx.mint, "jupiter", 1.0, &config, false)
+        .await
+        .unwrap();
+    assert!(tx.message.static_account_keys().contains(&fx.swap_program));
+
+    config.allowed_programs = vec![Pubkey::new_unique()];
+    let err = builder
+        .build_sell_transaction(&fx.mint, "jupiter", 1.0, &config, false)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not allowed"), "{}", err);
+}
+
+#[tokio::test]
+async fn empty_balance_is_reported_before_quoting() {
+    let fx = fixture();
+    let server = start_mock(&fx, 0);
+    let (builder, config) = builder_for(&server).await;
+    let err = builder
+        .build_sell_transaction(&fx.mint, "jupiter", 1.0, &config, false)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("nothing to sell"), "{}", err);
+    assert!(server.requests().iter().all(|r| !r.path.starts_with("/quote")));
+}
+
+#[tokio::test]
+async fn full_exit_closes_the_token_account() {
+    let fx = fixture();
+    // Not representable as f64: a rounded sell would leave dust and the close would revert it
+    let balance = (1u64 << 53) + 1;
+    let server = start_mock(&fx, balance);
+    let (builder, mut config) = builder_for(&server).await;
+    let owner = builder.wallet.pubkey();
+    let ata = spl_associated_token_account::get_associated_token_address(&owner, &fx.mint);
+    let close = |tx: &solana_sdk::transaction::VersionedTransaction| {
+        let keys = tx.message.static_account_keys();
+        tx.message.instructions().last().filter(|ix| {
+            keys[ix.program_id_index as usize] == spl_token::id()
+                && ix.data == [9]
+                && keys[ix.accounts[0] as usize] == ata
+                && keys[ix.accounts[1] as usize] == owner
+        }).is_some()
+    };
+
+    let full = builder
+        .build_sell_transaction(&fx.mint, "jupiter", 1.0, &config, false)
+        .await
+        .unwrap();
+    assert!(close(&full), "full exit ends with CloseAccount on the ATA");
+    let quote = server.requests().into_iter().find(|r| r.path.starts_with("/quote")).unwrap();
+    assert!(quote.path.contains(&format!("amount={}&", balance)), "{}", quote.path);
+
+    let partial = builder
+        .build_sell_transaction(&fx.mint, "jupiter", 0.5, &config, false)
+        .await
+        .unwrap();
+    assert!(!close(&partial));
+
+    config.close_token_account_on_exit = false;
+    let builder = builder_for(&server).await.0;
+    let tx = builder
+        .build_sell_transaction(&fx.mint, "jupiter", 1.0, &config, false)
+        .await
+        .unwrap();
+    assert!(!tx.message.instructions().iter().any(|ix| ix.data == [9]));
+}
+
+#[tokio::test]
+async fn position_sells_through_the_route_it_was_bought_on() {
+    let fx = fixture();
+    let server = start_mock(&fx, 1_000_000);
+    let (engine, rpc, app_state) = engine_holding(&server, &fx, "some-new-launchpad").await;
+
+    engine.sell(0.5).await.unwrap();
+
+    let sent = rpc.sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].message.static_account_keys().contains(&fx.swap_program));
+    let quote = server.requests().into_iter().find(|r| r.path.starts_with("/quote")).unwrap();
+    assert!(quote.path.contains(&format!("inputMint={}", fx.mint)), "{}", quote.path);
+    assert_eq!(app_state.lock().await.holdings_percent, 0.5);
+}
+
+#[tokio::test]
+async fn app_full_exit_closes_the_token_account_and_returns_to_sniffing() {
+    let fx = fixture();
+    let server = start_mock(&fx, 1_000_000);
+    let (engine, rpc, app_state) = engine_holding(&server, &fx, "jupiter").await;
+    let owner = engine.tx_builder.as_ref().unwrap().wallet.pubkey();
+    let ata = spl_associated_token_account::get_associated_token_address(&owner, &fx.mint);
+
+    // The GUI sells through the engine's seller
+    engine.seller().sell(1.0).await.unwrap();
+
+    let sent = rpc.sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    let keys = sent[0].message.static_account_keys();
+    let close = sent[0].message.instructions().last().unwrap();
+    assert_eq!(keys[close.program_id_index as usize], spl_token::id());
+    assert_eq!(close.data, [9], "full exit ends with CloseAccount");
+    assert_eq!(keys[close.accounts[0] as usize], ata);
+    assert!(app_state.lock().await.is_sniffing());
+}
@@ -0,0 +1,198 @@
+//! LetsBonk buy/sell through a local mock provider (the same server also answers RPC).
+
+mod common;
+
+use std::sync::Arc;
+
+use base64::Engine;
+use common::{MockHttpServer, RecordedRequest};
+use serde_json::{json, Value};
+use sniffer_bot_light::nonce_manager::NonceManager;
+use sniffer_bot_light::tx_builder::{TransactionBuilder, TransactionBuilderError, TransactionConfig};
+use sniffer_bot_light::tx_guard::GuardViolation;
+use sniffer_bot_light::types::PremintCandidate;
+use sniffer_bot_light::wallet::WalletManager;
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+
+const PROVIDER_PATH: &str = "/letsbonk";
+
+/// Answers the JSON-RPC calls the builder makes; everything else goes to `provider`.
+fn mock_with_provider<F>(token_balance: u64, provider: F) -> MockHttpServer
+where
+    F: Fn(&RecordedRequest) -> (u16, Value) + Send + Sync + 'static,
+{
+    MockHttpServer::start(move |req| {
+        if req.path == PROVIDER_PATH {
+            return provider(req);
+        }
+        let id = req.body["id"].clone();
+        let result = match req.body["method"].as_str() {
+            Some("getLatestBlockhash") => json!({
+                "context": {"slot": 1},
+                "value": {"blockhash": Hash::new_unique().to_string(), "lastValidBlockHeight": 100}
+            }),
+            Some("getAccountInfo") => {
+                let mut data = vec![0u8; 165];
+                data[64..72].copy_from_slice(&token_balance.to_le_bytes());
+                json!({
+                    "context": {"slot": 1},
+                    "value": {
+                        "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"],
+                        "executable": false,
+                        "lamports": 2_039_280,
+                        "owner": spl_token::id().to_string(),
+                        "rentEpoch": 0,
+                        "space": 165
+                    }
+                })
+            }
+            other => panic!("unexpected RPC call {:?}", other),
+        };
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    })
+}
+
+fn instruction_response(program: &Pubkey) -> Value {
+    json!({
+        "program_id": program.to_string(),
+        "data": base64::engine::general_purpose::STANDARD.encode([7u8, 1, 2, 3]),
+        "accounts": [{"pubkey": Pubkey::new_unique().to_string(), "is_signer": false, "is_writable": true}]
+    })
+}
+
+async fn builder_for(server: &MockHttpServer, attempts: usize) -> (TransactionBuilder, TransactionConfig) {
+    let config = TransactionConfig {
+        rpc_endpoints: vec![server.url.clone()],
+        rpc_retry_attempts: attempts,
+        letsbonk_api_url: Some(format!("{}{}", server.url, PROVIDER_PATH)),
+        letsbonk_api_key: Some("test-key".to_string()),
+        sell_min_sol_out_lamports: 42,
+        ..TransactionConfig::default()
+    };
+    let builder = TransactionBuilder::new(
+        Arc::new(WalletManager::new_random()),
+        vec![server.url.clone()],
+        Arc::new(NonceManager::new(2)),
+        &config,
+    )
+    .await
+    .unwrap();
+    (builder, config)
+}
+
+fn has_program(tx: &solana_sdk::transaction::VersionedTransaction, program: &Pubkey) -> bool {
+    tx.message.static_account_keys().contains(program)
+}
+
+#[tokio::test]
+async fn buy_and_sell_round_trip_through_provider() {
+    let program = Pubkey::new_unique();
+    let server = mock_with_provider(1_000_000, move |_| (200, instruction_response(&program)));
+    let (builder, config) = builder_for(&server, 3).await;
+    let mint = Pubkey::new_unique();
+
+    let candidate = PremintCandidate {
+        mint,
+        creator: Pubkey::new_unique(),
+        program: "letsbonk".to_string(),
+        slot: 1,
+        timestamp: 0,
+        instruction_summary: None,
+        is_jito_bundle: None,
+        detected_at_ms: 0,
+        token_program: None,
+    };
+    let buy = builder.build_buy_transaction(&candidate, &config, false).await.unwrap();
+    assert!(has_program(&buy, &program));
+
+    let sell = builder
+        .build_sell_transaction(&mint, "letsbonk", 0.5, &config, false)
+        .await
+        .unwrap();
+    assert!(has_program(&sell, &program));
+
+    let provider_calls: Vec<_> = server.requests().into_iter().filter(|r| r.path == PROVIDER_PATH).collect();
+    assert_eq!(provider_calls.len(), 2);
+    assert_eq!(provider_calls[0].body["amount"], json!(config.buy_amount_lamports));
+    assert!(provider_calls[0].body.get("side").is_none());
+
+    let sell_req = &provider_calls[1].body;
+    assert_eq!(sell_req["side"], "sell");
+    assert_eq!(sell_req["mint"], mint.to_string());
+    assert_eq!(sell_req["token_amount"], json!(500_000));
+    assert_eq!(sell_req["percent"], json!(50.0));
+    assert_eq!(sell_req["min_sol_out"], json!(42));
+    assert_eq!(sell_req["payer"], builder.wallet.pubkey().to_string());
+}
+
+#[tokio::test]
+async fn sell_retries_then_reports_provider_down() {
+    let server = mock_with_provider(1_000_000, |_| (503, json!({"error": "maintenance"})));
+    let (builder, config) = builder_for(&server, 3).await;
+
+    let err = builder
+        .build_sell_transaction(&Pubkey::new_unique(), "letsbonk", 1.0, &config, false)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("provider unavailable after 3 attempts"), "{}", err);
+    assert_eq!(server.requests().iter().filter(|r| r.path == PROVIDER_PATH).count(), 3);
+}
+
+#[tokio::test]
+async fn sell_does_not_retry_rejections_or_accept_bad_responses() {
+    let server = mock_with_provider(1_000_000, |_| (400, json!({"error": "unknown mint"})));
+    let (builder, config) = builder_for(&server, 3).await;
+    let err = builder
+        .build_sell_transaction(&Pubkey::new_unique(), "letsbonk", 1.0, &config, false)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("rejected"), "{}", err);
+    assert_eq!(server.requests().iter().filter(|r| r.path == PROVIDER_PATH).count(), 1);
+
+    // Same validation as buys: foreign signers are refused
+    let server = mock_with_provider(1_000_000, |_| {
+        (200, json!({
+            "program_id": Pubkey::new_unique().to_string(),
+            "data": "AQID",
+            "accounts": [{"pubkey": Pubkey::new_unique().to_string(), "is_signer": true, "is_writable": true}]
+        }))
+    });
+    let (builder, config) = builder_for(&server, 1).await;
+    let err = builder
+        .build_sell_transaction(&Pubkey::new_unique(), "letsbonk", 1.0, &config, false)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("unexpected signer"), "{}", err);
+}
+
+#[tokio::test]
+async fn signing_requires_provider_programs_to_be_allowlisted() {
+    let program = Pubkey::new_unique();
+    let server = mock_with_provider(1_000_000, move |_| (200, instruction_response(&program)));
+    let (builder, mut config) = builder_for(&server, 1).await;
+    let candidate = PremintCandidate {
+        mint: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        program: "letsbonk".to_string(),
+        slot: 1,
+        timestamp: 0,
+        instruction_summary: None,
+        is_jito_bundle: None,
+        detected_at_ms: 0,
+        token_program: None,
+    };
+
+    let err = builder.build_buy_transaction(&candidate, &config, true).await.unwrap_err();
+    assert!(
+        matches!(err, TransactionBuilderError::GuardRejected(GuardViolation::ProgramNotAllowed { program: p, .. }) if p == program),
+        "{:?}",
+        err
+    );
+
+    config.allowed_programs = vec![program];
+    let tx = builder.build_buy_transaction(&candidate, &config, true).await.unwrap();
+    assert!(tx.verify_with_results().into_iter().all(|ok| ok));
+}
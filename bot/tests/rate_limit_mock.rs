@@ -0,0 +1,51 @@
+//! Shared per-endpoint quotas and pooled clients against a local mock RPC.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::metrics::metrics;
+use sniffer_bot_light::rate_limit::{rate_limits, EndpointQuota, RateLimitConfig, RpcLane};
+use sniffer_bot_light::rpc_pool::{rpc_pool, EndpointRole};
+
+#[tokio::test]
+async fn clients_of_one_endpoint_share_its_quota() {
+    let server = MockHttpServer::start(|req| {
+        (200, json!({"jsonrpc": "2.0", "id": req.body["id"].clone(), "result": 42}))
+    });
+    let mut config = RateLimitConfig::default();
+    config.endpoints.insert(
+        server.url.clone(),
+        EndpointQuota { requests_per_second: 4, burst: Some(1) },
+    );
+    rate_limits().configure(Some(config));
+
+    // Two clients, as the builder and the sniffer would hold
+    let sender = rpc_pool().client(&server.url, EndpointRole::Read, RpcLane::Send);
+    let poller = rpc_pool().client(&server.url, EndpointRole::Read, RpcLane::Background);
+
+    let start = Instant::now();
+    assert_eq!(sender.get_slot().await.unwrap(), 42);
+    assert_eq!(poller.get_slot().await.unwrap(), 42);
+    assert_eq!(sender.get_slot().await.unwrap(), 42);
+    // One immediate request, then one every 250ms
+    assert!(start.elapsed() >= Duration::from_millis(450), "{:?}", start.elapsed());
+    assert_eq!(server.rpc_calls("getSlot").len(), 3);
+    assert!(metrics().get_counter("rpc_rate_limit_waits_send") >= 1);
+    assert!(metrics().get_counter("rpc_read_requests") >= 3);
+    assert!(Arc::ptr_eq(&sender, &rpc_pool().client(&server.url, EndpointRole::Read, RpcLane::Send)));
+}
+
+#[tokio::test]
+async fn failed_calls_are_counted_per_role() {
+    let server = MockHttpServer::start(|req| {
+        (200, json!({"jsonrpc": "2.0", "id": req.body["id"].clone(), "error": {"code": -32005, "message": "node is behind"}}))
+    });
+    let archival = rpc_pool().client(&server.url, EndpointRole::Archival, RpcLane::Background);
+    let before = metrics().get_counter("rpc_archival_errors");
+    assert!(archival.get_slot().await.is_err());
+    assert_eq!(metrics().get_counter("rpc_archival_errors"), before + 1);
+}
@@ -0,0 +1,155 @@
+//! Rebroadcast loop against a local mock RPC that lands transactions after a few resends.
+
+mod common;
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::MockHttpServer;
+use serde_json::{json, Value};
+use sniffer_bot_light::blockhash::{BlockhashConfig, BlockhashService};
+use sniffer_bot_light::config::Config;
+use sniffer_bot_light::confirmation::{ConfirmationOutcome, ConfirmationTracker};
+use sniffer_bot_light::metrics::metrics;
+use sniffer_bot_light::rebroadcast::{RebroadcastConfig, Rebroadcaster};
+use sniffer_bot_light::rpc_manager::RpcManager;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::VersionedTransaction,
+};
+
+const LAST_VALID_BLOCK_HEIGHT: u64 = 1_150;
+
+/// Node that confirms the transaction once it received `lands_after` sends (never when 0) and
+/// answers resends after the first as duplicates.
+struct Node {
+    sends: AtomicUsize,
+    lands_after: usize,
+    block_height: AtomicU64,
+}
+
+fn start(node: Arc<Node>, signature: String) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        let height = node.block_height.load(Ordering::SeqCst);
+        let result = match req.body["method"].as_str() {
+            Some("sendTransaction") => {
+                if node.sends.fetch_add(1, Ordering::SeqCst) > 0 {
+                    return (200, json!({"jsonrpc": "2.0", "id": id, "error": {
+                        "code": -32002, "message": "Transaction simulation failed: This transaction has already been processed"
+                    }}));
+                }
+                json!(signature)
+            }
+            Some("getEpochInfo") => json!({
+                "absoluteSlot": height, "blockHeight": height, "epoch": 1,
+                "slotIndex": height, "slotsInEpoch": 432000, "transactionCount": null
+            }),
+            Some("getSignatureStatuses") => {
+                let landed = node.lands_after > 0 && node.sends.load(Ordering::SeqCst) >= node.lands_after;
+                let status = if landed {
+                    json!({"slot": height, "confirmations": 1, "err": null, "status": {"Ok": null}, "confirmationStatus": "confirmed"})
+                } else {
+                    Value::Null
+                };
+                json!({"context": {"slot": height}, "value": [status]})
+            }
+            other => panic!("unexpected RPC call {:?}", other),
+        };
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    })
+}
+
+fn signed_tx() -> VersionedTransaction {
+    let payer = Keypair::new();
+    let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+    let message = v0::Message::try_compile(&payer.pubkey(), &[ix], &[], Hash::new_unique()).unwrap();
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer]).unwrap()
+}
+
+fn setup(lands_after: usize) -> (Arc<Node>, MockHttpServer, Rebroadcaster, VersionedTransaction) {
+    let tx = signed_tx();
+    let node = Arc::new(Node {
+        sends: AtomicUsize::new(0),
+        lands_after,
+        block_height: AtomicU64::new(1_000),
+    });
+    let server = start(node.clone(), tx.signatures[0].to_string());
+    let client = Arc::new(RpcClient::new(server.url.clone()));
+    let blockhashes = Arc::new(BlockhashService::new(
+        client.clone(),
+        BlockhashConfig {
+            confirm_poll_ms: 10,
+            ..BlockhashConfig::default()
+        },
+    ));
+    let tracker = Arc::new(ConfirmationTracker::new(client, blockhashes));
+    let config = Config {
+        rpc_timeout_sec: 2,
+        ..Config::default()
+    };
+    let rpc = Arc::new(RpcManager::new(vec![server.url.clone()], config));
+    let rebroadcaster = Rebroadcaster::new(rpc, tracker, RebroadcastConfig { interval_ms: 20 });
+    (node, server, rebroadcaster, tx)
+}
+
+#[tokio::test]
+async fn resends_until_confirmed() {
+    let (node, server, rebroadcaster, tx) = setup(4);
+    let duplicates_before = metrics().get_counter("rebroadcast_duplicates");
+
+    let outcome = tokio::time::timeout(Duration::from_secs(5), rebroadcaster.run(&tx, LAST_VALID_BLOCK_HEIGHT))
+        .await
+        .expect("confirms after a few resends")
+        .unwrap();
+    assert!(matches!(outcome, Some(ConfirmationOutcome::Confirmed { .. })), "{outcome:?}");
+    assert!(node.sends.load(Ordering::SeqCst) >= 4);
+    assert!(server.rpc_calls("sendTransaction").len() >= 4);
+    // The round that landed it may be cut short by the confirmation
+    assert!(metrics().get_counter("rebroadcast_duplicates") >= duplicates_before + 2);
+    assert!(metrics().get_counter("rebroadcast_confirmed") >= 1);
+}
+
+#[tokio::test]
+async fn stops_when_the_blockhash_expires() {
+    let (node, _server, rebroadcaster, tx) = setup(0);
+    let rebroadcaster = Arc::new(rebroadcaster);
+    let loop_task = tokio::spawn({
+        let rebroadcaster = rebroadcaster.clone();
+        async move { rebroadcaster.run(&tx, LAST_VALID_BLOCK_HEIGHT).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!loop_task.is_finished());
+    assert!(node.sends.load(Ordering::SeqCst) >= 2, "resending while valid");
+
+    node.block_height.store(LAST_VALID_BLOCK_HEIGHT + 1, Ordering::SeqCst);
+    let outcome = tokio::time::timeout(Duration::from_secs(5), loop_task).await.unwrap().unwrap().unwrap();
+    assert!(matches!(outcome, Some(ConfirmationOutcome::Expired { .. })), "{outcome:?}");
+
+    // No resends after the loop ended (one may still have been in flight)
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let sends = node.sends.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(node.sends.load(Ordering::SeqCst), sends);
+}
+
+#[tokio::test]
+async fn cancel_all_stops_running_loops() {
+    let (_node, _server, rebroadcaster, tx) = setup(0);
+    let rebroadcaster = Arc::new(rebroadcaster);
+    let loop_task = tokio::spawn({
+        let rebroadcaster = rebroadcaster.clone();
+        async move { rebroadcaster.run(&tx, LAST_VALID_BLOCK_HEIGHT).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    rebroadcaster.cancel_all();
+    let outcome = tokio::time::timeout(Duration::from_secs(5), loop_task).await.unwrap().unwrap().unwrap();
+    assert_eq!(outcome, None);
+    assert!(metrics().get_counter("rebroadcast_cancelled") >= 1);
+}
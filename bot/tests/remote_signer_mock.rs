@@ -0,0 +1,111 @@
+//! Builder signing through a remote signer daemon (the mock server also answers RPC).
+
+mod common;
+
+use std::sync::Arc;
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::nonce_manager::NonceManager;
+use sniffer_bot_light::signer::{RemoteSigner, RemoteSignerConfig};
+use sniffer_bot_light::tx_builder::{TransactionBuilder, TransactionBuilderError, TransactionConfig};
+use sniffer_bot_light::types::PremintCandidate;
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+/// Signing daemon stand-in: signs with `keypair` unless the policy allows less than `min_out`.
+fn daemon(keypair: Keypair, min_out: u64) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        if req.path == "/sign" {
+            let allowed = req.body["policy"]["max_lamports_out"].as_u64().unwrap_or(u64::MAX);
+            if allowed < min_out {
+                return (403, json!({"error": "policy below required spend"}));
+            }
+            let message = base64::engine::general_purpose::STANDARD
+                .decode(req.body["message"].as_str().unwrap())
+                .unwrap();
+            return (200, json!({"signature": keypair.sign_message(&message).to_string()}));
+        }
+        let result = match req.body["method"].as_str() {
+            Some("getLatestBlockhash") => json!({
+                "context": {"slot": 1},
+                "value": {"blockhash": Hash::new_unique().to_string(), "lastValidBlockHeight": 100}
+            }),
+            other => panic!("unexpected RPC call {:?}", other),
+        };
+        (200, json!({"jsonrpc": "2.0", "id": req.body["id"].clone(), "result": result}))
+    })
+}
+
+async fn remote_builder(server: &MockHttpServer, pubkey: &Pubkey) -> (TransactionBuilder, TransactionConfig) {
+    let signer = RemoteSigner::new(&RemoteSignerConfig {
+        endpoint: server.url.clone(),
+        pubkey: pubkey.to_string(),
+        timeout_ms: 2_000,
+        max_lamports_out: None,
+        auth_token_env: None,
+    })
+    .unwrap();
+    let config = TransactionConfig {
+        rpc_endpoints: vec![server.url.clone()],
+        buy_amount_lamports: 1_000_000,
+        slippage_bps: 1_000,
+        ..TransactionConfig::default()
+    };
+    let builder = TransactionBuilder::new(
+        Arc::new(signer),
+        vec![server.url.clone()],
+        Arc::new(NonceManager::new(2)),
+        &config,
+    )
+    .await
+    .unwrap();
+    (builder, config)
+}
+
+fn candidate() -> PremintCandidate {
+    PremintCandidate {
+        mint: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        program: "unknown-dex".to_string(),
+        slot: 1,
+        timestamp: 0,
+        instruction_summary: None,
+        is_jito_bundle: None,
+        detected_at_ms: 0,
+        token_program: None,
+    }
+}
+
+#[tokio::test]
+async fn buys_are_signed_by_the_daemon_with_a_spend_cap() {
+    let keypair = Keypair::new();
+    let pubkey = keypair.pubkey();
+    let server = daemon(keypair, 0);
+    let (builder, config) = remote_builder(&server, &pubkey).await;
+
+    let tx = builder.build_buy_transaction(&candidate(), &config, true).await.unwrap();
+    assert_eq!(tx.message.static_account_keys()[0], pubkey);
+    assert!(tx.verify_with_results().into_iter().all(|ok| ok));
+
+    let sign_requests: Vec<_> = server.requests().into_iter().filter(|r| r.path == "/sign").collect();
+    assert_eq!(sign_requests.len(), 1);
+    assert_eq!(sign_requests[0].body["pubkey"], pubkey.to_string());
+    // 1.1 SOL-equivalent slippage bound plus one token account's rent
+    assert_eq!(sign_requests[0].body["policy"]["max_lamports_out"], 1_100_000 + 2_039_280);
+}
+
+#[tokio::test]
+async fn daemon_refusal_fails_the_build() {
+    let keypair = Keypair::new();
+    let pubkey = keypair.pubkey();
+    let server = daemon(keypair, u64::MAX);
+    let (builder, config) = remote_builder(&server, &pubkey).await;
+
+    let err = builder.build_buy_transaction(&candidate(), &config, true).await.unwrap_err();
+    assert!(matches!(err, TransactionBuilderError::SigningFailed(_)), "{:?}", err);
+}
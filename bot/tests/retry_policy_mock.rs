@@ -0,0 +1,188 @@
+//! RpcManager retry policy decisions against local mock RPC endpoints.
+
+mod common;
+
+use std::time::Duration;
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::config::{BroadcastMode, Config};
+use sniffer_bot_light::rpc_manager::{BroadcastError, RetryAction, RetryPolicyConfig, RpcBroadcaster, RpcManager};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::VersionedTransaction,
+};
+
+/// Endpoint accepting every transaction, or answering every send with the RPC error `error`.
+fn endpoint(error: Option<&'static str>) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        assert_eq!(req.body["method"], "sendTransaction");
+        if let Some(message) = error {
+            return (200, json!({"jsonrpc": "2.0", "id": id, "error": {"code": -32002, "message": message}}));
+        }
+        let wire = base64::engine::general_purpose::STANDARD
+            .decode(req.body["params"][0].as_str().unwrap())
+            .unwrap();
+        let tx: VersionedTransaction = bincode::deserialize(&wire).unwrap();
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": tx.signatures[0].to_string()}))
+    })
+}
+
+/// Endpoint failing every send the way a node does when preflight simulation fails: code
+/// -32002 with the simulated transaction error in `data`.
+fn preflight_failure(message: &'static str, err: &'static str) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        assert_eq!(req.body["params"][1]["skipPreflight"], false, "preflight requested");
+        (200, json!({"jsonrpc": "2.0", "id": id, "error": {
+            "code": -32002,
+            "message": format!("Transaction simulation failed: {}", message),
+            "data": {"err": err, "logs": [], "accounts": null, "unitsConsumed": 0, "returnData": null}
+        }}))
+    })
+}
+
+fn signed_tx() -> VersionedTransaction {
+    let payer = Keypair::new();
+    let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+    let message = v0::Message::try_compile(&payer.pubkey(), &[ix], &[], Hash::new_unique()).unwrap();
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer]).unwrap()
+}
+
+fn manager(servers: &[&MockHttpServer], mode: BroadcastMode, retry_policy: Option<RetryPolicyConfig>) -> RpcManager {
+    let config = Config {
+        broadcast_mode: mode,
+        rpc_timeout_sec: 2,
+        retry_policy,
+        ..Config::default()
+    };
+    RpcManager::new(servers.iter().map(|s| s.url.clone()).collect(), config)
+}
+
+#[tokio::test]
+async fn rate_limited_endpoint_backs_off_alone() {
+    let limited = endpoint(Some("Too many requests for a specific RPC call, contact your app developer"));
+    let healthy = endpoint(None);
+    let rpc = manager(&[&limited, &healthy], BroadcastMode::Replicate, None);
+
+    rpc.send_on_many_rpc(vec![signed_tx()], None).await.unwrap();
+    // The rate-limited reply may land after the broadcast returned
+    for _ in 0..200 {
+        if rpc.get_ranked_endpoints().await == vec![1] {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let statuses = rpc.endpoint_statuses().await;
+    assert_eq!(statuses[0].endpoint, healthy.url);
+    assert_eq!(statuses[1].endpoint, limited.url);
+    assert!(statuses[1].backoff_remaining_ms.is_some());
+    assert_eq!(statuses[1].consecutive_failures, 0, "a rate limit is not a breaker failure");
+    assert!(statuses[0].backoff_remaining_ms.is_none());
+
+    rpc.send_on_many_rpc(vec![signed_tx()], None).await.unwrap();
+    assert_eq!(limited.rpc_calls("sendTransaction").len(), 1, "backing-off endpoint is skipped");
+    assert_eq!(healthy.rpc_calls("sendTransaction").len(), 2);
+}
+
+#[tokio::test]
+async fn insufficient_funds_aborts_the_broadcast() {
+    let broke = endpoint(Some("Transaction simulation failed: Attempt to debit an account but found no record of a prior credit. insufficient funds for fee"));
+    let rpc = manager(&[&broke], BroadcastMode::Pairwise, None);
+
+    let err = rpc.send_on_many_rpc(vec![signed_tx()], None).await.unwrap_err();
+    assert!(matches!(err.downcast_ref::<BroadcastError>(), Some(BroadcastError::Aborted(_))), "{err:#}");
+}
+
+#[tokio::test]
+async fn blockhash_not_found_asks_for_a_rebuild() {
+    let servers = [endpoint(Some("Transaction simulation failed: Blockhash not found")), endpoint(Some("Blockhash not found"))];
+    let rpc = manager(&[&servers[0], &servers[1]], BroadcastMode::Replicate, None);
+    let tx = signed_tx();
+
+    let err = rpc.send_on_many_rpc(vec![tx.clone()], None).await.unwrap_err();
+    match err.downcast_ref::<BroadcastError>() {
+        Some(BroadcastError::BlockhashNotFound(hash)) => assert_eq!(hash, tx.message.recent_blockhash()),
+        other => panic!("expected BlockhashNotFound, got {other:?}"),
+    }
+    assert!(rpc.endpoint_statuses().await.iter().all(|s| s.consecutive_failures == 0));
+
+    // Configured to treat it as a transaction-level refusal instead
+    let policy = RetryPolicyConfig {
+        blockhash_not_found: RetryAction::Reject,
+        ..RetryPolicyConfig::default()
+    };
+    let rpc = manager(&[&servers[0], &servers[1]], BroadcastMode::Replicate, Some(policy));
+    let err = rpc.send_on_many_rpc(vec![tx], None).await.unwrap_err();
+    assert!(err.downcast_ref::<BroadcastError>().is_none());
+}
+
+#[tokio::test]
+async fn blockhash_rejected_by_only_some_endpoints_is_not_a_rebuild() {
+    let stale = endpoint(Some("Blockhash not found"));
+    let behind = endpoint(Some("Node is behind by 150 slots"));
+    let rpc = manager(&[&stale, &behind], BroadcastMode::Replicate, None);
+
+    // The other endpoint failed for its own reasons: a fresh blockhash would not help it
+    let err = rpc.send_on_many_rpc(vec![signed_tx()], None).await.unwrap_err();
+    assert!(err.downcast_ref::<BroadcastError>().is_none(), "{err:#}");
+}
+
+#[tokio::test]
+async fn duplicate_signature_counts_as_delivered() {
+    let server = endpoint(Some("Duplicate signature"));
+    let tx = signed_tx();
+    let sig = manager(&[&server], BroadcastMode::Pairwise, None)
+        .send_on_many_rpc(vec![tx.clone()], None)
+        .await
+        .unwrap();
+    assert_eq!(sig, tx.signatures[0]);
+
+    let policy = RetryPolicyConfig {
+        duplicate_signature: RetryAction::Fail,
+        ..RetryPolicyConfig::default()
+    };
+    let rpc = manager(&[&server], BroadcastMode::Pairwise, Some(policy));
+    assert!(rpc.send_on_many_rpc(vec![tx], None).await.is_err());
+}
+
+#[tokio::test]
+async fn preflight_is_skipped_unless_configured() {
+    let server = endpoint(None);
+    manager(&[&server], BroadcastMode::Pairwise, None)
+        .send_on_many_rpc(vec![signed_tx()], None)
+        .await
+        .unwrap();
+    assert_eq!(server.rpc_calls("sendTransaction")[0].body["params"][1]["skipPreflight"], true);
+}
+
+#[tokio::test]
+async fn preflight_failures_drive_the_policy() {
+    let preflight = || Some(RetryPolicyConfig { preflight: true, ..RetryPolicyConfig::default() });
+
+    let stale = preflight_failure("Blockhash not found", "BlockhashNotFound");
+    let rpc = manager(&[&stale], BroadcastMode::Pairwise, preflight());
+    let tx = signed_tx();
+    let err = rpc.send_on_many_rpc(vec![tx.clone()], None).await.unwrap_err();
+    match err.downcast_ref::<BroadcastError>() {
+        Some(BroadcastError::BlockhashNotFound(hash)) => assert_eq!(hash, tx.message.recent_blockhash()),
+        other => panic!("expected BlockhashNotFound, got {other:?}"),
+    }
+
+    // An unfunded fee payer is only named by the simulated error
+    let unfunded = preflight_failure("Attempt to debit an account but found no record of a prior credit.", "AccountNotFound");
+    let rpc = manager(&[&unfunded], BroadcastMode::Pairwise, preflight());
+    let err = rpc.send_on_many_rpc(vec![signed_tx()], None).await.unwrap_err();
+    assert!(matches!(err.downcast_ref::<BroadcastError>(), Some(BroadcastError::Aborted(_))), "{err:#}");
+
+    let landed = preflight_failure("This transaction has already been processed", "AlreadyProcessed");
+    let rpc = manager(&[&landed], BroadcastMode::Pairwise, preflight());
+    let tx = signed_tx();
+    assert_eq!(rpc.send_on_many_rpc(vec![tx.clone()], None).await.unwrap(), tx.signatures[0]);
+}
@@ -0,0 +1,155 @@
+//! Sweeping empty token accounts against a local mock RPC.
+
+mod common;
+
+use std::sync::Arc;
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::{json, Value};
+use sniffer_bot_light::dex::mint::TOKEN_2022_PROGRAM_ID;
+use sniffer_bot_light::nonce_manager::NonceManager;
+use sniffer_bot_light::token_accounts::AccountSweepConfig;
+use sniffer_bot_light::tx_builder::{TransactionBuilder, TransactionConfig};
+use sniffer_bot_light::wallet::WalletManager;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, transaction::VersionedTransaction};
+
+fn b64(data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+fn token_account(owner: &Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; 165];
+    data[0..32].copy_from_slice(Pubkey::new_unique().as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1; // initialized
+    data
+}
+
+fn keyed(address: &Pubkey, program: &Pubkey, data: &[u8]) -> Value {
+    json!({"pubkey": address.to_string(), "account": {
+        "data": [b64(data), "base64"],
+        "executable": false,
+        "lamports": 2_039_280,
+        "owner": program.to_string(),
+        "rentEpoch": 0,
+        "space": data.len()
+    }})
+}
+
+/// Mock RPC serving `legacy` and `token_2022` as the owner's token accounts and accepting
+/// (and immediately confirming) every transaction.
+fn start_mock(legacy: Vec<Value>, token_2022: Vec<Value>) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        let result = match req.body["method"].as_str() {
+            Some("getTokenAccountsByOwner") => {
+                let program = req.body["params"][1]["programId"].as_str().unwrap();
+                let accounts = if program == TOKEN_2022_PROGRAM_ID.to_string() { &token_2022 } else { &legacy };
+                json!({"context": {"slot": 1}, "value": accounts})
+            }
+            Some("getLatestBlockhash") => json!({
+                "context": {"slot": 1},
+                "value": {"blockhash": Hash::new_unique().to_string(), "lastValidBlockHeight": 100}
+            }),
+            Some("sendTransaction") => {
+                let wire = base64::engine::general_purpose::STANDARD
+                    .decode(req.body["params"][0].as_str().unwrap())
+                    .unwrap();
+                let tx: VersionedTransaction = bincode::deserialize(&wire).unwrap();
+                json!(tx.signatures[0].to_string())
+            }
+            Some("getSignatureStatuses") => json!({"context": {"slot": 1}, "value": [{
+                "slot": 1, "confirmations": null, "err": null, "status": {"Ok": null},
+                "confirmationStatus": "finalized"
+            }]}),
+            Some("isBlockhashValid") => json!({"context": {"slot": 1}, "value": true}),
+            other => panic!("unexpected RPC call {:?}", other),
+        };
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    })
+}
+
+async fn builder_for(server: &MockHttpServer, wallet: Arc<WalletManager>) -> (TransactionBuilder, TransactionConfig) {
+    let config = TransactionConfig {
+        rpc_endpoints: vec![server.url.clone()],
+        ..TransactionConfig::default()
+    };
+    let builder = TransactionBuilder::new(wallet, vec![server.url.clone()], Arc::new(NonceManager::new(2)), &config)
+        .await
+        .unwrap();
+    (builder, config)
+}
+
+/// Token accounts closed by a sent transaction, with the program each close ran under.
+fn closes(tx: &VersionedTransaction) -> Vec<(Pubkey, Pubkey)> {
+    let keys = tx.message.static_account_keys();
+    tx.message
+        .instructions()
+        .iter()
+        .filter(|ix| ix.data == [9])
+        .map(|ix| (keys[ix.accounts[0] as usize], keys[ix.program_id_index as usize]))
+        .collect()
+}
+
+fn sent_transactions(server: &MockHttpServer) -> Vec<VersionedTransaction> {
+    server
+        .rpc_calls("sendTransaction")
+        .iter()
+        .map(|r| {
+            let wire = base64::engine::general_purpose::STANDARD
+                .decode(r.body["params"][0].as_str().unwrap())
+                .unwrap();
+            bincode::deserialize(&wire).unwrap()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn closes_empty_accounts_of_both_programs_in_batches() {
+    let wallet = Arc::new(WalletManager::new_random());
+    let owner = wallet.pubkey();
+    let empty_legacy: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+    let holding = Pubkey::new_unique();
+    let empty_2022 = Pubkey::new_unique();
+
+    let mut legacy: Vec<Value> = empty_legacy
+        .iter()
+        .map(|address| keyed(address, &spl_token::id(), &token_account(&owner, 0)))
+        .collect();
+    legacy.push(keyed(&holding, &spl_token::id(), &token_account(&owner, 42)));
+    let token_2022 = vec![keyed(&empty_2022, &TOKEN_2022_PROGRAM_ID, &token_account(&owner, 0))];
+    let server = start_mock(legacy, token_2022);
+    let (builder, config) = builder_for(&server, wallet).await;
+
+    let sweep = AccountSweepConfig {
+        max_closes_per_tx: 2,
+        ..AccountSweepConfig::default()
+    };
+    let closed = builder.sweep_empty_token_accounts(&config, &sweep).await.unwrap();
+    assert_eq!(closed.len(), 4);
+    assert_eq!(closed.iter().map(|a| a.lamports).sum::<u64>(), 4 * 2_039_280);
+
+    let sent = sent_transactions(&server);
+    assert_eq!(sent.len(), 2, "four closes in batches of two");
+    let all: Vec<(Pubkey, Pubkey)> = sent.iter().flat_map(closes).collect();
+    let mut expected: Vec<(Pubkey, Pubkey)> = empty_legacy.iter().map(|a| (*a, spl_token::id())).collect();
+    expected.push((empty_2022, TOKEN_2022_PROGRAM_ID));
+    assert_eq!(all, expected);
+    assert!(sent.iter().all(|tx| tx.message.static_account_keys()[0] == owner));
+    assert!(sent.iter().all(|tx| tx.verify_with_results().into_iter().all(|ok| ok)));
+}
+
+#[tokio::test]
+async fn nothing_to_sweep_sends_nothing() {
+    let wallet = Arc::new(WalletManager::new_random());
+    let owner = wallet.pubkey();
+    let server = start_mock(vec![keyed(&Pubkey::new_unique(), &spl_token::id(), &token_account(&owner, 1))], vec![]);
+    let (builder, config) = builder_for(&server, wallet).await;
+
+    let closed = builder.sweep_all_wallets(&config, &AccountSweepConfig::default()).await;
+    assert!(closed.is_empty());
+    assert_eq!(server.rpc_calls("getTokenAccountsByOwner").len(), 2);
+    assert!(server.rpc_calls("sendTransaction").is_empty());
+}
@@ -0,0 +1,83 @@
+//! Direct TPU/QUIC submission against a local solana-test-validator.
+//!
+//! Needs `solana-test-validator` on PATH and port 8899 free:
+//! `cargo test --test tpu_validator -- --ignored`
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sniffer_bot_light::config::Config;
+use sniffer_bot_light::metrics::metrics;
+use sniffer_bot_light::rpc_manager::{RpcBroadcaster, RpcManager};
+use sniffer_bot_light::test_environment::{TestEnvironment, TestValidatorConfig};
+use sniffer_bot_light::tpu::{RaceBroadcaster, TpuBroadcaster, TpuConfig, TpuMode};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::VersionedTransaction,
+};
+
+async fn transfer(rpc: &RpcClient, payer: &Keypair, lamports: u64) -> VersionedTransaction {
+    let blockhash = rpc.get_latest_blockhash().await.unwrap();
+    #[allow(deprecated)]
+    let ix = solana_sdk::system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), lamports);
+    let message = v0::Message::try_compile(&payer.pubkey(), &[ix], &[], blockhash).unwrap();
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &[payer]).unwrap()
+}
+
+async fn wait_confirmed(rpc: &RpcClient, signature: &Signature) {
+    for _ in 0..150 {
+        let statuses = rpc.get_signature_statuses(&[*signature]).await.unwrap();
+        if let Some(status) = statuses.value[0].as_ref() {
+            assert!(status.err.is_none(), "{signature} failed: {:?}", status.err);
+            if status.satisfies_commitment(CommitmentConfig::confirmed()) {
+                return;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    panic!("{signature} did not confirm");
+}
+
+#[tokio::test]
+#[ignore = "needs solana-test-validator on PATH"]
+async fn transactions_land_through_the_leader_tpu() {
+    let validator = TestValidatorConfig::default();
+    let rpc_url = validator.rpc_url.clone();
+    let mut env = TestEnvironment::new(validator);
+    env.start().await.expect("test validator starts");
+    let payer = env.test_keypair().unwrap().insecure_clone();
+    let rpc = Arc::new(RpcClient::new_with_commitment(rpc_url.clone(), CommitmentConfig::confirmed()));
+
+    let tpu = Arc::new(TpuBroadcaster::new(
+        rpc.clone(),
+        TpuConfig {
+            mode: TpuMode::TpuOnly,
+            ..TpuConfig::default()
+        },
+    ));
+    // A single validator leads every slot
+    assert_eq!(tpu.leader_tpus().await.unwrap().len(), 1);
+
+    let tx = transfer(&rpc, &payer, 1_000).await;
+    let sig = tpu.send_on_many_rpc(vec![tx.clone()], None).await.unwrap();
+    assert_eq!(sig, tx.signatures[0]);
+    wait_confirmed(&rpc, &sig).await;
+    assert!(metrics().get_counter("tpu_sends") >= 1);
+
+    // Race mode: both paths carry the same transaction, whichever accepts first wins
+    let race = RaceBroadcaster::new(vec![
+        ("rpc", Arc::new(RpcManager::new(vec![rpc_url], Config::default())) as Arc<dyn RpcBroadcaster>),
+        ("tpu", tpu as Arc<dyn RpcBroadcaster>),
+    ]);
+    let tx = transfer(&rpc, &payer, 2_000).await;
+    let sig = race.send_on_many_rpc(vec![tx.clone()], None).await.unwrap();
+    assert_eq!(sig, tx.signatures[0]);
+    wait_confirmed(&rpc, &sig).await;
+    assert!(metrics().get_counter("broadcast_race_wins_rpc") + metrics().get_counter("broadcast_race_wins_tpu") >= 1);
+
+    env.stop().await.unwrap();
+}
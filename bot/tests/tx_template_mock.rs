@@ -0,0 +1,249 @@
+//! Templated pump.fun buys against a local mock RPC.
+
+mod common;
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use base64::Engine;
+use common::MockHttpServer;
+use serde_json::json;
+use sniffer_bot_light::dex::mint::TOKEN_2022_PROGRAM_ID;
+use sniffer_bot_light::dex::pumpfun::{self, PUMP_FUN_PROGRAM_ID};
+use sniffer_bot_light::nonce_manager::NonceManager;
+use sniffer_bot_light::tx_builder::{TransactionBuilder, TransactionBuilderError, TransactionConfig};
+use sniffer_bot_light::tx_template::{TemplateCache, TemplateConfig};
+use sniffer_bot_light::types::PremintCandidate;
+use sniffer_bot_light::wallet::WalletManager;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+
+fn account(owner: &Pubkey, data: &[u8]) -> serde_json::Value {
+    json!({"context": {"slot": 1}, "value": {
+        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+        "executable": false,
+        "lamports": 1_000_000,
+        "owner": owner.to_string(),
+        "rentEpoch": 0,
+        "space": data.len()
+    }})
+}
+
+/// Mock RPC; any account other than the pump.fun global is served as a mint owned by
+/// `mint_owner` holding `mint_data`.
+fn start_mock(blockhash: Hash, fee_recipient: Pubkey, mint_owner: Pubkey, mint_data: Vec<u8>) -> MockHttpServer {
+    start_mock_with(move || blockhash, fee_recipient, mint_owner, mint_data)
+}
+
+/// `start_mock` answering each `getLatestBlockhash` with `next_blockhash()`.
+fn start_mock_with(
+    next_blockhash: impl Fn() -> Hash + Send + Sync + 'static,
+    fee_recipient: Pubkey,
+    mint_owner: Pubkey,
+    mint_data: Vec<u8>,
+) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        let id = req.body["id"].clone();
+        let result = match req.body["method"].as_str() {
+            Some("getLatestBlockhash") => json!({
+                "context": {"slot": 1},
+                "value": {"blockhash": next_blockhash().to_string(), "lastValidBlockHeight": 100}
+            }),
+            Some("getAccountInfo") if req.body["params"][0] == pumpfun::global_address().to_string() => {
+                let mut data = vec![0u8; 200];
+                data[41..73].copy_from_slice(fee_recipient.as_ref());
+                account(&PUMP_FUN_PROGRAM_ID, &data)
+            }
+            Some("getAccountInfo") => account(&mint_owner, &mint_data),
+            other => panic!("unexpected RPC call {:?}", other),
+        };
+        (200, json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    })
+}
+
+fn legacy_mint() -> Vec<u8> {
+    let mut data = vec![0u8; 82];
+    data[44] = 6;
+    data[45] = 1;
+    data
+}
+
+/// Token-2022 mint whose transfers run `hook_program`.
+fn hooked_mint(hook_program: Pubkey) -> Vec<u8> {
+    let mut data = legacy_mint();
+    data.resize(165, 0);
+    data.push(1); // account type: mint
+    data.extend_from_slice(&14u16.to_le_bytes());
+    data.extend_from_slice(&64u16.to_le_bytes());
+    data.extend_from_slice(&[0u8; 32]);
+    data.extend_from_slice(hook_program.as_ref());
+    data
+}
+
+async fn templated_builder(server: &MockHttpServer) -> (TransactionBuilder, Arc<TemplateCache>, TransactionConfig) {
+    let config = TransactionConfig {
+        rpc_endpoints: vec![server.url.clone()],
+        ..TransactionConfig::default()
+    };
+    let wallet = Arc::new(WalletManager::new_random());
+    let templates = Arc::new(TemplateCache::new(
+        Arc::new(RpcClient::new(server.url.clone())),
+        wallet.pubkey(),
+        TemplateConfig::default(),
+    ));
+    let builder = TransactionBuilder::new(wallet, vec![server.url.clone()], Arc::new(NonceManager::new(2)), &config)
+        .await
+        .unwrap()
+        .with_templates(templates.clone());
+    (builder, templates, config)
+}
+
+/// A launch whose create transaction invoked `token_program`.
+fn candidate(token_program: Option<Pubkey>) -> PremintCandidate {
+    PremintCandidate {
+        mint: Pubkey::new_unique(),
+        creator: Pubkey::new_unique(),
+        program: "pump.fun".to_string(),
+        slot: 1,
+        timestamp: 0,
+        instruction_summary: None,
+        is_jito_bundle: None,
+        detected_at_ms: 0,
+        token_program,
+    }
+}
+
+#[tokio::test]
+async fn templated_buy_needs_no_rpc_after_refresh() {
+    let blockhash = Hash::new_unique();
+    let fee_recipient = Pubkey::new_unique();
+    let server = start_mock(blockhash, fee_recipient, spl_token::id(), legacy_mint());
+    let (builder, templates, config) = templated_builder(&server).await;
+
+    let candidate = candidate(Some(spl_token::id()));
+    assert!(!builder.has_template_for(&candidate.program).await);
+    templates.refresh(config.priority_fee_lamports).await.unwrap();
+    assert!(builder.has_template_for(&candidate.program).await);
+    let calls_after_refresh = server.requests().len();
+
+    let tx = builder.build_buy_transaction(&candidate, &config, true).await.unwrap();
+    assert_eq!(server.requests().len(), calls_after_refresh, "hot path must not hit RPC");
+
+    assert_eq!(*tx.message.recent_blockhash(), blockhash);
+    assert_ne!(tx.signatures[0], Default::default());
+    let keys = tx.message.static_account_keys();
+    assert!(keys.contains(&PUMP_FUN_PROGRAM_ID));
+    assert!(keys.contains(&fee_recipient));
+    assert!(keys.contains(&candidate.mint));
+    assert!(keys.contains(&pumpfun::bonding_curve_address(&candidate.mint)));
+    assert!(keys.contains(&spl_token::id()));
+}
+
+#[tokio::test]
+async fn templated_rebuild_replaces_a_rejected_blockhash() {
+    let next = AtomicU8::new(1);
+    let server = start_mock_with(
+        move || Hash::new_from_array([next.fetch_add(1, Ordering::SeqCst); 32]),
+        Pubkey::new_unique(),
+        spl_token::id(),
+        legacy_mint(),
+    );
+    let (builder, templates, config) = templated_builder(&server).await;
+    templates.refresh(config.priority_fee_lamports).await.unwrap();
+    let candidate = candidate(Some(spl_token::id()));
+
+    let first = builder.build_buy_transaction(&candidate, &config, true).await.unwrap();
+    let rejected = *first.message.recent_blockhash();
+    builder.invalidate_blockhash(rejected).await;
+
+    // Still templated (same fee recipient and curve accounts), on a blockhash fetched anew
+    let rebuilt = builder.build_buy_transaction(&candidate, &config, true).await.unwrap();
+    assert_ne!(*rebuilt.message.recent_blockhash(), rejected);
+    assert_eq!(server.rpc_calls("getLatestBlockhash").len(), 2);
+    assert_eq!(first.message.static_account_keys(), rebuilt.message.static_account_keys());
+}
+
+#[tokio::test]
+async fn token_2022_mints_use_their_own_program() {
+    // The mock would report SPL Token: the program must come from the candidate
+    let server = start_mock(Hash::new_unique(), Pubkey::new_unique(), spl_token::id(), legacy_mint());
+    let (builder, templates, config) = templated_builder(&server).await;
+    templates.refresh(config.priority_fee_lamports).await.unwrap();
+    let calls_after_refresh = server.requests().len();
+
+    let candidate = candidate(Some(TOKEN_2022_PROGRAM_ID));
+    let tx = builder.build_buy_transaction(&candidate, &config, true).await.unwrap();
+    assert_eq!(server.requests().len(), calls_after_refresh);
+    let keys = tx.message.static_account_keys();
+    let payer = keys[0];
+    let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &payer,
+        &candidate.mint,
+        &TOKEN_2022_PROGRAM_ID,
+    );
+    assert!(keys.contains(&TOKEN_2022_PROGRAM_ID));
+    assert!(keys.contains(&ata));
+    assert!(!keys.contains(&spl_token::id()));
+}
+
+#[tokio::test]
+async fn unknown_token_program_is_looked_up_once() {
+    let server = start_mock(Hash::new_unique(), Pubkey::new_unique(), TOKEN_2022_PROGRAM_ID, legacy_mint());
+    let (builder, templates, config) = templated_builder(&server).await;
+    templates.refresh(config.priority_fee_lamports).await.unwrap();
+    let calls_after_refresh = server.requests().len();
+
+    let candidate = candidate(None);
+    let tx = builder.build_buy_transaction(&candidate, &config, true).await.unwrap();
+    let lookups: Vec<_> = server.requests()[calls_after_refresh..]
+        .iter()
+        .map(|r| (r.body["method"].clone(), r.body["params"][0].clone()))
+        .collect();
+    assert_eq!(lookups, vec![(json!("getAccountInfo"), json!(candidate.mint.to_string()))]);
+    assert!(tx.message.static_account_keys().contains(&TOKEN_2022_PROGRAM_ID));
+
+    // The mint's program is cached: a second build is RPC-free
+    let calls = server.requests().len();
+    builder.build_buy_transaction(&candidate, &config, false).await.unwrap();
+    assert_eq!(server.requests().len(), calls);
+}
+
+#[tokio::test]
+async fn transfer_hook_mints_are_refused_by_default() {
+    let hook = Pubkey::new_unique();
+    let server = start_mock(Hash::new_unique(), Pubkey::new_unique(), TOKEN_2022_PROGRAM_ID, hooked_mint(hook));
+    let config = TransactionConfig {
+        rpc_endpoints: vec![server.url.clone()],
+        ..TransactionConfig::default()
+    };
+    // Without a template the buy takes the full build path, which checks the mint policy
+    let builder = TransactionBuilder::new(
+        Arc::new(WalletManager::new_random()),
+        vec![server.url.clone()],
+        Arc::new(NonceManager::new(2)),
+        &config,
+    )
+    .await
+    .unwrap();
+
+    let err = builder
+        .build_buy_transaction(&candidate(Some(TOKEN_2022_PROGRAM_ID)), &config, true)
+        .await
+        .unwrap_err();
+    match err {
+        TransactionBuilderError::MintRejected { reason, .. } => assert!(reason.contains(&hook.to_string()), "{}", reason),
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn non_templated_dexes_reuse_the_warm_blockhash() {
+    let blockhash = Hash::new_unique();
+    let server = start_mock(blockhash, Pubkey::new_unique(), spl_token::id(), legacy_mint());
+    let (builder, templates, config) = templated_builder(&server).await;
+    templates.refresh(config.priority_fee_lamports).await.unwrap();
+
+    let before = server.rpc_calls("getLatestBlockhash").len();
+    assert_eq!(builder.get_recent_blockhash(&config).await.unwrap(), blockhash);
+    assert_eq!(server.rpc_calls("getLatestBlockhash").len(), before);
+}